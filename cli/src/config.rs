@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde_derive::Deserialize;
+
+use program_analysis::config::{AnalysisSettings, DEFAULT_CURVE, DEFAULT_LEVEL};
+use program_structure::constants::Curve;
+use program_structure::report::{MessageCategory, Report};
+use program_structure::report_code::ReportCode;
+
+/// The name of the project configuration file.
+pub const CONFIG_FILE_NAME: &str = "circomspect.toml";
+
+/// The project configuration as it is represented in `circomspect.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+struct ConfigFile {
+    libraries: Vec<PathBuf>,
+    curve: Option<String>,
+    prime: Option<String>,
+    level: Option<String>,
+    allow: Vec<String>,
+    fail_on: Option<String>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    severity: HashMap<String, String>,
+    analysis: AnalysisSettings,
+    rules: Vec<PathBuf>,
+}
+
+/// Project configuration read from a `circomspect.toml` file. Options given on
+/// the command line take precedence over the corresponding configuration
+/// options.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// The path to the configuration file, if any.
+    pub path: Option<PathBuf>,
+    /// Library paths, resolved against the directory of the configuration file.
+    libraries: Vec<PathBuf>,
+    curve: Option<Curve>,
+    level: Option<MessageCategory>,
+    allow_list: Vec<String>,
+    /// Issues with a level greater than or equal to this level cause
+    /// circomspect to exit with a non-zero exit code.
+    fail_on: Option<MessageCategory>,
+    /// Reports are only emitted for files matching these patterns (if any).
+    include: Option<GlobSet>,
+    /// Reports are never emitted for files matching these patterns.
+    exclude: Option<GlobSet>,
+    /// Settings (including severity overrides) passed to the analysis passes.
+    pub settings: AnalysisSettings,
+    /// Custom rule files, resolved against the directory of the configuration
+    /// file.
+    rules: Vec<PathBuf>,
+}
+
+impl Config {
+    /// Reads the configuration from the given file.
+    pub fn from_file(path: &Path) -> Result<Config> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read `{}`", path.display()))?;
+        let config_file: ConfigFile = toml::from_str(&contents)
+            .with_context(|| format!("failed to parse `{}`", path.display()))?;
+        let root = path.parent().unwrap_or_else(|| Path::new(""));
+        Config::new(config_file, root)
+            .map(|config| Config { path: Some(path.to_path_buf()), ..config })
+            .with_context(|| format!("invalid configuration in `{}`", path.display()))
+    }
+
+    /// Searches the current directory and its ancestors for a configuration
+    /// file. Returns the default configuration if no file is found.
+    pub fn discover() -> Result<Config> {
+        let current_dir = env::current_dir()?;
+        match current_dir
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+        {
+            Some(path) => Config::from_file(&path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    fn new(config_file: ConfigFile, root: &Path) -> Result<Config> {
+        let mut settings = config_file.analysis;
+        for (rule, level) in config_file.severity {
+            if !ReportCode::circomspect_codes()
+                .iter()
+                .any(|code| code.id() == rule || code.name() == rule)
+            {
+                return Err(anyhow!("unknown report ID or name `{rule}` in `severity`"));
+            }
+            settings.severity_overrides.insert(rule, MessageCategory::from_str(&level)?);
+        }
+        let curve = match (config_file.curve, config_file.prime) {
+            (Some(_), Some(_)) => return Err(anyhow!("both `curve` and `prime` are given")),
+            (Some(curve), None) => Some(Curve::from_str(&curve)?),
+            (None, Some(prime)) => Some(Curve::from_prime(&prime)?),
+            (None, None) => None,
+        };
+        Ok(Config {
+            path: None,
+            libraries: config_file.libraries.iter().map(|path| root.join(path)).collect(),
+            curve,
+            level: config_file.level.as_deref().map(MessageCategory::from_str).transpose()?,
+            allow_list: config_file.allow,
+            fail_on: config_file.fail_on.as_deref().map(MessageCategory::from_str).transpose()?,
+            include: build_glob_set(root, &config_file.include)?,
+            exclude: build_glob_set(root, &config_file.exclude)?,
+            settings,
+            rules: config_file.rules.iter().map(|path| root.join(path)).collect(),
+        })
+    }
+
+    /// Returns the project root (the directory containing the configuration
+    /// file), if a configuration file was found.
+    pub fn root(&self) -> Option<&Path> {
+        self.path.as_deref().and_then(Path::parent)
+    }
+
+    /// Returns the configured rule files followed by the given rule files.
+    pub fn rules(&self, rules: &[PathBuf]) -> Vec<PathBuf> {
+        self.rules.iter().chain(rules).cloned().collect()
+    }
+
+    /// Returns the configured libraries followed by the given libraries.
+    pub fn libraries(&self, libraries: &[PathBuf]) -> Vec<PathBuf> {
+        self.libraries.iter().chain(libraries).cloned().collect()
+    }
+
+    /// Returns the given curve, or the configured curve if no curve is given.
+    pub fn curve(&self, curve: Option<Curve>) -> Curve {
+        curve
+            .or_else(|| self.curve.clone())
+            .unwrap_or_else(|| Curve::from_str(DEFAULT_CURVE).expect("valid default curve"))
+    }
+
+    /// Returns the given output level, or the configured level if no level is
+    /// given.
+    pub fn level(&self, level: Option<MessageCategory>) -> MessageCategory {
+        level.or(self.level).unwrap_or_else(|| {
+            MessageCategory::from_str(DEFAULT_LEVEL).expect("valid default level")
+        })
+    }
+
+    /// Returns the configured allow list extended with the given report IDs.
+    pub fn allow_list(&self, allow_list: &[String]) -> Vec<String> {
+        self.allow_list.iter().chain(allow_list).cloned().collect()
+    }
+
+    /// Returns the given fail-on level, or the configured level if no level is
+    /// given. If neither is set, any issue causes a non-zero exit code.
+    pub fn fail_on(&self, fail_on: Option<MessageCategory>) -> MessageCategory {
+        fail_on.or(self.fail_on).unwrap_or(MessageCategory::Info)
+    }
+
+    /// Returns true if the file is included by the configured include and
+    /// exclude patterns.
+    pub fn is_included(&self, path: &Path) -> bool {
+        if let Some(include) = &self.include {
+            if !include.is_match(path) {
+                return false;
+            }
+        }
+        if let Some(exclude) = &self.exclude {
+            if exclude.is_match(path) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Applies the configured severity overrides to the given report.
+    pub fn override_severity(&self, report: &mut Report) {
+        self.settings.override_severity(report);
+    }
+}
+
+/// Builds a glob set from the given patterns. Relative patterns are resolved
+/// against the directory of the configuration file. Returns `None` if there are
+/// no patterns.
+fn build_glob_set(root: &Path, patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = if Path::new(pattern).is_absolute() {
+            pattern.clone()
+        } else {
+            root.join(pattern).to_string_lossy().to_string()
+        };
+        builder.add(Glob::new(&pattern)?);
+    }
+    Ok(Some(builder.build()?))
+}
@@ -1,48 +1,331 @@
+mod config;
+
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
-use clap::{CommandFactory, Parser};
+use std::rc::Rc;
+use std::str::FromStr;
+use anyhow::{anyhow, Context};
+use clap::{CommandFactory, Parser, Subcommand, Args};
 
-use program_analysis::config;
+use program_analysis::call_graph::CallGraph;
+use program_analysis::analysis_context::AnalysisContext;
 use program_analysis::analysis_runner::AnalysisRunner;
+use program_analysis::cache::Cache;
+use program_analysis::pass_registry::PassRegistry;
+use program_analysis::rules::RuleSet;
 
+use program_structure::cfg::DotOptions;
 use program_structure::constants::Curve;
+use program_structure::ir::pretty_printer::{Annotations, PrettyPrinter};
 use program_structure::file_definition::FileID;
 use program_structure::report::Report;
 use program_structure::report::MessageCategory;
+use program_structure::report_code::ReportCode;
+use program_structure::baseline::{Baseline, BaselineEntry};
+use program_structure::fix::{apply_fixes, write_fixed_file};
+use program_structure::suppression::Suppressions;
+use program_structure::file_definition::FileLibrary;
+use program_structure::html_report::Html;
+use program_structure::report_formats::{Checkstyle, GitHub, GitLab, JUnit, Json};
 use program_structure::writers::{LogWriter, ReportWriter, SarifWriter, CachedStdoutWriter};
+use program_structure::writers::{
+    CheckstyleWriter, GitHubWriter, GitLabWriter, HtmlWriter, JUnitWriter, JsonWriter,
+};
+
+use config::Config;
+
+/// The analysis pass documentation, used by the `explain` subcommand.
+const ANALYSIS_PASSES_DOC: &str = include_str!("../../doc/analysis_passes.md");
 
 #[derive(Parser, Debug)]
-#[command(styles=cli_styles())]
+#[command(styles=cli_styles(), args_conflicts_with_subcommands = true)]
 /// A static analyzer and linter for Circom programs.
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Initial input file(s)
     #[clap(name = "INPUT")]
     input_files: Vec<PathBuf>,
 
-    /// Library file paths
-    #[clap(short = 'L', long = "library", name = "LIBRARIES")]
-    libraries: Vec<PathBuf>,
+    #[command(flatten)]
+    project: ProjectOptions,
+
+    /// Output level (INFO, WARNING, or ERROR) [default: WARNING]
+    #[clap(short = 'l', long = "level", name = "LEVEL")]
+    output_level: Option<MessageCategory>,
 
-    /// Output level (INFO, WARNING, or ERROR)
-    #[clap(short = 'l', long = "level", name = "LEVEL", default_value = config::DEFAULT_LEVEL)]
-    output_level: MessageCategory,
+    /// Exit with a non-zero exit code only if issues at or above this level are found
+    #[clap(long = "fail-on", name = "FAIL_LEVEL")]
+    fail_on: Option<MessageCategory>,
 
     /// Output analysis results to a Sarif file
     #[clap(short, long, name = "OUTPUT")]
     sarif_file: Option<PathBuf>,
 
+    /// Output analysis results in the given format (SARIF, JSON, JUNIT, CHECKSTYLE, GITLAB,
+    /// GITHUB, or HTML), optionally to a file using FORMAT=FILE
+    #[clap(short = 'f', long = "format", name = "FORMAT")]
+    outputs: Vec<Output>,
+
+    /// Only report issues that are not recorded in the given baseline file
+    #[clap(long = "baseline", name = "BASELINE")]
+    baseline_file: Option<PathBuf>,
+
+    /// Record all issues found in the given baseline file
+    #[clap(long = "write-baseline", name = "NEW_BASELINE", conflicts_with = "BASELINE")]
+    write_baseline_file: Option<PathBuf>,
+
+    /// Apply the machine-applicable fixes suggested for the reported issues
+    #[clap(long = "fix", conflicts_with = "NEW_BASELINE")]
+    fix: bool,
+
     /// Ignore results from given analysis passes
     #[clap(short = 'a', long = "allow", name = "ID")]
     allow_list: Vec<String>,
 
+    /// Only run the given analysis passes (by pass name, or report ID or name)
+    #[clap(long = "only", name = "PASS")]
+    only: Vec<String>,
+
+    /// Do not run the given analysis passes (by pass name, or report ID or name)
+    #[clap(long = "disable", name = "DISABLED_PASS")]
+    disable: Vec<String>,
+
+    /// Run the custom rules defined in the given rule file
+    #[clap(long = "rules", name = "RULES")]
+    rule_files: Vec<PathBuf>,
+
+    /// List the available analysis passes and exit
+    #[clap(long = "list-passes")]
+    list_passes: bool,
+
     /// Enable verbose output
     #[clap(short = 'v', long = "verbose")]
     verbose: bool,
 
-    /// Set curve (BN254, BLS12_381, or GOLDILOCKS)
-    #[clap(short = 'c', long = "curve", name = "NAME", default_value = config::DEFAULT_CURVE)]
-    curve: Curve,
+    /// Do not load or store template and function CFGs in the cache
+    #[clap(long = "no-cache")]
+    no_cache: bool,
+
+    /// Cache directory (defaults to the user cache directory)
+    #[clap(long = "cache-dir", name = "CACHE_DIR")]
+    cache_dir: Option<PathBuf>,
+}
+
+/// Options used to set up the analysis runner, shared by all commands analyzing
+/// a project.
+#[derive(Args, Debug)]
+struct ProjectOptions {
+    /// Library file paths
+    #[clap(short = 'L', long = "library", name = "LIBRARIES")]
+    libraries: Vec<PathBuf>,
+
+    /// Set curve (BN254, BLS12_381, GOLDILOCKS, GRUMPKIN, PALLAS, VESTA, SECQ256R1, or BLS12_377) [default: BN254]
+    #[clap(short = 'c', long = "curve", name = "NAME")]
+    curve: Option<Curve>,
+
+    /// Set a custom prime (in decimal, or in hexadecimal with a `0x` prefix)
+    #[clap(long = "prime", name = "PRIME", value_parser = Curve::from_prime, conflicts_with = "NAME")]
+    prime: Option<Curve>,
+
+    /// Configuration file (defaults to the nearest `circomspect.toml`)
+    #[clap(long = "config", name = "CONFIG")]
+    config_file: Option<PathBuf>,
+}
+
+impl ProjectOptions {
+    /// Returns an analysis runner using the curve and libraries given by the
+    /// command line options and the project configuration.
+    fn runner(&self, config: &Config) -> AnalysisRunner {
+        let curve = self.prime.clone().or_else(|| self.curve.clone());
+        let runner = AnalysisRunner::new(config.curve(curve))
+            .with_libraries(&config.libraries(&self.libraries));
+        match config.root() {
+            Some(root) => runner.with_root(root),
+            None => runner,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Export the template instantiation and function call graph
+    Graph(GraphOptions),
+    /// Export the control-flow graph of a template or function in Graphviz DOT format
+    Cfg(CfgOptions),
+    /// Print the intermediate representation of templates and functions
+    Ir(IrOptions),
+    /// Remove all cached template and function CFGs
+    ClearCache(ClearCacheOptions),
+    /// Print the documentation for the given report ID or name
+    Explain(ExplainOptions),
+}
+
+#[derive(Args, Debug)]
+struct GraphOptions {
+    /// Initial input file(s)
+    #[clap(name = "INPUT", required = true)]
+    input_files: Vec<PathBuf>,
+
+    #[command(flatten)]
+    project: ProjectOptions,
+
+    /// Output format (DOT or JSON)
+    #[clap(short = 'f', long = "format", name = "FORMAT", default_value = "DOT")]
+    format: GraphFormat,
+
+    /// Write the graph to the given file instead of stdout
+    #[clap(short = 'o', long = "output", name = "OUTPUT")]
+    output_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct CfgOptions {
+    /// Name of the template or function
+    #[clap(short = 'n', long = "name", name = "DEFINITION")]
+    name: String,
+
+    /// Initial input file(s)
+    #[clap(name = "INPUT", required = true)]
+    input_files: Vec<PathBuf>,
+
+    #[command(flatten)]
+    project: ProjectOptions,
+
+    /// Overlay the dominator tree
+    #[clap(long = "dominator-tree")]
+    dominator_tree: bool,
+
+    /// Overlay the dominance frontier of each basic block
+    #[clap(long = "dominance-frontier")]
+    dominance_frontier: bool,
+
+    /// Write the graph to the given file instead of stdout
+    #[clap(short = 'o', long = "output", name = "OUTPUT")]
+    output_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct IrOptions {
+    /// Name of the template or function (defaults to all definitions in the input files)
+    #[clap(short = 'n', long = "name", name = "DEFINITION")]
+    name: Option<String>,
+
+    /// Initial input file(s)
+    #[clap(name = "INPUT", required = true)]
+    input_files: Vec<PathBuf>,
+
+    #[command(flatten)]
+    project: ProjectOptions,
+
+    /// Annotate the IR with inferred facts (DEGREE, VALUE, TYPE, USES, or ALL)
+    #[clap(short = 'A', long = "annotate", name = "FACT")]
+    annotations: Vec<IrAnnotation>,
+
+    /// Write the IR to the given file instead of stdout
+    #[clap(short = 'o', long = "output", name = "OUTPUT")]
+    output_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct ClearCacheOptions {
+    /// Cache directory (defaults to the user cache directory)
+    #[clap(long = "cache-dir", name = "CACHE_DIR")]
+    cache_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct ExplainOptions {
+    /// Report ID (e.g. CS0005) or name (e.g. signal-assignment-statement)
+    #[clap(name = "ID")]
+    id: String,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum IrAnnotation {
+    Degree,
+    Value,
+    Type,
+    Uses,
+    All,
+}
+
+impl FromStr for IrAnnotation {
+    type Err = anyhow::Error;
+
+    fn from_str(annotation: &str) -> Result<IrAnnotation, Self::Err> {
+        match annotation.to_lowercase().as_str() {
+            "degree" => Ok(IrAnnotation::Degree),
+            "value" => Ok(IrAnnotation::Value),
+            "type" => Ok(IrAnnotation::Type),
+            "uses" => Ok(IrAnnotation::Uses),
+            "all" => Ok(IrAnnotation::All),
+            _ => Err(anyhow!("unknown annotation '{annotation}'")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum OutputFormat {
+    Sarif,
+    Json,
+    JUnit,
+    Checkstyle,
+    GitLab,
+    GitHub,
+    Html,
+}
+
+/// A machine-readable output format, together with an optional output file.
+#[derive(Clone, Debug)]
+struct Output {
+    format: OutputFormat,
+    output_file: Option<PathBuf>,
+}
+
+impl FromStr for Output {
+    type Err = anyhow::Error;
+
+    fn from_str(output: &str) -> Result<Output, Self::Err> {
+        let (format, output_file) = match output.split_once('=') {
+            Some((format, output_file)) => (format, Some(PathBuf::from(output_file))),
+            None => (output, None),
+        };
+        let format = match format.to_lowercase().as_str() {
+            "sarif" => OutputFormat::Sarif,
+            "json" => OutputFormat::Json,
+            "junit" => OutputFormat::JUnit,
+            "checkstyle" => OutputFormat::Checkstyle,
+            "gitlab" => OutputFormat::GitLab,
+            "github" => OutputFormat::GitHub,
+            "html" => OutputFormat::Html,
+            _ => return Err(anyhow!("unknown output format '{format}'")),
+        };
+        if matches!(format, OutputFormat::Sarif) && output_file.is_none() {
+            return Err(anyhow!("the Sarif output format requires an output file (SARIF=FILE)"));
+        }
+        Ok(Output { format, output_file })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum GraphFormat {
+    Dot,
+    Json,
+}
+
+impl FromStr for GraphFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> Result<GraphFormat, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "dot" => Ok(GraphFormat::Dot),
+            "json" => Ok(GraphFormat::Json),
+            _ => Err(anyhow!("unknown graph format '{format}'")),
+        }
+    }
 }
 
 /// Styles the help output for the [`Cli`].
@@ -73,61 +356,542 @@ fn filter_by_id(report: &Report, allow_list: &[String]) -> bool {
     !allow_list.contains(&report.id())
 }
 
+/// Returns true if a primary location of the report corresponds to a file
+/// included by the project configuration.
+fn filter_by_path(report: &Report, included_files: &HashSet<FileID>) -> bool {
+    report.primary_file_ids().iter().any(|file_id| included_files.contains(file_id))
+}
+
+/// Returns true if the report does not match a known issue in the baseline.
+fn filter_by_baseline(report: &Report, baseline: Option<&Baseline>) -> bool {
+    baseline.map_or(true, |baseline| !baseline.contains(report))
+}
+
+/// Applies the fixes attached to the given reports and updates the
+/// corresponding files.
+fn fix_reports(reports: &[Report], file_library: &FileLibrary, writer: &mut impl LogWriter) {
+    let result = apply_fixes(reports.iter().filter_map(Report::fix), file_library);
+    for file in &result.files {
+        match write_fixed_file(file) {
+            Ok(()) => writer.write_message(format!(
+                "Applied {} {} to `{}`.",
+                file.nof_fixes,
+                if file.nof_fixes == 1 { "fix" } else { "fixes" },
+                file.path.display()
+            )),
+            Err(error) => writer.write_message(format!("{error:#}")),
+        }
+    }
+    match result.nof_skipped {
+        0 => {}
+        1 => writer.write_message("1 fix could not be applied."),
+        n => writer.write_message(format!("{n} fixes could not be applied.")),
+    }
+}
+
+/// Returns a short description of a baseline entry.
+fn describe_entry(entry: &BaselineEntry) -> String {
+    let mut description = entry.id.clone();
+    if let Some(definition) = &entry.definition {
+        description.push_str(&format!(" in `{definition}`"));
+    }
+    if let Some(file) = &entry.file {
+        description.push_str(&format!(" ({file})"));
+    }
+    format!("{description}: {}", entry.message)
+}
+
+/// Returns the IDs of all files included by the project configuration.
+fn included_files(config: &Config, file_library: &FileLibrary) -> HashSet<FileID> {
+    file_library
+        .file_ids()
+        .filter(|file_id| match file_library.to_storage().get(*file_id) {
+            Ok(file) => config.is_included(Path::new(file.name())),
+            Err(_) => false,
+        })
+        .collect()
+}
+
+/// Reads the given configuration file, or the nearest `circomspect.toml` if no
+/// configuration file is given.
+fn load_config(config_file: Option<&Path>) -> Option<Config> {
+    let config = match config_file {
+        Some(config_file) => Config::from_file(config_file),
+        None => Config::discover(),
+    };
+    match config {
+        Ok(config) => Some(config),
+        Err(error) => {
+            CachedStdoutWriter::new(false).write_message(format!("{error:#}"));
+            None
+        }
+    }
+}
+
+/// Loads the custom rules from the given rule files and registers them as
+/// analysis passes.
+fn load_rules(rule_files: &[PathBuf], passes: &mut PassRegistry) -> anyhow::Result<()> {
+    for rule_file in rule_files {
+        RuleSet::from_file(rule_file)
+            .and_then(|rules| rules.register(passes))
+            .with_context(|| format!("failed to load rules from `{}`", rule_file.display()))?;
+    }
+    Ok(())
+}
+
+/// Builds the call graph of the given input files and writes it to `stdout` or
+/// the given output file.
+fn export_graph(options: GraphOptions) -> ExitCode {
+    let Some(config) = load_config(options.project.config_file.as_deref()) else {
+        return ExitCode::FAILURE;
+    };
+    let (runner, reports) = options.project.runner(&config).with_files(&options.input_files);
+
+    // Only parse errors are written to `stdout`.
+    let mut stdout_writer = CachedStdoutWriter::new(false)
+        .add_filter(|report: &Report| filter_by_level(report, &MessageCategory::Error));
+    if stdout_writer.write_reports(&reports, runner.file_library()) > 0 {
+        return ExitCode::FAILURE;
+    }
+    let graph = CallGraph::new(&runner);
+    let output = match options.format {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Json => {
+            match serde_json::to_string_pretty(&graph.to_json(runner.file_library())) {
+                Ok(json) => json + "\n",
+                Err(_) => return ExitCode::FAILURE,
+            }
+        }
+    };
+    write_output(&output, options.output_file, &mut stdout_writer)
+}
+
+/// Exports the CFG of the given template or function and writes it to `stdout`
+/// or the given output file.
+fn export_cfg(options: CfgOptions) -> ExitCode {
+    let Some(config) = load_config(options.project.config_file.as_deref()) else {
+        return ExitCode::FAILURE;
+    };
+    let (runner, reports) = options.project.runner(&config).with_files(&options.input_files);
+
+    // Only parse errors are written to `stdout`.
+    let mut stdout_writer = CachedStdoutWriter::new(false)
+        .add_filter(|report: &Report| filter_by_level(report, &MessageCategory::Error));
+    if stdout_writer.write_reports(&reports, runner.file_library()) > 0 {
+        return ExitCode::FAILURE;
+    }
+    let cfg = if runner.is_template(&options.name) {
+        runner.template(&options.name)
+    } else {
+        runner.function(&options.name)
+    };
+    let output = match cfg {
+        Ok(cfg) => cfg.to_dot(&DotOptions {
+            dominator_tree: options.dominator_tree,
+            dominance_frontier: options.dominance_frontier,
+        }),
+        Err(error) => {
+            stdout_writer.write_message(format!("Failed to generate CFG: {error}"));
+            return ExitCode::FAILURE;
+        }
+    };
+    write_output(&output, options.output_file, &mut stdout_writer)
+}
+
+/// Prints the IR of the given template or function (or all templates and
+/// functions defined in the input files) and writes it to `stdout` or the given
+/// output file.
+fn export_ir(options: IrOptions) -> ExitCode {
+    let Some(config) = load_config(options.project.config_file.as_deref()) else {
+        return ExitCode::FAILURE;
+    };
+    let (runner, reports) = options.project.runner(&config).with_files(&options.input_files);
+
+    // Only parse errors are written to `stdout`.
+    let mut stdout_writer = CachedStdoutWriter::new(false)
+        .add_filter(|report: &Report| filter_by_level(report, &MessageCategory::Error));
+    if stdout_writer.write_reports(&reports, runner.file_library()) > 0 {
+        return ExitCode::FAILURE;
+    }
+    let mut annotations = Annotations::default();
+    for annotation in options.annotations {
+        match annotation {
+            IrAnnotation::Degree => annotations.degree = true,
+            IrAnnotation::Value => annotations.value = true,
+            IrAnnotation::Type => annotations.variable_type = true,
+            IrAnnotation::Uses => annotations.variable_use = true,
+            IrAnnotation::All => annotations = Annotations::all(),
+        }
+    }
+    let names = match options.name {
+        Some(name) => vec![name],
+        None => {
+            let mut function_names = runner.function_names(true);
+            let mut template_names = runner.template_names(true);
+            function_names.sort();
+            template_names.sort();
+            function_names.into_iter().chain(template_names).collect()
+        }
+    };
+    let printer = PrettyPrinter::new(annotations);
+    let mut output = Vec::new();
+    for name in names {
+        let cfg =
+            if runner.is_template(&name) { runner.template(&name) } else { runner.function(&name) };
+        match cfg {
+            Ok(cfg) => output.push(printer.print_cfg(&cfg)),
+            Err(error) => {
+                stdout_writer.write_message(format!("Failed to generate IR: {error}"));
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    write_output(&output.join("\n"), options.output_file, &mut stdout_writer)
+}
+
+/// Writes the output to the given file, or to `stdout` if no file is given.
+fn write_output<W: LogWriter>(
+    output: &str,
+    output_file: Option<PathBuf>,
+    writer: &mut W,
+) -> ExitCode {
+    match output_file {
+        Some(output_file) => match std::fs::write(&output_file, output) {
+            Ok(()) => {
+                writer.write_message(format!("Output written to `{}`.", output_file.display()));
+                ExitCode::SUCCESS
+            }
+            Err(error) => {
+                writer.write_message(format!(
+                    "Failed to write output to `{}`: {error}",
+                    output_file.display()
+                ));
+                ExitCode::FAILURE
+            }
+        },
+        None => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// Returns the on-disk cache of template and function CFGs, if a cache
+/// directory is available.
+fn cfg_cache(cache_dir: Option<&Path>) -> Option<Cache> {
+    let directory = match cache_dir {
+        Some(cache_dir) => cache_dir.to_path_buf(),
+        None => dirs::cache_dir()?.join("circomspect"),
+    };
+    Some(Cache::new(&directory, env!("CARGO_PKG_VERSION")))
+}
+
+/// Removes all cached template and function CFGs.
+fn clear_cache(options: ClearCacheOptions) -> ExitCode {
+    let mut stdout_writer = CachedStdoutWriter::new(false);
+    let Some(cache) = cfg_cache(options.cache_dir.as_deref()) else {
+        stdout_writer.write_message("Failed to find the cache directory.");
+        return ExitCode::FAILURE;
+    };
+    match cache.clear() {
+        Ok(()) => {
+            stdout_writer.write_message(format!("Removed `{}`.", cache.directory().display()));
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            stdout_writer.write_message(format!(
+                "Failed to remove `{}`: {error}",
+                cache.directory().display()
+            ));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Lists all analysis passes, together with the reports emitted by each pass.
+fn list_passes(passes: &PassRegistry) -> ExitCode {
+    let mut output = String::new();
+    for pass in passes.iter() {
+        let status = if pass.is_enabled() { "enabled" } else { "disabled" };
+        output.push_str(&format!("{} ({}, {status})\n", pass.name(), pass.kind()));
+        output.push_str(&format!("    {}\n", pass.description()));
+        let codes = pass
+            .codes()
+            .iter()
+            .map(|code| format!("{} ({})", code.id(), code.name()))
+            .collect::<Vec<_>>();
+        output.push_str(&format!("    Reports: {}\n", codes.join(", ")));
+    }
+    write_output(&output, None, &mut CachedStdoutWriter::new(false))
+}
+
+/// Returns the section of the analysis pass documentation with the given
+/// anchor, if it exists.
+fn doc_section(anchor: &str) -> Option<String> {
+    let mut section: Option<Vec<&str>> = None;
+    for line in ANALYSIS_PASSES_DOC.lines() {
+        if let Some(heading) = line.strip_prefix("### ") {
+            if section.is_some() {
+                break;
+            }
+            if slugify(heading) == anchor {
+                section = Some(vec![line]);
+            }
+        } else if let Some(section) = section.as_mut() {
+            section.push(line);
+        }
+    }
+    section.map(|lines| lines.join("\n").trim_end().to_string())
+}
+
+/// Returns the anchor GitHub generates for the given Markdown heading.
+fn slugify(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Prints the description and documentation of the given report.
+fn explain(options: ExplainOptions, passes: &PassRegistry) -> ExitCode {
+    let mut stdout_writer = CachedStdoutWriter::new(false);
+    let code = ReportCode::circomspect_codes()
+        .into_iter()
+        .chain(passes.iter().flat_map(|pass| pass.codes().to_vec()))
+        .find(|code| code.id().eq_ignore_ascii_case(&options.id) || code.name() == options.id);
+    let Some(code) = code else {
+        stdout_writer.write_message(format!("Unknown report ID or name `{}`.", options.id));
+        return ExitCode::FAILURE;
+    };
+    let mut output = format!("{} ({}): {}\n", code.id(), code.name(), code.description());
+    let reported_by = passes
+        .iter()
+        .filter(|pass| pass.codes().iter().any(|other| other.id() == code.id()))
+        .map(|pass| pass.name())
+        .collect::<Vec<_>>();
+    if !reported_by.is_empty() {
+        output.push_str(&format!("Reported by: {}\n", reported_by.join(", ")));
+    }
+    let section = code
+        .url()
+        .and_then(|url| url.split_once('#').map(|(_, anchor)| anchor.to_string()))
+        .and_then(|anchor| doc_section(&anchor));
+    if let Some(section) = section {
+        output.push_str(&format!("\n{section}\n"));
+    }
+    write_output(&output, None, &mut stdout_writer)
+}
+
 fn main() -> ExitCode {
     // Initialize logger and options.
     pretty_env_logger::init();
     let options = Cli::parse();
-    if options.input_files.is_empty() {
+    match options.command {
+        Some(Command::Graph(options)) => return export_graph(options),
+        Some(Command::Cfg(options)) => return export_cfg(options),
+        Some(Command::Ir(options)) => return export_ir(options),
+        Some(Command::ClearCache(options)) => return clear_cache(options),
+        Some(Command::Explain(explain_options)) => {
+            return explain(explain_options, &PassRegistry::default())
+        }
+        None => {}
+    }
+
+    if options.input_files.is_empty() && !options.list_passes {
         match Cli::command().print_help() {
             Ok(()) => return ExitCode::SUCCESS,
             Err(_) => return ExitCode::FAILURE,
         }
     }
 
+    // Read the project configuration.
+    let Some(config) = load_config(options.project.config_file.as_deref()) else {
+        return ExitCode::FAILURE;
+    };
+
+    // Select the analysis passes to run, including any custom rules.
+    let mut passes = PassRegistry::default();
+    if let Err(error) = load_rules(&config.rules(&options.rule_files), &mut passes) {
+        CachedStdoutWriter::new(false).write_message(format!("{error:#}"));
+        return ExitCode::FAILURE;
+    }
+    if let Err(error) = passes.select(&options.only, &options.disable) {
+        CachedStdoutWriter::new(false)
+            .write_message(format!("{error} Use `--list-passes` to list all passes."));
+        return ExitCode::FAILURE;
+    }
+    if options.list_passes {
+        return list_passes(&passes);
+    }
+    let output_level = config.level(options.output_level);
+    let fail_on = config.fail_on(options.fail_on);
+    let allow_list = config.allow_list(&options.allow_list);
+
     // Set up analysis runner.
-    let (mut runner, reports) = AnalysisRunner::new(options.curve)
-        .with_libraries(&options.libraries)
-        .with_files(&options.input_files);
+    let mut runner =
+        options.project.runner(&config).with_settings(config.settings.clone()).with_passes(passes);
+    if !options.no_cache {
+        if let Some(cache) = cfg_cache(options.cache_dir.as_deref()) {
+            runner = runner.with_cache(cache);
+        }
+    }
+    let (mut runner, mut reports) = runner.with_files(&options.input_files);
+    for report in &mut reports {
+        config.override_severity(report);
+    }
+
+    // Read the baseline of known issues.
+    let baseline = match &options.baseline_file {
+        Some(baseline_file) => match Baseline::read(baseline_file, runner.file_library()) {
+            Ok(baseline) => Some(Rc::new(baseline)),
+            Err(error) => {
+                CachedStdoutWriter::new(false).write_message(format!("{error:#}"));
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
 
     // Set up writer and write reports to `stdout`.
-    let allow_list = options.allow_list.clone();
     let user_inputs = runner.file_library().user_inputs().clone();
+    let included_files = included_files(&config, runner.file_library());
+    let suppressions = Rc::new(Suppressions::new(runner.file_library()));
+    let filter_suppressions = suppressions.clone();
+    let filter_allow_list = allow_list.clone();
+    let filter_baseline = baseline.clone();
     let mut stdout_writer = CachedStdoutWriter::new(options.verbose)
-        .add_filter(move |report: &Report| filter_by_level(report, &options.output_level))
+        // Suppressions and baseline findings are matched against all reports
+        // before any other filter is applied. Otherwise, suppressions and
+        // findings matching filtered reports would be reported as unused.
+        .add_filter(move |report: &Report| {
+            let is_suppressed = filter_suppressions.is_suppressed(report);
+            let is_known = !filter_by_baseline(report, filter_baseline.as_deref());
+            !is_suppressed && !is_known
+        })
+        .add_filter(move |report: &Report| filter_by_level(report, &output_level))
         .add_filter(move |report: &Report| filter_by_file(report, &user_inputs))
-        .add_filter(move |report: &Report| filter_by_id(report, &allow_list));
-    stdout_writer.write_reports(&reports, runner.file_library());
+        .add_filter(move |report: &Report| filter_by_path(report, &included_files))
+        .add_filter(move |report: &Report| filter_by_id(report, &filter_allow_list));
+    if let (true, Some(path)) = (options.verbose, &config.path) {
+        stdout_writer.write_message(format!("Using configuration file `{}`.", path.display()));
+    }
+    let nof_parse_reports = stdout_writer.write_reports(&reports, runner.file_library());
 
     // Analyze functions and templates in user provided input files.
     runner.analyze_functions(&mut stdout_writer, true);
     runner.analyze_templates(&mut stdout_writer, true);
 
-    // If a Sarif file is passed to the program we write the reports to it.
+    // Analyze template instances reachable from the main component.
+    runner.analyze_instances(&mut stdout_writer, true);
+
+    // Report suppression comments that did not suppress any reports.
+    stdout_writer.write_reports(&suppressions.unused_suppressions(), runner.file_library());
+
+    // List the known issues from the baseline that are no longer found.
+    if let Some(baseline) = baseline {
+        let fixed = baseline.fixed();
+        if !fixed.is_empty() {
+            stdout_writer.write_message(format!(
+                "{} of {} issues in the baseline are no longer found:",
+                fixed.len(),
+                baseline.len()
+            ));
+            for entry in fixed {
+                stdout_writer.write_message(format!("  fixed: {}", describe_entry(entry)));
+            }
+        }
+    }
+
+    // Record all issues found if a new baseline file is passed to the program.
+    if let Some(baseline_file) = options.write_baseline_file {
+        return match Baseline::write(&baseline_file, stdout_writer.reports(), runner.file_library())
+        {
+            Ok(n) => {
+                stdout_writer.write_message(format!(
+                    "Baseline with {n} issues written to `{}`.",
+                    baseline_file.display()
+                ));
+                ExitCode::SUCCESS
+            }
+            Err(error) => {
+                stdout_writer.write_message(format!("{error:#}"));
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // Write the reports in the requested output formats. If a Sarif file is
+    // passed to the program we write the reports to it.
+    let mut outputs = options.outputs;
     if let Some(sarif_file) = options.sarif_file {
-        let allow_list = options.allow_list.clone();
-        let user_inputs = runner.file_library().user_inputs().clone();
-        let mut sarif_writer = SarifWriter::new(&sarif_file)
-            .add_filter(move |report: &Report| filter_by_level(report, &options.output_level))
-            .add_filter(move |report: &Report| filter_by_file(report, &user_inputs))
-            .add_filter(move |report: &Report| filter_by_id(report, &allow_list));
-        if sarif_writer.write_reports(stdout_writer.reports(), runner.file_library()) > 0 {
-            stdout_writer.write_message(&format!("Result written to `{}`.", sarif_file.display()));
+        outputs.push(Output { format: OutputFormat::Sarif, output_file: Some(sarif_file) });
+    }
+    let mut definitions = runner.function_names(true);
+    definitions.extend(runner.template_names(true));
+    let reports = stdout_writer.reports().clone();
+    for output in outputs {
+        let mut writer: Box<dyn ReportWriter> = match output.format {
+            OutputFormat::Sarif => {
+                // Reports from the parser are recorded as tool notifications.
+                let (parse_reports, analysis_reports) = reports.split_at(nof_parse_reports);
+                let Some(sarif_file) = &output.output_file else {
+                    continue;
+                };
+                let mut sarif_writer = SarifWriter::new(sarif_file)
+                    .with_tool_version(env!("CARGO_PKG_VERSION"))
+                    .with_notifications(parse_reports);
+                if sarif_writer.write_reports(analysis_reports, runner.file_library()) > 0 {
+                    stdout_writer
+                        .write_message(format!("Result written to `{}`.", sarif_file.display()));
+                }
+                continue;
+            }
+            OutputFormat::Json => Box::new(JsonWriter::new(Json, output.output_file.as_deref())),
+            OutputFormat::JUnit => Box::new(JUnitWriter::new(
+                JUnit::new(definitions.clone()),
+                output.output_file.as_deref(),
+            )),
+            OutputFormat::Checkstyle => {
+                Box::new(CheckstyleWriter::new(Checkstyle, output.output_file.as_deref()))
+            }
+            OutputFormat::GitLab => {
+                Box::new(GitLabWriter::new(GitLab, output.output_file.as_deref()))
+            }
+            OutputFormat::GitHub => {
+                Box::new(GitHubWriter::new(GitHub, output.output_file.as_deref()))
+            }
+            OutputFormat::Html => Box::new(HtmlWriter::new(Html, output.output_file.as_deref())),
+        };
+        if writer.write_reports(&reports, runner.file_library()) > 0 {
+            if let Some(output_file) = output.output_file {
+                stdout_writer
+                    .write_message(format!("Result written to `{}`.", output_file.display()));
+            }
         }
     }
 
-    // Use the exit code to indicate if any issues were found.
+    // Apply the fixes attached to the reported issues.
+    if options.fix {
+        let reports = stdout_writer.reports().clone();
+        fix_reports(&reports, runner.file_library(), &mut stdout_writer);
+    }
+
+    // Use the exit code to indicate if any issues at or above the fail-on
+    // level were found.
     match stdout_writer.reports_written() {
-        0 => {
-            stdout_writer.write_message("No issues found.");
-            ExitCode::SUCCESS
-        }
-        1 => {
-            stdout_writer.write_message("1 issue found.");
-            ExitCode::FAILURE
-        }
-        n => {
-            stdout_writer.write_message(&format!("{n} issues found."));
-            ExitCode::FAILURE
-        }
+        0 => stdout_writer.write_message("No issues found."),
+        1 => stdout_writer.write_message("1 issue found."),
+        n => stdout_writer.write_message(format!("{n} issues found.")),
+    }
+    if stdout_writer.reports().iter().any(|report| report.category() >= &fail_on) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
     }
 }
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SRC: &str = r#"
+pragma circom 2.0.0;
+
+template T() {
+    signal input in;
+    signal output out;
+
+    // circomspect: allow CS0004, CS0013
+    var x = in + 1;
+    out <== x;
+}
+
+component main = T();
+"#;
+
+/// Creates a new project directory containing the given circuit.
+fn project_dir(name: &str, src: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("circomspect-test-{name}-{}", std::process::id()))
+        .join("project");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("circomspect.toml"), "").unwrap();
+    fs::write(dir.join("main.circom"), src).unwrap();
+    dir
+}
+
+/// Runs Circomspect in the given directory and returns the output.
+fn circomspect(current_dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_circomspect"))
+        .current_dir(current_dir)
+        .arg("--no-cache")
+        .args(args)
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).to_string() + &String::from_utf8_lossy(&output.stderr)
+}
+
+#[test]
+fn test_suppressed_info_report() {
+    let dir = project_dir("suppression", SRC);
+
+    // The `CS0004` report is suppressed even though it is below the output level.
+    let output = circomspect(&dir, &["main.circom"]);
+    assert!(!output.contains("The suppression of `CS0004` is never used."));
+    assert!(output.contains("The suppression of `CS0013` is never used."));
+
+    let output = circomspect(&dir, &["main.circom", "-a", "CS0013"]);
+    assert!(!output.contains("The suppression of `CS0004` is never used."));
+    assert!(output.contains("The suppression of `CS0013` is never used."));
+    fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_filtered_baseline_findings() {
+    let dir = project_dir("baseline", SRC);
+    let output = circomspect(&dir, &["main.circom", "--write-baseline", "baseline.json"]);
+    assert!(output.contains("Baseline with 1 issues written"));
+
+    // Known issues filtered by `--allow` are not reported as fixed.
+    let output = circomspect(&dir, &["main.circom", "--baseline", "baseline.json", "-a", "CS0022"]);
+    assert!(!output.contains("no longer found"));
+    assert!(output.contains("No issues found."));
+
+    // Findings are matched relative to the project root, not the current directory.
+    let parent = dir.parent().unwrap();
+    let output = circomspect(
+        parent,
+        &[
+            "project/main.circom",
+            "--config",
+            "project/circomspect.toml",
+            "--baseline",
+            "project/baseline.json",
+        ],
+    );
+    assert!(!output.contains("no longer found"));
+    assert!(output.contains("No issues found."));
+    fs::remove_dir_all(parent).unwrap();
+}
+
+#[test]
+fn test_fix_multiple_declarations() {
+    let src = r#"
+pragma circom 2.0.0;
+
+template T(n) {
+    signal input in;
+    signal output out;
+
+    var unused = n + 1;
+    var a = n + 1, b = n * 2;
+    out <== in * b;
+}
+
+component main = T(2);
+"#;
+    let dir = project_dir("fix", src);
+    let output = circomspect(&dir, &["main.circom", "--fix"]);
+    assert!(output.contains("Applied 1 fix"));
+
+    // Only the declaration of `unused` is removed, since removing the
+    // declaration of `a` would also remove the declaration of `b`.
+    let fixed = fs::read_to_string(dir.join("main.circom")).unwrap();
+    assert_eq!(fixed, src.replace("    var unused = n + 1;\n", ""));
+    fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_unknown_severity_key() {
+    let dir = project_dir("severity", SRC);
+    fs::write(dir.join("circomspect.toml"), "[severity]\nsignal-asignment = \"ERROR\"\n").unwrap();
+    let output = circomspect(&dir, &["main.circom"]);
+    assert!(output.contains("unknown report ID or name `signal-asignment` in `severity`"));
+
+    fs::write(dir.join("circomspect.toml"), "[severity]\nCS0005 = \"ERROR\"\n").unwrap();
+    let output = circomspect(&dir, &["main.circom"]);
+    assert!(!output.contains("unknown report ID or name"));
+    fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+}
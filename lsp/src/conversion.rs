@@ -0,0 +1,150 @@
+use std::ops::Range;
+
+use lsp_types::{
+    CodeDescription, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location,
+    NumberOrString, Position, Url,
+};
+
+use program_structure::file_definition::{FileID, FileLibrary};
+use program_structure::report::{MessageCategory, Report};
+
+/// Converts a byte offset into an LSP position. (LSP positions count UTF-16
+/// code units rather than bytes.)
+pub fn offset_to_position(source: &str, offset: usize) -> Position {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let line = source[..line_start].matches('\n').count();
+    let character = source[line_start..offset].encode_utf16().count();
+    Position::new(line as u32, character as u32)
+}
+
+/// Converts an LSP position into a byte offset. Positions past the end of a
+/// line are mapped to the end of the line.
+pub fn position_to_offset(source: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        if index == position.line as usize {
+            let line = line.trim_end_matches(['\n', '\r']);
+            let mut character = 0;
+            for (offset, c) in line.char_indices() {
+                if character >= position.character as usize {
+                    return line_start + offset;
+                }
+                character += c.len_utf16();
+            }
+            return line_start + line.len();
+        }
+        line_start += line.len();
+    }
+    source.len()
+}
+
+/// Returns the LSP location corresponding to the given file and byte range.
+pub fn to_location(
+    file_library: &FileLibrary,
+    file_id: FileID,
+    range: &Range<usize>,
+) -> Option<Location> {
+    let file = file_library.to_storage().get(file_id).ok()?;
+    let uri = Url::from_file_path(file.name()).ok()?;
+    let range = lsp_types::Range::new(
+        offset_to_position(file.source(), range.start),
+        offset_to_position(file.source(), range.end),
+    );
+    Some(Location::new(uri, range))
+}
+
+/// Converts a report into an LSP diagnostic located at the first primary label
+/// of the report. Remaining labels are included as related information.
+pub fn to_diagnostic(report: &Report, file_library: &FileLibrary) -> Option<Diagnostic> {
+    let (primary, others) = report.primary().split_first()?;
+    let location = to_location(file_library, primary.file_id, &primary.range)?;
+
+    let mut message = report.message().clone();
+    for note in report.notes() {
+        message.push('\n');
+        message.push_str(note);
+    }
+    if let Some(instance) = report.instance() {
+        message.push_str(&format!(
+            "\nThis issue was found when analyzing the template instance `{instance}`."
+        ));
+    }
+    let related_information = others
+        .iter()
+        .chain(report.secondary())
+        .filter_map(|label| {
+            let location = to_location(file_library, label.file_id, &label.range)?;
+            Some(DiagnosticRelatedInformation { location, message: label.message.clone() })
+        })
+        .collect::<Vec<_>>();
+    let severity = match report.category() {
+        MessageCategory::Error => DiagnosticSeverity::ERROR,
+        MessageCategory::Warning => DiagnosticSeverity::WARNING,
+        MessageCategory::Info => DiagnosticSeverity::INFORMATION,
+    };
+    Some(Diagnostic {
+        range: location.range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String(report.id())),
+        code_description: report
+            .code()
+            .url()
+            .and_then(|url| Url::parse(&url).ok())
+            .map(|href| CodeDescription { href }),
+        source: Some("circomspect".to_string()),
+        message,
+        related_information: (!related_information.is_empty()).then_some(related_information),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use program_structure::report_code::ReportCode;
+
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        let source = "a\n€b\r\nc";
+        for (offset, position) in
+            [(0, (0, 0)), (2, (1, 0)), (5, (1, 1)), (6, (1, 2)), (8, (2, 0)), (9, (2, 1))]
+        {
+            let position = Position::new(position.0, position.1);
+            assert_eq!(offset_to_position(source, offset), position);
+            assert_eq!(position_to_offset(source, position), offset);
+        }
+        // Positions past the end of a line are mapped to the end of the line.
+        assert_eq!(position_to_offset(source, Position::new(1, 10)), 6);
+        assert_eq!(position_to_offset(source, Position::new(10, 0)), source.len());
+    }
+
+    #[test]
+    fn test_diagnostic() {
+        let source = "template T() {\n    x <-- 1;\n}\n";
+        let mut file_library = FileLibrary::new();
+        let file_id = file_library.add_file("/test.circom".to_string(), source.to_string(), true);
+        let mut report = Report::warning(
+            "Signal assignment.".to_string(),
+            ReportCode::SignalAssignmentStatement,
+        );
+        report.add_primary(19..27, file_id, "here".to_string());
+        report.add_secondary(25..26, file_id, Some("value".to_string()));
+        report.add_note("Use `<==`.".to_string());
+
+        let diagnostic = to_diagnostic(&report, &file_library).unwrap();
+        assert_eq!(diagnostic.range.start, Position::new(1, 4));
+        assert_eq!(diagnostic.range.end, Position::new(1, 12));
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostic.code, Some(NumberOrString::String("CS0005".to_string())));
+        assert_eq!(diagnostic.message, "Signal assignment.\nUse `<==`.");
+        let related_information = diagnostic.related_information.unwrap();
+        assert_eq!(related_information.len(), 1);
+        assert_eq!(related_information[0].message, "value");
+        assert_eq!(related_information[0].location.range.start, Position::new(1, 10));
+    }
+}
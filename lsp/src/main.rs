@@ -0,0 +1,81 @@
+mod conversion;
+mod navigation;
+mod server;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Result;
+use clap::Parser;
+use log::{error, info};
+use lsp_server::Connection;
+use lsp_types::{
+    HoverProviderCapability, InitializeParams, OneOf, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind,
+};
+
+use program_structure::constants::Curve;
+
+use server::Server;
+
+#[derive(Parser, Debug)]
+#[command(styles=cli_styles())]
+/// A language server for Circom, based on Circomspect
+struct Cli {
+    /// Library file paths
+    #[clap(short = 'L', long = "library", name = "LIBRARIES")]
+    libraries: Vec<PathBuf>,
+
+    /// Set curve (BN254, BLS12_381, GOLDILOCKS, GRUMPKIN, PALLAS, VESTA, SECQ256R1, or BLS12_377)
+    #[clap(short = 'c', long = "curve", name = "NAME", default_value = "BN254")]
+    curve: Curve,
+
+    /// Set a custom prime (in decimal, or in hexadecimal with a `0x` prefix)
+    #[clap(long = "prime", name = "PRIME", value_parser = Curve::from_prime, conflicts_with = "NAME")]
+    prime: Option<Curve>,
+}
+
+/// Styles the help output for the [`Cli`].
+fn cli_styles() -> clap::builder::Styles {
+    use clap::builder::styling::*;
+
+    Styles::styled()
+        .header(AnsiColor::Yellow.on_default())
+        .usage(AnsiColor::Green.on_default())
+        .literal(AnsiColor::Green.on_default())
+        .placeholder(AnsiColor::Green.on_default())
+}
+
+/// Runs the language server over `stdin` and `stdout`.
+fn run(options: Cli) -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    let params = connection.initialize(serde_json::to_value(capabilities)?)?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+    if let Some(client_info) = params.client_info {
+        info!("connected to `{}`", client_info.name);
+    }
+    Server::new(options.prime.unwrap_or(options.curve), options.libraries).run(&connection)?;
+    // The connection must be dropped before joining the IO threads.
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    // The logger writes to `stderr` since `stdout` is used by the protocol.
+    pretty_env_logger::init();
+    let options = Cli::parse();
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            error!("{err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::ops::Range;
+
+use program_structure::cfg::Cfg;
+use program_structure::ir::degree_meta::{DegreeMeta, DegreeRange};
+use program_structure::ir::{Statement, VariableName};
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Returns the identifier at the given byte offset, together with its range.
+pub fn identifier_at(source: &str, offset: usize) -> Option<(Range<usize>, &str)> {
+    let offset = offset.min(source.len());
+    if !source.is_char_boundary(offset) {
+        return None;
+    }
+    let start = source[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_identifier_char(*c))
+        .last()
+        .map_or(offset, |(index, _)| index);
+    let end = source[offset..]
+        .char_indices()
+        .find(|(_, c)| !is_identifier_char(*c))
+        .map_or(source.len(), |(index, _)| offset + index);
+    if start == end || source[start..].starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((start..end, &source[start..end]))
+}
+
+/// Returns the range of the name of a template or function, given the source
+/// and the start of the body of the definition. This is the last occurrence of
+/// the name (as a complete identifier) before the body.
+pub fn definition_name_range(source: &str, name: &str, body_start: usize) -> Option<Range<usize>> {
+    let header = source.get(..body_start)?;
+    header
+        .match_indices(name)
+        .map(|(start, _)| start..start + name.len())
+        .filter(|range| {
+            identifier_at(source, range.start).map_or(false, |(identifier, _)| identifier == *range)
+        })
+        .last()
+}
+
+/// Returns a Markdown description of the variable or signal with the given
+/// name used at the given offset. The description contains the declared type of
+/// the variable, and the inferred degree of the expressions assigned to it.
+pub fn describe_variable(cfg: &Cfg, name: &str, offset: usize) -> Option<String> {
+    // Variables are renamed when they shadow other variables, and versioned
+    // when the CFG is converted to SSA. We use the innermost use of the name
+    // at the given offset to find the corresponding IR variable.
+    let mut variable: Option<(usize, VariableName)> = None;
+    for stmt in cfg.iter().flat_map(|basic_block| basic_block.iter()) {
+        for variable_use in stmt.meta().variable_knowledge().variables_used() {
+            let location = variable_use.meta().file_location();
+            if variable_use.name().name() != name || !location.contains(&offset) {
+                continue;
+            }
+            if variable.as_ref().map_or(true, |(length, _)| location.len() < *length) {
+                variable = Some((location.len(), variable_use.name().clone()));
+            }
+        }
+    }
+    let variable = match variable {
+        Some((_, variable)) => variable,
+        None => cfg
+            .declarations()
+            .iter()
+            .find(|(variable, declaration)| {
+                variable.name() == name && declaration.file_location().contains(&offset)
+            })
+            .map(|(variable, _)| variable.clone())?,
+    };
+    // Declarations of local variables are versioned.
+    let variable_type = cfg
+        .declarations()
+        .iter()
+        .find(|(name, _)| name.without_version() == variable.without_version())
+        .map(|(_, declaration)| declaration.variable_type())?;
+
+    // Compute the degree of the expressions assigned to the variable. If the
+    // variable is versioned we only consider the given version.
+    let degrees = cfg
+        .iter()
+        .flat_map(|basic_block| basic_block.iter())
+        .filter_map(|stmt| match stmt {
+            Statement::Substitution { var, rhe, .. }
+                if (variable.version().is_some() && *var == variable)
+                    || (variable.version().is_none() && var.without_version() == variable) =>
+            {
+                rhe.degree().cloned()
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut description = format!("```circom\n{variable_type} {name}\n```");
+    if !degrees.is_empty() {
+        let range = DegreeRange::iter_inf(&degrees);
+        if range.start() == range.end() {
+            description.push_str(&format!("\n\nInferred degree: {:?}", range.start()));
+        } else {
+            description.push_str(&format!(
+                "\n\nInferred degree: {:?} to {:?}",
+                range.start(),
+                range.end()
+            ));
+        }
+    }
+    Some(description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier_at() {
+        let source = "c <== Num2Bits(n)(in);";
+        assert_eq!(identifier_at(source, 0), Some((0..1, "c")));
+        assert_eq!(identifier_at(source, 1), Some((0..1, "c")));
+        assert_eq!(identifier_at(source, 9), Some((6..14, "Num2Bits")));
+        assert_eq!(identifier_at(source, 3), None);
+        assert_eq!(identifier_at(source, 22), None);
+    }
+
+    #[test]
+    fn test_definition_name_range() {
+        let source = "template A(A_size, n) {\n}\n";
+        let body_start = source.find('{').unwrap();
+        assert_eq!(definition_name_range(source, "A", body_start), Some(9..10));
+        assert_eq!(definition_name_range(source, "B", body_start), None);
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::{debug, warn};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+    PublishDiagnostics,
+};
+use lsp_types::request::{GotoDefinition, HoverRequest, Request as _};
+use lsp_types::{
+    Diagnostic, DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams, MarkupContent,
+    MarkupKind, PublishDiagnosticsParams, Url,
+};
+
+use program_analysis::analysis_context::AnalysisContext;
+use program_analysis::analysis_runner::AnalysisRunner;
+use program_structure::constants::Curve;
+use program_structure::file_definition::{FileID, FileLibrary};
+use program_structure::report::{Report, ReportCollection};
+use program_structure::suppression::Suppressions;
+use program_structure::writers::{LogWriter, ReportWriter};
+
+use crate::conversion::{offset_to_position, position_to_offset, to_diagnostic, to_location};
+use crate::navigation::{definition_name_range, describe_variable, identifier_at};
+
+/// A writer which collects the reports produced by the analysis runner.
+#[derive(Default)]
+struct ReportCollector {
+    reports: ReportCollection,
+}
+
+impl LogWriter for ReportCollector {
+    fn write_messages<D: Display>(&mut self, messages: &[D]) {
+        for message in messages {
+            debug!("{message}");
+        }
+    }
+}
+
+impl ReportWriter for ReportCollector {
+    fn write_reports(&mut self, reports: &[Report], _: &FileLibrary) -> usize {
+        self.reports.extend_from_slice(reports);
+        reports.len()
+    }
+
+    fn reports_written(&self) -> usize {
+        self.reports.len()
+    }
+}
+
+/// The language server state. Each open document is analyzed as a separate
+/// program (including any files included by the document). The analysis runner
+/// for each document is kept in memory, so that CFGs of templates and functions
+/// defined in unchanged files can be reused when a document changes.
+pub struct Server {
+    curve: Curve,
+    libraries: Vec<PathBuf>,
+    /// The contents of open documents, indexed by canonical path.
+    documents: HashMap<PathBuf, String>,
+    /// The analysis runner for each open document.
+    runners: HashMap<PathBuf, AnalysisRunner>,
+}
+
+impl Server {
+    pub fn new(curve: Curve, libraries: Vec<PathBuf>) -> Server {
+        Server { curve, libraries, documents: HashMap::new(), runners: HashMap::new() }
+    }
+
+    /// Handles messages from the client until the client requests shutdown.
+    pub fn run(&mut self, connection: &Connection) -> Result<()> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => {
+                    for notification in self.handle_notification(notification) {
+                        connection.sender.send(Message::Notification(notification))?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Response {
+        match request.method.as_str() {
+            HoverRequest::METHOD => self.dispatch::<HoverRequest>(request, Server::hover),
+            GotoDefinition::METHOD => {
+                self.dispatch::<GotoDefinition>(request, Server::goto_definition)
+            }
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported request `{}`", request.method),
+            ),
+        }
+    }
+
+    fn dispatch<R: lsp_types::request::Request>(
+        &mut self,
+        request: Request,
+        handler: impl FnOnce(&mut Server, R::Params) -> R::Result,
+    ) -> Response {
+        match serde_json::from_value::<R::Params>(request.params) {
+            Ok(params) => Response::new_ok(request.id, handler(self, params)),
+            Err(error) => {
+                Response::new_err(request.id, ErrorCode::InvalidParams as i32, error.to_string())
+            }
+        }
+    }
+
+    /// Handles a notification from the client. Returns the notifications
+    /// (diagnostics) to send to the client.
+    fn handle_notification(&mut self, notification: Notification) -> Vec<Notification> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                match serde_json::from_value::<DidOpenTextDocumentParams>(notification.params) {
+                    Ok(params) => {
+                        self.update_document(&params.text_document.uri, params.text_document.text)
+                    }
+                    Err(error) => {
+                        warn!("invalid notification parameters: {error}");
+                        Vec::new()
+                    }
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                match serde_json::from_value::<DidChangeTextDocumentParams>(notification.params) {
+                    // Documents are synchronized in full, so the last change
+                    // contains the contents of the document.
+                    Ok(mut params) => match params.content_changes.pop() {
+                        Some(change) => {
+                            self.update_document(&params.text_document.uri, change.text)
+                        }
+                        None => Vec::new(),
+                    },
+                    Err(error) => {
+                        warn!("invalid notification parameters: {error}");
+                        Vec::new()
+                    }
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                match serde_json::from_value::<DidCloseTextDocumentParams>(notification.params) {
+                    Ok(params) => self.close_document(params.text_document.uri),
+                    Err(error) => {
+                        warn!("invalid notification parameters: {error}");
+                        Vec::new()
+                    }
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Updates the contents of the given document, and re-analyzes all open
+    /// documents that include it.
+    fn update_document(&mut self, uri: &Url, text: String) -> Vec<Notification> {
+        let Some(path) = to_path(uri) else {
+            warn!("ignoring document `{uri}`");
+            return Vec::new();
+        };
+        self.documents.insert(path.clone(), text);
+        let affected = self
+            .documents
+            .keys()
+            .filter(|document| {
+                **document == path
+                    || self
+                        .runners
+                        .get(*document)
+                        .map_or(false, |runner| file_id(runner.file_library(), &path).is_some())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        affected.iter().filter_map(|document| self.analyze_document(document)).collect()
+    }
+
+    fn close_document(&mut self, uri: Url) -> Vec<Notification> {
+        if let Some(path) = to_path(&uri) {
+            self.documents.remove(&path);
+            self.runners.remove(&path);
+        }
+        // Clear any diagnostics published for the document.
+        vec![publish_diagnostics(uri, Vec::new())]
+    }
+
+    /// Analyzes the given document and returns the diagnostics for the
+    /// document. CFGs are reused from the previous analysis of the document
+    /// for templates and functions defined in unchanged files.
+    fn analyze_document(&mut self, path: &Path) -> Option<Notification> {
+        debug!("analyzing `{}`", path.display());
+        let (mut runner, reports) = AnalysisRunner::new(self.curve.clone())
+            .with_libraries(&self.libraries)
+            .with_sources(self.documents.clone())
+            .with_files(&[path.to_path_buf()]);
+        if let Some(previous) = self.runners.remove(path) {
+            runner.reuse_cfgs(previous);
+        }
+        let mut collector = ReportCollector::default();
+        collector.write_reports(&reports, runner.file_library());
+        runner.analyze_functions(&mut collector, true);
+        runner.analyze_templates(&mut collector, true);
+        runner.analyze_instances(&mut collector, true);
+
+        // Only reports with a primary location in the document are published.
+        let file_library = runner.file_library();
+        let diagnostics = match file_id(file_library, path) {
+            Some(file_id) => {
+                let suppressions = Suppressions::new(file_library);
+                collector
+                    .reports
+                    .iter()
+                    .filter(|report| report.primary_file_ids().contains(&file_id))
+                    .filter(|report| !suppressions.is_suppressed(report))
+                    .filter_map(|report| to_diagnostic(report, file_library))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        self.runners.insert(path.to_path_buf(), runner);
+        let uri = Url::from_file_path(path).ok()?;
+        Some(publish_diagnostics(uri, diagnostics))
+    }
+
+    /// Returns the location of the template or function with the name at the
+    /// given position.
+    fn goto_definition(&mut self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let params = params.text_document_position_params;
+        let path = to_path(&params.text_document.uri)?;
+        let source = self.documents.get(&path)?;
+        let runner = self.runners.get(&path)?;
+        let offset = position_to_offset(source, params.position);
+        let (_, name) = identifier_at(source, offset)?;
+        let (file_id, body) = runner.definition_location(name)?;
+        let file_library = runner.file_library();
+        let file = file_library.to_storage().get(file_id).ok()?;
+        let range = definition_name_range(file.source(), name, body.start)?;
+        to_location(file_library, file_id, &range).map(GotoDefinitionResponse::Scalar)
+    }
+
+    /// Returns the declared type and inferred degree of the variable or signal
+    /// at the given position.
+    fn hover(&mut self, params: HoverParams) -> Option<Hover> {
+        let params = params.text_document_position_params;
+        let path = to_path(&params.text_document.uri)?;
+        let source = self.documents.get(&path)?;
+        let runner = self.runners.get(&path)?;
+        let offset = position_to_offset(source, params.position);
+        let (range, name) = identifier_at(source, offset)?;
+
+        // Find the template or function containing the position.
+        let file_id = file_id(runner.file_library(), &path)?;
+        let definition =
+            runner.template_names(true).into_iter().chain(runner.function_names(true)).find(
+                |definition| match runner.definition_location(definition) {
+                    Some((definition_file_id, body)) => {
+                        definition_file_id == file_id && body.contains(&offset)
+                    }
+                    None => false,
+                },
+            )?;
+        let cfg = if runner.is_template(&definition) {
+            runner.template(&definition)
+        } else {
+            runner.function(&definition)
+        }
+        .ok()?;
+        let description = describe_variable(&cfg, name, offset)?;
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: description,
+            }),
+            range: Some(lsp_types::Range::new(
+                offset_to_position(source, range.start),
+                offset_to_position(source, range.end),
+            )),
+        })
+    }
+}
+
+/// Returns the canonical path of the file with the given URI.
+fn to_path(uri: &Url) -> Option<PathBuf> {
+    let path = uri.to_file_path().ok()?;
+    Some(fs::canonicalize(&path).unwrap_or(path))
+}
+
+/// Returns the ID of the file with the given path.
+fn file_id(file_library: &FileLibrary, path: &Path) -> Option<FileID> {
+    file_library.file_ids().find(|file_id| match file_library.to_storage().get(*file_id) {
+        Ok(file) => Path::new(file.name()) == path,
+        Err(_) => false,
+    })
+}
+
+fn publish_diagnostics(uri: Url, diagnostics: Vec<Diagnostic>) -> Notification {
+    let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+    Notification::new(PublishDiagnostics::METHOD.to_string(), params)
+}
@@ -40,6 +40,18 @@ pub fn parse_files(
     file_paths: &[PathBuf],
     libraries: &[PathBuf],
     compiler_version: &Version,
+) -> ParseResult {
+    parse_files_with_sources(file_paths, libraries, compiler_version, &HashMap::new())
+}
+
+/// Parses the given files like `parse_files`, but reads the contents of any
+/// file in `sources` from the map rather than from disk. This allows us to
+/// analyze unsaved changes in an editor. Paths in `sources` must be canonical.
+pub fn parse_files_with_sources(
+    file_paths: &[PathBuf],
+    libraries: &[PathBuf],
+    compiler_version: &Version,
+    sources: &HashMap<PathBuf, String>,
 ) -> ParseResult {
     let mut reports = ReportCollection::new();
     let mut file_stack = FileStack::new(file_paths, libraries, &mut reports);
@@ -47,7 +59,8 @@ pub fn parse_files(
     let mut definitions = HashMap::new();
     let mut main_components = Vec::new();
     while let Some(file_path) = FileStack::take_next(&mut file_stack) {
-        match parse_file(&file_path, &mut file_stack, &mut file_library, compiler_version) {
+        match parse_file(&file_path, &mut file_stack, &mut file_library, compiler_version, sources)
+        {
             Ok((file_id, program, mut warnings)) => {
                 if let Some(main_component) = program.main_component {
                     main_components.push((file_id, main_component, program.custom_gates));
@@ -138,16 +151,23 @@ pub fn parse_file(
     file_stack: &mut FileStack,
     file_library: &mut FileLibrary,
     compiler_version: &Version,
+    sources: &HashMap<PathBuf, String>,
 ) -> Result<(FileID, AST, ReportCollection), Box<Report>> {
     let mut reports = ReportCollection::new();
 
-    debug!("reading file `{}`", file_path.display());
-    let (path_str, file_content) = open_file(file_path)?;
+    let (path_str, file_content) = match sources.get(file_path) {
+        Some(file_content) => (file_path.display().to_string(), file_content.clone()),
+        None => {
+            debug!("reading file `{}`", file_path.display());
+            open_file(file_path)?
+        }
+    };
     let is_user_input = file_stack.is_user_input(file_path);
     let file_id = file_library.add_file(path_str, file_content.clone(), is_user_input);
 
     debug!("parsing file `{}`", file_path.display());
-    let program = parser_logic::parse_file(&file_content, file_id)?;
+    let (program, comments) = parser_logic::parse_file(&file_content, file_id)?;
+    file_library.add_comments(file_id, comments);
     match check_compiler_version(file_path, program.compiler_version, compiler_version) {
         Ok(warnings) => reports.extend(warnings),
         Err(error) => reports.push(*error),
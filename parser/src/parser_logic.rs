@@ -3,13 +3,21 @@ use super::lang;
 
 use program_structure::ast::AST;
 use program_structure::report::Report;
-use program_structure::file_definition::FileID;
+use program_structure::file_definition::{Comment, FileID};
 
-pub fn preprocess(expr: &str, file_id: FileID) -> Result<String, Box<Report>> {
+/// Replaces comments by whitespace, preserving the byte offsets of all
+/// remaining tokens. Returns the preprocessed source together with the removed
+/// comments.
+pub fn preprocess(expr: &str, file_id: FileID) -> Result<(String, Vec<Comment>), Box<Report>> {
     let mut pp = String::new();
     let mut state = 0;
     let mut loc = 0;
     let mut block_start = 0;
+    // Since comments are replaced by the same number of bytes, `pp.len()` is
+    // the byte offset of the current character in the original source.
+    let mut comments = Vec::new();
+    let mut comment_start = 0;
+    let mut comment_text = String::new();
 
     let mut it = expr.chars();
     while let Some(c0) = it.next() {
@@ -20,12 +28,14 @@ pub fn preprocess(expr: &str, file_id: FileID) -> Result<String, Box<Report>> {
                 match it.next() {
                     Some('/') => {
                         state = 1;
+                        comment_start = pp.len();
                         pp.push(' ');
                         pp.push(' ');
                     }
                     Some('*') => {
                         block_start = loc;
                         state = 2;
+                        comment_start = pp.len();
                         pp.push(' ');
                         pp.push(' ');
                     }
@@ -41,6 +51,8 @@ pub fn preprocess(expr: &str, file_id: FileID) -> Result<String, Box<Report>> {
             }
             (0, _) => pp.push(c0),
             (1, '\n') => {
+                let text = std::mem::take(&mut comment_text);
+                comments.push(Comment::new(comment_start..pp.len(), text));
                 pp.push(c0);
                 state = 0;
             }
@@ -50,9 +62,13 @@ pub fn preprocess(expr: &str, file_id: FileID) -> Result<String, Box<Report>> {
                     Some('/') => {
                         pp.push(' ');
                         pp.push(' ');
+                        let text = std::mem::take(&mut comment_text);
+                        comments.push(Comment::new(comment_start..pp.len(), text));
                         state = 0;
                     }
                     Some(c) => {
+                        comment_text.push(c0);
+                        comment_text.push(c);
                         pp.push(' ');
                         for _i in 0..c.len_utf8() {
                             pp.push(' ');
@@ -66,25 +82,32 @@ pub fn preprocess(expr: &str, file_id: FileID) -> Result<String, Box<Report>> {
                 }
             }
             (_, c) => {
+                comment_text.push(c);
                 for _i in 0..c.len_utf8() {
                     pp.push(' ');
                 }
             }
         }
     }
-    Ok(pp)
+    if state == 1 {
+        // The file ends with a line comment.
+        comments.push(Comment::new(comment_start..pp.len(), comment_text));
+    }
+    Ok((pp, comments))
 }
 
-pub fn parse_file(src: &str, file_id: FileID) -> Result<AST, Box<Report>> {
+/// Parses the given file, returning the AST together with all comments.
+pub fn parse_file(src: &str, file_id: FileID) -> Result<(AST, Vec<Comment>), Box<Report>> {
     use lalrpop_util::ParseError::*;
+    let (src, comments) = preprocess(src, file_id)?;
     lang::ParseAstParser::new()
-        .parse(&preprocess(src, file_id)?)
+        .parse(&src)
         .map(|mut ast| {
             // Set file ID for better error reporting.
             for include in &mut ast.includes {
                 include.meta.set_file_id(file_id);
             }
-            ast
+            (ast, comments)
         })
         .map_err(|parse_error| match parse_error {
             InvalidToken { location } => ParsingError {
@@ -112,7 +135,7 @@ pub fn parse_file(src: &str, file_id: FileID) -> Result<AST, Box<Report>> {
 }
 
 pub fn parse_string(src: &str) -> Option<AST> {
-    let src = preprocess(src, 0).ok()?;
+    let (src, _) = preprocess(src, 0).ok()?;
     lang::ParseAstParser::new().parse(&src).ok()
 }
 
@@ -151,7 +174,7 @@ fn format_expected(tokens: &[String]) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_string;
+    use super::{parse_string, preprocess, Definition};
 
     #[test]
     fn test_parse_string() {
@@ -186,4 +209,46 @@ mod tests {
         "#;
         let _ = parse_string(template);
     }
+
+    #[test]
+    fn test_parse_bus() {
+        let src = r#"
+            bus Point(n) {
+                signal x[n];
+                signal y;
+            }
+
+            template T() {
+                input Point(2) {binary} p;
+                output Point(2) q;
+                Point(2) r, s[2];
+
+                r <== p;
+                q.x <== r.x;
+                q.y <== r.y + s[0].y;
+            }
+        "#;
+        let ast = parse_string(src).unwrap();
+        assert_eq!(ast.definitions.len(), 2);
+        assert!(
+            matches!(&ast.definitions[0], Definition::Bus { name, args, .. } if name == "Point" && args.len() == 1)
+        );
+        assert!(matches!(&ast.definitions[1], Definition::Template { name, .. } if name == "T"));
+    }
+
+    #[test]
+    fn test_preprocess_comments() {
+        let src =
+            "var x = 1; // circomspect: allow CS0010\n/* A block\ncomment. */ var y;\n// Last";
+        let (pp, comments) = preprocess(src, 0).ok().unwrap();
+        assert_eq!(pp.len(), src.len());
+        assert_eq!(comments.len(), 3);
+        assert_eq!(comments[0].text(), " circomspect: allow CS0010");
+        assert_eq!(&src[comments[0].location().clone()], "// circomspect: allow CS0010");
+        assert_eq!(comments[1].text(), " A block\ncomment. ");
+        assert_eq!(&src[comments[1].location().clone()], "/* A block\ncomment. */");
+        assert_eq!(comments[2].text(), " Last");
+        assert!(pp.contains("var y;"));
+        assert!(!pp.contains("circomspect"));
+    }
 }
@@ -1,3 +1,6 @@
+use num_bigint::BigInt;
+use std::any::{Any, TypeId};
+use std::sync::Arc;
 use thiserror::Error;
 
 use program_structure::{
@@ -5,6 +8,8 @@ use program_structure::{
     cfg::Cfg,
 };
 
+use crate::config::AnalysisSettings;
+
 /// Errors returned by the analysis context.
 #[derive(Debug, Error)]
 pub enum AnalysisError {
@@ -22,6 +27,10 @@ pub enum AnalysisError {
     /// CFG.
     #[error("Failed to lift the template `{name}`.")]
     FailedToLiftTemplate { name: String },
+    /// The number of template arguments does not match the number of template
+    /// parameters.
+    #[error("Invalid number of arguments for the template `{name}`.")]
+    InvalidTemplateArguments { name: String },
     /// The file ID does not correspond to a known file.
     #[error("Unknown file ID `{file_id}`.")]
     UnknownFile { file_id: FileID },
@@ -30,8 +39,10 @@ pub enum AnalysisError {
     InvalidLocation { file_id: FileID, file_location: FileLocation },
 }
 
-/// Context passed to each analysis pass.
-pub trait AnalysisContext {
+/// Context passed to each analysis pass. The context may be shared by analysis
+/// passes running on different threads, which is why CFGs are returned as
+/// shared references.
+pub trait AnalysisContext: Sync {
     /// Returns true if the context knows of a function with the given name.
     /// This method does not compute the CFG of the function which saves time
     /// compared to `AnalysisContext::function`.
@@ -43,10 +54,29 @@ pub trait AnalysisContext {
     fn is_template(&self, name: &str) -> bool;
 
     /// Returns the CFG for the function with the given name.
-    fn function(&mut self, name: &str) -> Result<&Cfg, AnalysisError>;
+    fn function(&self, name: &str) -> Result<Arc<Cfg>, AnalysisError>;
 
     /// Returns the CFG for the template with the given name.
-    fn template(&mut self, name: &str) -> Result<&Cfg, AnalysisError>;
+    fn template(&self, name: &str) -> Result<Arc<Cfg>, AnalysisError>;
+
+    /// Returns the CFG for the template with the given name, specialized to
+    /// the given constant template arguments.
+    fn instance(&self, name: &str, arguments: &[BigInt]) -> Result<Arc<Cfg>, AnalysisError>;
+
+    /// Returns the analysis with the given type ID for the given CFG, computing
+    /// it using `compute` if it has not been computed before. Analysis passes
+    /// should use `AnalysisContext::analysis` rather than calling this method
+    /// directly.
+    fn cached_analysis(
+        &self,
+        cfg: &Cfg,
+        analysis: TypeId,
+        compute: &dyn Fn() -> SharedAnalysis,
+    ) -> SharedAnalysis;
+
+    /// Returns the settings (e.g. thresholds and allow-lists) used by the
+    /// analysis passes.
+    fn settings(&self) -> &AnalysisSettings;
 
     /// Returns the string corresponding to the given file ID and location.
     fn underlying_str(
@@ -55,3 +85,23 @@ pub trait AnalysisContext {
         file_location: &FileLocation,
     ) -> Result<String, AnalysisError>;
 }
+
+/// A type-erased derived analysis shared between analysis passes.
+pub type SharedAnalysis = Arc<dyn Any + Send + Sync>;
+
+/// An analysis (e.g. taint analysis) derived from a single CFG. Derived
+/// analyses are computed lazily by the analysis context, and the first result
+/// computed for each CFG is shared between all analysis passes requesting it.
+pub trait CfgAnalysis: Any + Send + Sync + Sized {
+    /// Computes the analysis for the given CFG.
+    fn compute(cfg: &Cfg) -> Self;
+}
+
+impl dyn AnalysisContext + '_ {
+    /// Returns the analysis of type `T` for the given CFG.
+    pub fn analysis<T: CfgAnalysis>(&self, cfg: &Cfg) -> Arc<T> {
+        self.cached_analysis(cfg, TypeId::of::<T>(), &|| Arc::new(T::compute(cfg)))
+            .downcast::<T>()
+            .expect("cached analysis has the requested type")
+    }
+}
@@ -1,13 +1,26 @@
 use log::{debug, trace};
-use std::path::PathBuf;
-use std::collections::HashMap;
+use num_bigint::BigInt;
+use rayon::prelude::*;
+use std::any::{Any, TypeId};
+use std::hash::Hash;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
 use parser::ParseResult;
 
 use program_structure::{
+    ast,
+    ir::{
+        Expression, Statement,
+        value_meta::{ValueMeta, ValueReduction},
+    },
+    program_archive::ProgramArchive,
     writers::{LogWriter, ReportWriter},
     template_data::TemplateInfo,
     function_data::FunctionInfo,
+    bus_data::BusInfo,
     file_definition::{FileLibrary, FileLocation, FileID},
     cfg::{Cfg, IntoCfg},
     constants::Curve,
@@ -18,33 +31,126 @@ use program_structure::{
 use program_structure::template_library::TemplateLibrary;
 
 use crate::{
-    analysis_context::{AnalysisContext, AnalysisError},
-    get_analysis_passes, config,
+    analysis_context::{AnalysisContext, AnalysisError, SharedAnalysis},
+    cache::{content_hash, Cache},
+    pass_registry::PassRegistry,
+    config::{self, AnalysisSettings},
 };
 
-type CfgCache = HashMap<String, Cfg>;
-type ReportCache = HashMap<String, ReportCollection>;
+/// A CFG generated from a template or function AST, together with any reports
+/// created during CFG generation. The CFG is `None` if it could not be
+/// generated.
+#[derive(Default)]
+struct Lifted {
+    cfg: Option<Arc<Cfg>>,
+    reports: ReportCollection,
+}
+
+/// A thread-safe cache of values (e.g. CFGs) computed on demand. Values are
+/// computed without holding a lock, so that computing a value may request
+/// other cached values (and Rayon may run other tasks on the same thread while
+/// the value is computed). If two threads compute the same value at the same
+/// time, the value inserted first is returned to both.
+struct LazyCache<K, V: ?Sized> {
+    values: Mutex<HashMap<K, Arc<V>>>,
+}
+
+type CfgCache<K> = LazyCache<K, Lifted>;
+
+/// Derived analyses are identified by the name and template arguments of the
+/// CFG, and the type of the analysis.
+type AnalysisKey = (String, Option<Vec<BigInt>>, TypeId);
+
+impl<K, V: ?Sized> Default for LazyCache<K, V> {
+    fn default() -> Self {
+        LazyCache { values: Mutex::default() }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: ?Sized> LazyCache<K, V> {
+    /// Returns the cached value for the given key, computing it using
+    /// `compute` if it is not yet cached.
+    fn get_or_insert_with(&self, key: &K, compute: impl FnOnce() -> Arc<V>) -> Arc<V> {
+        if let Some(value) = lock(&self.values).get(key) {
+            return value.clone();
+        }
+        let value = compute();
+        lock(&self.values).entry(key.clone()).or_insert(value).clone()
+    }
+
+    /// Returns true if the value for the given key has been computed.
+    #[cfg(test)]
+    fn contains(&self, key: &K) -> bool {
+        lock(&self.values).contains_key(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<Arc<V>> {
+        self.values.get_mut().unwrap_or_else(PoisonError::into_inner).remove(key)
+    }
+
+    fn insert(&mut self, key: K, value: Arc<V>) {
+        self.values.get_mut().unwrap_or_else(PoisonError::into_inner).insert(key, value);
+    }
+}
+
+/// Locks the given mutex. A poisoned mutex means that another thread panicked
+/// during analysis. The panic is propagated by Rayon, so we ignore the poison.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Template instances are identified by the template name and the constant
+/// template arguments.
+type InstanceKey = (String, Vec<BigInt>);
+
+/// Reports are identified by the report ID, the message, and the primary
+/// locations of the report.
+type ReportKey = (String, String, Vec<(FileID, Range<usize>)>);
 
 /// A type responsible for caching CFGs and running analysis passes over all
-/// functions and templates.
+/// functions and templates. Templates, functions, and template instances are
+/// analyzed in parallel, but reports are always written in the same order.
 #[derive(Default)]
 pub struct AnalysisRunner {
     curve: Curve,
+    /// Settings exposed to the analysis passes.
+    settings: AnalysisSettings,
     libraries: Vec<PathBuf>,
+    /// File contents used instead of the contents on disk, indexed by the
+    /// canonical path of the file.
+    sources: HashMap<PathBuf, String>,
+    /// The project root used to compute relative file paths.
+    root: Option<PathBuf>,
     /// The corresponding file library including file includes.
     file_library: FileLibrary,
     /// Template ASTs generated by the parser.
     template_asts: TemplateInfo,
     /// Function ASTs generated by the parser.
     function_asts: FunctionInfo,
-    /// Cached template CFGs generated on demand.
-    template_cfgs: CfgCache,
-    /// Cached function CFGs generated on demand.
-    function_cfgs: CfgCache,
-    /// Reports created during CFG generation.
-    template_reports: ReportCache,
-    /// Reports created during CFG generation.
-    function_reports: ReportCache,
+    /// Bus ASTs generated by the parser.
+    bus_asts: BusInfo,
+    /// Cached template CFGs (and reports created during CFG generation)
+    /// generated on demand.
+    template_cfgs: CfgCache<String>,
+    /// Cached function CFGs (and reports created during CFG generation)
+    /// generated on demand.
+    function_cfgs: CfgCache<String>,
+    /// The template of the main component, if known.
+    main_template: Option<String>,
+    /// The template and constant arguments of the main component, if known.
+    main_component: Option<InstanceKey>,
+    /// Cached specialized template CFGs generated on demand.
+    instance_cfgs: CfgCache<InstanceKey>,
+    /// On-disk cache used to avoid regenerating CFGs across runs.
+    cache: Option<Cache>,
+    /// The analysis passes run by the runner.
+    passes: PassRegistry,
+    /// Derived analyses (e.g. taint analysis) computed on demand by the
+    /// analysis passes.
+    analyses: LazyCache<AnalysisKey, dyn Any + Send + Sync>,
+    /// Reports written by the runner. This is used to avoid writing the same
+    /// report more than once when analyzing template instances.
+    written_reports: HashSet<ReportKey>,
 }
 
 impl AnalysisRunner {
@@ -57,22 +163,66 @@ impl AnalysisRunner {
         self
     }
 
+    /// Load and store template and function CFGs using the given on-disk
+    /// cache.
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Run the enabled passes in the given registry rather than the default
+    /// set of analysis passes.
+    pub fn with_passes(mut self, passes: PassRegistry) -> Self {
+        self.passes = passes;
+        self
+    }
+
+    pub fn with_settings(mut self, settings: AnalysisSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Report file paths (and compute report fingerprints) relative to the
+    /// given project root rather than the current directory.
+    pub fn with_root(mut self, root: &Path) -> Self {
+        self.root = Some(root.to_path_buf());
+        self
+    }
+
+    /// Use the given file contents instead of the contents on disk when
+    /// parsing. Files are indexed by their canonical path.
+    pub fn with_sources(mut self, sources: HashMap<PathBuf, String>) -> Self {
+        self.sources = sources;
+        self
+    }
+
     pub fn with_files(mut self, input_files: &[PathBuf]) -> (Self, ReportCollection) {
-        let reports =
-            match parser::parse_files(input_files, &self.libraries, &config::COMPILER_VERSION) {
-                ParseResult::Program(program, warnings) => {
-                    self.template_asts = program.templates;
-                    self.function_asts = program.functions;
-                    self.file_library = program.file_library;
-                    warnings
-                }
-                ParseResult::Library(library, warnings) => {
-                    self.template_asts = library.templates;
-                    self.function_asts = library.functions;
-                    self.file_library = library.file_library;
-                    warnings
-                }
-            };
+        let reports = match parser::parse_files_with_sources(
+            input_files,
+            &self.libraries,
+            &config::COMPILER_VERSION,
+            &self.sources,
+        ) {
+            ParseResult::Program(program, warnings) => {
+                self.main_template = main_template(&program);
+                self.main_component = main_component(&program);
+                self.template_asts = program.templates;
+                self.function_asts = program.functions;
+                self.bus_asts = program.buses;
+                self.file_library = program.file_library;
+                warnings
+            }
+            ParseResult::Library(library, warnings) => {
+                self.template_asts = library.templates;
+                self.function_asts = library.functions;
+                self.bus_asts = library.buses;
+                self.file_library = library.file_library;
+                warnings
+            }
+        };
+        if let Some(root) = &self.root {
+            self.file_library.set_root(root);
+        }
         (self, reports)
     }
 
@@ -91,15 +241,29 @@ impl AnalysisRunner {
         let template_library = TemplateLibrary::new(library_contents, file_library.clone());
         self.template_asts = template_library.templates;
         self.function_asts = template_library.functions;
+        self.bus_asts = template_library.buses;
         self.file_library = template_library.file_library;
 
         self
     }
 
+    /// Convenience method used to set the main template for testing purposes.
+    #[cfg(test)]
+    pub fn with_main_template(mut self, name: &str) -> Self {
+        self.main_template = Some(name.to_string());
+        self
+    }
+
     pub fn file_library(&self) -> &FileLibrary {
         &self.file_library
     }
 
+    /// Returns the name of the template instantiated by the main component, if
+    /// known.
+    pub fn main_template(&self) -> Option<&str> {
+        self.main_template.as_deref()
+    }
+
     pub fn template_names(&self, user_input_only: bool) -> Vec<String> {
         // Clone template names to avoid holding multiple references to `self`.
         self.template_asts
@@ -130,160 +294,413 @@ impl AnalysisRunner {
             .collect()
     }
 
-    fn analyze_template<W: LogWriter + ReportWriter>(&mut self, name: &str, writer: &mut W) {
-        writer.write_message(&format!("analyzing template '{name}'"));
-
-        // We take ownership of the CFG and any previously generated reports
-        // here to avoid holding multiple mutable and immutable references to
-        // `self`. This may lead to the CFG being regenerated during analysis if
-        // the template is invoked recursively. If it is then ¯\_(ツ)_/¯.
-        let mut reports = self.take_template_reports(name);
-        if let Ok(cfg) = self.take_template(name) {
-            for analysis_pass in get_analysis_passes() {
-                reports.append(&mut analysis_pass(self, &cfg));
-            }
-            // Re-insert the CFG into the hash map.
-            if self.replace_template(name, cfg) {
-                debug!("template `{name}` CFG was regenerated during analysis");
+    /// Runs all enabled analysis passes on the given template or function CFG.
+    /// Reports created during CFG generation are included in the returned
+    /// reports.
+    fn analyze_definition(&self, name: &str, lifted: &Lifted) -> ReportCollection {
+        let mut reports = lifted.reports.clone();
+        if let Some(cfg) = &lifted.cfg {
+            for analysis_pass in self.passes.enabled() {
+                reports.append(&mut analysis_pass.run(self, cfg));
             }
         }
-        writer.write_reports(&reports, &self.file_library);
+        for report in &mut reports {
+            report.set_definition(name.to_string());
+        }
+        reports
     }
 
+    /// Analyze all templates. Templates are analyzed in parallel, and reports
+    /// are written ordered by template name.
     pub fn analyze_templates<W: LogWriter + ReportWriter>(
         &mut self,
         writer: &mut W,
         user_input_only: bool,
     ) {
-        for name in self.template_names(user_input_only) {
-            self.analyze_template(&name, writer);
+        let mut names = self.template_names(user_input_only);
+        names.sort();
+        let runner = &*self;
+        let reports = names
+            .par_iter()
+            .map(|name| match runner.lift_template(name) {
+                Ok(lifted) => runner.analyze_definition(name, &lifted),
+                Err(_) => ReportCollection::new(),
+            })
+            .collect::<Vec<_>>();
+        for (name, reports) in names.iter().zip(reports) {
+            writer.write_message(format!("analyzing template '{name}'"));
+            self.write_reports(&reports, writer);
         }
     }
 
-    fn analyze_function<W: LogWriter + ReportWriter>(&mut self, name: &str, writer: &mut W) {
-        writer.write_message(&format!("analyzing function '{name}'"));
+    /// Analyze all functions. Functions are analyzed in parallel, and reports
+    /// are written ordered by function name.
+    pub fn analyze_functions<W: LogWriter + ReportWriter>(
+        &mut self,
+        writer: &mut W,
+        user_input_only: bool,
+    ) {
+        let mut names = self.function_names(user_input_only);
+        names.sort();
+        let runner = &*self;
+        let reports = names
+            .par_iter()
+            .map(|name| match runner.lift_function(name) {
+                Ok(lifted) => runner.analyze_definition(name, &lifted),
+                Err(_) => ReportCollection::new(),
+            })
+            .collect::<Vec<_>>();
+        for (name, reports) in names.iter().zip(reports) {
+            writer.write_message(format!("analyzing function '{name}'"));
+            self.write_reports(&reports, writer);
+        }
+    }
 
-        // We take ownership of the CFG and any previously generated reports
-        // here to avoid holding multiple mutable and immutable references to
-        // `self`. This may lead to the CFG being regenerated during analysis if
-        // the function is invoked recursively. If it is then ¯\_(ツ)_/¯.
-        let mut reports = self.take_function_reports(name);
-        if let Ok(cfg) = self.take_function(name) {
-            for analysis_pass in get_analysis_passes() {
-                reports.append(&mut analysis_pass(self, &cfg));
-            }
-            // Re-insert the CFG into the hash map.
-            if self.replace_function(name, cfg) {
-                debug!("function `{name}` CFG was regenerated during analysis");
-            }
+    /// Runs all enabled analysis passes on the given template instance CFG.
+    fn analyze_instance(&self, cfg: &Cfg) -> ReportCollection {
+        let mut reports = ReportCollection::new();
+        for analysis_pass in self.passes.enabled() {
+            reports.append(&mut analysis_pass.run(self, cfg));
         }
-        writer.write_reports(&reports, &self.file_library);
+        for report in &mut reports {
+            report.set_definition(cfg.name().to_string());
+            report.set_instance(cfg.instance_name());
+        }
+        reports
     }
 
-    pub fn analyze_functions<W: LogWriter + ReportWriter>(
+    /// Analyze all template instances reachable from the main component. Each
+    /// instance is analyzed using a CFG specialized to the constant template
+    /// arguments of the instance, and reports are tagged by the corresponding
+    /// instance. Reports that were already written when analyzing the
+    /// generic templates are not written again.
+    ///
+    /// The instance tree is traversed breadth-first, and the instances on each
+    /// level of the tree are analyzed in parallel. Reports are written in the
+    /// order the instances are discovered.
+    pub fn analyze_instances<W: LogWriter + ReportWriter>(
         &mut self,
         writer: &mut W,
         user_input_only: bool,
     ) {
-        for name in self.function_names(user_input_only) {
-            self.analyze_function(&name, writer);
+        let Some(main_component) = self.main_component.clone() else {
+            return;
+        };
+        let mut visited = HashSet::new();
+        let mut instances = vec![main_component];
+        while !instances.is_empty() {
+            let mut level = Vec::new();
+            for key in instances {
+                if visited.contains(&key) {
+                    continue;
+                }
+                if visited.len() >= config::MAX_TEMPLATE_INSTANCES {
+                    debug!("reached the maximum number of template instances");
+                    break;
+                }
+                visited.insert(key.clone());
+                level.push(key);
+            }
+            let runner = &*self;
+            let results = level
+                .par_iter()
+                .map(|(name, arguments)| {
+                    let cfg = runner.instance(name, arguments).ok()?;
+                    let is_user_input = cfg
+                        .file_id()
+                        .map_or(false, |file_id| runner.file_library.is_user_input(file_id));
+                    let reports =
+                        (!user_input_only || is_user_input).then(|| runner.analyze_instance(&cfg));
+                    Some((cfg.instance_name(), runner.template_instances(&cfg), reports))
+                })
+                .collect::<Vec<_>>();
+
+            instances = Vec::new();
+            for (instance_name, children, reports) in results.into_iter().flatten() {
+                instances.extend(children);
+                let Some(reports) = reports else {
+                    continue;
+                };
+                writer.write_message(format!("analyzing template instance '{instance_name}'"));
+                // Only write reports that were not already written when
+                // analyzing the generic template (or another instance of the
+                // same template).
+                let reports = reports
+                    .into_iter()
+                    .filter(|report| !self.written_reports.contains(&report_key(report)))
+                    .collect::<ReportCollection>();
+                self.write_reports(&reports, writer);
+            }
         }
     }
 
-    /// Report cache from CFG generation. These will be emitted when the
-    /// template is analyzed.
-    fn append_template_reports(&mut self, name: &str, reports: &mut ReportCollection) {
-        self.template_reports.entry(name.to_string()).or_default().append(reports);
+    /// Returns the templates instantiated by the given CFG with constant
+    /// template arguments.
+    fn template_instances(&self, cfg: &Cfg) -> Vec<InstanceKey> {
+        let mut instances = Vec::new();
+        for stmt in cfg.iter().flat_map(|basic_block| basic_block.iter()) {
+            let Statement::Substitution { rhe, .. } = stmt else {
+                continue;
+            };
+            let rhe = match rhe {
+                Expression::Update { rhe, .. } => rhe,
+                rhe => rhe,
+            };
+            let Expression::Call { name, args, .. } = rhe else {
+                continue;
+            };
+            if !self.is_template(name) {
+                continue;
+            }
+            let arguments = args
+                .iter()
+                .map(|arg| match arg.value() {
+                    Some(ValueReduction::FieldElement { value }) => Some(value.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            if let Some(arguments) = arguments {
+                instances.push((name.clone(), arguments));
+            }
+        }
+        instances
     }
 
-    /// Report cache from CFG generation. These will be emitted when the
-    /// template is analyzed.
-    fn take_template_reports(&mut self, name: &str) -> ReportCollection {
-        self.template_reports.remove(name).unwrap_or_default()
+    /// Write the reports and record that they have been written.
+    /// Severity overrides are applied before the reports are written.
+    fn write_reports<W: ReportWriter>(&mut self, reports: &[Report], writer: &mut W) {
+        self.written_reports.extend(reports.iter().map(report_key));
+        let mut reports = reports.to_vec();
+        for report in &mut reports {
+            self.settings.override_severity(report);
+        }
+        writer.write_reports(&reports, &self.file_library);
     }
 
-    /// Report cache from CFG generation. These will be emitted when the
-    /// function is analyzed.
-    fn append_function_reports(&mut self, name: &str, reports: &mut ReportCollection) {
-        self.function_reports.entry(name.to_string()).or_default().append(reports);
+    fn lift_template(&self, name: &str) -> Result<Arc<Lifted>, AnalysisError> {
+        // Get the AST corresponding to the template.
+        let Some(ast) = self.template_asts.get(name) else {
+            trace!("failed to lift unknown template `{name}`");
+            return Err(AnalysisError::UnknownTemplate { name: name.to_string() });
+        };
+        Ok(self.template_cfgs.get_or_insert_with(&name.to_string(), || {
+            Arc::new(self.load_or_lift("template", name, ast.get_file_id(), || {
+                // Generate the template CFG from the AST. Cache any reports.
+                let mut reports = ReportCollection::new();
+                match generate_cfg(ast, &self.curve, &mut reports) {
+                    Ok(cfg) => {
+                        trace!("successfully lifted template `{name}`");
+                        Lifted { cfg: Some(Arc::new(cfg)), reports }
+                    }
+                    Err(report) => {
+                        trace!("failed to lift template `{name}`");
+                        reports.push(*report);
+                        Lifted { cfg: None, reports }
+                    }
+                }
+            }))
+        }))
     }
 
-    /// Report cache from CFG generation. These will be emitted when the
-    /// function is analyzed.
-    fn take_function_reports(&mut self, name: &str) -> ReportCollection {
-        self.function_reports.remove(name).unwrap_or_default()
+    fn lift_function(&self, name: &str) -> Result<Arc<Lifted>, AnalysisError> {
+        // Get the AST corresponding to the function.
+        let Some(ast) = self.function_asts.get(name) else {
+            trace!("failed to lift unknown function `{name}`");
+            return Err(AnalysisError::UnknownFunction { name: name.to_string() });
+        };
+        Ok(self.function_cfgs.get_or_insert_with(&name.to_string(), || {
+            Arc::new(self.load_or_lift("function", name, ast.get_file_id(), || {
+                // Generate the function CFG from the AST. Cache any reports.
+                let mut reports = ReportCollection::new();
+                match generate_cfg(ast, &self.curve, &mut reports) {
+                    Ok(cfg) => {
+                        trace!("successfully lifted function `{name}`");
+                        Lifted { cfg: Some(Arc::new(cfg)), reports }
+                    }
+                    Err(report) => {
+                        trace!("failed to lift function `{name}`");
+                        reports.push(*report);
+                        Lifted { cfg: None, reports }
+                    }
+                }
+            }))
+        }))
     }
 
-    fn cache_template(&mut self, name: &str) -> Result<&Cfg, AnalysisError> {
-        if !self.template_cfgs.contains_key(name) {
-            // The template CFG needs to be generated from the AST.
-            if self.template_reports.contains_key(name) {
-                // We have already failed to generate the CFG.
-                return Err(AnalysisError::FailedToLiftTemplate { name: name.to_string() });
+    /// Loads the CFG of the given template or function from the on-disk cache
+    /// if possible. Otherwise, the CFG is generated using `lift` and written
+    /// to the cache.
+    fn load_or_lift(
+        &self,
+        kind: &str,
+        name: &str,
+        file_id: FileID,
+        lift: impl FnOnce() -> Lifted,
+    ) -> Lifted {
+        let (Some(cache), Some(file_hash)) = (&self.cache, self.file_hash(file_id)) else {
+            return lift();
+        };
+        let key =
+            cache.key(&[&self.curve.to_string(), kind, name, &file_id.to_string(), &file_hash]);
+        if let Some(entry) = cache.load(&key) {
+            let is_valid = entry
+                .dependencies
+                .iter()
+                .all(|(template, hash)| self.template_file_hash(template) == *hash);
+            if is_valid {
+                trace!("loaded {kind} `{name}` from the cache");
+                return Lifted { cfg: entry.cfg.map(Arc::new), reports: entry.reports };
             }
-            // Get the AST corresponding to the template.
-            let Some(ast) = self.template_asts.get(name) else {
-                trace!("failed to lift unknown template `{name}`");
-                return Err(AnalysisError::UnknownTemplate { name: name.to_string() });
-            };
-            // Generate the template CFG from the AST. Cache any reports.
-            let mut reports = ReportCollection::new();
-            let cfg = generate_cfg(ast, &self.curve, &mut reports).map_err(|report| {
-                reports.push(*report);
-                trace!("failed to lift template `{name}`");
-                AnalysisError::FailedToLiftTemplate { name: name.to_string() }
-            })?;
-            self.append_template_reports(name, &mut reports);
-            self.template_cfgs.insert(name.to_string(), cfg);
-            trace!("successfully lifted template `{name}`");
         }
-        Ok(self.template_cfgs.get(name).unwrap())
+        let lifted = lift();
+        // CFGs that failed to lift are not cached since we cannot tell which
+        // templates they depend on.
+        if let Some(cfg) = &lifted.cfg {
+            let dependencies = self
+                .template_dependencies(cfg)
+                .into_iter()
+                .map(|template| {
+                    let hash = self.template_file_hash(&template);
+                    (template, hash)
+                })
+                .collect::<Vec<_>>();
+            cache.store(&key, Some(cfg), &lifted.reports, &dependencies);
+        }
+        lifted
     }
 
-    fn cache_function(&mut self, name: &str) -> Result<&Cfg, AnalysisError> {
-        if !self.function_cfgs.contains_key(name) {
-            // The function CFG needs to be generated from the AST.
-            if self.function_reports.contains_key(name) {
-                // We have already failed to generate the CFG.
-                return Err(AnalysisError::FailedToLiftFunction { name: name.to_string() });
-            }
-            // Get the AST corresponding to the function.
-            let Some(ast) = self.function_asts.get(name) else {
-                trace!("failed to lift unknown function `{name}`");
-                return Err(AnalysisError::UnknownFunction { name: name.to_string() });
+    /// Returns the (sorted) names of all templates instantiated by the CFG.
+    fn template_dependencies(&self, cfg: &Cfg) -> Vec<String> {
+        let mut templates = Vec::new();
+        for stmt in cfg.iter().flat_map(|basic_block| basic_block.iter()) {
+            let Statement::Substitution { rhe, .. } = stmt else {
+                continue;
             };
-            // Generate the function CFG from the AST. Cache any reports.
-            let mut reports = ReportCollection::new();
-            let cfg = generate_cfg(ast, &self.curve, &mut reports).map_err(|report| {
-                reports.push(*report);
-                trace!("failed to lift function `{name}`");
-                AnalysisError::FailedToLiftFunction { name: name.to_string() }
-            })?;
-            self.append_function_reports(name, &mut reports);
-            self.function_cfgs.insert(name.to_string(), cfg);
-            trace!("successfully lifted function `{name}`");
+            let rhe = match rhe {
+                Expression::Update { rhe, .. } => rhe,
+                rhe => rhe,
+            };
+            if let Expression::Call { name, .. } = rhe {
+                if self.is_template(name) {
+                    templates.push(name.clone());
+                }
+            }
         }
-        Ok(self.function_cfgs.get(name).unwrap())
+        templates.sort();
+        templates.dedup();
+        templates
     }
 
-    pub fn take_template(&mut self, name: &str) -> Result<Cfg, AnalysisError> {
-        self.cache_template(name)?;
-        // The CFG must be available since caching was successful.
-        Ok(self.template_cfgs.remove(name).unwrap())
+    /// Returns the content hash of the file with the given ID.
+    fn file_hash(&self, file_id: FileID) -> Option<String> {
+        let file = self.file_library.to_storage().get(file_id).ok()?;
+        Some(content_hash(file.source()))
     }
 
-    pub fn take_function(&mut self, name: &str) -> Result<Cfg, AnalysisError> {
-        self.cache_function(name)?;
-        // The CFG must be available since caching was successful.
-        Ok(self.function_cfgs.remove(name).unwrap())
+    /// Returns the content hash of the file defining the given template.
+    fn template_file_hash(&self, name: &str) -> Option<String> {
+        self.template_asts.get(name).and_then(|ast| self.file_hash(ast.get_file_id()))
+    }
+
+    fn lift_instance(
+        &self,
+        name: &str,
+        arguments: &[BigInt],
+    ) -> Result<Arc<Lifted>, AnalysisError> {
+        // Get the AST corresponding to the template.
+        let Some(ast) = self.template_asts.get(name) else {
+            trace!("failed to specialize unknown template `{name}`");
+            return Err(AnalysisError::UnknownTemplate { name: name.to_string() });
+        };
+        if ast.get_name_of_params().len() != arguments.len() {
+            trace!("invalid number of arguments for template `{name}`");
+            return Err(AnalysisError::InvalidTemplateArguments { name: name.to_string() });
+        }
+        let key = (name.to_string(), arguments.to_vec());
+        Ok(self.instance_cfgs.get_or_insert_with(&key, || {
+            // Generate the specialized template CFG from the AST. Reports
+            // generated during CFG generation are discarded here since they
+            // are emitted when the generic template is analyzed.
+            let mut reports = ReportCollection::new();
+            Arc::new(match generate_specialized_cfg(ast, arguments, &self.curve, &mut reports) {
+                Ok(cfg) => {
+                    trace!("successfully specialized template `{name}`");
+                    Lifted { cfg: Some(Arc::new(cfg)), reports: ReportCollection::new() }
+                }
+                Err(_) => {
+                    trace!("failed to specialize template `{name}`");
+                    Lifted::default()
+                }
+            })
+        }))
     }
 
-    pub fn replace_template(&mut self, name: &str, cfg: Cfg) -> bool {
-        self.template_cfgs.insert(name.to_string(), cfg).is_some()
+    /// Reuse the CFGs (and any reports generated during CFG generation) from a
+    /// previous runner for templates and functions defined in files that are
+    /// unchanged. This avoids regenerating CFGs when the runner is recreated
+    /// after a file has been edited.
+    pub fn reuse_cfgs(&mut self, mut previous: AnalysisRunner) {
+        let is_unchanged = |runner: &AnalysisRunner, file_id: FileID| {
+            let previous_file = previous.file_library.to_storage().get(file_id);
+            let current_file = runner.file_library.to_storage().get(file_id);
+            match (previous_file, current_file) {
+                (Ok(previous_file), Ok(current_file)) => {
+                    previous_file.name() == current_file.name()
+                        && previous_file.source() == current_file.source()
+                }
+                _ => false,
+            }
+        };
+        let mut templates = Vec::new();
+        for (name, ast) in &previous.template_asts {
+            let file_id = ast.get_file_id();
+            let is_same_definition = self
+                .template_asts
+                .get(name)
+                .map_or(false, |current_ast| current_ast.get_file_id() == file_id);
+            if is_same_definition && is_unchanged(self, file_id) {
+                templates.push(name.clone());
+            }
+        }
+        let mut functions = Vec::new();
+        for (name, ast) in &previous.function_asts {
+            let file_id = ast.get_file_id();
+            let is_same_definition = self
+                .function_asts
+                .get(name)
+                .map_or(false, |current_ast| current_ast.get_file_id() == file_id);
+            if is_same_definition && is_unchanged(self, file_id) {
+                functions.push(name.clone());
+            }
+        }
+        for name in templates {
+            if let Some(lifted) = previous.template_cfgs.remove(&name) {
+                self.template_cfgs.insert(name, lifted);
+            }
+        }
+        for name in functions {
+            if let Some(lifted) = previous.function_cfgs.remove(&name) {
+                self.function_cfgs.insert(name, lifted);
+            }
+        }
     }
 
-    pub fn replace_function(&mut self, name: &str, cfg: Cfg) -> bool {
-        self.function_cfgs.insert(name.to_string(), cfg).is_some()
+    /// Returns the file and location of the body of the given template,
+    /// function, or bus.
+    pub fn definition_location(&self, name: &str) -> Option<(FileID, FileLocation)> {
+        if let Some(ast) = self.template_asts.get(name) {
+            let meta = ast.get_body().get_meta();
+            return Some((ast.get_file_id(), meta.start..meta.end));
+        }
+        if let Some(ast) = self.function_asts.get(name) {
+            let meta = ast.get_body().get_meta();
+            return Some((ast.get_file_id(), meta.start..meta.end));
+        }
+        if let Some(ast) = self.bus_asts.get(name) {
+            let meta = ast.get_body().get_meta();
+            return Some((ast.get_file_id(), meta.start..meta.end));
+        }
+        None
     }
 }
 
@@ -296,12 +713,39 @@ impl AnalysisContext for AnalysisRunner {
         self.function_asts.contains_key(name)
     }
 
-    fn template(&mut self, name: &str) -> Result<&Cfg, AnalysisError> {
-        self.cache_template(name)
+    fn template(&self, name: &str) -> Result<Arc<Cfg>, AnalysisError> {
+        self.lift_template(name)?
+            .cfg
+            .clone()
+            .ok_or_else(|| AnalysisError::FailedToLiftTemplate { name: name.to_string() })
+    }
+
+    fn instance(&self, name: &str, arguments: &[BigInt]) -> Result<Arc<Cfg>, AnalysisError> {
+        self.lift_instance(name, arguments)?
+            .cfg
+            .clone()
+            .ok_or_else(|| AnalysisError::FailedToLiftTemplate { name: name.to_string() })
     }
 
-    fn function(&mut self, name: &str) -> Result<&Cfg, AnalysisError> {
-        self.cache_function(name)
+    fn function(&self, name: &str) -> Result<Arc<Cfg>, AnalysisError> {
+        self.lift_function(name)?
+            .cfg
+            .clone()
+            .ok_or_else(|| AnalysisError::FailedToLiftFunction { name: name.to_string() })
+    }
+
+    fn cached_analysis(
+        &self,
+        cfg: &Cfg,
+        analysis: TypeId,
+        compute: &dyn Fn() -> SharedAnalysis,
+    ) -> SharedAnalysis {
+        let key = (cfg.name().to_string(), cfg.arguments().map(<[BigInt]>::to_vec), analysis);
+        self.analyses.get_or_insert_with(&key, compute)
+    }
+
+    fn settings(&self) -> &AnalysisSettings {
+        &self.settings
     }
 
     fn underlying_str(
@@ -334,15 +778,60 @@ fn generate_cfg<Ast: IntoCfg>(
         .map_err(|error| Box::new(error.into()))
 }
 
+fn generate_specialized_cfg<Ast: IntoCfg>(
+    ast: Ast,
+    arguments: &[BigInt],
+    curve: &Curve,
+    reports: &mut ReportCollection,
+) -> Result<Cfg, Box<Report>> {
+    ast.into_cfg(curve, reports)
+        .map_err(|error| Box::new(error.into()))?
+        .specialize(arguments)
+        .into_ssa()
+        .map_err(|error| Box::new(error.into()))
+}
+
+/// Returns the template instantiated by the main component.
+fn main_template(program: &ProgramArchive) -> Option<String> {
+    match program.main_expression() {
+        ast::Expression::Call { id, .. } => Some(id.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the template and constant template arguments of the main component.
+/// Only literal arguments are supported.
+fn main_component(program: &ProgramArchive) -> Option<InstanceKey> {
+    let ast::Expression::Call { id, args, .. } = program.main_expression() else {
+        return None;
+    };
+    let arguments = args
+        .iter()
+        .map(|arg| match arg {
+            ast::Expression::Number(_, value) => Some(value.clone()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some((id.clone(), arguments))
+}
+
+fn report_key(report: &Report) -> ReportKey {
+    let locations =
+        report.primary().iter().map(|label| (label.file_id, label.range.clone())).collect();
+    (report.id(), report.message().clone(), locations)
+}
+
 #[cfg(test)]
 mod tests {
-    use program_structure::ir::Statement;
+    use program_structure::report::MessageCategory;
+    use program_structure::report_code::ReportCode;
+    use program_structure::writers::CachedStdoutWriter;
 
     use super::*;
 
     #[test]
     fn test_function() {
-        let mut runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&[r#"
+        let runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&[r#"
             function foo(a) {
                 return a[0] + a[1];
             }
@@ -351,27 +840,111 @@ mod tests {
         // Check that `foo` is a known function, that we can access the CFG
         // for `foo`, and that the CFG is properly cached.
         assert!(runner.is_function("foo"));
-        assert!(!runner.function_cfgs.contains_key("foo"));
+        assert!(!runner.function_cfgs.contains(&"foo".to_string()));
         assert!(runner.function("foo").is_ok());
-        assert!(runner.function_cfgs.contains_key("foo"));
+        assert!(runner.function_cfgs.contains(&"foo".to_string()));
 
-        // Check that the `take_function` and `replace_function` APIs work as expected.
-        let cfg = runner.take_function("foo").unwrap();
-        assert!(!runner.function_cfgs.contains_key("foo"));
-        assert!(!runner.replace_function("foo", cfg));
-        assert!(runner.function_cfgs.contains_key("foo"));
+        // Check that the same CFG is returned when the CFG is requested again.
+        let cfg = runner.function("foo").unwrap();
+        assert!(Arc::ptr_eq(&cfg, &runner.function("foo").unwrap()));
 
         // Check that `baz` is not a known function, that attempting to access
         // `baz` produces an error, and that nothing is cached.
         assert!(!runner.is_function("baz"));
-        assert!(!runner.function_cfgs.contains_key("baz"));
+        assert!(!runner.function_cfgs.contains(&"baz".to_string()));
         assert!(matches!(runner.function("baz"), Err(AnalysisError::UnknownFunction { .. })));
-        assert!(!runner.function_cfgs.contains_key("baz"));
+        assert!(!runner.function_cfgs.contains(&"baz".to_string()));
+    }
+
+    #[test]
+    fn test_reuse_cfgs() {
+        let foo = r#"
+            function foo(a) {
+                return a[0] + a[1];
+            }
+        "#;
+        let bar = r#"
+            function bar(a) {
+                return a[0] * a[1];
+            }
+        "#;
+        let previous = AnalysisRunner::new(Curve::Goldilocks).with_src(&[foo, bar]);
+        assert!(previous.function("foo").is_ok());
+        assert!(previous.function("bar").is_ok());
+
+        // Only the CFG of the function defined in the unchanged file is reused.
+        let changed_bar = bar.replace('*', "-");
+        let mut runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&[foo, &changed_bar]);
+        runner.reuse_cfgs(previous);
+        assert!(runner.function_cfgs.contains(&"foo".to_string()));
+        assert!(!runner.function_cfgs.contains(&"bar".to_string()));
+        assert!(runner.function("bar").is_ok());
+    }
+
+    #[test]
+    fn test_lazy_cache() {
+        let cache = LazyCache::<usize, usize>::default();
+
+        // Values may be computed from other cached values.
+        let value = cache.get_or_insert_with(&1, || {
+            let other = cache.get_or_insert_with(&0, || Arc::new(1));
+            Arc::new(*other + 1)
+        });
+        assert_eq!(*value, 2);
+        assert!(cache.contains(&0));
+
+        // All threads get the same value, even if it is computed concurrently.
+        let values = (0..64)
+            .into_par_iter()
+            .map(|index| cache.get_or_insert_with(&2, || Arc::new(index)))
+            .collect::<Vec<_>>();
+        assert!(values.iter().all(|value| Arc::ptr_eq(value, &values[0])));
+    }
+
+    #[test]
+    fn test_cache() {
+        let foo = r#"
+            function foo(a) {
+                return a[0] + a[1];
+            }
+        "#;
+        let directory =
+            std::env::temp_dir().join(format!("circomspect-test-cache-{}", std::process::id()));
+        let cache = Cache::new(&directory, "test");
+        let entries = || std::fs::read_dir(&directory).map_or(0, |entries| entries.count());
+
+        // The CFG is written to the cache when it is generated.
+        let runner =
+            AnalysisRunner::new(Curve::Goldilocks).with_cache(cache.clone()).with_src(&[foo]);
+        let cfg = runner.function("foo").unwrap();
+        assert_eq!(entries(), 1);
+
+        // The CFG is loaded from the cache by the next runner.
+        let runner =
+            AnalysisRunner::new(Curve::Goldilocks).with_cache(cache.clone()).with_src(&[foo]);
+        let cached_cfg = runner.function("foo").unwrap();
+        assert_eq!(cached_cfg.name(), cfg.name());
+        assert_eq!(cached_cfg.len(), cfg.len());
+        assert_eq!(entries(), 1);
+
+        // Changing the file or the curve invalidates the entry.
+        let changed_foo = foo.replace('+', "-");
+        let runner = AnalysisRunner::new(Curve::Goldilocks)
+            .with_cache(cache.clone())
+            .with_src(&[&changed_foo]);
+        assert!(runner.function("foo").is_ok());
+        assert_eq!(entries(), 2);
+        let runner = AnalysisRunner::new(Curve::Bn254).with_cache(cache.clone()).with_src(&[foo]);
+        assert!(runner.function("foo").is_ok());
+        assert_eq!(entries(), 3);
+
+        cache.clear().unwrap();
+        assert_eq!(entries(), 0);
     }
 
     #[test]
     fn test_template() {
-        let mut runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&[r#"
+        let runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&[r#"
             template Foo(n) {
                 signal input a[2];
 
@@ -382,28 +955,84 @@ mod tests {
         // Check that `Foo` is a known template, that we can access the CFG
         // for `Foo`, and that the CFG is properly cached.
         assert!(runner.is_template("Foo"));
-        assert!(!runner.template_cfgs.contains_key("Foo"));
+        assert!(!runner.template_cfgs.contains(&"Foo".to_string()));
         assert!(runner.template("Foo").is_ok());
-        assert!(runner.template_cfgs.contains_key("Foo"));
+        assert!(runner.template_cfgs.contains(&"Foo".to_string()));
 
-        // Check that the `take_template` and `replace_template` APIs work as expected.
-        let cfg = runner.take_template("Foo").unwrap();
-        assert!(!runner.template_cfgs.contains_key("Foo"));
-        assert!(!runner.replace_template("Foo", cfg));
-        assert!(runner.template_cfgs.contains_key("Foo"));
+        // Check that the same CFG is returned when the CFG is requested again.
+        let cfg = runner.template("Foo").unwrap();
+        assert!(Arc::ptr_eq(&cfg, &runner.template("Foo").unwrap()));
 
         // Check that `Baz` is not a known template, that attempting to access
         // `Baz` produces an error, and that nothing is cached.
         assert!(!runner.is_template("Baz"));
-        assert!(!runner.template_cfgs.contains_key("Baz"));
+        assert!(!runner.template_cfgs.contains(&"Baz".to_string()));
         assert!(matches!(runner.template("Baz"), Err(AnalysisError::UnknownTemplate { .. })));
-        assert!(!runner.template_cfgs.contains_key("Baz"));
+        assert!(!runner.template_cfgs.contains(&"Baz".to_string()));
+    }
+
+    #[test]
+    fn test_bus() {
+        let runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&[
+            r#"
+            bus Point() {
+                signal x;
+                signal y;
+            }
+        "#,
+            r#"
+            template Foo() {
+                input Point() a;
+                output Point() b;
+                Point() c;
+
+                c <== a;
+                b.x <== c.x;
+                b.y <== c.y;
+            }
+        "#,
+        ]);
+
+        // Check that bus inputs and outputs are identified.
+        let cfg = runner.template("Foo").unwrap();
+        let inputs = cfg.input_signals().map(|name| name.to_string()).collect::<Vec<_>>();
+        let outputs = cfg.output_signals().map(|name| name.to_string()).collect::<Vec<_>>();
+        assert_eq!(inputs, vec!["a"]);
+        assert_eq!(outputs, vec!["b"]);
+
+        // Check that buses are not templates, but have a known location.
+        assert!(!runner.is_template("Point"));
+        assert!(runner.definition_location("Point").is_some());
+    }
+
+    #[test]
+    fn test_analysis() {
+        use crate::taint_analysis::TaintAnalysis;
+
+        let runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&[r#"
+            template Foo(n) {
+                signal input a[2];
+
+                a[0] === a[1];
+            }
+        "#]);
+        let context: &dyn AnalysisContext = &runner;
+
+        // Check that the analysis is computed once and then shared.
+        let cfg = runner.template("Foo").unwrap();
+        let taint_analysis = context.analysis::<TaintAnalysis>(&cfg);
+        assert!(Arc::ptr_eq(&taint_analysis, &context.analysis::<TaintAnalysis>(&cfg)));
+
+        // Check that instances do not share analyses with the template.
+        let instance = runner.instance("Foo", &[BigInt::from(2)]).unwrap();
+        let instance_analysis = context.analysis::<TaintAnalysis>(&instance);
+        assert!(!Arc::ptr_eq(&taint_analysis, &instance_analysis));
     }
 
     #[test]
     fn test_underlying_str() {
         use Statement::*;
-        let mut runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&[r#"
+        let runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&[r#"
             template Foo(n) {
                 signal input a[2];
 
@@ -411,7 +1040,7 @@ mod tests {
             }
         "#]);
 
-        let cfg = runner.take_template("Foo").unwrap();
+        let cfg = runner.template("Foo").unwrap();
         for stmt in cfg.entry_block().iter() {
             let file_id = stmt.meta().file_id().unwrap();
             let file_location = stmt.meta().file_location();
@@ -424,4 +1053,106 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_instances() {
+        let mut runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&[
+            r#"
+            template Num2Bits(n) {
+                signal input in;
+                signal output out[n];
+
+                var lc = 0;
+                var e = 1;
+                for (var i = 0; i < n; i++) {
+                    out[i] <-- (in >> i) & 1;
+                    lc += out[i] * e;
+                    e = e + e;
+                }
+                lc === in;
+            }
+        "#,
+            r#"
+            template Main() {
+                signal input in;
+                signal output out[4];
+
+                component n2b = Num2Bits(4);
+                n2b.in <== in;
+                out <== n2b.out;
+            }
+        "#,
+        ]);
+        runner.main_component = Some(("Main".to_string(), Vec::new()));
+
+        // Check that template instances are found and properly cached.
+        let arguments = vec![BigInt::from(4)];
+        let cfg = runner.instance("Main", &[]).unwrap();
+        assert_eq!(
+            runner.template_instances(&cfg),
+            vec![("Num2Bits".to_string(), arguments.clone())]
+        );
+        assert!(runner.instance_cfgs.contains(&("Main".to_string(), Vec::new())));
+        assert_eq!(runner.instance("Num2Bits", &arguments).unwrap().instance_name(), "Num2Bits(4)");
+        assert!(matches!(
+            runner.instance("Num2Bits", &[]),
+            Err(AnalysisError::InvalidTemplateArguments { .. })
+        ));
+
+        // The missing bit constraints in `Num2Bits` are only detected when
+        // the template is specialized.
+        let mut writer = CachedStdoutWriter::new(false);
+        runner.analyze_templates(&mut writer, true);
+        // The generic template cannot be analyzed, which is reported at info level.
+        let is_under_constrained = |report: &&Report| {
+            matches!(report.code(), ReportCode::UnderConstrainedOutputSignal)
+                && matches!(report.category(), MessageCategory::Warning)
+        };
+        assert_eq!(writer.reports().iter().filter(is_under_constrained).count(), 0);
+        let reports_written = writer.reports().len();
+
+        runner.analyze_instances(&mut writer, true);
+        let reports = &writer.reports()[reports_written..];
+        assert_eq!(reports.iter().filter(is_under_constrained).count(), 1);
+        // Reports from the generic analysis are not repeated.
+        assert!(reports.iter().all(|report| report.instance().is_some()));
+        assert!(reports.iter().any(|report| report.instance() == Some("Num2Bits(4)")));
+    }
+
+    #[test]
+    fn test_deterministic_output() {
+        let src = (0..16)
+            .map(|index| {
+                format!(
+                    r#"
+                    template T{index:02}() {{
+                        signal input in;
+                        signal output out;
+
+                        out <-- in;
+                    }}
+                "#
+                )
+            })
+            .collect::<Vec<_>>();
+        let src = src.iter().map(String::as_str).collect::<Vec<_>>();
+
+        // Templates are analyzed in parallel, but reports are written in order.
+        let definitions = || {
+            let mut runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&src);
+            let mut writer = CachedStdoutWriter::new(false);
+            runner.analyze_templates(&mut writer, true);
+            writer
+                .reports()
+                .iter()
+                .map(|report| report.definition().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        let first = definitions();
+        assert!(!first.is_empty());
+        assert!(first.windows(2).all(|names| names[0] <= names[1]));
+        for _ in 0..4 {
+            assert_eq!(definitions(), first);
+        }
+    }
 }
@@ -9,7 +9,9 @@ use program_structure::report::{Report, ReportCollection};
 use program_structure::report_code::ReportCode;
 use program_structure::file_definition::{FileLocation, FileID};
 
-const PROBLEMATIC_GOLDILOCK_TEMPLATES: [&str; 26] = [
+// Templates that should not be used with primes smaller than the BN254 prime
+// (like Goldilocks).
+const PROBLEMATIC_SMALL_PRIME_TEMPLATES: [&str; 26] = [
     "BabyPbk",
     "AliasCheck",
     "CompConstant",
@@ -38,7 +40,9 @@ const PROBLEMATIC_GOLDILOCK_TEMPLATES: [&str; 26] = [
     "SMTVerifierLevel",
 ];
 
-const PROBLEMATIC_BLS12_381_TEMPLATES: [&str; 13] = [
+// Templates that should not be used with primes of the same size as, or larger
+// than, the BN254 prime (like BLS12-381).
+const PROBLEMATIC_LARGE_PRIME_TEMPLATES: [&str; 13] = [
     "AliasCheck",
     "CompConstant",
     "Num2Bits_strict",
@@ -84,9 +88,13 @@ impl Bn254SpecificCircuitWarning {
 // related to BN254. If these are used together with a different prime, this may
 // be an issue.
 //
-// The following table contains a check for each problematic template-curve pair.
+// Templates with hard-coded constants larger than the prime are problematic for
+// primes smaller than the BN254 prime, while templates relying on the size of
+// the BN254 prime are problematic for all primes other than the BN254 prime.
+// The following table contains a check for each problematic template and
+// prime, using Goldilocks and BLS12-381 as examples.
 //
-// Template             Goldilocks (64 bits)        BLS12-381 (255 bits)
+// Template             < BN254 (Goldilocks)        > BN254 (BLS12-381)
 // -----------------------------------------------------------------
 // AliasCheck                   x                           x
 // BabyPbk                      x
@@ -116,12 +124,14 @@ impl Bn254SpecificCircuitWarning {
 // SMTVerifierLevel             x
 pub fn find_bn254_specific_circuits(cfg: &Cfg) -> ReportCollection {
     let problematic_templates = match cfg.constants().curve() {
-        Curve::Goldilocks => HashSet::from(PROBLEMATIC_GOLDILOCK_TEMPLATES),
-        Curve::Bls12_381 => HashSet::from(PROBLEMATIC_BLS12_381_TEMPLATES),
         Curve::Bn254 => {
             // Exit early if we're using the default curve.
             return ReportCollection::new();
         }
+        _ if cfg.constants().prime() < &Curve::Bn254.prime() => {
+            HashSet::from(PROBLEMATIC_SMALL_PRIME_TEMPLATES)
+        }
+        _ => HashSet::from(PROBLEMATIC_LARGE_PRIME_TEMPLATES),
     };
     debug!("running bn254-specific circuit analysis pass");
     let mut reports = ReportCollection::new();
@@ -206,12 +216,35 @@ mod tests {
         validate_reports(src, 0);
     }
 
+    #[test]
+    fn test_prime_size() {
+        let src = r#"
+            template T(n) {
+                signal input in[2];
+                signal output out;
+
+                component poseidon = Poseidon(2);
+                poseidon.inputs <== in;
+                out <== poseidon.out;
+            }
+        "#;
+        // `Poseidon` is only problematic for primes smaller than the BN254 prime.
+        validate_curve_reports(src, &Curve::Bls12_377, 1);
+        validate_curve_reports(src, &Curve::from_prime("0x7fffffff").unwrap(), 1);
+        validate_curve_reports(src, &Curve::Pallas, 0);
+        validate_curve_reports(src, &Curve::Bn254, 0);
+    }
+
     fn validate_reports(src: &str, expected_len: usize) {
+        validate_curve_reports(src, &Curve::Bls12_381, expected_len);
+    }
+
+    fn validate_curve_reports(src: &str, curve: &Curve, expected_len: usize) {
         // Build CFG.
         let mut reports = ReportCollection::new();
         let cfg = parse_definition(src)
             .unwrap()
-            .into_cfg(&Curve::Bls12_381, &mut reports)
+            .into_cfg(curve, &mut reports)
             .unwrap()
             .into_ssa()
             .unwrap();
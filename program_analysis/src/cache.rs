@@ -0,0 +1,168 @@
+use log::{debug, trace};
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use program_structure::{cfg::Cfg, report::ReportCollection};
+
+/// A template instantiated by a cached definition, together with the content
+/// hash of the file defining the template (if known).
+pub(crate) type Dependency = (String, Option<String>);
+
+/// A cache entry as it is written to disk.
+#[derive(Serialize)]
+struct EntryRef<'a> {
+    cfg: Option<&'a Cfg>,
+    reports: &'a ReportCollection,
+    dependencies: &'a [Dependency],
+}
+
+/// A cache entry as it is read from disk.
+#[derive(Deserialize)]
+pub(crate) struct CacheEntry {
+    /// The lifted CFG, or `None` if the definition could not be lifted.
+    pub cfg: Option<Cfg>,
+    /// Reports generated during CFG generation.
+    pub reports: ReportCollection,
+    /// Anonymous components are rewritten using the signals declared by the
+    /// instantiated template. The entry is only valid if the files defining
+    /// the instantiated templates are unchanged.
+    pub dependencies: Vec<Dependency>,
+}
+
+/// An on-disk cache of template and function CFGs (and any reports generated
+/// during CFG generation). Entries are keyed by the tool version, the curve,
+/// and a content hash of the file defining the template or function. This
+/// allows CFGs of definitions in unchanged files (e.g. library templates) to
+/// be loaded rather than regenerated on each run.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    directory: PathBuf,
+    version: String,
+}
+
+impl Cache {
+    /// Creates a cache backed by the given directory. Entries created by other
+    /// tool versions are ignored.
+    pub fn new(directory: &Path, version: &str) -> Cache {
+        Cache { directory: directory.to_path_buf(), version: version.to_string() }
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Removes all cache entries, together with any temporary files left
+    /// behind by interrupted runs.
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.directory) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Returns the cache key for the given definition. The key depends on the
+    /// tool version, the curve, the definition, and the file defining it.
+    pub(crate) fn key(&self, fields: &[&str]) -> String {
+        let mut hasher = Sha256::new();
+        for field in [self.version.as_str()].iter().chain(fields) {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+        to_hex(&hasher.finalize())
+    }
+
+    /// Loads the entry with the given key, if it exists. Entries that cannot
+    /// be read or deserialized are treated as missing.
+    pub(crate) fn load(&self, key: &str) -> Option<CacheEntry> {
+        let contents = fs::read(self.entry_path(key)).ok()?;
+        match bincode::deserialize(&contents) {
+            Ok(entry) => Some(entry),
+            Err(error) => {
+                debug!("failed to deserialize cache entry `{key}`: {error}");
+                None
+            }
+        }
+    }
+
+    /// Stores an entry under the given key. Failing to write the entry is not
+    /// an error since the CFG can always be regenerated.
+    pub(crate) fn store(
+        &self,
+        key: &str,
+        cfg: Option<&Cfg>,
+        reports: &ReportCollection,
+        dependencies: &[Dependency],
+    ) {
+        let entry = EntryRef { cfg, reports, dependencies };
+        if let Err(error) = self.write_entry(key, &entry) {
+            debug!("failed to write cache entry `{key}`: {error}");
+        }
+    }
+
+    fn write_entry(&self, key: &str, entry: &EntryRef) -> io::Result<()> {
+        let contents = bincode::serialize(entry)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        fs::create_dir_all(&self.directory)?;
+        // Write to a temporary file first so that concurrent runs never read
+        // a partially written entry.
+        let temp_path = self.directory.join(format!("{key}.{}.tmp", std::process::id()));
+        let result = fs::write(&temp_path, contents)
+            .and_then(|_| fs::rename(&temp_path, self.entry_path(key)));
+        if result.is_err() {
+            // Ignore errors since the file may not exist.
+            let _ = fs::remove_file(&temp_path);
+        }
+        result?;
+        trace!("wrote cache entry `{key}`");
+        Ok(())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.directory.join(key)
+    }
+}
+
+/// Returns a hex-encoded SHA-256 hash of the given file contents.
+pub(crate) fn content_hash(contents: &str) -> String {
+    to_hex(&Sha256::digest(contents.as_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temporary_files() {
+        let directory = std::env::temp_dir()
+            .join(format!("circomspect-test-temporary-files-{}", std::process::id()));
+        let cache = Cache::new(&directory, "test");
+        let temp_files = || {
+            fs::read_dir(&directory)
+                .unwrap()
+                .filter(|entry| entry.as_ref().unwrap().path().extension() == Some("tmp".as_ref()))
+                .count()
+        };
+
+        // A non-empty directory at the entry path makes the rename fail. The
+        // temporary file is removed.
+        let key = cache.key(&["foo"]);
+        fs::create_dir_all(directory.join(&key).join("foo")).unwrap();
+        cache.store(&key, None, &ReportCollection::new(), &[]);
+        assert!(cache.load(&key).is_none());
+        assert_eq!(temp_files(), 0);
+
+        // Temporary files left behind by interrupted runs are removed when the
+        // cache is cleared.
+        fs::write(directory.join(format!("{key}.1.tmp")), "").unwrap();
+        assert_eq!(temp_files(), 1);
+        cache.clear().unwrap();
+        assert!(!directory.exists());
+    }
+}
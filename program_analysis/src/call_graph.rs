@@ -0,0 +1,634 @@
+use log::debug;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use program_structure::cfg::{escape_label, Cfg};
+use program_structure::file_definition::{FileID, FileLibrary, FileLocation};
+use program_structure::ir::*;
+
+use crate::analysis_context::AnalysisContext;
+use crate::analysis_runner::AnalysisRunner;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Template,
+    Function,
+}
+
+impl NodeKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            NodeKind::Template => "template",
+            NodeKind::Function => "function",
+        }
+    }
+}
+
+/// A template or function.
+#[derive(Clone, Debug)]
+pub struct Node {
+    name: String,
+    kind: NodeKind,
+    file_id: Option<FileID>,
+}
+
+impl Node {
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    #[must_use]
+    pub fn file_id(&self) -> Option<FileID> {
+        self.file_id
+    }
+}
+
+/// A template instantiation or function call.
+#[derive(Clone, Debug)]
+pub struct Edge {
+    source: usize,
+    target: usize,
+    arguments: Vec<Expression>,
+    file_id: Option<FileID>,
+    file_location: FileLocation,
+}
+
+impl Edge {
+    /// Returns the index of the instantiating or calling template or function.
+    #[must_use]
+    pub fn source(&self) -> usize {
+        self.source
+    }
+
+    /// Returns the index of the instantiated template or called function.
+    #[must_use]
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    /// Returns the template or function arguments.
+    #[must_use]
+    pub fn arguments(&self) -> &[Expression] {
+        &self.arguments
+    }
+
+    #[must_use]
+    pub fn file_id(&self) -> Option<FileID> {
+        self.file_id
+    }
+
+    #[must_use]
+    pub fn file_location(&self) -> FileLocation {
+        self.file_location.clone()
+    }
+}
+
+/// The template instantiation and function call graph of a program. Nodes
+/// correspond to templates and functions, and there is an edge from `A` to `B`
+/// for each location where `A` instantiates or calls `B`.
+#[derive(Clone, Debug, Default)]
+pub struct CallGraph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    indices: HashMap<String, usize>,
+    main_component: Option<usize>,
+}
+
+impl CallGraph {
+    /// Builds the call graph from all templates and functions known to the
+    /// runner. Templates and functions that cannot be lifted to a CFG are
+    /// included in the graph, but have no outgoing edges.
+    pub fn new(runner: &AnalysisRunner) -> CallGraph {
+        debug!("building call graph");
+        let mut graph = CallGraph::default();
+        let mut template_names = runner.template_names(false);
+        let mut function_names = runner.function_names(false);
+        template_names.sort();
+        function_names.sort();
+        for name in &template_names {
+            let file_id = runner.template(name).ok().and_then(|cfg| *cfg.file_id());
+            graph.add_node(name, NodeKind::Template, file_id);
+        }
+        for name in &function_names {
+            let file_id = runner.function(name).ok().and_then(|cfg| *cfg.file_id());
+            graph.add_node(name, NodeKind::Function, file_id);
+        }
+        for name in &template_names {
+            if let Ok(cfg) = runner.template(name) {
+                let edges = graph.find_edges(&cfg);
+                graph.edges.extend(edges);
+            }
+        }
+        for name in &function_names {
+            if let Ok(cfg) = runner.function(name) {
+                let edges = graph.find_edges(&cfg);
+                graph.edges.extend(edges);
+            }
+        }
+        graph.main_component = runner.main_template().and_then(|name| graph.get_index(name));
+        graph
+    }
+
+    fn add_node(&mut self, name: &str, kind: NodeKind, file_id: Option<FileID>) {
+        self.indices.insert(name.to_string(), self.nodes.len());
+        self.nodes.push(Node { name: name.to_string(), kind, file_id });
+    }
+
+    /// Returns all template instantiations and function calls in the CFG.
+    fn find_edges(&self, cfg: &Cfg) -> Vec<Edge> {
+        let Some(source) = self.get_index(cfg.name()) else {
+            return Vec::new();
+        };
+        let mut edges = Vec::new();
+        let mut add_edge = |expr: &Expression| {
+            if let Expression::Call { meta, name, args } = expr {
+                if let Some(target) = self.get_index(name) {
+                    edges.push(Edge {
+                        source,
+                        target,
+                        arguments: args.clone(),
+                        file_id: meta.file_id(),
+                        file_location: meta.file_location(),
+                    });
+                }
+            }
+        };
+        for basic_block in cfg.iter() {
+            for stmt in basic_block.iter() {
+                visit_statement(stmt, &mut add_edge);
+            }
+        }
+        edges
+    }
+
+    #[must_use]
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    #[must_use]
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    #[must_use]
+    pub fn get_node(&self, index: usize) -> Option<&Node> {
+        self.nodes.get(index)
+    }
+
+    /// Returns the index of the template or function with the given name.
+    #[must_use]
+    pub fn get_index(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
+
+    /// Returns the index of the main component template, if known.
+    #[must_use]
+    pub fn main_component(&self) -> Option<usize> {
+        self.main_component
+    }
+
+    /// Returns the templates and functions instantiated or called by the given
+    /// node.
+    #[must_use]
+    pub fn successors(&self, index: usize) -> BTreeSet<usize> {
+        self.edges.iter().filter(|edge| edge.source == index).map(|edge| edge.target).collect()
+    }
+
+    /// Returns the set of nodes reachable from the given node (including the
+    /// node itself).
+    #[must_use]
+    pub fn reachable_from(&self, index: usize) -> BTreeSet<usize> {
+        let mut reachable = BTreeSet::new();
+        let mut worklist = vec![index];
+        while let Some(index) = worklist.pop() {
+            if reachable.insert(index) {
+                worklist.extend(self.successors(index));
+            }
+        }
+        reachable
+    }
+
+    /// Returns the set of nodes reachable from the main component. If the main
+    /// component is not known, this returns the empty set.
+    #[must_use]
+    pub fn reachable_from_main(&self) -> BTreeSet<usize> {
+        self.main_component.map(|index| self.reachable_from(index)).unwrap_or_default()
+    }
+
+    /// Returns the sets of mutually recursive templates and functions. (These
+    /// are the strongly connected components of the graph containing a cycle.)
+    #[must_use]
+    pub fn recursive_components(&self) -> Vec<Vec<usize>> {
+        let successors = (0..self.nodes.len()).map(|index| self.successors(index)).collect();
+        let mut tarjan = Tarjan::new(successors);
+        for index in 0..self.nodes.len() {
+            if tarjan.indices[index].is_none() {
+                tarjan.visit(index);
+            }
+        }
+        let mut components = tarjan
+            .components
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || self.successors(component[0]).contains(&component[0])
+            })
+            .map(|mut component| {
+                component.sort();
+                component
+            })
+            .collect::<Vec<_>>();
+        components.sort();
+        components
+    }
+
+    /// Returns the nodes in topological order. That is, each template or
+    /// function precedes the templates and functions that it instantiates or
+    /// calls. Returns `None` if the graph contains recursion.
+    #[must_use]
+    pub fn topological_order(&self) -> Option<Vec<usize>> {
+        let mut in_degree = vec![0; self.nodes.len()];
+        let successors =
+            (0..self.nodes.len()).map(|index| self.successors(index)).collect::<Vec<_>>();
+        for targets in &successors {
+            for target in targets {
+                in_degree[*target] += 1;
+            }
+        }
+        let mut ready =
+            (0..self.nodes.len()).filter(|index| in_degree[*index] == 0).collect::<BTreeSet<_>>();
+        let mut order = Vec::new();
+        while let Some(index) = ready.iter().next().copied() {
+            ready.remove(&index);
+            order.push(index);
+            for target in &successors[index] {
+                in_degree[*target] -= 1;
+                if in_degree[*target] == 0 {
+                    ready.insert(*target);
+                }
+            }
+        }
+        (order.len() == self.nodes.len()).then_some(order)
+    }
+
+    /// Renders the graph in Graphviz DOT format. Templates are rendered as
+    /// boxes and functions as ellipses. The main component is rendered in bold.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph \"call graph\" {{").unwrap();
+        for (index, node) in self.nodes.iter().enumerate() {
+            let shape = match node.kind {
+                NodeKind::Template => "box",
+                NodeKind::Function => "ellipse",
+            };
+            let style = if Some(index) == self.main_component { ", style=bold" } else { "" };
+            writeln!(
+                dot,
+                "    {index} [label=\"{}\", shape={shape}{style}];",
+                escape_label(&node.name)
+            )
+            .unwrap();
+        }
+        for edge in &self.edges {
+            let arguments = edge.arguments.iter().map(ToString::to_string).collect::<Vec<_>>();
+            writeln!(
+                dot,
+                "    {} -> {} [label=\"({})\"];",
+                edge.source,
+                edge.target,
+                escape_label(&arguments.join(", "))
+            )
+            .unwrap();
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+
+    /// Renders the graph as JSON. Source locations are given as file names and
+    /// line numbers using the given file library.
+    #[must_use]
+    pub fn to_json(&self, file_library: &FileLibrary) -> Value {
+        let file_name = |file_id: Option<FileID>| {
+            file_id.and_then(|file_id| {
+                file_library.to_storage().get(file_id).ok().map(|file| file.name().clone())
+            })
+        };
+        let names = |indices: &[usize]| {
+            indices.iter().map(|index| self.nodes[*index].name.clone()).collect::<Vec<_>>()
+        };
+        let reachable = self.reachable_from_main();
+        let nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                json!({
+                    "id": index,
+                    "name": node.name,
+                    "kind": node.kind.as_str(),
+                    "file": file_name(node.file_id),
+                    "reachable": reachable.contains(&index),
+                })
+            })
+            .collect::<Vec<_>>();
+        let edges = self
+            .edges
+            .iter()
+            .map(|edge| {
+                let line = edge
+                    .file_id
+                    .and_then(|file_id| file_library.get_line(edge.file_location.start, file_id));
+                json!({
+                    "source": edge.source,
+                    "target": edge.target,
+                    "kind": match self.nodes[edge.target].kind {
+                        NodeKind::Template => "instantiation",
+                        NodeKind::Function => "call",
+                    },
+                    "arguments": edge.arguments.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                    "location": {
+                        "file": file_name(edge.file_id),
+                        "line": line,
+                        "start": edge.file_location.start,
+                        "end": edge.file_location.end,
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "main": self.main_component.map(|index| self.nodes[index].name.clone()),
+            "nodes": nodes,
+            "edges": edges,
+            "recursive": self
+                .recursive_components()
+                .iter()
+                .map(|component| names(component))
+                .collect::<Vec<_>>(),
+            "topological_order": self.topological_order().map(|order| names(&order)),
+        })
+    }
+}
+
+/// Tarjan's strongly connected components algorithm.
+struct Tarjan {
+    successors: Vec<BTreeSet<usize>>,
+    indices: Vec<Option<usize>>,
+    low_links: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    next_index: usize,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan {
+    fn new(successors: Vec<BTreeSet<usize>>) -> Tarjan {
+        let len = successors.len();
+        Tarjan {
+            successors,
+            indices: vec![None; len],
+            low_links: vec![0; len],
+            on_stack: vec![false; len],
+            stack: Vec::new(),
+            next_index: 0,
+            components: Vec::new(),
+        }
+    }
+
+    fn visit(&mut self, node: usize) {
+        self.indices[node] = Some(self.next_index);
+        self.low_links[node] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+
+        for successor in self.successors[node].clone() {
+            match self.indices[successor] {
+                None => {
+                    self.visit(successor);
+                    self.low_links[node] = self.low_links[node].min(self.low_links[successor]);
+                }
+                Some(index) if self.on_stack[successor] => {
+                    self.low_links[node] = self.low_links[node].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+        if Some(self.low_links[node]) == self.indices[node] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+fn visit_statement<F: FnMut(&Expression)>(stmt: &Statement, visit: &mut F) {
+    use Statement::*;
+    match stmt {
+        Declaration { dimensions, .. } => {
+            for size in dimensions {
+                visit_expression(size, visit);
+            }
+        }
+        LogCall { args, .. } => {
+            for arg in args {
+                if let LogArgument::Expr(value) = arg {
+                    visit_expression(value, visit);
+                }
+            }
+        }
+        IfThenElse { cond, .. } => visit_expression(cond, visit),
+        Substitution { rhe, .. } => visit_expression(rhe, visit),
+        Return { value, .. } => visit_expression(value, visit),
+        Assert { arg, .. } => visit_expression(arg, visit),
+        ConstraintEquality { lhe, rhe, .. } => {
+            visit_expression(lhe, visit);
+            visit_expression(rhe, visit);
+        }
+    }
+}
+
+fn visit_expression<F: FnMut(&Expression)>(expr: &Expression, visit: &mut F) {
+    use Expression::*;
+    visit(expr);
+    match expr {
+        InfixOp { lhe, rhe, .. } => {
+            visit_expression(lhe, visit);
+            visit_expression(rhe, visit);
+        }
+        PrefixOp { rhe, .. } => visit_expression(rhe, visit),
+        SwitchOp { cond, if_true, if_false, .. } => {
+            visit_expression(cond, visit);
+            visit_expression(if_true, visit);
+            visit_expression(if_false, visit);
+        }
+        Call { args, .. } => {
+            for arg in args {
+                visit_expression(arg, visit);
+            }
+        }
+        InlineArray { values, .. } => {
+            for value in values {
+                visit_expression(value, visit);
+            }
+        }
+        Access { access, .. } => {
+            for access in access {
+                if let AccessType::ArrayAccess(index) = access {
+                    visit_expression(index, visit);
+                }
+            }
+        }
+        Update { access, rhe, .. } => {
+            for access in access {
+                if let AccessType::ArrayAccess(index) = access {
+                    visit_expression(index, visit);
+                }
+            }
+            visit_expression(rhe, visit);
+        }
+        Number(_, _) | Variable { .. } | Phi { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use program_structure::constants::Curve;
+
+    use super::*;
+
+    #[test]
+    fn test_call_graph() {
+        let runner = AnalysisRunner::new(Curve::Goldilocks)
+            .with_src(&[
+                r#"
+            function square(x) {
+                return x * x;
+            }
+        "#,
+                r#"
+            function even(n) {
+                return n == 0 ? 1 : odd(n - 1);
+            }
+        "#,
+                r#"
+            function odd(n) {
+                return n == 0 ? 0 : even(n - 1);
+            }
+        "#,
+                r#"
+            template Square() {
+                signal input in;
+                signal output out;
+
+                out <== in * in;
+            }
+        "#,
+                r#"
+            template Main(n) {
+                signal input in;
+                signal output out;
+
+                component square = Square();
+                square.in <== in * square(n);
+                out <== square.out;
+            }
+        "#,
+            ])
+            .with_main_template("Main");
+        let graph = CallGraph::new(&runner);
+        let index = |name: &str| graph.get_index(name).unwrap();
+
+        assert_eq!(graph.nodes().len(), 5);
+        assert_eq!(graph.edges().len(), 4);
+        assert_eq!(graph.main_component(), Some(index("Main")));
+        assert_eq!(
+            graph.successors(index("Main")),
+            BTreeSet::from([index("Square"), index("square")])
+        );
+        assert_eq!(
+            graph
+                .edges()
+                .iter()
+                .find(|edge| edge.target() == index("square"))
+                .unwrap()
+                .arguments()
+                .len(),
+            1
+        );
+
+        // `even` and `odd` are not reachable from `Main`.
+        assert_eq!(
+            graph.reachable_from_main(),
+            BTreeSet::from([index("Main"), index("Square"), index("square")])
+        );
+
+        // `even` and `odd` are mutually recursive.
+        let mut recursive = vec![index("even"), index("odd")];
+        recursive.sort();
+        assert_eq!(graph.recursive_components(), vec![recursive]);
+        assert!(graph.topological_order().is_none());
+
+        // The DOT output contains all nodes.
+        let dot = graph.to_dot();
+        assert!(graph.nodes().iter().all(|node| dot.contains(&format!("\"{}\"", node.name()))));
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let runner = AnalysisRunner::new(Curve::Goldilocks).with_src(&[
+            r#"
+            template A() {
+                signal input in;
+                component b = B();
+                component c = C();
+                b.in <== in;
+                c.in <== b.out;
+            }
+        "#,
+            r#"
+            template B() {
+                signal input in;
+                signal output out;
+                component c = C();
+                c.in <== in;
+                out <== c.out;
+            }
+        "#,
+            r#"
+            template C() {
+                signal input in;
+                signal output out;
+                out <== in;
+            }
+        "#,
+        ]);
+        let graph = CallGraph::new(&runner);
+        let names = graph
+            .topological_order()
+            .unwrap()
+            .into_iter()
+            .map(|index| graph.get_node(index).unwrap().name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["A", "B", "C"]);
+        assert!(graph.recursive_components().is_empty());
+        assert!(graph.reachable_from_main().is_empty());
+
+        let json = graph.to_json(runner.file_library());
+        assert_eq!(json["edges"].as_array().unwrap().len(), 3);
+        assert_eq!(json["topological_order"][0], "A");
+    }
+}
@@ -1,5 +1,77 @@
+use std::collections::HashMap;
+
+use serde_derive::Deserialize;
+
 use program_structure::ast::Version;
+use program_structure::report::{MessageCategory, Report};
 
-pub const COMPILER_VERSION: Version = (2, 1, 4);
+pub const COMPILER_VERSION: Version = (2, 2, 2);
 pub const DEFAULT_LEVEL: &str = "WARNING";
 pub const DEFAULT_CURVE: &str = "BN254";
+
+/// The maximum number of template instances analyzed, starting from the main
+/// component.
+pub const MAX_TEMPLATE_INSTANCES: usize = 1024;
+
+/// The default maximum cyclomatic complexity of a function or template.
+pub const DEFAULT_MAX_CYCLOMATIC_COMPLEXITY: usize = 20;
+
+/// The default maximum number of function or template parameters.
+pub const DEFAULT_MAX_NOF_PARAMETERS: usize = 7;
+
+/// The default minimum number of constraints for intermediate signals.
+pub const DEFAULT_MIN_CONSTRAINT_COUNT: usize = 2;
+
+/// Known templates that are commonly instantiated without accessing the
+/// corresponding output signals.
+pub const DEFAULT_UNUSED_OUTPUT_SIGNAL_ALLOW_LIST: [&str; 1] = ["Num2Bits"];
+
+/// Settings used by the analysis passes. These are exposed to each pass
+/// through `AnalysisContext::settings`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AnalysisSettings {
+    /// Functions and templates with a cyclomatic complexity above this
+    /// threshold are reported.
+    pub max_cyclomatic_complexity: usize,
+    /// Functions and templates with more parameters than this are reported.
+    pub max_nof_parameters: usize,
+    /// Intermediate signals occurring in fewer constraints than this are
+    /// reported.
+    pub min_constraint_count: usize,
+    /// Templates which are ignored by the unused output signal analysis pass.
+    pub unused_output_signal_allow_list: Vec<String>,
+    /// Severity overrides, indexed by report ID (e.g. `CS0005`) or report name
+    /// (e.g. `signal-assignment-statement`).
+    #[serde(skip)]
+    pub severity_overrides: HashMap<String, MessageCategory>,
+}
+
+impl Default for AnalysisSettings {
+    fn default() -> AnalysisSettings {
+        AnalysisSettings {
+            max_cyclomatic_complexity: DEFAULT_MAX_CYCLOMATIC_COMPLEXITY,
+            max_nof_parameters: DEFAULT_MAX_NOF_PARAMETERS,
+            min_constraint_count: DEFAULT_MIN_CONSTRAINT_COUNT,
+            unused_output_signal_allow_list: DEFAULT_UNUSED_OUTPUT_SIGNAL_ALLOW_LIST
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            severity_overrides: HashMap::new(),
+        }
+    }
+}
+
+impl AnalysisSettings {
+    /// Updates the category of the report if there is a severity override
+    /// matching the report ID or name.
+    pub fn override_severity(&self, report: &mut Report) {
+        let category = self
+            .severity_overrides
+            .get(&report.id())
+            .or_else(|| self.severity_overrides.get(&report.name()));
+        if let Some(category) = category {
+            report.set_category(*category);
+        }
+    }
+}
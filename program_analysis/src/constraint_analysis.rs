@@ -3,10 +3,13 @@ use std::collections::{HashMap, HashSet};
 
 use program_structure::cfg::Cfg;
 use program_structure::intermediate_representation::variable_meta::VariableMeta;
-use program_structure::intermediate_representation::AssignOp;
+use program_structure::intermediate_representation::{AssignOp, Meta};
 use program_structure::ir::variable_meta::VariableUse;
 use program_structure::ir::{Statement, VariableName};
 
+use crate::analysis_context::CfgAnalysis;
+use crate::witness_path::{shortest_path, to_variable_uses};
+
 /// This analysis computes the transitive closure of the constraint relation.
 /// (Note that the resulting relation will be symmetric, but not reflexive in
 /// general.)
@@ -15,6 +18,7 @@ pub struct ConstraintAnalysis {
     constraint_map: HashMap<VariableName, HashSet<VariableName>>,
     declarations: HashMap<VariableName, VariableUse>,
     definitions: HashMap<VariableName, VariableUse>,
+    step_metas: HashMap<(VariableName, VariableName), Meta>,
 }
 
 impl ConstraintAnalysis {
@@ -61,10 +65,12 @@ impl ConstraintAnalysis {
         self.declarations.values()
     }
 
-    /// Add a constraint from source to sink.
-    fn add_constraint_step(&mut self, source: &VariableName, sink: &VariableName) {
+    /// Add a constraint from source to sink. The meta is the location of the
+    /// constraint.
+    fn add_constraint_step(&mut self, source: &VariableName, sink: &VariableName, meta: &Meta) {
         let sinks = self.constraint_map.entry(source.clone()).or_default();
         sinks.insert(sink.clone());
+        self.step_metas.entry((source.clone(), sink.clone())).or_insert_with(|| meta.clone());
     }
 
     /// Returns variables constrained in a single step by `source`.
@@ -83,6 +89,21 @@ impl ConstraintAnalysis {
         result
     }
 
+    /// Returns a shortest path explaining why `source` constrains `sink`, or
+    /// `None` if `sink` is not constrained by `source`. The first element of
+    /// the path is the definition (or declaration) of `source`, and each
+    /// subsequent element is a use of the next variable on the path, located
+    /// at the corresponding constraint.
+    pub fn constraint_path(
+        &self,
+        source: &VariableName,
+        sink: &VariableName,
+    ) -> Option<Vec<VariableUse>> {
+        let path = shortest_path(&self.constraint_map, source, sink)?;
+        let start = self.get_definition(source).or_else(|| self.get_declaration(source))?;
+        to_variable_uses(&path, start, &self.step_metas)
+    }
+
     /// Returns true if the source constrains any of the sinks.
     pub fn constrains_any(&self, source: &VariableName, sinks: &HashSet<VariableName>) -> bool {
         self.multi_step_constraint(source).iter().any(|sink| sinks.contains(sink))
@@ -95,6 +116,12 @@ impl ConstraintAnalysis {
     }
 }
 
+impl CfgAnalysis for ConstraintAnalysis {
+    fn compute(cfg: &Cfg) -> ConstraintAnalysis {
+        run_constraint_analysis(cfg)
+    }
+}
+
 pub fn run_constraint_analysis(cfg: &Cfg) -> ConstraintAnalysis {
     debug!("running constraint analysis pass");
     let mut result = ConstraintAnalysis::new();
@@ -124,7 +151,7 @@ pub fn run_constraint_analysis(cfg: &Cfg) -> ConstraintAnalysis {
                                     source.name(),
                                     sink.name()
                                 );
-                                result.add_constraint_step(source.name(), sink.name());
+                                result.add_constraint_step(source.name(), sink.name(), stmt.meta());
                             }
                         }
                     }
@@ -186,6 +213,43 @@ mod tests {
         validate_constraints(src, &sources, &sinks);
     }
 
+    #[test]
+    fn test_constraint_path() {
+        let src = r#"
+            template T() {
+                signal input in;
+                signal tmp;
+                signal output out;
+
+                tmp <== 2 * in;
+                out <== tmp * tmp;
+            }
+        "#;
+        let mut reports = ReportCollection::new();
+        let cfg = parse_definition(src)
+            .unwrap()
+            .into_cfg(&Curve::default(), &mut reports)
+            .unwrap()
+            .into_ssa()
+            .unwrap();
+        assert!(reports.is_empty());
+
+        let constraint_analysis = run_constraint_analysis(&cfg);
+        let source = VariableName::from_string("in");
+        let sink = VariableName::from_string("out");
+        let path = constraint_analysis.constraint_path(&source, &sink).unwrap();
+        let names = path.iter().map(|var| var.name().to_string()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["in", "tmp", "out"]);
+        let lines = path
+            .iter()
+            .map(|var| src[..var.meta().file_location().start].lines().count())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![3, 7, 8]);
+
+        // The constraint relation is symmetric.
+        assert_eq!(constraint_analysis.constraint_path(&sink, &source).unwrap().len(), 3);
+    }
+
     fn validate_constraints(src: &str, sources: &[VariableName], sinks: &[usize]) {
         // Build CFG.
         let mut reports = ReportCollection::new();
@@ -0,0 +1,150 @@
+use program_structure::cfg::data_flow::{
+    AvailableConstraints, DataFlowAnalysis, DataFlowResult, Liveness, ReachingDefinitions,
+};
+use program_structure::cfg::Cfg;
+
+use crate::analysis_context::CfgAnalysis;
+
+// The reference data-flow analyses are available to analysis passes through
+// `AnalysisContext::analysis`, which caches the solution for each CFG.
+
+impl CfgAnalysis for DataFlowResult<Liveness> {
+    fn compute(cfg: &Cfg) -> Self {
+        Liveness.solve(cfg)
+    }
+}
+
+impl CfgAnalysis for DataFlowResult<ReachingDefinitions> {
+    fn compute(cfg: &Cfg) -> Self {
+        ReachingDefinitions::new(cfg).solve(cfg)
+    }
+}
+
+impl CfgAnalysis for DataFlowResult<AvailableConstraints> {
+    fn compute(cfg: &Cfg) -> Self {
+        AvailableConstraints::new(cfg).solve(cfg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use parser::parse_definition;
+    use program_structure::cfg::IntoCfg;
+    use program_structure::constants::Curve;
+    use program_structure::ir::variable_meta::VariableMeta;
+    use program_structure::ir::{Statement, VariableName};
+    use program_structure::report::ReportCollection;
+
+    use super::*;
+
+    fn build_cfg(src: &str) -> Cfg {
+        let mut reports = ReportCollection::new();
+        let cfg = parse_definition(src)
+            .unwrap()
+            .into_cfg(&Curve::default(), &mut reports)
+            .unwrap()
+            .into_ssa()
+            .unwrap();
+        assert!(reports.is_empty());
+        cfg
+    }
+
+    fn names(names: &[&str]) -> HashSet<VariableName> {
+        names.iter().map(VariableName::from_string).collect()
+    }
+
+    #[test]
+    fn test_liveness() {
+        let src = r#"
+            function f(n) {
+                var x = n + 1;
+                var y = 0;
+                if (n > 0) {
+                    y = x;
+                }
+                return y;
+            }
+        "#;
+        let cfg = build_cfg(src);
+        let liveness = DataFlowResult::<Liveness>::compute(&cfg);
+
+        // `n` is read by the first statement, so `n` is live on entry, but `x`
+        // is not since it is defined by the first statement.
+        let n = VariableName::from_string("n").with_version(0);
+        let x = VariableName::from_string("x").with_version(0);
+        assert!(liveness.entry(cfg.entry_block()).contains(&n));
+        assert!(!liveness.entry(cfg.entry_block()).contains(&x));
+
+        // Nothing is live after the return statement.
+        for basic_block in cfg.iter() {
+            if basic_block.successors().is_empty() {
+                assert!(liveness.exit(basic_block).is_empty());
+            }
+        }
+        // `x` is live until the end of the entry block since it is read in
+        // the true branch of the if-statement.
+        assert!(liveness.exit(cfg.entry_block()).contains(&x));
+    }
+
+    #[test]
+    fn test_reaching_definitions() {
+        let src = r#"
+            function f(n) {
+                var x = 0;
+                for (var i = 0; i < n; i++) {
+                    x += i;
+                }
+                return x;
+            }
+        "#;
+        let cfg = build_cfg(src);
+        let reaching_definitions = DataFlowResult::<ReachingDefinitions>::compute(&cfg);
+
+        // The parameter definition reaches every statement since `n` is never
+        // overwritten.
+        let n = VariableName::from_string("n").with_version(0);
+        for basic_block in cfg.iter() {
+            assert!(reaching_definitions.exit(basic_block).iter().any(|def| def.name() == &n));
+        }
+        // The loop body is reached by definitions from both the entry block
+        // and the loop body itself.
+        let loop_body = cfg.iter().find(|basic_block| basic_block.in_loop()).unwrap();
+        for (stmt, before, _) in reaching_definitions.statement_facts(loop_body) {
+            for var in stmt.variables_read() {
+                assert!(before.iter().any(|def| def.name() == var.name()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_available_constraints() {
+        let src = r#"
+            template T(n) {
+                signal input a;
+                signal input b;
+                signal output c;
+
+                a * b === 1;
+                if (n > 0) {
+                    c <== a;
+                } else {
+                    c <-- b;
+                }
+                log(c);
+            }
+        "#;
+        let cfg = build_cfg(src);
+        let available_constraints = DataFlowResult::<AvailableConstraints>::compute(&cfg);
+
+        // `a` and `b` are constrained on every path to the log statement,
+        // but `c` is only constrained on one of the branches.
+        let (_, before, _) = cfg
+            .iter()
+            .flat_map(|basic_block| available_constraints.statement_facts(basic_block))
+            .find(|(stmt, _, _)| matches!(stmt, Statement::LogCall { .. }))
+            .unwrap();
+        assert_eq!(before, names(&["a", "b"]));
+    }
+}
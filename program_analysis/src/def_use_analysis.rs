@@ -0,0 +1,106 @@
+use log::debug;
+use std::collections::HashMap;
+
+use program_structure::cfg::Cfg;
+use program_structure::ir::variable_meta::{VariableMeta, VariableUse};
+use program_structure::ir::{Meta, VariableName};
+
+use crate::analysis_context::CfgAnalysis;
+
+/// This analysis maps each variable, signal, and component to the statements
+/// defining and using it. (Since signals and components are not versioned when
+/// the CFG is converted to SSA, they may have more than one definition.)
+#[derive(Clone, Default)]
+pub struct DefUseAnalysis {
+    definitions: HashMap<VariableName, Vec<VariableUse>>,
+    uses: HashMap<VariableName, Vec<VariableUse>>,
+}
+
+impl DefUseAnalysis {
+    /// Returns the definitions of the given variable. Template and function
+    /// parameters are defined at the location of the parameter list.
+    pub fn definitions(&self, var: &VariableName) -> &[VariableUse] {
+        self.definitions.get(var).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the uses (reads) of the given variable.
+    pub fn uses(&self, var: &VariableName) -> &[VariableUse] {
+        self.uses.get(var).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns true if the given variable is read anywhere in the CFG.
+    pub fn is_used(&self, var: &VariableName) -> bool {
+        !self.uses(var).is_empty()
+    }
+}
+
+impl CfgAnalysis for DefUseAnalysis {
+    fn compute(cfg: &Cfg) -> DefUseAnalysis {
+        run_def_use_analysis(cfg)
+    }
+}
+
+pub fn run_def_use_analysis(cfg: &Cfg) -> DefUseAnalysis {
+    debug!("running def-use analysis pass");
+    let mut result = DefUseAnalysis::default();
+
+    let parameters = cfg.parameters();
+    let meta = Meta::new(parameters.file_location(), parameters.file_id());
+    for name in parameters.iter() {
+        let definition = VariableUse::new(&meta, name, &Vec::new());
+        result.definitions.entry(name.clone()).or_default().push(definition);
+    }
+    for stmt in cfg.iter().flat_map(|basic_block| basic_block.iter()) {
+        for var in stmt.variables_written() {
+            result.definitions.entry(var.name().clone()).or_default().push(var.clone());
+        }
+        for var in stmt.variables_read() {
+            result.uses.entry(var.name().clone()).or_default().push(var.clone());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use parser::parse_definition;
+    use program_structure::cfg::IntoCfg;
+    use program_structure::constants::Curve;
+    use program_structure::report::ReportCollection;
+
+    use super::*;
+
+    #[test]
+    fn test_def_use_analysis() {
+        let src = r#"
+            template T(n) {
+                signal input in;
+                signal output out;
+
+                var x = n + 1;
+                var y = x * 2;
+                out <== in * x;
+            }
+        "#;
+        let mut reports = ReportCollection::new();
+        let cfg = parse_definition(src)
+            .unwrap()
+            .into_cfg(&Curve::default(), &mut reports)
+            .unwrap()
+            .into_ssa()
+            .unwrap();
+        assert!(reports.is_empty());
+
+        let def_use = run_def_use_analysis(&cfg);
+        let n = VariableName::from_string("n").with_version(0);
+        let x = VariableName::from_string("x").with_version(0);
+        let y = VariableName::from_string("y").with_version(0);
+        let out = VariableName::from_string("out");
+        assert_eq!(def_use.definitions(&n).len(), 1);
+        assert_eq!(def_use.uses(&n).len(), 1);
+        assert_eq!(def_use.definitions(&x).len(), 1);
+        assert_eq!(def_use.uses(&x).len(), 2);
+        assert!(!def_use.is_used(&y));
+        assert_eq!(def_use.definitions(&out).len(), 1);
+    }
+}
@@ -3,6 +3,8 @@ use program_structure::report_code::ReportCode;
 use program_structure::report::{Report, ReportCollection};
 use program_structure::file_definition::{FileID, FileLocation};
 
+use crate::config::AnalysisSettings;
+
 pub struct TooManyArgumentsWarning {
     definition_name: String,
     definition_type: DefinitionType,
@@ -48,10 +50,7 @@ impl CyclomaticComplexityWarning {
     }
 }
 
-const MAX_NOF_PARAMETERS: usize = 7;
-const MAX_CYCLOMATIC_COMPLEXITY: usize = 20;
-
-pub fn run_complexity_analysis(cfg: &Cfg) -> ReportCollection {
+pub fn run_complexity_analysis(settings: &AnalysisSettings, cfg: &Cfg) -> ReportCollection {
     // Compute the cyclomatic complexity as `M = E - N + 2P` where `E` is the
     // number of edges, `N` is the number of nodes, and `P` is the number of
     // connected components (which is always 1 here).
@@ -65,7 +64,7 @@ pub fn run_complexity_analysis(cfg: &Cfg) -> ReportCollection {
 
     let mut reports = ReportCollection::new();
     // Generate a report if the cyclomatic complexity is high.
-    if complexity > MAX_CYCLOMATIC_COMPLEXITY {
+    if complexity > settings.max_cyclomatic_complexity {
         reports.push(
             CyclomaticComplexityWarning {
                 definition_name: cfg.name().to_string(),
@@ -75,7 +74,7 @@ pub fn run_complexity_analysis(cfg: &Cfg) -> ReportCollection {
         );
     }
     // Generate a report if the number of arguments is high.
-    if cfg.parameters().len() > MAX_NOF_PARAMETERS {
+    if cfg.parameters().len() > settings.max_nof_parameters {
         reports.push(
             TooManyArgumentsWarning {
                 definition_name: cfg.name().to_string(),
@@ -94,6 +93,7 @@ mod tests {
     use parser::parse_definition;
     use program_structure::{report::ReportCollection, constants::Curve, cfg::IntoCfg};
 
+    use crate::config::AnalysisSettings;
     use crate::definition_complexity::run_complexity_analysis;
 
     #[test]
@@ -105,10 +105,23 @@ mod tests {
                a <== b;
             }
         "#;
-        validate_reports(src, 0);
+        validate_reports(src, &AnalysisSettings::default(), 0);
+    }
+
+    #[test]
+    fn test_configured_thresholds() {
+        let src = r#"
+            function f(a, b) {
+               return a + b;
+            }
+        "#;
+        validate_reports(src, &AnalysisSettings::default(), 0);
+
+        let settings = AnalysisSettings { max_nof_parameters: 1, ..Default::default() };
+        validate_reports(src, &settings, 1);
     }
 
-    fn validate_reports(src: &str, expected_len: usize) {
+    fn validate_reports(src: &str, settings: &AnalysisSettings, expected_len: usize) {
         // Build CFG.
         let mut reports = ReportCollection::new();
         let cfg = parse_definition(src)
@@ -120,7 +133,7 @@ mod tests {
         assert!(reports.is_empty());
 
         // Generate report collection.
-        let reports = run_complexity_analysis(&cfg);
+        let reports = run_complexity_analysis(settings, &cfg);
 
         assert_eq!(reports.len(), expected_len);
     }
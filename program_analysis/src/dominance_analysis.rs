@@ -0,0 +1,50 @@
+use log::debug;
+use std::collections::HashSet;
+
+use program_structure::cfg::basic_block::BasicBlock;
+use program_structure::cfg::Cfg;
+
+use crate::analysis_context::CfgAnalysis;
+
+type Index = usize;
+
+/// This analysis precomputes the dominance relation on the basic blocks of a
+/// CFG, allowing dominance queries in constant time. The basic block `i`
+/// dominates `j` if any path from the entry point to `j` must contain `i`.
+#[derive(Clone, Default)]
+pub struct DominanceAnalysis {
+    dominators: Vec<HashSet<Index>>,
+}
+
+impl DominanceAnalysis {
+    /// Returns true if `dominator` dominates `basic_block`. (Note that this
+    /// relation is reflexive, so each basic block dominates itself.)
+    pub fn dominates(&self, dominator: &BasicBlock, basic_block: &BasicBlock) -> bool {
+        self.dominators
+            .get(basic_block.index())
+            .map_or(false, |dominators| dominators.contains(&dominator.index()))
+    }
+
+    /// Returns true if `dominator` dominates `basic_block` and the two basic
+    /// blocks are different.
+    pub fn strictly_dominates(&self, dominator: &BasicBlock, basic_block: &BasicBlock) -> bool {
+        dominator.index() != basic_block.index() && self.dominates(dominator, basic_block)
+    }
+}
+
+impl CfgAnalysis for DominanceAnalysis {
+    fn compute(cfg: &Cfg) -> DominanceAnalysis {
+        run_dominance_analysis(cfg)
+    }
+}
+
+pub fn run_dominance_analysis(cfg: &Cfg) -> DominanceAnalysis {
+    debug!("running dominance analysis pass");
+    let dominators = cfg
+        .iter()
+        .map(|basic_block| {
+            cfg.get_dominators(basic_block).iter().map(|dominator| dominator.index()).collect()
+        })
+        .collect();
+    DominanceAnalysis { dominators }
+}
@@ -0,0 +1,1219 @@
+use log::{debug, trace};
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+
+use circom_algebra::modular_arithmetic::{self, ArithmeticError};
+use program_structure::cfg::{Cfg, Index};
+use program_structure::file_definition::{FileID, FileLocation};
+use program_structure::ir::*;
+
+use crate::analysis_context::{AnalysisContext, AnalysisError};
+
+/// The maximum number of statements executed during a single emulation run
+/// (including statements executed by called functions and instantiated
+/// subcomponents).
+const MAX_EMULATION_STEPS: usize = 1_000_000;
+
+/// The maximum depth of nested function calls and component instantiations.
+const MAX_CALL_DEPTH: usize = 256;
+
+/// Errors returned by the emulator.
+#[derive(Debug, Error)]
+pub enum EmulationError {
+    /// The CFG of a called function or instantiated template is unavailable.
+    #[error(transparent)]
+    AnalysisError(#[from] AnalysisError),
+    /// The variable has not been declared.
+    #[error("Unknown variable `{name}`.")]
+    UnknownVariable { name: String },
+    /// The variable (or signal) is read before it is assigned.
+    #[error("The variable `{name}` is read before it is assigned.")]
+    UndefinedValue { name: String },
+    /// The access does not match the shape of the variable.
+    #[error("Invalid access to the variable `{name}`.")]
+    InvalidAccess { name: String },
+    /// The value cannot be used as an array index or array dimension.
+    #[error("Invalid array index `{value}`.")]
+    InvalidIndex { value: BigInt },
+    /// The value is an array or a component, but a field element was expected.
+    #[error("Expected a field element, but found `{value}`.")]
+    ExpectedFieldElement { value: String },
+    #[error("Division by zero.")]
+    DivisionByZero,
+    /// Bus-typed signals are not supported by the emulator.
+    #[error("The signal `{name}` is a bus, which is not supported.")]
+    UnsupportedBus { name: String },
+    #[error("The shift amount is too large.")]
+    InvalidShift,
+    #[error("Assertion failed.")]
+    AssertionFailed { file_id: Option<FileID>, file_location: FileLocation },
+    #[error("`{name}` expects {expected} arguments, but {actual} were given.")]
+    InvalidArgumentCount { name: String, expected: usize, actual: usize },
+    /// The given input value does not match the declaration of the signal.
+    #[error("Invalid value for the input signal `{name}`.")]
+    InvalidInput { name: String },
+    #[error("The template `{template}` has no input signal `{name}`.")]
+    UnknownInput { template: String, name: String },
+    #[error("The function `{name}` returned without a value.")]
+    MissingReturnValue { name: String },
+    #[error("Emulation exceeded the maximum number of steps ({max_steps}).")]
+    StepLimitExceeded { max_steps: usize },
+    #[error("Emulation exceeded the maximum call depth ({max_depth}).")]
+    CallDepthExceeded { max_depth: usize },
+}
+
+pub type EmulationResult<T> = Result<T, EmulationError>;
+
+impl From<ArithmeticError> for EmulationError {
+    fn from(error: ArithmeticError) -> EmulationError {
+        match error {
+            ArithmeticError::DivisionByZero => EmulationError::DivisionByZero,
+            ArithmeticError::BitOverFlowInShift => EmulationError::InvalidShift,
+        }
+    }
+}
+
+/// A concrete value computed by the emulator.
+#[derive(Clone)]
+pub enum Value {
+    /// An unassigned signal or an uninitialized component.
+    Undefined,
+    /// A field element in the range `[0, p)`.
+    FieldElement(BigInt),
+    Array(Vec<Value>),
+    Component(Box<Component>),
+}
+
+impl Value {
+    #[must_use]
+    pub fn as_field_element(&self) -> Option<&BigInt> {
+        match self {
+            Value::FieldElement(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_component(&self) -> Option<&Component> {
+        match self {
+            Value::Component(component) => Some(component),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the value (including all array elements) is defined.
+    #[must_use]
+    pub fn is_defined(&self) -> bool {
+        match self {
+            Value::Undefined => false,
+            Value::Array(values) => values.iter().all(Value::is_defined),
+            Value::FieldElement(_) | Value::Component(_) => true,
+        }
+    }
+
+    /// Returns true if the two values are defined and equal.
+    fn is_equal_to(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::FieldElement(lhv), Value::FieldElement(rhv)) => lhv == rhv,
+            (Value::Array(lhv), Value::Array(rhv)) => {
+                lhv.len() == rhv.len() && lhv.iter().zip(rhv).all(|(lhv, rhv)| lhv.is_equal_to(rhv))
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns an array with the given dimensions where each element is set
+    /// to the given value.
+    fn with_dimensions(dimensions: &[usize], value: Value) -> Value {
+        match dimensions.split_first() {
+            Some((size, dimensions)) => {
+                Value::Array(vec![Value::with_dimensions(dimensions, value); *size])
+            }
+            None => value,
+        }
+    }
+
+    fn get(&self, indices: &[usize]) -> Option<&Value> {
+        match indices.split_first() {
+            Some((index, indices)) => self.as_array()?.get(*index)?.get(indices),
+            None => Some(self),
+        }
+    }
+
+    fn get_mut(&mut self, indices: &[usize]) -> Option<&mut Value> {
+        match (indices.split_first(), self) {
+            (Some((index, indices)), Value::Array(values)) => {
+                values.get_mut(*index)?.get_mut(indices)
+            }
+            (Some(_), _) => None,
+            (None, value) => Some(value),
+        }
+    }
+
+    /// Sets the element with the given indices, growing the array as needed.
+    /// This is used to track input signals assigned to a subcomponent before
+    /// the subcomponent has been instantiated (and the signal dimensions are
+    /// known).
+    fn set_or_grow(&mut self, indices: &[usize], value: Value) -> bool {
+        let Some((index, indices)) = indices.split_first() else {
+            *self = value;
+            return true;
+        };
+        if matches!(self, Value::Undefined) {
+            *self = Value::Array(Vec::new());
+        }
+        match self {
+            Value::Array(values) => {
+                if values.len() <= *index {
+                    values.resize(*index + 1, Value::Undefined);
+                }
+                values[*index].set_or_grow(indices, value)
+            }
+            _ => false,
+        }
+    }
+
+    /// Overlays the given (possibly partially defined) value on top of the
+    /// declared value. Returns `None` if the shapes of the values differ.
+    fn overlay(&self, value: &Value, prime: &BigInt) -> Option<Value> {
+        match (self, value) {
+            (_, Value::Undefined) => Some(self.clone()),
+            (Value::Undefined, Value::FieldElement(value)) => {
+                Some(Value::FieldElement(modulus(value, prime)))
+            }
+            (Value::Array(declared), Value::Array(values)) if values.len() <= declared.len() => {
+                declared
+                    .iter()
+                    .enumerate()
+                    .map(|(index, declared)| match values.get(index) {
+                        Some(value) => declared.overlay(value, prime),
+                        None => Some(declared.clone()),
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .map(Value::Array)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<BigInt> for Value {
+    fn from(value: BigInt) -> Value {
+        Value::FieldElement(value)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(values: Vec<Value>) -> Value {
+        Value::Array(values)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Undefined => write!(f, "undefined"),
+            Value::FieldElement(value) => write!(f, "{value}"),
+            Value::Array(values) => {
+                let values = values.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "[{}]", values.join(", "))
+            }
+            Value::Component(component) => {
+                let args = component.args.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "{}({})", component.template, args.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+/// An instantiated subcomponent. The subcomponent is emulated either when one
+/// of its signals is first read, or when the parent template exits.
+#[derive(Clone, Debug)]
+pub struct Component {
+    template: String,
+    args: Vec<Value>,
+    inputs: HashMap<String, Value>,
+    emulation: Option<Emulation>,
+}
+
+impl Component {
+    fn new(template: &str, args: Vec<Value>) -> Component {
+        Component { template: template.to_string(), args, inputs: HashMap::new(), emulation: None }
+    }
+
+    #[must_use]
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    #[must_use]
+    pub fn args(&self) -> &[Value] {
+        &self.args
+    }
+
+    /// Returns the result of emulating the component, if it was instantiated.
+    #[must_use]
+    pub fn emulation(&self) -> Option<&Emulation> {
+        self.emulation.as_ref()
+    }
+}
+
+/// A constraint `lhs === rhs` which is not satisfied by the computed witness.
+#[derive(Clone, Debug)]
+pub struct FailedConstraint {
+    template: String,
+    file_id: Option<FileID>,
+    file_location: FileLocation,
+    lhs: Value,
+    rhs: Value,
+}
+
+impl FailedConstraint {
+    /// Returns the name of the template containing the constraint.
+    #[must_use]
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    #[must_use]
+    pub fn file_id(&self) -> Option<FileID> {
+        self.file_id
+    }
+
+    #[must_use]
+    pub fn file_location(&self) -> FileLocation {
+        self.file_location.clone()
+    }
+
+    #[must_use]
+    pub fn lhs(&self) -> &Value {
+        &self.lhs
+    }
+
+    #[must_use]
+    pub fn rhs(&self) -> &Value {
+        &self.rhs
+    }
+}
+
+/// The result of emulating a template on concrete inputs.
+#[derive(Clone, Debug)]
+pub struct Emulation {
+    name: String,
+    signals: BTreeMap<String, Value>,
+    components: BTreeMap<String, Value>,
+    failed_constraints: Vec<FailedConstraint>,
+}
+
+impl Emulation {
+    /// Returns the name of the emulated template.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value of the given signal declared by the template.
+    #[must_use]
+    pub fn get_signal(&self, name: &str) -> Option<&Value> {
+        self.signals.get(name)
+    }
+
+    /// Returns an iterator over the signals declared by the template.
+    pub fn signals(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.signals.iter()
+    }
+
+    /// Returns the given component (or array of components) declared by the
+    /// template.
+    #[must_use]
+    pub fn get_component(&self, name: &str) -> Option<&Value> {
+        self.components.get(name)
+    }
+
+    /// Returns an iterator over the components declared by the template.
+    pub fn components(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.components.iter()
+    }
+
+    /// Returns the constraints that are not satisfied by the computed witness,
+    /// including constraints from instantiated subcomponents.
+    #[must_use]
+    pub fn failed_constraints(&self) -> &[FailedConstraint] {
+        &self.failed_constraints
+    }
+
+    /// Returns true if all constraints are satisfied by the computed witness.
+    #[must_use]
+    pub fn is_satisfied(&self) -> bool {
+        self.failed_constraints.is_empty()
+    }
+
+    /// Returns the flattened witness. Each signal (including signals belonging
+    /// to subcomponents) is identified by its full path (e.g. `c[0].out[1]`).
+    /// Unassigned signals are mapped to `None`.
+    #[must_use]
+    pub fn witness(&self) -> Vec<(String, Option<BigInt>)> {
+        let mut witness = Vec::new();
+        for (name, value) in self.signals() {
+            flatten_signal(name.clone(), value, &mut witness);
+        }
+        for (name, value) in self.components() {
+            flatten_component(name.clone(), value, &mut witness);
+        }
+        witness
+    }
+}
+
+fn flatten_signal(path: String, value: &Value, witness: &mut Vec<(String, Option<BigInt>)>) {
+    match value {
+        Value::Undefined => witness.push((path, None)),
+        Value::FieldElement(value) => witness.push((path, Some(value.clone()))),
+        Value::Array(values) => {
+            for (index, value) in values.iter().enumerate() {
+                flatten_signal(format!("{path}[{index}]"), value, witness);
+            }
+        }
+        Value::Component(_) => {}
+    }
+}
+
+fn flatten_component(path: String, value: &Value, witness: &mut Vec<(String, Option<BigInt>)>) {
+    match value {
+        Value::Array(values) => {
+            for (index, value) in values.iter().enumerate() {
+                flatten_component(format!("{path}[{index}]"), value, witness);
+            }
+        }
+        Value::Component(component) => {
+            if let Some(emulation) = component.emulation() {
+                for (name, value) in emulation.witness() {
+                    witness.push((format!("{path}.{name}"), value));
+                }
+            }
+        }
+        Value::Undefined | Value::FieldElement(_) => {}
+    }
+}
+
+/// An element of an array or component access.
+enum PathElement {
+    Index(usize),
+    Signal(String),
+}
+
+/// An access path split into the array indices preceding the component
+/// access, and the accessed signal together with the trailing array indices.
+type SplitPath<'a> = (Vec<usize>, Option<(&'a str, Vec<usize>)>);
+
+/// Splits the access path into the array indices preceding the first
+/// component access, the accessed signal, and the trailing array indices.
+/// Returns `None` if the path is not on this form.
+fn split_path(path: &[PathElement]) -> Option<SplitPath<'_>> {
+    let mut prefix = Vec::new();
+    let mut elements = path.iter();
+    for element in elements.by_ref() {
+        match element {
+            PathElement::Index(index) => prefix.push(*index),
+            PathElement::Signal(signal) => {
+                let suffix = elements
+                    .map(|element| match element {
+                        PathElement::Index(index) => Some(*index),
+                        PathElement::Signal(_) => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                return Some((prefix, Some((signal, suffix))));
+            }
+        }
+    }
+    Some((prefix, None))
+}
+
+/// Control flow resulting from executing a single statement.
+enum Control {
+    Next,
+    Jump(Index),
+    Exit,
+    Return(Value),
+}
+
+/// The state of a single function or template emulation.
+struct Frame {
+    prime: BigInt,
+    inputs: HashMap<String, Value>,
+    values: HashMap<VariableName, Value>,
+    // Since SSA variables are versioned we can resolve phi expressions by
+    // tracking when each version was last assigned.
+    timestamps: HashMap<VariableName, usize>,
+    failed_constraints: Vec<FailedConstraint>,
+}
+
+impl Frame {
+    fn new(cfg: &Cfg, inputs: &HashMap<String, Value>) -> Frame {
+        Frame {
+            prime: cfg.constants().prime().clone(),
+            inputs: inputs.clone(),
+            values: HashMap::new(),
+            timestamps: HashMap::new(),
+            failed_constraints: Vec::new(),
+        }
+    }
+
+    fn get(&self, name: &VariableName) -> EmulationResult<&Value> {
+        self.values
+            .get(name)
+            .ok_or_else(|| EmulationError::UnknownVariable { name: name.to_string() })
+    }
+
+    fn get_mut(&mut self, name: &VariableName) -> EmulationResult<&mut Value> {
+        self.values
+            .get_mut(name)
+            .ok_or_else(|| EmulationError::UnknownVariable { name: name.to_string() })
+    }
+
+    fn declare(&mut self, name: &VariableName, value: Value) {
+        self.values.insert(name.clone(), value);
+        self.timestamps.insert(name.clone(), 0);
+    }
+
+    fn assign(&mut self, name: &VariableName, value: Value, timestamp: usize) {
+        self.values.insert(name.clone(), value);
+        self.timestamps.insert(name.clone(), timestamp);
+    }
+
+    /// Phi expressions evaluate to the most recently assigned argument.
+    fn read_phi(&self, args: &[VariableName]) -> EmulationResult<Value> {
+        args.iter()
+            .filter_map(|name| Some((name, self.timestamps.get(name)?)))
+            .max_by_key(|(_, timestamp)| **timestamp)
+            .map(|(name, _)| self.values[name].clone())
+            .ok_or_else(|| EmulationError::UnknownVariable {
+                name: args.first().map(ToString::to_string).unwrap_or_default(),
+            })
+    }
+}
+
+/// A concrete emulator for functions and templates. Subcomponents are
+/// instantiated, and called functions are evaluated, using the CFGs provided
+/// by the analysis context.
+pub struct Emulator<'a> {
+    context: &'a dyn AnalysisContext,
+    functions: HashMap<String, Arc<Cfg>>,
+    templates: HashMap<String, Arc<Cfg>>,
+    max_steps: usize,
+    steps: usize,
+    depth: usize,
+}
+
+impl<'a> Emulator<'a> {
+    pub fn new(context: &'a dyn AnalysisContext) -> Emulator<'a> {
+        Emulator {
+            context,
+            functions: HashMap::new(),
+            templates: HashMap::new(),
+            max_steps: MAX_EMULATION_STEPS,
+            steps: 0,
+            depth: 0,
+        }
+    }
+
+    /// Sets the maximum number of statements executed during a single run.
+    #[must_use]
+    pub fn with_max_steps(mut self, max_steps: usize) -> Emulator<'a> {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Emulates the given template using the given template arguments and
+    /// input signal values. Input signals that are not given remain undefined,
+    /// and reading them results in an error.
+    pub fn emulate_template(
+        &mut self,
+        cfg: &Cfg,
+        args: &[Value],
+        inputs: &HashMap<String, Value>,
+    ) -> EmulationResult<Emulation> {
+        self.steps = 0;
+        self.depth = 0;
+        self.run_template(cfg, args, inputs)
+    }
+
+    /// Emulates the given function using the given arguments and returns the
+    /// return value of the function.
+    pub fn emulate_function(&mut self, cfg: &Cfg, args: &[Value]) -> EmulationResult<Value> {
+        self.steps = 0;
+        self.depth = 0;
+        self.run_function(cfg, args)
+    }
+
+    fn run_template(
+        &mut self,
+        cfg: &Cfg,
+        args: &[Value],
+        inputs: &HashMap<String, Value>,
+    ) -> EmulationResult<Emulation> {
+        debug!("emulating template `{}`", cfg.name());
+        for name in inputs.keys() {
+            if !cfg.input_signals().any(|signal| signal.name() == name) {
+                return Err(EmulationError::UnknownInput {
+                    template: cfg.name().to_string(),
+                    name: name.clone(),
+                });
+            }
+        }
+        let mut frame = self.enter(cfg, args, inputs)?;
+        self.execute(cfg, &mut frame)?;
+        self.depth -= 1;
+
+        // Emulate remaining subcomponents and collect the results.
+        let mut signals = BTreeMap::new();
+        let mut components = BTreeMap::new();
+        for (name, declaration) in cfg.declarations().iter() {
+            let Some(mut value) = frame.values.remove(name) else {
+                continue;
+            };
+            match declaration.variable_type() {
+                VariableType::Signal(_, _) | VariableType::Bus(_, _, _) => {
+                    signals.insert(name.to_string(), value);
+                }
+                VariableType::Component | VariableType::AnonymousComponent => {
+                    self.instantiate_all(&mut value)?;
+                    collect_failed_constraints(&value, &mut frame.failed_constraints);
+                    components.insert(name.to_string(), value);
+                }
+                VariableType::Local => {}
+            }
+        }
+        Ok(Emulation {
+            name: cfg.name().to_string(),
+            signals,
+            components,
+            failed_constraints: frame.failed_constraints,
+        })
+    }
+
+    fn run_function(&mut self, cfg: &Cfg, args: &[Value]) -> EmulationResult<Value> {
+        trace!("emulating function `{}`", cfg.name());
+        let mut frame = self.enter(cfg, args, &HashMap::new())?;
+        let value = self.execute(cfg, &mut frame)?;
+        self.depth -= 1;
+        value.ok_or_else(|| EmulationError::MissingReturnValue { name: cfg.name().to_string() })
+    }
+
+    fn enter(
+        &mut self,
+        cfg: &Cfg,
+        args: &[Value],
+        inputs: &HashMap<String, Value>,
+    ) -> EmulationResult<Frame> {
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(EmulationError::CallDepthExceeded { max_depth: MAX_CALL_DEPTH });
+        }
+        if args.len() != cfg.parameters().len() {
+            return Err(EmulationError::InvalidArgumentCount {
+                name: cfg.name().to_string(),
+                expected: cfg.parameters().len(),
+                actual: args.len(),
+            });
+        }
+        self.depth += 1;
+        let mut frame = Frame::new(cfg, inputs);
+        for (name, value) in cfg.parameters().iter().zip(args) {
+            frame.assign(name, value.clone(), self.steps);
+        }
+        Ok(frame)
+    }
+
+    /// Emulates all components in the given value which have not yet been
+    /// emulated.
+    fn instantiate_all(&mut self, value: &mut Value) -> EmulationResult<()> {
+        match value {
+            Value::Array(values) => {
+                for value in values {
+                    self.instantiate_all(value)?;
+                }
+            }
+            Value::Component(component) if component.emulation.is_none() => {
+                component.emulation = Some(self.instantiate(component)?);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn instantiate(&mut self, component: &Component) -> EmulationResult<Emulation> {
+        let cfg = self.get_template(&component.template)?;
+        self.run_template(&cfg, &component.args, &component.inputs)
+    }
+
+    fn get_template(&mut self, name: &str) -> EmulationResult<Arc<Cfg>> {
+        if let Some(cfg) = self.templates.get(name) {
+            return Ok(cfg.clone());
+        }
+        let cfg = self.context.template(name)?;
+        self.templates.insert(name.to_string(), cfg.clone());
+        Ok(cfg)
+    }
+
+    fn get_function(&mut self, name: &str) -> EmulationResult<Arc<Cfg>> {
+        if let Some(cfg) = self.functions.get(name) {
+            return Ok(cfg.clone());
+        }
+        let cfg = self.context.function(name)?;
+        self.functions.insert(name.to_string(), cfg.clone());
+        Ok(cfg)
+    }
+
+    fn step(&mut self) -> EmulationResult<()> {
+        self.steps += 1;
+        if self.steps > self.max_steps {
+            return Err(EmulationError::StepLimitExceeded { max_steps: self.max_steps });
+        }
+        Ok(())
+    }
+
+    /// Executes the CFG starting from the entry block. Returns the return
+    /// value if the CFG represents a function.
+    fn execute(&mut self, cfg: &Cfg, frame: &mut Frame) -> EmulationResult<Option<Value>> {
+        let mut basic_block = cfg.entry_block();
+        loop {
+            let mut next = basic_block.successors().iter().next().copied();
+            for stmt in basic_block.iter() {
+                self.step()?;
+                match self.execute_statement(cfg, stmt, frame)? {
+                    Control::Next => {}
+                    Control::Jump(index) => next = Some(index),
+                    Control::Exit => next = None,
+                    Control::Return(value) => return Ok(Some(value)),
+                }
+            }
+            match next.and_then(|index| cfg.get_basic_block(index)) {
+                Some(next) => basic_block = next,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn execute_statement(
+        &mut self,
+        cfg: &Cfg,
+        stmt: &Statement,
+        frame: &mut Frame,
+    ) -> EmulationResult<Control> {
+        use Statement::*;
+        match stmt {
+            Declaration { names, var_type, dimensions, .. } => {
+                let dimensions = dimensions
+                    .iter()
+                    .map(|size| self.evaluate_index(size, frame))
+                    .collect::<EmulationResult<Vec<_>>>()?;
+                for name in names.iter() {
+                    let value = match var_type {
+                        VariableType::Bus(_, _, _) => {
+                            return Err(EmulationError::UnsupportedBus { name: name.to_string() });
+                        }
+                        VariableType::Local => {
+                            let zero = Value::FieldElement(BigInt::from(0));
+                            Value::with_dimensions(&dimensions, zero)
+                        }
+                        VariableType::Signal(SignalType::Input, _) => {
+                            let value = Value::with_dimensions(&dimensions, Value::Undefined);
+                            match frame.inputs.get(name.name()) {
+                                Some(input) => {
+                                    value.overlay(input, &frame.prime).ok_or_else(|| {
+                                        EmulationError::InvalidInput { name: name.to_string() }
+                                    })?
+                                }
+                                None => value,
+                            }
+                        }
+                        _ => Value::with_dimensions(&dimensions, Value::Undefined),
+                    };
+                    frame.declare(name, value);
+                }
+                Ok(Control::Next)
+            }
+            IfThenElse { cond, true_index, false_index, .. } => {
+                if self.evaluate_condition(cond, frame)? {
+                    Ok(Control::Jump(*true_index))
+                } else {
+                    Ok(false_index.map(Control::Jump).unwrap_or(Control::Exit))
+                }
+            }
+            Return { value, .. } => Ok(Control::Return(self.evaluate(value, frame)?)),
+            Substitution { var, rhe, .. } => {
+                let value = self.evaluate(rhe, frame)?;
+                trace!("assigning `{value}` to `{var:?}` in `{}`", cfg.name());
+                frame.assign(var, value, self.steps);
+                Ok(Control::Next)
+            }
+            ConstraintEquality { meta, lhe, rhe } => {
+                let lhs = self.evaluate(lhe, frame)?;
+                let rhs = self.evaluate(rhe, frame)?;
+                if !lhs.is_equal_to(&rhs) {
+                    trace!("constraint `{lhs} === {rhs}` failed in `{}`", cfg.name());
+                    frame.failed_constraints.push(FailedConstraint {
+                        template: cfg.name().to_string(),
+                        file_id: meta.file_id(),
+                        file_location: meta.file_location(),
+                        lhs,
+                        rhs,
+                    });
+                }
+                Ok(Control::Next)
+            }
+            LogCall { args, .. } => {
+                for arg in args {
+                    if let LogArgument::Expr(value) = arg {
+                        let value = self.evaluate(value, frame)?;
+                        trace!("log output from `{}`: {value}", cfg.name());
+                    }
+                }
+                Ok(Control::Next)
+            }
+            Assert { meta, arg } => {
+                if self.evaluate_condition(arg, frame)? {
+                    Ok(Control::Next)
+                } else {
+                    Err(EmulationError::AssertionFailed {
+                        file_id: meta.file_id(),
+                        file_location: meta.file_location(),
+                    })
+                }
+            }
+        }
+    }
+
+    fn evaluate(&mut self, expr: &Expression, frame: &mut Frame) -> EmulationResult<Value> {
+        use Expression::*;
+        match expr {
+            Number(_, value) => Ok(Value::FieldElement(modulus(value, &frame.prime))),
+            Variable { name, .. } => match frame.get(name)? {
+                Value::Undefined => Err(EmulationError::UndefinedValue { name: name.to_string() }),
+                value => Ok(value.clone()),
+            },
+            InfixOp { lhe, infix_op, rhe, .. } => {
+                let lhv = self.evaluate_field_element(lhe, frame)?;
+                let rhv = self.evaluate_field_element(rhe, frame)?;
+                evaluate_infix_op(infix_op, &lhv, &rhv, &frame.prime).map(Value::FieldElement)
+            }
+            PrefixOp { prefix_op, rhe, .. } => {
+                let rhv = self.evaluate_field_element(rhe, frame)?;
+                Ok(Value::FieldElement(evaluate_prefix_op(prefix_op, &rhv, &frame.prime)))
+            }
+            SwitchOp { cond, if_true, if_false, .. } => {
+                if self.evaluate_condition(cond, frame)? {
+                    self.evaluate(if_true, frame)
+                } else {
+                    self.evaluate(if_false, frame)
+                }
+            }
+            Call { name, args, .. } => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.evaluate(arg, frame))
+                    .collect::<EmulationResult<Vec<_>>>()?;
+                if self.context.is_function(name) {
+                    let cfg = self.get_function(name)?;
+                    self.run_function(&cfg, &args)
+                } else if self.context.is_template(name) {
+                    Ok(Value::Component(Box::new(Component::new(name, args))))
+                } else {
+                    Err(AnalysisError::UnknownFunction { name: name.clone() }.into())
+                }
+            }
+            InlineArray { values, .. } => values
+                .iter()
+                .map(|value| self.evaluate(value, frame))
+                .collect::<EmulationResult<Vec<_>>>()
+                .map(Value::Array),
+            Access { var, access, .. } => self.evaluate_access(var, access, frame),
+            Update { var, access, rhe, .. } => self.evaluate_update(var, access, rhe, frame),
+            Phi { args, .. } => frame.read_phi(args),
+        }
+    }
+
+    fn evaluate_field_element(
+        &mut self,
+        expr: &Expression,
+        frame: &mut Frame,
+    ) -> EmulationResult<BigInt> {
+        match self.evaluate(expr, frame)? {
+            Value::FieldElement(value) => Ok(value),
+            value => Err(EmulationError::ExpectedFieldElement { value: value.to_string() }),
+        }
+    }
+
+    fn evaluate_condition(
+        &mut self,
+        expr: &Expression,
+        frame: &mut Frame,
+    ) -> EmulationResult<bool> {
+        let value = self.evaluate_field_element(expr, frame)?;
+        Ok(modular_arithmetic::as_bool(&value, &frame.prime))
+    }
+
+    fn evaluate_index(&mut self, expr: &Expression, frame: &mut Frame) -> EmulationResult<usize> {
+        let value = self.evaluate_field_element(expr, frame)?;
+        value.to_usize().ok_or(EmulationError::InvalidIndex { value })
+    }
+
+    fn evaluate_path(
+        &mut self,
+        access: &[AccessType],
+        frame: &mut Frame,
+    ) -> EmulationResult<Vec<PathElement>> {
+        access
+            .iter()
+            .map(|access| match access {
+                AccessType::ArrayAccess(index) => {
+                    self.evaluate_index(index, frame).map(PathElement::Index)
+                }
+                AccessType::ComponentAccess(signal) => Ok(PathElement::Signal(signal.clone())),
+            })
+            .collect()
+    }
+
+    fn evaluate_access(
+        &mut self,
+        var: &VariableName,
+        access: &[AccessType],
+        frame: &mut Frame,
+    ) -> EmulationResult<Value> {
+        let invalid_access = || EmulationError::InvalidAccess { name: var.to_string() };
+        let path = self.evaluate_path(access, frame)?;
+        let (indices, signal) = split_path(&path).ok_or_else(invalid_access)?;
+        let value = frame.get_mut(var)?.get_mut(&indices).ok_or_else(invalid_access)?;
+        let value = match (signal, value) {
+            (None, value) => value.clone(),
+            (Some((signal, indices)), Value::Component(component)) => {
+                // Emulate the component the first time one of its signals is read.
+                if component.emulation.is_none() {
+                    component.emulation = Some(self.instantiate(component)?);
+                }
+                component
+                    .emulation()
+                    .and_then(|emulation| emulation.get_signal(signal))
+                    .and_then(|value| value.get(&indices))
+                    .ok_or_else(invalid_access)?
+                    .clone()
+            }
+            (Some(_), Value::Undefined) => Value::Undefined,
+            (Some(_), _) => return Err(invalid_access()),
+        };
+        match value {
+            Value::Undefined => Err(EmulationError::UndefinedValue { name: var.to_string() }),
+            value => Ok(value),
+        }
+    }
+
+    fn evaluate_update(
+        &mut self,
+        var: &VariableName,
+        access: &[AccessType],
+        rhe: &Expression,
+        frame: &mut Frame,
+    ) -> EmulationResult<Value> {
+        let invalid_access = || EmulationError::InvalidAccess { name: var.to_string() };
+        let rhv = self.evaluate(rhe, frame)?;
+        let path = self.evaluate_path(access, frame)?;
+        let (indices, signal) = split_path(&path).ok_or_else(invalid_access)?;
+        let mut result = frame.get(var)?.clone();
+        let value = result.get_mut(&indices).ok_or_else(invalid_access)?;
+        match (signal, value) {
+            (None, value) => *value = rhv,
+            (Some((signal, indices)), Value::Component(component)) => {
+                // Assigning an input invalidates any previous emulation result.
+                component.emulation = None;
+                let input = component.inputs.entry(signal.to_string()).or_insert(Value::Undefined);
+                if !input.set_or_grow(&indices, rhv) {
+                    return Err(invalid_access());
+                }
+            }
+            (Some(_), Value::Undefined) => {
+                return Err(EmulationError::UndefinedValue { name: var.to_string() })
+            }
+            (Some(_), _) => return Err(invalid_access()),
+        }
+        Ok(result)
+    }
+}
+
+pub(crate) fn evaluate_infix_op(
+    op: &ExpressionInfixOpcode,
+    lhv: &BigInt,
+    rhv: &BigInt,
+    prime: &BigInt,
+) -> EmulationResult<BigInt> {
+    use ExpressionInfixOpcode::*;
+    let value = match op {
+        Mul => modular_arithmetic::mul(lhv, rhv, prime),
+        Div => modular_arithmetic::div(lhv, rhv, prime)?,
+        Add => modular_arithmetic::add(lhv, rhv, prime),
+        Sub => modular_arithmetic::sub(lhv, rhv, prime),
+        Pow => modular_arithmetic::pow(lhv, rhv, prime),
+        IntDiv => modular_arithmetic::idiv(lhv, rhv, prime)?,
+        Mod => modular_arithmetic::mod_op(lhv, rhv, prime)?,
+        ShiftL => modular_arithmetic::shift_l(lhv, rhv, prime)?,
+        ShiftR => modular_arithmetic::shift_r(lhv, rhv, prime)?,
+        LesserEq => modular_arithmetic::lesser_eq(lhv, rhv, prime),
+        GreaterEq => modular_arithmetic::greater_eq(lhv, rhv, prime),
+        Lesser => modular_arithmetic::lesser(lhv, rhv, prime),
+        Greater => modular_arithmetic::greater(lhv, rhv, prime),
+        Eq => modular_arithmetic::eq(lhv, rhv, prime),
+        NotEq => modular_arithmetic::not_eq(lhv, rhv, prime),
+        BoolOr => modular_arithmetic::bool_or(lhv, rhv, prime),
+        BoolAnd => modular_arithmetic::bool_and(lhv, rhv, prime),
+        BitOr => modular_arithmetic::bit_or(lhv, rhv, prime),
+        BitAnd => modular_arithmetic::bit_and(lhv, rhv, prime),
+        BitXor => modular_arithmetic::bit_xor(lhv, rhv, prime),
+    };
+    Ok(value)
+}
+
+pub(crate) fn evaluate_prefix_op(
+    op: &ExpressionPrefixOpcode,
+    rhv: &BigInt,
+    prime: &BigInt,
+) -> BigInt {
+    use ExpressionPrefixOpcode::*;
+    match op {
+        Sub => modular_arithmetic::prefix_sub(rhv, prime),
+        BoolNot => modular_arithmetic::not(rhv, prime),
+        Complement => modular_arithmetic::complement_256(rhv, prime),
+    }
+}
+
+fn collect_failed_constraints(value: &Value, failed_constraints: &mut Vec<FailedConstraint>) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                collect_failed_constraints(value, failed_constraints);
+            }
+        }
+        Value::Component(component) => {
+            if let Some(emulation) = component.emulation() {
+                failed_constraints.extend(emulation.failed_constraints().iter().cloned());
+            }
+        }
+        Value::Undefined | Value::FieldElement(_) => {}
+    }
+}
+
+fn modulus(value: &BigInt, prime: &BigInt) -> BigInt {
+    ((value % prime) + prime) % prime
+}
+
+#[cfg(test)]
+mod tests {
+    use program_structure::constants::{Curve, UsefulConstants};
+
+    use crate::analysis_runner::AnalysisRunner;
+
+    use super::*;
+
+    #[test]
+    fn test_emulate_function() {
+        let src = [r#"
+            function sum(n) {
+                var result = 0;
+                for (var i = 0; i < n; i++) {
+                    if (i % 2 == 0) {
+                        result += i;
+                    } else {
+                        result -= 1;
+                    }
+                }
+                return result;
+            }
+        "#];
+        // 0 - 1 + 2 - 1 + 4 - 1 = 3
+        assert_eq!(emulate_function("sum", &src, &[value(6)]).unwrap(), 3);
+
+        let src = [
+            r#"
+            function square(x) {
+                return x * x;
+            }
+        "#,
+            r#"
+            function sumOfSquares(x) {
+                var squares[3];
+                for (var i = 0; i < 3; i++) {
+                    squares[i] = square(x[i]);
+                }
+                return squares[0] + squares[1] + squares[2];
+            }
+        "#,
+        ];
+        let args = [Value::Array(vec![value(1), value(2), value(3)])];
+        assert_eq!(emulate_function("sumOfSquares", &src, &args).unwrap(), 14);
+    }
+
+    #[test]
+    fn test_emulate_template() {
+        let src = [NUM_2_BITS];
+        let emulation = emulate_template("Num2Bits", &src, &[value(4)], &[("in", value(11))]);
+        let emulation = emulation.unwrap();
+        assert!(emulation.is_satisfied());
+        assert_eq!(field_elements(emulation.get_signal("out").unwrap()), vec![1, 1, 0, 1]);
+
+        // 20 does not fit in 4 bits, so the final constraint fails.
+        let emulation = emulate_template("Num2Bits", &src, &[value(4)], &[("in", value(20))]);
+        let emulation = emulation.unwrap();
+        assert_eq!(emulation.failed_constraints().len(), 1);
+        assert_eq!(emulation.failed_constraints()[0].template(), "Num2Bits");
+    }
+
+    #[test]
+    fn test_emulate_subcomponents() {
+        let src = [
+            NUM_2_BITS,
+            r#"
+            template Main() {
+                signal input in[2];
+                signal output out;
+
+                component n2b[2];
+                for (var i = 0; i < 2; i++) {
+                    n2b[i] = Num2Bits(4);
+                    n2b[i].in <== in[i];
+                }
+                out <== n2b[0].out[0] + n2b[1].out[3];
+            }
+        "#,
+        ];
+        let input = Value::Array(vec![value(5), value(8)]);
+        let emulation = emulate_template("Main", &src, &[], &[("in", input)]).unwrap();
+        assert!(emulation.is_satisfied());
+        assert_eq!(field_elements(emulation.get_signal("out").unwrap()), vec![2]);
+
+        let witness = emulation.witness();
+        assert_eq!(witness.len(), 3 + 2 * 5);
+        assert!(witness.contains(&("n2b[1].out[3]".to_string(), Some(BigInt::from(1)))));
+
+        // Failing constraints in subcomponents are propagated to the parent.
+        let input = Value::Array(vec![value(5), value(16)]);
+        let emulation = emulate_template("Main", &src, &[], &[("in", input)]).unwrap();
+        assert_eq!(emulation.failed_constraints().len(), 1);
+    }
+
+    #[test]
+    fn test_failed_constraints() {
+        let src = [r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                out <-- in + 1;
+                out === in * in;
+            }
+        "#];
+        let emulation = emulate_template("T", &src, &[], &[("in", value(2))]).unwrap();
+        assert_eq!(emulation.failed_constraints().len(), 1);
+        let constraint = &emulation.failed_constraints()[0];
+        assert_eq!(field_elements(constraint.lhs()), vec![3]);
+        assert_eq!(field_elements(constraint.rhs()), vec![4]);
+    }
+
+    #[test]
+    fn test_emulation_errors() {
+        let src = [r#"
+            template T(n) {
+                signal input in;
+                signal output out;
+
+                assert(n > 0);
+                out <== in * n;
+            }
+        "#];
+        let result = emulate_template("T", &src, &[value(0)], &[("in", value(1))]);
+        assert!(matches!(result, Err(EmulationError::AssertionFailed { .. })));
+
+        let result = emulate_template("T", &src, &[value(1)], &[]);
+        assert!(matches!(result, Err(EmulationError::UndefinedValue { .. })));
+
+        let result = emulate_template("T", &src, &[value(1)], &[("x", value(1))]);
+        assert!(matches!(result, Err(EmulationError::UnknownInput { .. })));
+
+        let src = [r#"
+            function f(n) {
+                while (n > 0) {
+                    n += 1;
+                }
+                return n;
+            }
+        "#];
+        let context = AnalysisRunner::new(Curve::Goldilocks).with_src(&src);
+        let cfg = context.function("f").unwrap();
+        let mut emulator = Emulator::new(&context).with_max_steps(1000);
+        let result = emulator.emulate_function(&cfg, &[value(1)]);
+        assert!(matches!(result, Err(EmulationError::StepLimitExceeded { .. })));
+    }
+
+    const NUM_2_BITS: &str = r#"
+        template Num2Bits(n) {
+            signal input in;
+            signal output out[n];
+
+            var lc = 0;
+            var e = 1;
+            for (var i = 0; i < n; i++) {
+                out[i] <-- (in >> i) & 1;
+                out[i] * (out[i] - 1) === 0;
+                lc += out[i] * e;
+                e = e + e;
+            }
+            lc === in;
+        }
+    "#;
+
+    fn value(value: i64) -> Value {
+        let constants = UsefulConstants::new(&Curve::Goldilocks);
+        Value::FieldElement(modulus(&BigInt::from(value), constants.prime()))
+    }
+
+    fn field_elements(value: &Value) -> Vec<i64> {
+        match value {
+            Value::FieldElement(value) => vec![value.to_i64().unwrap()],
+            Value::Array(values) => values.iter().flat_map(field_elements).collect(),
+            _ => panic!("expected field element or array"),
+        }
+    }
+
+    fn emulate_function(name: &str, src: &[&str], args: &[Value]) -> EmulationResult<i64> {
+        let context = AnalysisRunner::new(Curve::Goldilocks).with_src(src);
+        let cfg = context.function(name).unwrap();
+        let value = Emulator::new(&context).emulate_function(&cfg, args)?;
+        Ok(field_elements(&value)[0])
+    }
+
+    fn emulate_template(
+        name: &str,
+        src: &[&str],
+        args: &[Value],
+        inputs: &[(&str, Value)],
+    ) -> EmulationResult<Emulation> {
+        let context = AnalysisRunner::new(Curve::Goldilocks).with_src(src);
+        let cfg = context.template(name).unwrap();
+        let inputs = inputs.iter().map(|(name, value)| (name.to_string(), value.clone())).collect();
+        Emulator::new(&context).emulate_template(&cfg, args, &inputs)
+    }
+}
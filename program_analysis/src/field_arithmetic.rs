@@ -1,4 +1,5 @@
 use log::debug;
+use num_bigint::BigInt;
 
 use program_structure::cfg::Cfg;
 use program_structure::report_code::ReportCode;
@@ -31,88 +32,92 @@ impl FieldElementArithmeticWarning {
 
 /// Field element arithmetic in Circom may overflow, which could produce
 /// unexpected results. Worst case, it may allow a malicious prover to forge
-/// proofs.
+/// proofs. Arithmetic where the result is known to be contained in `[0, p/2]`
+/// (using value-range analysis) is not reported.
 pub fn find_field_element_arithmetic(cfg: &Cfg) -> ReportCollection {
     debug!("running field element arithmetic analysis pass");
     let mut reports = ReportCollection::new();
+    let prime = cfg.constants().prime();
     for basic_block in cfg.iter() {
         for stmt in basic_block.iter() {
-            visit_statement(stmt, &mut reports);
+            visit_statement(stmt, prime, &mut reports);
         }
     }
     debug!("{} new reports generated", reports.len());
     reports
 }
 
-fn visit_statement(stmt: &Statement, reports: &mut ReportCollection) {
+fn visit_statement(stmt: &Statement, prime: &BigInt, reports: &mut ReportCollection) {
     use Statement::*;
     match stmt {
         Declaration { dimensions, .. } => {
             for size in dimensions {
-                visit_expression(size, reports);
+                visit_expression(size, prime, reports);
             }
         }
         LogCall { args, .. } => {
             use LogArgument::*;
             for arg in args {
                 if let Expr(value) = arg {
-                    visit_expression(value, reports);
+                    visit_expression(value, prime, reports);
                 }
             }
         }
-        IfThenElse { cond, .. } => visit_expression(cond, reports),
-        Substitution { rhe, .. } => visit_expression(rhe, reports),
-        Return { value, .. } => visit_expression(value, reports),
-        Assert { arg, .. } => visit_expression(arg, reports),
+        IfThenElse { cond, .. } => visit_expression(cond, prime, reports),
+        Substitution { rhe, .. } => visit_expression(rhe, prime, reports),
+        Return { value, .. } => visit_expression(value, prime, reports),
+        Assert { arg, .. } => visit_expression(arg, prime, reports),
         ConstraintEquality { lhe, rhe, .. } => {
-            visit_expression(lhe, reports);
-            visit_expression(rhe, reports);
+            visit_expression(lhe, prime, reports);
+            visit_expression(rhe, prime, reports);
         }
     }
 }
 
-fn visit_expression(expr: &Expression, reports: &mut ReportCollection) {
+fn visit_expression(expr: &Expression, prime: &BigInt, reports: &mut ReportCollection) {
     use Expression::*;
     match expr {
-        InfixOp { meta, infix_op, .. } if may_overflow(infix_op) => {
+        InfixOp { meta, infix_op, .. }
+            if may_overflow(infix_op) && !meta.range_knowledge().is_below_half_prime(prime) =>
+        {
             reports.push(build_report(meta));
         }
         InfixOp { lhe, rhe, .. } => {
-            visit_expression(lhe, reports);
-            visit_expression(rhe, reports);
+            visit_expression(lhe, prime, reports);
+            visit_expression(rhe, prime, reports);
         }
         PrefixOp { rhe, .. } => {
-            visit_expression(rhe, reports);
+            visit_expression(rhe, prime, reports);
         }
         SwitchOp { cond, if_true, if_false, .. } => {
-            visit_expression(cond, reports);
-            visit_expression(if_true, reports);
-            visit_expression(if_false, reports);
+            visit_expression(cond, prime, reports);
+            visit_expression(if_true, prime, reports);
+            visit_expression(if_false, prime, reports);
         }
         Call { args, .. } => {
             for arg in args {
-                visit_expression(arg, reports);
+                visit_expression(arg, prime, reports);
             }
         }
         InlineArray { values, .. } => {
             for value in values {
-                visit_expression(value, reports);
+                visit_expression(value, prime, reports);
             }
         }
         Access { access, .. } => {
             for index in access {
                 if let AccessType::ArrayAccess(index) = index {
-                    visit_expression(index, reports);
+                    visit_expression(index, prime, reports);
                 }
             }
         }
         Update { access, rhe, .. } => {
             for index in access {
                 if let AccessType::ArrayAccess(index) = index {
-                    visit_expression(index, reports);
+                    visit_expression(index, prime, reports);
                 }
             }
-            visit_expression(rhe, reports);
+            visit_expression(rhe, prime, reports);
         }
         Number(_, _) | Variable { .. } | Phi { .. } => (),
     }
@@ -154,6 +159,18 @@ mod tests {
             }
         "#;
         validate_reports(src, 2);
+
+        let src = r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                component n2b = Num2Bits(8);
+                n2b.in <== in;
+                out <== in * 2 + n2b.out[0];
+            }
+        "#;
+        validate_reports(src, 0);
     }
 
     fn validate_reports(src: &str, expected_len: usize) {
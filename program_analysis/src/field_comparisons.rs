@@ -1,4 +1,5 @@
 use log::debug;
+use num_bigint::BigInt;
 
 use program_structure::cfg::Cfg;
 use program_structure::report_code::ReportCode;
@@ -40,88 +41,94 @@ impl FieldElementComparisonWarning {
 ///   2. `p/2 + 1 < p/2 - 1`, and
 ///   3. `2 * x < x` for any `p/4 < x < p/2`
 ///
-/// are all true.
+/// are all true. Comparisons where both operands are known to be contained in
+/// `[0, p/2]` (using value-range analysis) are not reported.
 pub fn find_field_element_comparisons(cfg: &Cfg) -> ReportCollection {
     debug!("running field element comparison analysis pass");
     let mut reports = ReportCollection::new();
+    let prime = cfg.constants().prime();
     for basic_block in cfg.iter() {
         for stmt in basic_block.iter() {
-            visit_statement(stmt, &mut reports);
+            visit_statement(stmt, prime, &mut reports);
         }
     }
     debug!("{} new reports generated", reports.len());
     reports
 }
 
-fn visit_statement(stmt: &Statement, reports: &mut ReportCollection) {
+fn visit_statement(stmt: &Statement, prime: &BigInt, reports: &mut ReportCollection) {
     use Statement::*;
     match stmt {
         Declaration { dimensions, .. } => {
             for size in dimensions {
-                visit_expression(size, reports);
+                visit_expression(size, prime, reports);
             }
         }
         LogCall { args, .. } => {
             use LogArgument::*;
             for arg in args {
                 if let Expr(value) = arg {
-                    visit_expression(value, reports);
+                    visit_expression(value, prime, reports);
                 }
             }
         }
-        IfThenElse { cond, .. } => visit_expression(cond, reports),
-        Substitution { rhe, .. } => visit_expression(rhe, reports),
-        Return { value, .. } => visit_expression(value, reports),
-        Assert { arg, .. } => visit_expression(arg, reports),
+        IfThenElse { cond, .. } => visit_expression(cond, prime, reports),
+        Substitution { rhe, .. } => visit_expression(rhe, prime, reports),
+        Return { value, .. } => visit_expression(value, prime, reports),
+        Assert { arg, .. } => visit_expression(arg, prime, reports),
         ConstraintEquality { lhe, rhe, .. } => {
-            visit_expression(lhe, reports);
-            visit_expression(rhe, reports);
+            visit_expression(lhe, prime, reports);
+            visit_expression(rhe, prime, reports);
         }
     }
 }
 
-fn visit_expression(expr: &Expression, reports: &mut ReportCollection) {
+fn visit_expression(expr: &Expression, prime: &BigInt, reports: &mut ReportCollection) {
     use Expression::*;
     match expr {
-        InfixOp { meta, infix_op, .. } if is_comparison_op(infix_op) => {
+        InfixOp { meta, lhe, infix_op, rhe }
+            if is_comparison_op(infix_op)
+                && !(lhe.meta().range_knowledge().is_below_half_prime(prime)
+                    && rhe.meta().range_knowledge().is_below_half_prime(prime)) =>
+        {
             reports.push(build_report(meta));
         }
         InfixOp { lhe, rhe, .. } => {
-            visit_expression(lhe, reports);
-            visit_expression(rhe, reports);
+            visit_expression(lhe, prime, reports);
+            visit_expression(rhe, prime, reports);
         }
         PrefixOp { rhe, .. } => {
-            visit_expression(rhe, reports);
+            visit_expression(rhe, prime, reports);
         }
         SwitchOp { cond, if_true, if_false, .. } => {
-            visit_expression(cond, reports);
-            visit_expression(if_true, reports);
-            visit_expression(if_false, reports);
+            visit_expression(cond, prime, reports);
+            visit_expression(if_true, prime, reports);
+            visit_expression(if_false, prime, reports);
         }
         Call { args, .. } => {
             for arg in args {
-                visit_expression(arg, reports);
+                visit_expression(arg, prime, reports);
             }
         }
         InlineArray { values, .. } => {
             for value in values {
-                visit_expression(value, reports);
+                visit_expression(value, prime, reports);
             }
         }
         Access { access, .. } => {
             for index in access {
                 if let AccessType::ArrayAccess(index) = index {
-                    visit_expression(index, reports);
+                    visit_expression(index, prime, reports);
                 }
             }
         }
         Update { access, rhe, .. } => {
             for index in access {
                 if let AccessType::ArrayAccess(index) = index {
-                    visit_expression(index, reports);
+                    visit_expression(index, prime, reports);
                 }
             }
-            visit_expression(rhe, reports);
+            visit_expression(rhe, prime, reports);
         }
         Number(_, _) | Variable { .. } | Phi { .. } => (),
     }
@@ -160,6 +167,22 @@ mod tests {
             }
         "#;
         validate_reports(src, 4);
+
+        let src = r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                component n2b = Num2Bits(8);
+                n2b.in <== in;
+                var x = 0;
+                if (in < 128) {
+                    x = 1;
+                }
+                out <== x;
+            }
+        "#;
+        validate_reports(src, 0);
     }
 
     fn validate_reports(src: &str, expected_len: usize) {
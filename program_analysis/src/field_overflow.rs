@@ -0,0 +1,244 @@
+use log::debug;
+use num_bigint::BigInt;
+
+use program_structure::cfg::Cfg;
+use program_structure::report_code::ReportCode;
+use program_structure::report::{Report, ReportCollection};
+use program_structure::file_definition::{FileID, FileLocation};
+use program_structure::ir::range_meta::{RangeMeta, ValueRange};
+use program_structure::ir::value_meta::ValueMeta;
+use program_structure::ir::*;
+
+pub struct FieldElementOverflowWarning {
+    file_id: Option<FileID>,
+    file_location: FileLocation,
+    lhr: ValueRange,
+    rhr: ValueRange,
+}
+
+impl FieldElementOverflowWarning {
+    pub fn into_report(self) -> Report {
+        let mut report = Report::warning(
+            "Field element arithmetic may overflow the prime, which may produce unexpected results."
+                .to_string(),
+            ReportCode::FieldElementOverflow,
+        );
+        if let Some(file_id) = self.file_id {
+            report.add_primary(
+                self.file_location,
+                file_id,
+                "The result of this expression may exceed the prime.".to_string(),
+            );
+        }
+        report.add_note(format!(
+            "The operands are bounded by `{}` and `{}` respectively.",
+            self.lhr, self.rhr
+        ));
+        report
+    }
+}
+
+/// Arithmetic where the operands are known to be bounded (e.g. because they
+/// are constrained using `Num2Bits`) may still exceed the prime. Since the
+/// result is reduced modulo the prime this is typically unexpected and may
+/// allow a malicious prover to forge proofs.
+pub fn find_field_element_overflow(cfg: &Cfg) -> ReportCollection {
+    debug!("running field element overflow analysis pass");
+    let mut reports = ReportCollection::new();
+    let prime = cfg.constants().prime();
+    for basic_block in cfg.iter() {
+        for stmt in basic_block.iter() {
+            visit_statement(stmt, prime, &mut reports);
+        }
+    }
+    debug!("{} new reports generated", reports.len());
+    reports
+}
+
+fn visit_statement(stmt: &Statement, prime: &BigInt, reports: &mut ReportCollection) {
+    use Statement::*;
+    match stmt {
+        Declaration { dimensions, .. } => {
+            for size in dimensions {
+                visit_expression(size, prime, reports);
+            }
+        }
+        LogCall { args, .. } => {
+            use LogArgument::*;
+            for arg in args {
+                if let Expr(value) = arg {
+                    visit_expression(value, prime, reports);
+                }
+            }
+        }
+        IfThenElse { cond, .. } => visit_expression(cond, prime, reports),
+        Substitution { rhe, .. } => visit_expression(rhe, prime, reports),
+        Return { value, .. } => visit_expression(value, prime, reports),
+        Assert { arg, .. } => visit_expression(arg, prime, reports),
+        ConstraintEquality { lhe, rhe, .. } => {
+            visit_expression(lhe, prime, reports);
+            visit_expression(rhe, prime, reports);
+        }
+    }
+}
+
+fn visit_expression(expr: &Expression, prime: &BigInt, reports: &mut ReportCollection) {
+    use Expression::*;
+    match expr {
+        InfixOp { meta, lhe, infix_op, rhe } => {
+            if let (Some(lhr), Some(rhr)) = (lhe.range(), rhe.range()) {
+                // Arithmetic on constants is evaluated at compile time and
+                // is assumed to be intentional.
+                if !(lhe.is_constant() && rhe.is_constant())
+                    && may_overflow(infix_op, lhr, rhr, prime)
+                {
+                    reports.push(build_report(meta, lhr, rhr));
+                }
+            }
+            visit_expression(lhe, prime, reports);
+            visit_expression(rhe, prime, reports);
+        }
+        PrefixOp { rhe, .. } => {
+            visit_expression(rhe, prime, reports);
+        }
+        SwitchOp { cond, if_true, if_false, .. } => {
+            visit_expression(cond, prime, reports);
+            visit_expression(if_true, prime, reports);
+            visit_expression(if_false, prime, reports);
+        }
+        Call { args, .. } => {
+            for arg in args {
+                visit_expression(arg, prime, reports);
+            }
+        }
+        InlineArray { values, .. } => {
+            for value in values {
+                visit_expression(value, prime, reports);
+            }
+        }
+        Access { access, .. } => {
+            for index in access {
+                if let AccessType::ArrayAccess(index) = index {
+                    visit_expression(index, prime, reports);
+                }
+            }
+        }
+        Update { access, rhe, .. } => {
+            for index in access {
+                if let AccessType::ArrayAccess(index) = index {
+                    visit_expression(index, prime, reports);
+                }
+            }
+            visit_expression(rhe, prime, reports);
+        }
+        Number(_, _) | Variable { .. } | Phi { .. } => (),
+    }
+}
+
+/// Returns true if the result of the operation may be greater than or equal to
+/// the prime when the operands are bounded by the given ranges.
+fn may_overflow(
+    op: &ExpressionInfixOpcode,
+    lhr: &ValueRange,
+    rhr: &ValueRange,
+    prime: &BigInt,
+) -> bool {
+    use ExpressionInfixOpcode::*;
+    // If the range of the result is too large to compute it is guaranteed to
+    // exceed the prime.
+    let range = match op {
+        Add => Some(lhr.add(rhr)),
+        Mul => lhr.mul(rhr),
+        Pow => lhr.pow(rhr),
+        ShiftL => lhr.shift_left(rhr),
+        _ => return false,
+    };
+    match range {
+        Some(range) => range.upper() >= prime,
+        None => true,
+    }
+}
+
+fn build_report(meta: &Meta, lhr: &ValueRange, rhr: &ValueRange) -> Report {
+    FieldElementOverflowWarning {
+        file_id: meta.file_id(),
+        file_location: meta.file_location(),
+        lhr: lhr.clone(),
+        rhr: rhr.clone(),
+    }
+    .into_report()
+}
+
+#[cfg(test)]
+mod tests {
+    use parser::parse_definition;
+    use program_structure::{cfg::IntoCfg, constants::Curve};
+
+    use super::*;
+
+    #[test]
+    fn test_field_overflow() {
+        let src = r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                component n2b = Num2Bits(200);
+                n2b.in <== in;
+                out <== in * in;
+            }
+        "#;
+        validate_reports(src, 1);
+
+        let src = r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                component n2b = Num2Bits(100);
+                n2b.in <== in;
+                out <== in * in + (in << 8);
+            }
+        "#;
+        validate_reports(src, 0);
+
+        let src = r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                in * (in - 1) === 0;
+                out <== in * in;
+            }
+        "#;
+        validate_reports(src, 0);
+
+        // Unbounded inputs are reported by the field element arithmetic pass.
+        let src = r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                out <== in * in;
+            }
+        "#;
+        validate_reports(src, 0);
+    }
+
+    fn validate_reports(src: &str, expected_len: usize) {
+        // Build CFG.
+        let mut reports = ReportCollection::new();
+        let cfg = parse_definition(src)
+            .unwrap()
+            .into_cfg(&Curve::default(), &mut reports)
+            .unwrap()
+            .into_ssa()
+            .unwrap();
+        assert!(reports.is_empty());
+
+        // Generate report collection.
+        let reports = find_field_element_overflow(&cfg);
+
+        assert_eq!(reports.len(), expected_len);
+    }
+}
@@ -1,15 +1,29 @@
 use analysis_context::AnalysisContext;
+use pass_registry::{PassKind, PassRegistry, RegisteredPass};
 
 use program_structure::cfg::Cfg;
 use program_structure::report::ReportCollection;
+use program_structure::report_code::ReportCode;
 
 extern crate num_bigint_dig as num_bigint;
 
 pub mod constraint_analysis;
+pub mod def_use_analysis;
+pub mod dominance_analysis;
 pub mod taint_analysis;
 pub mod analysis_context;
 pub mod analysis_runner;
+pub mod cache;
 pub mod config;
+pub mod emulation;
+pub mod pass_registry;
+pub mod polynomial;
+pub mod rules;
+pub mod symbolic_execution;
+pub mod call_graph;
+mod data_flow;
+mod variable_access;
+mod witness_path;
 
 // Intra-process analysis passes.
 mod bitwise_complement;
@@ -18,6 +32,7 @@ mod constant_conditional;
 mod definition_complexity;
 mod field_arithmetic;
 mod field_comparisons;
+mod field_overflow;
 mod nonstrict_binary_conversion;
 mod under_constrained_signals;
 mod unconstrained_less_than;
@@ -27,27 +42,137 @@ mod signal_assignments;
 
 // Inter-process analysis passes.
 mod unused_output_signal;
+mod unconstrained_component_signal;
+mod under_constrained_outputs;
 
 /// An analysis pass is a function which takes an analysis context and a CFG and
 /// returns a set of reports.
-type AnalysisPass = dyn Fn(&mut dyn AnalysisContext, &Cfg) -> ReportCollection;
+pub(crate) type AnalysisPass = dyn Fn(&dyn AnalysisContext, &Cfg) -> ReportCollection + Send + Sync;
 
-pub fn get_analysis_passes() -> Vec<Box<AnalysisPass>> {
-    vec![
+/// Returns a registry containing all built-in analysis passes.
+pub fn get_analysis_passes() -> PassRegistry {
+    use PassKind::*;
+    use ReportCode::*;
+
+    let passes = vec![
         // Intra-process analysis passes.
-        Box::new(|_, cfg| bitwise_complement::find_bitwise_complement(cfg)),
-        Box::new(|_, cfg| signal_assignments::find_signal_assignments(cfg)),
-        Box::new(|_, cfg| definition_complexity::run_complexity_analysis(cfg)),
-        Box::new(|_, cfg| side_effect_analysis::run_side_effect_analysis(cfg)),
-        Box::new(|_, cfg| field_arithmetic::find_field_element_arithmetic(cfg)),
-        Box::new(|_, cfg| field_comparisons::find_field_element_comparisons(cfg)),
-        Box::new(|_, cfg| unconstrained_division::find_unconstrained_division(cfg)),
-        Box::new(|_, cfg| bn254_specific_circuit::find_bn254_specific_circuits(cfg)),
-        Box::new(|_, cfg| unconstrained_less_than::find_unconstrained_less_than(cfg)),
-        Box::new(|_, cfg| constant_conditional::find_constant_conditional_statement(cfg)),
-        Box::new(|_, cfg| under_constrained_signals::find_under_constrained_signals(cfg)),
-        Box::new(|_, cfg| nonstrict_binary_conversion::find_nonstrict_binary_conversion(cfg)),
+        RegisteredPass::new(
+            "bitwise-complement",
+            IntraProcess,
+            &[FieldElementArithmetic],
+            "Uses of the bitwise complement operator `~`, which is reduced modulo the prime.",
+            |_, cfg| bitwise_complement::find_bitwise_complement(cfg),
+        ),
+        RegisteredPass::new(
+            "signal-assignments",
+            IntraProcess,
+            &[SignalAssignmentStatement, UnnecessarySignalAssignment],
+            "Signal assignments using `<--` which do not constrain the assigned signal.",
+            signal_assignments::find_signal_assignments,
+        ),
+        RegisteredPass::new(
+            "definition-complexity",
+            IntraProcess,
+            &[CyclomaticComplexity, TooManyArguments],
+            "Templates and functions which are overly complex or take too many parameters.",
+            |context, cfg| definition_complexity::run_complexity_analysis(context.settings(), cfg),
+        ),
+        RegisteredPass::new(
+            "side-effects",
+            IntraProcess,
+            &[UnusedVariableValue, UnusedParameterValue, VariableWithoutSideEffect, UnconstrainedSignal],
+            "Variables, parameters, and signals which do not affect witness or constraint generation.",
+            side_effect_analysis::run_side_effect_analysis,
+        ),
+        RegisteredPass::new(
+            "field-arithmetic",
+            IntraProcess,
+            &[FieldElementArithmetic],
+            "Field element arithmetic which may overflow.",
+            |_, cfg| field_arithmetic::find_field_element_arithmetic(cfg),
+        ),
+        RegisteredPass::new(
+            "field-comparisons",
+            IntraProcess,
+            &[FieldElementComparison],
+            "Comparisons of field elements which use the signed representation of the elements.",
+            |_, cfg| field_comparisons::find_field_element_comparisons(cfg),
+        ),
+        RegisteredPass::new(
+            "field-overflow",
+            IntraProcess,
+            &[FieldElementOverflow],
+            "Arithmetic expressions on bounded values which may exceed the prime.",
+            |_, cfg| field_overflow::find_field_element_overflow(cfg),
+        ),
+        RegisteredPass::new(
+            "unconstrained-division",
+            IntraProcess,
+            &[UnconstrainedDivision],
+            "Signal assignments containing a division where the divisor is not constrained to be non-zero.",
+            unconstrained_division::find_unconstrained_division,
+        ),
+        RegisteredPass::new(
+            "bn254-specific-circuit",
+            IntraProcess,
+            &[Bn254SpecificCircuit],
+            "Circomlib templates hard-coded for BN254 which are used with a different curve.",
+            |_, cfg| bn254_specific_circuit::find_bn254_specific_circuits(cfg),
+        ),
+        RegisteredPass::new(
+            "unconstrained-less-than",
+            IntraProcess,
+            &[UnconstrainedLessThan],
+            "Uses of `LessThan` where the inputs are not constrained to the size of the comparison.",
+            |_, cfg| unconstrained_less_than::find_unconstrained_less_than(cfg),
+        ),
+        RegisteredPass::new(
+            "constant-conditional",
+            IntraProcess,
+            &[ConstantBranchCondition],
+            "Branching conditions which always evaluate to the same value.",
+            |_, cfg| constant_conditional::find_constant_conditional_statement(cfg),
+        ),
+        RegisteredPass::new(
+            "under-constrained-signals",
+            IntraProcess,
+            &[UnderConstrainedSignal],
+            "Intermediate signals which occur in too few constraints.",
+            under_constrained_signals::find_under_constrained_signals,
+        ),
+        RegisteredPass::new(
+            "nonstrict-binary-conversion",
+            IntraProcess,
+            &[NonStrictBinaryConversion],
+            "Uses of `Num2Bits` and `Bits2Num` which allow more than one binary representation.",
+            nonstrict_binary_conversion::find_nonstrict_binary_conversion,
+        ),
         // Inter-process analysis passes.
-        Box::new(unused_output_signal::find_unused_output_signals),
-    ]
+        RegisteredPass::new(
+            "unused-output-signal",
+            InterProcess,
+            &[UnusedOutputSignal],
+            "Output signals of instantiated components which are never used.",
+            unused_output_signal::find_unused_output_signals,
+        ),
+        RegisteredPass::new(
+            "unconstrained-component-signal",
+            InterProcess,
+            &[UnconstrainedComponentSignal],
+            "Input signals of instantiated components which are not constrained.",
+            unconstrained_component_signal::find_unconstrained_component_signals,
+        ),
+        RegisteredPass::new(
+            "under-constrained-outputs",
+            InterProcess,
+            &[UnderConstrainedOutputSignal],
+            "Output signals which are not uniquely determined by the input signals.",
+            under_constrained_outputs::find_under_constrained_outputs,
+        ),
+    ];
+    let mut registry = PassRegistry::new();
+    for pass in passes {
+        registry.register(pass);
+    }
+    registry
 }
@@ -3,21 +3,26 @@ use num_bigint::BigInt;
 
 use program_structure::cfg::{Cfg, DefinitionType};
 use program_structure::constants::Curve;
+use program_structure::fix::{Fix, TextEdit};
 use program_structure::report_code::ReportCode;
 use program_structure::report::{Report, ReportCollection};
 use program_structure::file_definition::{FileID, FileLocation};
 use program_structure::ir::value_meta::{ValueMeta, ValueReduction};
 use program_structure::ir::*;
 
+use crate::analysis_context::AnalysisContext;
+
+// The strict versions of the templates rely on BN254 specific parameters, so
+// they are only suggested when the prime is the BN254 prime.
 pub enum NonStrictBinaryConversionWarning {
-    Num2Bits { file_id: Option<FileID>, location: FileLocation },
-    Bits2Num { file_id: Option<FileID>, location: FileLocation },
+    Num2Bits { file_id: Option<FileID>, location: FileLocation, is_bn254: bool, fix: Option<Fix> },
+    Bits2Num { file_id: Option<FileID>, location: FileLocation, is_bn254: bool, fix: Option<Fix> },
 }
 
 impl NonStrictBinaryConversionWarning {
     pub fn into_report(self) -> Report {
         match self {
-            NonStrictBinaryConversionWarning::Num2Bits { file_id, location } => {
+            NonStrictBinaryConversionWarning::Num2Bits { file_id, location, is_bn254, fix } => {
                 let mut report = Report::warning(
                     "Using `Num2Bits` to convert field elements to bits may lead to aliasing issues.".to_string(),
                     ReportCode::NonStrictBinaryConversion,
@@ -29,13 +34,23 @@ impl NonStrictBinaryConversionWarning {
                         "Circomlib template `Num2Bits` instantiated here.".to_string(),
                     );
                 }
-                report.add_note(
-                    "Consider using `Num2Bits_strict` if the input size may be >= than the prime size."
-                        .to_string(),
-                );
+                if is_bn254 {
+                    report.add_note(
+                        "Consider using `Num2Bits_strict` if the input size may be >= than the prime size."
+                            .to_string(),
+                    );
+                } else {
+                    report.add_note(
+                        "Consider ensuring that the input size is less than the prime size."
+                            .to_string(),
+                    );
+                }
+                if let Some(fix) = fix {
+                    report.set_fix(fix);
+                }
                 report
             }
-            NonStrictBinaryConversionWarning::Bits2Num { file_id, location } => {
+            NonStrictBinaryConversionWarning::Bits2Num { file_id, location, is_bn254, fix } => {
                 let mut report = Report::warning(
                     "Using `Bits2Num` to convert arrays to field elements may lead to aliasing issues.".to_string(),
                     ReportCode::NonStrictBinaryConversion,
@@ -47,10 +62,20 @@ impl NonStrictBinaryConversionWarning {
                         "Circomlib template `Bits2Num` instantiated here.".to_string(),
                     );
                 }
-                report.add_note(
-                    "Consider using `Bits2Num_strict` if the input size may be >= than the prime size."
-                        .to_string(),
-                );
+                if is_bn254 {
+                    report.add_note(
+                        "Consider using `Bits2Num_strict` if the input size may be >= than the prime size."
+                            .to_string(),
+                    );
+                } else {
+                    report.add_note(
+                        "Consider ensuring that the input size is less than the prime size."
+                            .to_string(),
+                    );
+                }
+                if let Some(fix) = fix {
+                    report.set_fix(fix);
+                }
                 report
             }
         }
@@ -58,32 +83,41 @@ impl NonStrictBinaryConversionWarning {
 }
 
 /// If the size in bits of the input `x` to the Circomlib circuit `NumBits` is
-/// >= than the size of the prime there will be two valid bit-representations of
-/// the input: One representation of `x` and one of `p + x`. This is typically
+/// at least the size of the prime there will be two valid bit-representations
+/// of the input: One representation of `x` and one of `p + x`. This is typically
 /// not expected by developers and may lead to issues.
-pub fn find_nonstrict_binary_conversion(cfg: &Cfg) -> ReportCollection {
+pub fn find_nonstrict_binary_conversion(
+    context: &dyn AnalysisContext,
+    cfg: &Cfg,
+) -> ReportCollection {
     use DefinitionType::*;
     if matches!(cfg.definition_type(), Function | CustomTemplate) {
         // Exit early if this is a function or custom template.
         return ReportCollection::new();
     }
-    if cfg.constants().curve() != &Curve::Bn254 {
-        // Exit early if we're not using the default curve.
-        return ReportCollection::new();
-    }
     debug!("running non-strict `Num2Bits` analysis pass");
     let mut reports = ReportCollection::new();
     let prime_size = BigInt::from(cfg.constants().prime_size());
+    let is_bn254 = cfg.constants().curve() == &Curve::Bn254;
+    // Fixes apply to all instances of the template, so we only suggest fixes
+    // when analyzing the generic template.
+    let context = if cfg.arguments().is_none() && is_bn254 { Some(context) } else { None };
     for basic_block in cfg.iter() {
         for stmt in basic_block.iter() {
-            visit_statement(stmt, &prime_size, &mut reports);
+            visit_statement(stmt, &prime_size, is_bn254, context, &mut reports);
         }
     }
     debug!("{} new reports generated", reports.len());
     reports
 }
 
-fn visit_statement(stmt: &Statement, prime_size: &BigInt, reports: &mut ReportCollection) {
+fn visit_statement(
+    stmt: &Statement,
+    prime_size: &BigInt,
+    is_bn254: bool,
+    context: Option<&dyn AnalysisContext>,
+    reports: &mut ReportCollection,
+) {
     use AssignOp::*;
     use Expression::*;
     use Statement::*;
@@ -103,41 +137,87 @@ fn visit_statement(stmt: &Statement, prime_size: &BigInt, reports: &mut ReportCo
                 let arg = &args[0];
                 // If the input size is known to be less than the prime size, this
                 // initialization is safe.
-                if let Some(FieldElement { value }) = arg.value() {
-                    if value < prime_size {
-                        return;
-                    }
+                let value = match arg.value() {
+                    Some(FieldElement { value }) => Some(value),
+                    _ => None,
+                };
+                if value.map_or(false, |value| value < prime_size) {
+                    return;
                 }
-                reports.push(build_num2bits(component_meta));
+                // If the input size is equal to the prime size, the strict
+                // version of the template has the same interface.
+                let fix = match context {
+                    Some(context) if value == Some(prime_size) => {
+                        build_strict_conversion_fix(context, component_meta, component_name)
+                    }
+                    _ => None,
+                };
+                reports.push(build_num2bits(component_meta, is_bn254, fix));
             }
             // We assume this is the `Bits2Num` circuit from Circomlib.
             if component_name == "Bits2Num" && args.len() == 1 {
                 let arg = &args[0];
                 // If the input size is known to be less than the prime size, this
                 // initialization is safe.
-                if let Some(FieldElement { value }) = arg.value() {
-                    if value < prime_size {
-                        return;
-                    }
+                let value = match arg.value() {
+                    Some(FieldElement { value }) => Some(value),
+                    _ => None,
+                };
+                if value.map_or(false, |value| value < prime_size) {
+                    return;
                 }
-                reports.push(build_bits2num(component_meta));
+                // If the input size is equal to the prime size, the strict
+                // version of the template has the same interface.
+                let fix = match context {
+                    Some(context) if value == Some(prime_size) => {
+                        build_strict_conversion_fix(context, component_meta, component_name)
+                    }
+                    _ => None,
+                };
+                reports.push(build_bits2num(component_meta, is_bn254, fix));
             }
         }
     }
 }
 
-fn build_num2bits(meta: &Meta) -> Report {
+/// Returns a fix replacing the call `name(n)` with `name_strict()`, provided
+/// that the strict template is defined.
+fn build_strict_conversion_fix(
+    context: &dyn AnalysisContext,
+    meta: &Meta,
+    name: &str,
+) -> Option<Fix> {
+    let strict_name = format!("{name}_strict");
+    if !context.is_template(&strict_name) {
+        return None;
+    }
+    let file_id = meta.file_id()?;
+    let call = context.underlying_str(&file_id, &meta.file_location()).ok()?;
+    if !call.starts_with(name) || !call.ends_with(')') {
+        return None;
+    }
+    Some(Fix::new(
+        &format!("Replace `{name}` with `{strict_name}`."),
+        vec![TextEdit::new(file_id, meta.file_location(), &format!("{strict_name}()"))],
+    ))
+}
+
+fn build_num2bits(meta: &Meta, is_bn254: bool, fix: Option<Fix>) -> Report {
     NonStrictBinaryConversionWarning::Num2Bits {
         file_id: meta.file_id(),
         location: meta.file_location(),
+        is_bn254,
+        fix,
     }
     .into_report()
 }
 
-fn build_bits2num(meta: &Meta) -> Report {
+fn build_bits2num(meta: &Meta, is_bn254: bool, fix: Option<Fix>) -> Report {
     NonStrictBinaryConversionWarning::Bits2Num {
         file_id: meta.file_id(),
         location: meta.file_location(),
+        is_bn254,
+        fix,
     }
     .into_report()
 }
@@ -147,6 +227,8 @@ mod tests {
     use parser::parse_definition;
     use program_structure::{cfg::IntoCfg, constants::Curve};
 
+    use crate::analysis_runner::AnalysisRunner;
+
     use super::*;
 
     #[test]
@@ -181,6 +263,72 @@ mod tests {
         validate_reports(src, 0);
     }
 
+    #[test]
+    fn test_prime_size() {
+        let num2bits = r#"
+            template Num2Bits(n) {
+                signal input in;
+                signal output out[n];
+            }
+        "#;
+        let num2bits_strict = r#"
+            template Num2Bits_strict() {
+                signal input in;
+                signal output out[254];
+            }
+        "#;
+        let src = r#"
+            template T() {
+                signal input in;
+                component safe = Num2Bits(63);
+                safe.in <== in;
+                component unsafe = Num2Bits(64);
+                unsafe.in <== in;
+            }
+        "#;
+        // The strict template is BN254 specific, so no fix is suggested.
+        let runner =
+            AnalysisRunner::new(Curve::Goldilocks).with_src(&[num2bits, num2bits_strict, src]);
+        let cfg = runner.template("T").unwrap();
+        let reports = find_nonstrict_binary_conversion(&runner, &cfg);
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].fix().is_none());
+    }
+
+    #[test]
+    fn test_strict_conversion_fix() {
+        let num2bits = r#"
+            template Num2Bits(n) {
+                signal input in;
+                signal output out[n];
+            }
+        "#;
+        let num2bits_strict = r#"
+            template Num2Bits_strict() {
+                signal input in;
+                signal output out[254];
+            }
+        "#;
+        let src = r#"
+            template T(n) {
+                signal input in;
+                component fixed = Num2Bits(254);
+                fixed.in <== in;
+                component generic = Num2Bits(n);
+                generic.in <== in;
+            }
+        "#;
+        let runner = AnalysisRunner::new(Curve::Bn254).with_src(&[num2bits, num2bits_strict, src]);
+        let cfg = runner.template("T").unwrap();
+        let reports = find_nonstrict_binary_conversion(&runner, &cfg);
+        assert_eq!(reports.len(), 2);
+        let fixes = reports.iter().filter_map(Report::fix).collect::<Vec<_>>();
+        assert_eq!(fixes.len(), 1);
+        let edit = &fixes[0].edits()[0];
+        assert_eq!(runner.underlying_str(&edit.file_id, &edit.location).unwrap(), "Num2Bits(254)");
+        assert_eq!(edit.replacement, "Num2Bits_strict()");
+    }
+
     fn validate_reports(src: &str, expected_len: usize) {
         // Build CFG.
         let mut reports = ReportCollection::new();
@@ -193,7 +341,7 @@ mod tests {
         assert!(reports.is_empty());
 
         // Generate report collection.
-        let reports = find_nonstrict_binary_conversion(&cfg);
+        let reports = find_nonstrict_binary_conversion(&AnalysisRunner::new(Curve::Bn254), &cfg);
 
         assert_eq!(reports.len(), expected_len);
     }
@@ -0,0 +1,209 @@
+use std::fmt;
+use thiserror::Error;
+
+use program_structure::cfg::Cfg;
+use program_structure::report::ReportCollection;
+use program_structure::report_code::ReportCode;
+
+use crate::analysis_context::AnalysisContext;
+use crate::AnalysisPass;
+
+/// Errors returned when selecting analysis passes.
+#[derive(Debug, Error)]
+pub enum PassRegistryError {
+    /// The name does not match a pass, or a report emitted by a pass.
+    #[error("Unknown analysis pass `{name}`.")]
+    UnknownPass { name: String },
+}
+
+/// Intra-process passes analyze each template or function in isolation.
+/// Inter-process passes may also inspect the templates and functions used by
+/// the analyzed template or function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PassKind {
+    IntraProcess,
+    InterProcess,
+}
+
+impl fmt::Display for PassKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PassKind::IntraProcess => write!(f, "intra-process"),
+            PassKind::InterProcess => write!(f, "inter-process"),
+        }
+    }
+}
+
+/// An analysis pass together with the metadata used to select and describe
+/// the pass.
+pub struct RegisteredPass {
+    name: String,
+    kind: PassKind,
+    codes: Vec<ReportCode>,
+    description: String,
+    enabled_by_default: bool,
+    enabled: bool,
+    pass: Box<AnalysisPass>,
+}
+
+impl RegisteredPass {
+    /// Creates a new pass which is enabled by default.
+    pub fn new(
+        name: &str,
+        kind: PassKind,
+        codes: &[ReportCode],
+        description: &str,
+        pass: impl Fn(&dyn AnalysisContext, &Cfg) -> ReportCollection + Send + Sync + 'static,
+    ) -> RegisteredPass {
+        RegisteredPass {
+            name: name.to_string(),
+            kind,
+            codes: codes.to_vec(),
+            description: description.to_string(),
+            enabled_by_default: true,
+            enabled: true,
+            pass: Box::new(pass),
+        }
+    }
+
+    /// Only run the pass if it is explicitly selected.
+    #[must_use]
+    pub fn disabled_by_default(mut self) -> RegisteredPass {
+        self.enabled_by_default = false;
+        self.enabled = false;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> PassKind {
+        self.kind
+    }
+
+    /// Returns the report codes emitted by the pass.
+    pub fn codes(&self) -> &[ReportCode] {
+        &self.codes
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn is_enabled_by_default(&self) -> bool {
+        self.enabled_by_default
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns true if the given name is the name of the pass, or the ID or
+    /// name of a report emitted by the pass.
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name
+            || self
+                .codes
+                .iter()
+                .any(|code| code.id().eq_ignore_ascii_case(name) || code.name() == name)
+    }
+
+    /// Runs the pass on the given CFG.
+    pub fn run(&self, context: &dyn AnalysisContext, cfg: &Cfg) -> ReportCollection {
+        (self.pass)(context, cfg)
+    }
+}
+
+/// The set of analysis passes run by the analysis runner. The default registry
+/// contains all built-in passes.
+pub struct PassRegistry {
+    passes: Vec<RegisteredPass>,
+}
+
+impl Default for PassRegistry {
+    fn default() -> PassRegistry {
+        crate::get_analysis_passes()
+    }
+}
+
+impl PassRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> PassRegistry {
+        PassRegistry { passes: Vec::new() }
+    }
+
+    /// Adds a pass to the registry. Passes are run in the order they are
+    /// registered.
+    pub fn register(&mut self, pass: RegisteredPass) {
+        self.passes.push(pass);
+    }
+
+    /// Returns all registered passes.
+    pub fn iter(&self) -> impl Iterator<Item = &RegisteredPass> {
+        self.passes.iter()
+    }
+
+    /// Returns the passes that should be run.
+    pub fn enabled(&self) -> impl Iterator<Item = &RegisteredPass> {
+        self.passes.iter().filter(|pass| pass.is_enabled())
+    }
+
+    /// Returns the pass with the given name, if it exists.
+    pub fn get(&self, name: &str) -> Option<&RegisteredPass> {
+        self.passes.iter().find(|pass| pass.name() == name)
+    }
+
+    /// Selects the passes to run. If `only` is non-empty, only the given passes
+    /// are enabled. Otherwise, passes enabled by default are enabled. Passes
+    /// in `disable` are then disabled. Passes may be given either by name or by
+    /// the ID or name of a report emitted by the pass.
+    pub fn select(&mut self, only: &[String], disable: &[String]) -> Result<(), PassRegistryError> {
+        for name in only.iter().chain(disable) {
+            if !self.passes.iter().any(|pass| pass.matches(name)) {
+                return Err(PassRegistryError::UnknownPass { name: name.clone() });
+            }
+        }
+        for pass in &mut self.passes {
+            pass.enabled = if only.is_empty() {
+                pass.enabled_by_default
+            } else {
+                only.iter().any(|name| pass.matches(name))
+            };
+            if disable.iter().any(|name| pass.matches(name)) {
+                pass.enabled = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select() {
+        let mut registry = PassRegistry::default();
+        let nof_passes = registry.iter().count();
+        assert_eq!(registry.enabled().count(), nof_passes);
+
+        // Passes may be selected by name or by report ID.
+        registry.select(&["field-overflow".to_string(), "cs0014".to_string()], &[]).unwrap();
+        let enabled = registry.enabled().map(RegisteredPass::name).collect::<Vec<_>>();
+        assert_eq!(enabled, vec!["field-overflow", "unconstrained-less-than"]);
+
+        // Passes may be disabled by name or by report name.
+        registry
+            .select(&[], &["unused-variable-value".to_string(), "bitwise-complement".to_string()])
+            .unwrap();
+        assert_eq!(registry.enabled().count(), nof_passes - 2);
+        assert!(!registry.get("side-effects").unwrap().is_enabled());
+
+        // Unknown passes are rejected.
+        assert!(matches!(
+            registry.select(&["unknown-pass".to_string()], &[]),
+            Err(PassRegistryError::UnknownPass { .. })
+        ));
+    }
+}
@@ -0,0 +1,342 @@
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+use std::collections::{BTreeMap, BTreeSet};
+
+use circom_algebra::modular_arithmetic;
+
+/// The maximum number of terms of a polynomial. Operations producing larger
+/// polynomials fail, to avoid blowing up the size of the constraint system.
+const MAX_TERMS: usize = 1024;
+
+/// The maximum exponent supported when raising a non-constant polynomial to a
+/// power.
+const MAX_EXPONENT: usize = 64;
+
+/// Variables are identified by their index in the constraint system.
+pub type Variable = usize;
+
+/// A monomial given by a list of variables and (positive) exponents, sorted by
+/// variable.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Monomial(Vec<(Variable, u32)>);
+
+impl Monomial {
+    #[must_use]
+    pub fn one() -> Monomial {
+        Monomial(Vec::new())
+    }
+
+    #[must_use]
+    pub fn variable(var: Variable) -> Monomial {
+        Monomial(vec![(var, 1)])
+    }
+
+    #[must_use]
+    pub fn is_one(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the total degree of the monomial.
+    #[must_use]
+    pub fn degree(&self) -> u32 {
+        self.0.iter().map(|(_, exp)| exp).sum()
+    }
+
+    /// Returns the degree of the given variable in the monomial.
+    #[must_use]
+    pub fn degree_in(&self, var: Variable) -> u32 {
+        self.0.iter().find(|(other, _)| *other == var).map(|(_, exp)| *exp).unwrap_or_default()
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = Variable> + '_ {
+        self.0.iter().map(|(var, _)| *var)
+    }
+
+    /// Returns the monomial with the given variable removed.
+    #[must_use]
+    pub fn without(&self, var: Variable) -> Monomial {
+        Monomial(self.0.iter().filter(|(other, _)| *other != var).cloned().collect())
+    }
+
+    #[must_use]
+    pub fn mul(&self, other: &Monomial) -> Monomial {
+        let mut result = BTreeMap::new();
+        for (var, exp) in self.0.iter().chain(other.0.iter()) {
+            *result.entry(*var).or_insert(0) += exp;
+        }
+        Monomial(result.into_iter().collect())
+    }
+
+    /// Formats the monomial using the given variable names.
+    pub fn format<F: Fn(Variable) -> String>(&self, names: &F) -> String {
+        self.0
+            .iter()
+            .map(
+                |(var, exp)| if *exp == 1 { names(*var) } else { format!("{}^{exp}", names(*var)) },
+            )
+            .collect::<Vec<_>>()
+            .join("*")
+    }
+}
+
+/// A multivariate polynomial over the prime field. Coefficients are reduced
+/// and zero coefficients are never stored.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Polynomial {
+    terms: BTreeMap<Monomial, BigInt>,
+}
+
+impl Polynomial {
+    #[must_use]
+    pub fn zero() -> Polynomial {
+        Polynomial::default()
+    }
+
+    #[must_use]
+    pub fn constant(value: &BigInt, prime: &BigInt) -> Polynomial {
+        let mut result = Polynomial::zero();
+        result.add_term(Monomial::one(), value.clone(), prime);
+        result
+    }
+
+    #[must_use]
+    pub fn variable(var: Variable) -> Polynomial {
+        let mut terms = BTreeMap::new();
+        terms.insert(Monomial::variable(var), BigInt::one());
+        Polynomial { terms }
+    }
+
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Returns the value of the polynomial if it is constant.
+    #[must_use]
+    pub fn as_constant(&self) -> Option<BigInt> {
+        match self.terms.len() {
+            0 => Some(BigInt::zero()),
+            1 => self.terms.get(&Monomial::one()).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of (non-zero) terms of the polynomial.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn terms(&self) -> impl Iterator<Item = (&Monomial, &BigInt)> {
+        self.terms.iter()
+    }
+
+    /// Returns the total degree of the polynomial.
+    #[must_use]
+    pub fn degree(&self) -> u32 {
+        self.terms.keys().map(Monomial::degree).max().unwrap_or_default()
+    }
+
+    /// Returns the degree of the given variable in the polynomial.
+    #[must_use]
+    pub fn degree_in(&self, var: Variable) -> u32 {
+        self.terms.keys().map(|monomial| monomial.degree_in(var)).max().unwrap_or_default()
+    }
+
+    /// Returns the set of variables occurring in the polynomial.
+    #[must_use]
+    pub fn variables(&self) -> BTreeSet<Variable> {
+        self.terms.keys().flat_map(Monomial::variables).collect()
+    }
+
+    fn add_term(&mut self, monomial: Monomial, coefficient: BigInt, prime: &BigInt) {
+        let coefficient = match self.terms.remove(&monomial) {
+            Some(value) => modular_arithmetic::add(&value, &coefficient, prime),
+            None => modular_arithmetic::add(&BigInt::zero(), &coefficient, prime),
+        };
+        if !coefficient.is_zero() {
+            self.terms.insert(monomial, coefficient);
+        }
+    }
+
+    #[must_use]
+    pub fn add(&self, other: &Polynomial, prime: &BigInt) -> Polynomial {
+        let mut result = self.clone();
+        for (monomial, coefficient) in other.terms() {
+            result.add_term(monomial.clone(), coefficient.clone(), prime);
+        }
+        result
+    }
+
+    #[must_use]
+    pub fn sub(&self, other: &Polynomial, prime: &BigInt) -> Polynomial {
+        self.add(&other.neg(prime), prime)
+    }
+
+    #[must_use]
+    pub fn neg(&self, prime: &BigInt) -> Polynomial {
+        self.scale(&(prime - BigInt::one()), prime)
+    }
+
+    #[must_use]
+    pub fn scale(&self, factor: &BigInt, prime: &BigInt) -> Polynomial {
+        let mut result = Polynomial::zero();
+        for (monomial, coefficient) in self.terms() {
+            result.add_term(monomial.clone(), coefficient * factor, prime);
+        }
+        result
+    }
+
+    /// Multiplies the two polynomials. Returns `None` if the result is too
+    /// large.
+    #[must_use]
+    pub fn mul(&self, other: &Polynomial, prime: &BigInt) -> Option<Polynomial> {
+        if self.len() * other.len() > MAX_TERMS {
+            return None;
+        }
+        let mut result = Polynomial::zero();
+        for (lhm, lhc) in self.terms() {
+            for (rhm, rhc) in other.terms() {
+                result.add_term(lhm.mul(rhm), lhc * rhc, prime);
+            }
+        }
+        Some(result)
+    }
+
+    /// Raises the polynomial to the given power. Returns `None` if the result
+    /// is too large.
+    #[must_use]
+    pub fn pow(&self, exp: usize, prime: &BigInt) -> Option<Polynomial> {
+        if let Some(value) = self.as_constant() {
+            return Some(Polynomial::constant(
+                &modular_arithmetic::pow(&value, &BigInt::from(exp), prime),
+                prime,
+            ));
+        }
+        if exp > MAX_EXPONENT {
+            return None;
+        }
+        let mut result = Polynomial::constant(&BigInt::one(), prime);
+        for _ in 0..exp {
+            result = result.mul(self, prime)?;
+        }
+        Some(result)
+    }
+
+    /// If the polynomial is at most linear in the given variable, returns the
+    /// pair `(a, b)` such that the polynomial is given by `a * var + b`.
+    #[must_use]
+    pub fn split(&self, var: Variable) -> Option<(Polynomial, Polynomial)> {
+        if self.degree_in(var) > 1 {
+            return None;
+        }
+        let mut coefficient = Polynomial::zero();
+        let mut remainder = Polynomial::zero();
+        for (monomial, value) in self.terms() {
+            if monomial.degree_in(var) == 1 {
+                coefficient.terms.insert(monomial.without(var), value.clone());
+            } else {
+                remainder.terms.insert(monomial.clone(), value.clone());
+            }
+        }
+        Some((coefficient, remainder))
+    }
+
+    /// Returns the polynomial obtained by setting the given variable to zero.
+    #[must_use]
+    pub fn set_to_zero(&self, var: Variable) -> Polynomial {
+        let terms = self
+            .terms()
+            .filter(|(monomial, _)| monomial.degree_in(var) == 0)
+            .map(|(monomial, value)| (monomial.clone(), value.clone()))
+            .collect();
+        Polynomial { terms }
+    }
+
+    /// Formats the polynomial using the given variable names. Coefficients
+    /// larger than `p/2` are displayed as negative numbers.
+    pub fn format<F: Fn(Variable) -> String>(&self, names: &F, prime: &BigInt) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        let half = prime / BigInt::from(2);
+        // Display terms in order of decreasing degree.
+        let mut terms = self.terms().collect::<Vec<_>>();
+        terms.sort_by(|(lhm, _), (rhm, _)| rhm.degree().cmp(&lhm.degree()).then(lhm.cmp(rhm)));
+
+        let mut result = String::new();
+        for (index, (monomial, value)) in terms.into_iter().enumerate() {
+            let (negative, value) =
+                if value > &half { (true, prime - value) } else { (false, value.clone()) };
+            match (index, negative) {
+                (0, true) => result.push('-'),
+                (0, false) => {}
+                (_, true) => result.push_str(" - "),
+                (_, false) => result.push_str(" + "),
+            }
+            if monomial.is_one() {
+                result.push_str(&value.to_string());
+            } else if value.is_one() {
+                result.push_str(&monomial.format(names));
+            } else {
+                result.push_str(&format!("{value}*{}", monomial.format(names)));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polynomial_arithmetic() {
+        let prime = BigInt::from(17);
+        let x = Polynomial::variable(0);
+        let y = Polynomial::variable(1);
+        let one = Polynomial::constant(&BigInt::from(1), &prime);
+
+        // (x + 1) * (x - 1) = x^2 - 1
+        let lhs = x.add(&one, &prime).mul(&x.sub(&one, &prime), &prime).unwrap();
+        let rhs = x.pow(2, &prime).unwrap().sub(&one, &prime);
+        assert!(lhs == rhs);
+        assert_eq!(lhs.degree(), 2);
+        assert_eq!(lhs.len(), 2);
+
+        // x * y + x - x = x * y
+        let result = x.mul(&y, &prime).unwrap().add(&x, &prime).sub(&x, &prime);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.variables(), BTreeSet::from([0, 1]));
+        assert!(result.set_to_zero(0).is_zero());
+
+        // 17 = 0 mod 17
+        assert!(Polynomial::constant(&BigInt::from(17), &prime).is_zero());
+        assert_eq!(x.sub(&x, &prime).as_constant(), Some(BigInt::from(0)));
+    }
+
+    #[test]
+    fn test_polynomial_split() {
+        let prime = BigInt::from(17);
+        let x = Polynomial::variable(0);
+        let y = Polynomial::variable(1);
+        let one = Polynomial::constant(&BigInt::from(1), &prime);
+
+        // x * y + y + 1 = (x + 1) * y + 1
+        let p = x.mul(&y, &prime).unwrap().add(&y, &prime).add(&one, &prime);
+        let (a, b) = p.split(1).unwrap();
+        assert!(a == x.add(&one, &prime));
+        assert!(b == one);
+
+        // x^2 is not linear in x.
+        assert!(x.pow(2, &prime).unwrap().split(0).is_none());
+
+        let names = |var: Variable| ["x", "y"][var].to_string();
+        assert_eq!(p.neg(&prime).format(&names, &prime), "-x*y - y - 1");
+    }
+}
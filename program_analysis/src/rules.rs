@@ -0,0 +1,518 @@
+//! Custom rules loaded from TOML files. Each rule describes a pattern in the
+//! IR, and generates a report for each statement or expression matching the
+//! pattern. A rule file contains one or more rules on the following form.
+//!
+//! ```toml
+//! [[rule]]
+//! name = "num2bits-too-large"
+//! message = "`Num2Bits` must not be instantiated with more than 252 bits."
+//! severity = "error"
+//! call = "Num2Bits"
+//! arguments = [{ index = 0, greater-than = 252 }]
+//! ```
+//!
+//! All criteria given by a rule must match for the rule to match. The
+//! supported criteria are
+//!
+//!   - `statement`: the kind of statement (`declaration`, `if`, `return`,
+//!     `substitution`, `constraint`, `log`, or `assert`),
+//!   - `op`: the assignment operator of a substitution (`=`, `<--`, or `<==`),
+//!   - `call`: the name of a template or function called by the statement,
+//!   - `arguments`: conditions on the constant arguments passed to `call`,
+//!   - `operator`: an infix operator (e.g. `/` or `<<`) used by the statement,
+//!   - `flows-to-constraint`: whether the value assigned by a substitution
+//!     flows into a constraint.
+use log::debug;
+use num_bigint::BigInt;
+use serde_derive::Deserialize;
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+use program_structure::cfg::Cfg;
+use program_structure::ir::value_meta::{ValueMeta, ValueReduction};
+use program_structure::ir::variable_meta::VariableMeta;
+use program_structure::ir::*;
+use program_structure::report::{MessageCategory, Report, ReportCollection};
+use program_structure::report_code::ReportCode;
+
+use crate::analysis_context::AnalysisContext;
+use crate::pass_registry::{PassKind, PassRegistry, RegisteredPass};
+use crate::taint_analysis::TaintAnalysis;
+
+const STATEMENT_KINDS: [&str; 7] =
+    ["declaration", "if", "return", "substitution", "constraint", "log", "assert"];
+
+const ASSIGN_OPS: [&str; 3] = ["=", "<--", "<=="];
+
+const INFIX_OPERATORS: [&str; 20] = [
+    "*", "/", "+", "-", "**", "\\", "%", "<<", ">>", "<=", ">=", "<", ">", "==", "!=", "||", "&&",
+    "|", "&", "^",
+];
+
+/// Errors returned when loading rules.
+#[derive(Debug, Error)]
+pub enum RuleError {
+    /// The rule file could not be read.
+    #[error("Failed to read rules: {0}")]
+    Io(io::Error),
+    /// The rule file is not a valid TOML file.
+    #[error("Failed to parse rules: {0}")]
+    Parse(toml::de::Error),
+    /// The rule is not valid.
+    #[error("Invalid rule `{name}`: {reason}")]
+    InvalidRule { name: String, reason: String },
+    /// The rule has the same name as an existing analysis pass.
+    #[error("The rule `{name}` has the same name as an existing analysis pass.")]
+    DuplicateRule { name: String },
+}
+
+/// A rule file as it is represented on disk.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    #[serde(default)]
+    rule: Vec<Rule>,
+}
+
+/// A condition on a constant call argument.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ArgumentCondition {
+    /// The index of the argument.
+    pub index: usize,
+    pub equals: Option<i64>,
+    pub greater_than: Option<i64>,
+    pub less_than: Option<i64>,
+}
+
+impl ArgumentCondition {
+    /// Returns true if the argument is constant and satisfies the condition.
+    fn matches(&self, args: &[Expression]) -> bool {
+        let value = match args.get(self.index).and_then(|arg| arg.value()) {
+            Some(ValueReduction::FieldElement { value }) => value,
+            _ => return false,
+        };
+        self.equals.map_or(true, |bound| value == &BigInt::from(bound))
+            && self.greater_than.map_or(true, |bound| value > &BigInt::from(bound))
+            && self.less_than.map_or(true, |bound| value < &BigInt::from(bound))
+    }
+}
+
+/// A custom rule.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Rule {
+    /// The name of the rule. This is also the name of the corresponding pass.
+    pub name: String,
+    /// The message of reports generated by the rule.
+    pub message: String,
+    /// The severity (`info`, `warning`, or `error`) of reports generated by
+    /// the rule. Defaults to `warning`.
+    pub severity: Option<String>,
+    pub statement: Option<String>,
+    pub op: Option<String>,
+    pub call: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<ArgumentCondition>,
+    pub operator: Option<String>,
+    pub flows_to_constraint: Option<bool>,
+}
+
+impl Rule {
+    fn validate(&self) -> Result<(), RuleError> {
+        let invalid = |reason: String| RuleError::InvalidRule { name: self.name.clone(), reason };
+        if let Some(severity) = &self.severity {
+            MessageCategory::from_str(severity).map_err(|error| invalid(error.to_string()))?;
+        }
+        if let Some(statement) = &self.statement {
+            if !STATEMENT_KINDS.contains(&statement.as_str()) {
+                return Err(invalid(format!("unknown statement kind '{statement}'")));
+            }
+        }
+        if let Some(op) = &self.op {
+            if !ASSIGN_OPS.contains(&op.as_str()) {
+                return Err(invalid(format!("unknown assignment operator '{op}'")));
+            }
+        }
+        if let Some(operator) = &self.operator {
+            if !INFIX_OPERATORS.contains(&operator.as_str()) {
+                return Err(invalid(format!("unknown operator '{operator}'")));
+            }
+        }
+        if !self.arguments.is_empty() && self.call.is_none() {
+            return Err(invalid("`arguments` requires `call`".to_string()));
+        }
+        Ok(())
+    }
+
+    fn category(&self) -> MessageCategory {
+        self.severity
+            .as_deref()
+            .and_then(|severity| MessageCategory::from_str(severity).ok())
+            .unwrap_or(MessageCategory::Warning)
+    }
+
+    /// Returns the reports generated by the rule for the given CFG.
+    pub fn check(&self, context: &dyn AnalysisContext, cfg: &Cfg) -> ReportCollection {
+        debug!("running custom rule `{}`", self.name);
+        let mut reports = ReportCollection::new();
+        for basic_block in cfg.iter() {
+            for stmt in basic_block.iter() {
+                if let Some(meta) = self.match_statement(context, cfg, stmt) {
+                    reports.push(self.build_report(meta));
+                }
+            }
+        }
+        debug!("{} new reports generated", reports.len());
+        reports
+    }
+
+    /// Returns the metadata of the matching statement or expression if the
+    /// statement matches the rule.
+    fn match_statement<'a>(
+        &self,
+        context: &dyn AnalysisContext,
+        cfg: &Cfg,
+        stmt: &'a Statement,
+    ) -> Option<&'a Meta> {
+        use Statement::*;
+        if let Some(statement) = &self.statement {
+            let kind = match stmt {
+                Declaration { .. } => "declaration",
+                IfThenElse { .. } => "if",
+                Return { .. } => "return",
+                Substitution { .. } => "substitution",
+                ConstraintEquality { .. } => "constraint",
+                LogCall { .. } => "log",
+                Assert { .. } => "assert",
+            };
+            if kind != statement {
+                return None;
+            }
+        }
+        if let Some(op) = &self.op {
+            match stmt {
+                Substitution { op: stmt_op, .. } if &stmt_op.to_string() == op => {}
+                _ => return None,
+            }
+        }
+        if let Some(flows_to_constraint) = self.flows_to_constraint {
+            let Substitution { var, .. } = stmt else {
+                return None;
+            };
+            let taint_analysis = context.analysis::<TaintAnalysis>(cfg);
+            if self::flows_to_constraint(cfg, &taint_analysis, var) != flows_to_constraint {
+                return None;
+            }
+        }
+
+        // Find the sub-expression matching the rule (if any).
+        let mut result = stmt.meta();
+        if let Some(name) = &self.call {
+            let mut calls = Vec::new();
+            visit_statement(stmt, &mut |expr| {
+                if let Expression::Call { meta, name: call_name, args } = expr {
+                    if call_name == name && self.arguments.iter().all(|arg| arg.matches(args)) {
+                        calls.push(meta);
+                    }
+                }
+            });
+            result = calls.first()?;
+        }
+        if let Some(operator) = &self.operator {
+            let mut exprs = Vec::new();
+            visit_statement(stmt, &mut |expr| {
+                if let Expression::InfixOp { meta, infix_op, .. } = expr {
+                    if &infix_op.to_string() == operator {
+                        exprs.push(meta);
+                    }
+                }
+            });
+            // Prefer the call location if both `call` and `operator` are given.
+            if self.call.is_none() {
+                result = exprs.first()?;
+            } else if exprs.is_empty() {
+                return None;
+            }
+        }
+        Some(result)
+    }
+
+    fn build_report(&self, meta: &Meta) -> Report {
+        let mut report = Report::warning(self.message.clone(), ReportCode::CustomRule);
+        report.set_category(self.category());
+        if let Some(file_id) = meta.file_id {
+            report.add_primary(
+                meta.file_location(),
+                file_id,
+                format!("This matches the rule `{}`.", self.name),
+            );
+        }
+        report
+    }
+}
+
+/// Returns true if the given variable flows into a constraint which does not
+/// only update the variable itself (e.g. a constraint assigning an input
+/// signal of a component).
+fn flows_to_constraint(cfg: &Cfg, taint_analysis: &TaintAnalysis, var: &VariableName) -> bool {
+    use AssignOp::*;
+    use Statement::*;
+    let tainted = taint_analysis.multi_step_taint(var);
+    cfg.iter().flat_map(|basic_block| basic_block.iter()).any(|stmt| {
+        if !matches!(
+            stmt,
+            Substitution { op: AssignConstraintSignal, .. } | ConstraintEquality { .. }
+        ) {
+            return false;
+        }
+        let written = stmt.variables_written().map(|var| var.name()).collect::<HashSet<_>>();
+        stmt.variables_read()
+            .map(|var| var.name())
+            .any(|name| !written.contains(name) && tainted.contains(name))
+    })
+}
+
+fn visit_statement<'a, F: FnMut(&'a Expression)>(stmt: &'a Statement, visit: &mut F) {
+    use Statement::*;
+    match stmt {
+        Declaration { dimensions, .. } => {
+            for size in dimensions {
+                visit_expression(size, visit);
+            }
+        }
+        LogCall { args, .. } => {
+            for arg in args {
+                if let LogArgument::Expr(value) = arg {
+                    visit_expression(value, visit);
+                }
+            }
+        }
+        IfThenElse { cond, .. } => visit_expression(cond, visit),
+        Substitution { rhe, .. } => visit_expression(rhe, visit),
+        Return { value, .. } => visit_expression(value, visit),
+        Assert { arg, .. } => visit_expression(arg, visit),
+        ConstraintEquality { lhe, rhe, .. } => {
+            visit_expression(lhe, visit);
+            visit_expression(rhe, visit);
+        }
+    }
+}
+
+fn visit_expression<'a, F: FnMut(&'a Expression)>(expr: &'a Expression, visit: &mut F) {
+    use Expression::*;
+    visit(expr);
+    match expr {
+        InfixOp { lhe, rhe, .. } => {
+            visit_expression(lhe, visit);
+            visit_expression(rhe, visit);
+        }
+        PrefixOp { rhe, .. } => visit_expression(rhe, visit),
+        SwitchOp { cond, if_true, if_false, .. } => {
+            visit_expression(cond, visit);
+            visit_expression(if_true, visit);
+            visit_expression(if_false, visit);
+        }
+        Call { args, .. } => {
+            for arg in args {
+                visit_expression(arg, visit);
+            }
+        }
+        InlineArray { values, .. } => {
+            for value in values {
+                visit_expression(value, visit);
+            }
+        }
+        Access { access, .. } => {
+            for access in access {
+                if let AccessType::ArrayAccess(index) = access {
+                    visit_expression(index, visit);
+                }
+            }
+        }
+        Update { access, rhe, .. } => {
+            for access in access {
+                if let AccessType::ArrayAccess(index) = access {
+                    visit_expression(index, visit);
+                }
+            }
+            visit_expression(rhe, visit);
+        }
+        Number(_, _) | Variable { .. } | Phi { .. } => {}
+    }
+}
+
+/// A set of custom rules.
+#[derive(Clone, Debug, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// Reads the rules in the given file.
+    pub fn from_file(path: &Path) -> Result<RuleSet, RuleError> {
+        RuleSet::from_str(&std::fs::read_to_string(path).map_err(RuleError::Io)?)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Rule> {
+        self.rules.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Adds the rules from another rule set.
+    pub fn extend(&mut self, other: RuleSet) {
+        self.rules.extend(other.rules);
+    }
+
+    /// Registers each rule as an (intra-process) analysis pass with the given
+    /// registry. Rules are run on both generic templates and template
+    /// instances, so argument conditions on template parameters are checked
+    /// once the parameters are known.
+    pub fn register(self, registry: &mut PassRegistry) -> Result<(), RuleError> {
+        for rule in self.rules {
+            if registry.get(&rule.name).is_some() {
+                return Err(RuleError::DuplicateRule { name: rule.name });
+            }
+            let name = rule.name.clone();
+            let message = rule.message.clone();
+            registry.register(RegisteredPass::new(
+                &name,
+                PassKind::IntraProcess,
+                &[ReportCode::CustomRule],
+                &message,
+                move |context, cfg| rule.check(context, cfg),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = RuleError;
+
+    fn from_str(contents: &str) -> Result<RuleSet, RuleError> {
+        let rule_file: RuleFile = toml::from_str(contents).map_err(RuleError::Parse)?;
+        for rule in &rule_file.rule {
+            rule.validate()?;
+        }
+        Ok(RuleSet { rules: rule_file.rule })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use program_structure::constants::Curve;
+
+    use crate::analysis_runner::AnalysisRunner;
+
+    use super::*;
+
+    #[test]
+    fn test_call_arguments() {
+        let rules = r#"
+            [[rule]]
+            name = "num2bits-too-large"
+            message = "`Num2Bits` must not be instantiated with more than 252 bits."
+            severity = "error"
+            call = "Num2Bits"
+            arguments = [{ index = 0, greater-than = 252 }]
+        "#;
+        let src = r#"
+            template T() {
+                component small = Num2Bits(252);
+                component large = Num2Bits(254);
+            }
+        "#;
+        let reports = validate_reports(rules, src, 1);
+        assert!(matches!(reports[0].category(), MessageCategory::Error));
+    }
+
+    #[test]
+    fn test_statement_and_operator() {
+        let rules = r#"
+            [[rule]]
+            name = "no-signal-division"
+            message = "Do not assign the result of a division to a signal."
+            op = "<--"
+            operator = "/"
+        "#;
+        let src = r#"
+            template T() {
+                signal input a;
+                signal input b;
+                signal output c;
+                signal output d;
+
+                c <-- a / b;
+                d <-- a * b;
+                c * b === a;
+            }
+        "#;
+        validate_reports(rules, src, 1);
+    }
+
+    #[test]
+    fn test_flows_to_constraint() {
+        let rules = r#"
+            [[rule]]
+            name = "unconstrained-poseidon-output"
+            message = "The output of `Poseidon` must flow into a constraint."
+            call = "Poseidon"
+            flows-to-constraint = false
+        "#;
+        let src = r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                component used = Poseidon(1);
+                used.inputs[0] <== in;
+                out <== used.out;
+
+                component unused = Poseidon(1);
+                unused.inputs[0] <== in;
+            }
+        "#;
+        validate_reports(rules, src, 1);
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let rules = r#"
+            [[rule]]
+            name = "invalid-operator"
+            message = "This rule is invalid."
+            operator = "<=="
+        "#;
+        assert!(matches!(RuleSet::from_str(rules), Err(RuleError::InvalidRule { .. })));
+
+        let rules = r#"
+            [[rule]]
+            name = "under-constrained-signals"
+            message = "This rule has the same name as an existing pass."
+        "#;
+        let mut registry = PassRegistry::default();
+        let result = RuleSet::from_str(rules).unwrap().register(&mut registry);
+        assert!(matches!(result, Err(RuleError::DuplicateRule { .. })));
+    }
+
+    fn validate_reports(rules: &str, src: &str, expected_len: usize) -> ReportCollection {
+        let runner = AnalysisRunner::new(Curve::default()).with_src(&[src]);
+        let cfg = runner.template("T").unwrap();
+        let rules = RuleSet::from_str(rules).unwrap();
+        let reports =
+            rules.iter().flat_map(|rule| rule.check(&runner, &cfg)).collect::<ReportCollection>();
+        assert_eq!(reports.len(), expected_len);
+        reports
+    }
+}
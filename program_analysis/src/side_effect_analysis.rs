@@ -3,18 +3,23 @@ use std::fmt::Write;
 use std::collections::{HashMap, HashSet};
 
 use program_structure::cfg::{Cfg, DefinitionType};
+use program_structure::fix::{Fix, TextEdit};
 use program_structure::report_code::ReportCode;
 use program_structure::report::{Report, ReportCollection};
 use program_structure::file_definition::{FileID, FileLocation};
 use program_structure::ir::declarations::Declaration;
 use program_structure::ir::variable_meta::{VariableMeta, VariableUse};
-use program_structure::ir::{Expression, SignalType, Statement, VariableType};
+use program_structure::ir::{
+    AccessType, AssignOp, Expression, Meta, SignalType, Statement, VariableType,
+};
 
-use crate::constraint_analysis::run_constraint_analysis;
-use crate::taint_analysis::run_taint_analysis;
+use crate::analysis_context::AnalysisContext;
+use crate::constraint_analysis::ConstraintAnalysis;
+use crate::taint_analysis::TaintAnalysis;
 
 pub struct UnusedVariableWarning {
     var: VariableUse,
+    fix: Option<Fix>,
 }
 
 impl UnusedVariableWarning {
@@ -33,6 +38,9 @@ impl UnusedVariableWarning {
                 format!("The value assigned to `{}` here is never read.", self.var),
             );
         }
+        if let Some(fix) = self.fix {
+            report.set_fix(fix);
+        }
         report
     }
 }
@@ -225,12 +233,12 @@ impl ParamWithoutSideEffectsWarning {
 ///
 /// are side-effect free and do not affect either witness or constraint
 /// generation.
-pub fn run_side_effect_analysis(cfg: &Cfg) -> ReportCollection {
+pub fn run_side_effect_analysis(context: &dyn AnalysisContext, cfg: &Cfg) -> ReportCollection {
     debug!("running side-effect analysis pass");
 
-    // 1. Run taint and constraint analysis to be able to track data flow.
-    let taint_analysis = run_taint_analysis(cfg);
-    let constraint_analysis = run_constraint_analysis(cfg);
+    // 1. Use taint and constraint analysis to be able to track data flow.
+    let taint_analysis = context.analysis::<TaintAnalysis>(cfg);
+    let constraint_analysis = context.analysis::<ConstraintAnalysis>(cfg);
 
     // 2. Compute the set of variables read.
     let mut variables_read = HashSet::new();
@@ -255,7 +263,10 @@ pub fn run_side_effect_analysis(cfg: &Cfg) -> ReportCollection {
         .declarations()
         .iter()
         .filter_map(|(name, declaration)| {
-            if matches!(declaration.variable_type(), VariableType::Signal(_, _)) {
+            if matches!(
+                declaration.variable_type(),
+                VariableType::Signal(_, _) | VariableType::Bus(_, _, _)
+            ) {
                 Some((name, declaration))
             } else {
                 None
@@ -268,6 +279,7 @@ pub fn run_side_effect_analysis(cfg: &Cfg) -> ReportCollection {
             if matches!(
                 declaration.variable_type(),
                 VariableType::Signal(SignalType::Input | SignalType::Output, _)
+                    | VariableType::Bus(_, SignalType::Input | SignalType::Output, _)
             ) {
                 Some(*name)
             } else {
@@ -338,7 +350,14 @@ pub fn run_side_effect_analysis(cfg: &Cfg) -> ReportCollection {
             if cfg.parameters().contains(source.name()) {
                 reports.push(build_unused_param(source, cfg.name()))
             } else {
-                reports.push(build_unused_variable(source));
+                // The fix applies to all instances of the template, so we only
+                // suggest a fix when analyzing the generic template.
+                let fix = if cfg.arguments().is_none() {
+                    build_removal_fix(context, cfg, source)
+                } else {
+                    None
+                };
+                reports.push(build_unused_variable(source, fix));
             }
             reported_vars.insert(source.name().to_string());
         } else if !taint_analysis.taints_any(source.name(), &sinks) {
@@ -380,8 +399,126 @@ pub fn run_side_effect_analysis(cfg: &Cfg) -> ReportCollection {
     reports
 }
 
-fn build_unused_variable(definition: &VariableUse) -> Report {
-    UnusedVariableWarning { var: definition.clone() }.into_report()
+/// Returns a fix removing the assignment defining the given (unused) local
+/// variable. To ensure that removing the assignment preserves the semantics of
+/// the program and produces well-formed code, we require that
+///
+///   1. the assigned expression does not contain any function calls,
+///   2. the assignment is the only statement on its line(s), and the previous
+///      non-empty line ends with `;`, `{`, or `}` (ruling out assignments
+///      forming the body of an if-statement or loop without braces), and
+///   3. if the assignment also declares the variable, the variable is not
+///      used anywhere else, and
+///   4. the statement does not declare or assign any other variables (as in
+///      `var a = 1, b = 2;`).
+fn build_removal_fix(
+    context: &dyn AnalysisContext,
+    cfg: &Cfg,
+    definition: &VariableUse,
+) -> Option<Fix> {
+    use Statement::*;
+    let stmt = cfg
+        .iter()
+        .flat_map(|basic_block| basic_block.iter())
+        .find(|stmt| matches!(stmt, Substitution { var, .. } if var == definition.name()))?;
+    let Substitution { meta, op: AssignOp::AssignLocalOrComponent, rhe, .. } = stmt else {
+        return None;
+    };
+    if !meta.type_knowledge().is_local() || contains_call(rhe) {
+        return None;
+    }
+    let file_id = meta.file_id()?;
+    let location = meta.file_location();
+    // A statement like `var a = 1, b = 2;` is lifted to several declarations
+    // and assignments sharing the same source location. Removing the source
+    // line would remove the other declarations as well.
+    let name = definition.name().without_version();
+    let overlaps = |other_meta: &Meta| {
+        other_meta.file_id() == Some(file_id)
+            && other_meta.file_location().start < location.end
+            && location.start < other_meta.file_location().end
+    };
+    let is_shared =
+        cfg.iter().flat_map(|basic_block| basic_block.iter()).any(|other| match other {
+            Substitution { meta: other_meta, .. } => {
+                !std::ptr::eq(stmt, other) && overlaps(other_meta)
+            }
+            Statement::Declaration { meta: other_meta, names, .. } => {
+                overlaps(other_meta) && names.iter().any(|other| other.without_version() != name)
+            }
+            _ => false,
+        });
+    if is_shared {
+        return None;
+    }
+    let text = context.underlying_str(&file_id, &location).ok()?;
+    if text.starts_with("var ") {
+        let is_used = cfg.iter().flat_map(|basic_block| basic_block.iter()).any(|other| {
+            !std::ptr::eq(stmt, other)
+                && other.variables_used().any(|var| var.name().without_version() == name)
+        });
+        if is_used {
+            return None;
+        }
+    }
+
+    // Check that the statement is the only statement on its line.
+    let prefix = context.underlying_str(&file_id, &(0..location.start)).ok()?;
+    let line_start = prefix.rfind('\n').map_or(0, |index| index + 1);
+    if !prefix[line_start..].trim().is_empty() {
+        return None;
+    }
+    let previous_line = prefix[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with("//"))?;
+    if ["//", "/*", "*/"].iter().any(|comment| previous_line.contains(comment))
+        || !previous_line.ends_with(&[';', '{', '}'][..])
+    {
+        return None;
+    }
+    let suffix = |length: usize| {
+        context.underlying_str(&file_id, &(location.end..location.end + length)).ok()
+    };
+    let line_end = if suffix(2).as_deref() == Some(";\n") {
+        location.end + 2
+    } else if suffix(3).as_deref() == Some(";\r\n") {
+        location.end + 3
+    } else {
+        return None;
+    };
+    Some(Fix::new(
+        &format!("Remove the assignment to `{definition}`."),
+        vec![TextEdit::delete(file_id, line_start..line_end)],
+    ))
+}
+
+fn contains_call(expr: &Expression) -> bool {
+    use Expression::*;
+    match expr {
+        Call { .. } => true,
+        InfixOp { lhe, rhe, .. } => contains_call(lhe) || contains_call(rhe),
+        PrefixOp { rhe, .. } => contains_call(rhe),
+        SwitchOp { cond, if_true, if_false, .. } => {
+            contains_call(cond) || contains_call(if_true) || contains_call(if_false)
+        }
+        InlineArray { values, .. } => values.iter().any(contains_call),
+        Access { access, .. } => access_contains_call(access),
+        Update { access, rhe, .. } => access_contains_call(access) || contains_call(rhe),
+        Variable { .. } | Number(..) | Phi { .. } => false,
+    }
+}
+
+fn access_contains_call(access: &[AccessType]) -> bool {
+    access.iter().any(|access| match access {
+        AccessType::ArrayAccess(index) => contains_call(index),
+        AccessType::ComponentAccess(_) => false,
+    })
+}
+
+fn build_unused_variable(definition: &VariableUse, fix: Option<Fix>) -> Report {
+    UnusedVariableWarning { var: definition.clone(), fix }.into_report()
 }
 
 fn build_unused_param(definition: &VariableUse, cfg_name: &str) -> Report {
@@ -436,6 +573,8 @@ mod tests {
     use parser::parse_definition;
     use program_structure::{cfg::IntoCfg, constants::Curve};
 
+    use crate::analysis_runner::AnalysisRunner;
+
     use super::*;
 
     #[test]
@@ -555,6 +694,57 @@ mod tests {
         validate_reports(src, 0);
     }
 
+    #[test]
+    fn test_unused_variable_fix() {
+        let src = r#"
+            template T(n) {
+                signal input in;
+                signal output out;
+
+                var unused = n + 1;
+                var x = 0;
+                if (n > 0) x = 1;
+                x = 2;
+                var y = n * 2;
+                out <== in * x;
+            }
+        "#;
+        let runner = AnalysisRunner::new(Curve::default()).with_src(&[src]);
+        let cfg = runner.template("T").unwrap();
+        let reports = run_side_effect_analysis(&runner, &cfg);
+        let mut removed = reports
+            .iter()
+            .filter_map(Report::fix)
+            .map(|fix| {
+                let edit = &fix.edits()[0];
+                runner.underlying_str(&edit.file_id, &edit.location).unwrap().trim().to_string()
+            })
+            .collect::<Vec<_>>();
+        removed.sort();
+        // The assignment `x = 1` is the body of an if-statement and cannot be
+        // removed, and `var x = 0` also declares `x`.
+        assert_eq!(removed, vec!["var unused = n + 1;", "var y = n * 2;"]);
+    }
+
+    #[test]
+    fn test_multiple_declarations_fix() {
+        let src = r#"
+            template T(n) {
+                signal input in;
+                signal output out;
+
+                var a = n + 1, b = n * 2;
+                out <== in * b;
+            }
+        "#;
+        let runner = AnalysisRunner::new(Curve::default()).with_src(&[src]);
+        let cfg = runner.template("T").unwrap();
+        let reports = run_side_effect_analysis(&runner, &cfg);
+        // `a` is unused, but removing the statement would also remove `b`.
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].fix().is_none());
+    }
+
     fn validate_reports(src: &str, expected_len: usize) {
         // Build CFG.
         let mut reports = ReportCollection::new();
@@ -567,7 +757,7 @@ mod tests {
         assert!(reports.is_empty());
 
         // Generate report collection.
-        let reports = run_side_effect_analysis(&cfg);
+        let reports = run_side_effect_analysis(&AnalysisRunner::new(Curve::default()), &cfg);
         assert_eq!(reports.len(), expected_len);
     }
 }
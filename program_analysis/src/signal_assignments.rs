@@ -3,12 +3,15 @@ use program_structure::intermediate_representation::degree_meta::{DegreeRange, D
 use std::collections::HashSet;
 
 use program_structure::cfg::{Cfg, DefinitionType};
+use program_structure::fix::{Fix, TextEdit};
 use program_structure::report_code::ReportCode;
 use program_structure::report::{Report, ReportCollection};
 use program_structure::ir::*;
 use program_structure::ir::AccessType;
 use program_structure::ir::variable_meta::VariableMeta;
 
+use crate::analysis_context::AnalysisContext;
+
 pub struct SignalAssignmentWarning {
     signal: VariableName,
     access: Vec<AccessType>,
@@ -64,6 +67,7 @@ pub struct UnecessarySignalAssignmentWarning {
     signal: VariableName,
     access: Vec<AccessType>,
     assignment_meta: Meta,
+    fix: Option<Fix>,
 }
 
 impl UnecessarySignalAssignmentWarning {
@@ -89,6 +93,9 @@ impl UnecessarySignalAssignmentWarning {
             "Consider rewriting the statement using the constraint assignment operator `<==`."
                 .to_string(),
         );
+        if let Some(fix) = self.fix {
+            report.set_fix(fix);
+        }
         report
     }
 }
@@ -205,7 +212,7 @@ impl SignalUse {
 /// The signal assignment operator `y <-- x` does not constrain the signal `y`.
 /// If the developer meant to use the constraint assignment operator `<==` this
 /// could lead to unexpected results.
-pub fn find_signal_assignments(cfg: &Cfg) -> ReportCollection {
+pub fn find_signal_assignments(context: &dyn AnalysisContext, cfg: &Cfg) -> ReportCollection {
     use DefinitionType::*;
     if matches!(cfg.definition_type(), Function | CustomTemplate) {
         // Exit early if this is a function or custom template.
@@ -221,10 +228,18 @@ pub fn find_signal_assignments(cfg: &Cfg) -> ReportCollection {
     let mut reports = ReportCollection::new();
     for assignment in signal_use.get_assignments() {
         if assignment.is_quadratic() {
+            // The fix applies to all instances of the template, so we only
+            // suggest a fix when analyzing the generic template.
+            let fix = if cfg.arguments().is_none() {
+                build_constraint_assignment_fix(context, &assignment.meta)
+            } else {
+                None
+            };
             reports.push(build_unecessary_assignment_report(
                 &assignment.signal,
                 &assignment.access,
                 &assignment.meta,
+                fix,
             ))
         } else {
             let constraint_metas =
@@ -270,15 +285,36 @@ fn visit_statement(stmt: &Statement, signal_use: &mut SignalUse) {
     }
 }
 
+/// Returns a fix replacing the signal assignment operator `<--` (or `-->`) in
+/// the given statement with the constraint assignment operator `<==` (or
+/// `==>`). Returns `None` if the operator cannot be uniquely identified.
+fn build_constraint_assignment_fix(context: &dyn AnalysisContext, meta: &Meta) -> Option<Fix> {
+    let file_id = meta.file_id?;
+    let stmt = context.underlying_str(&file_id, &meta.location).ok()?;
+    for (op, replacement) in [("<--", "<=="), ("-->", "==>")] {
+        let mut offsets = stmt.match_indices(op).map(|(offset, _)| offset);
+        if let (Some(offset), None) = (offsets.next(), offsets.next()) {
+            let start = meta.location.start + offset;
+            return Some(Fix::new(
+                &format!("Replace `{op}` with `{replacement}`."),
+                vec![TextEdit::new(file_id, start..start + op.len(), replacement)],
+            ));
+        }
+    }
+    None
+}
+
 fn build_unecessary_assignment_report(
     signal: &VariableName,
     access: &[AccessType],
     assignment_meta: &Meta,
+    fix: Option<Fix>,
 ) -> Report {
     UnecessarySignalAssignmentWarning {
         signal: signal.clone(),
         access: access.to_owned(),
         assignment_meta: assignment_meta.clone(),
+        fix,
     }
     .into_report()
 }
@@ -308,6 +344,8 @@ mod tests {
     use parser::parse_definition;
     use program_structure::{cfg::IntoCfg, constants::Curve};
 
+    use crate::analysis_runner::AnalysisRunner;
+
     use super::*;
 
     #[test]
@@ -355,6 +393,31 @@ mod tests {
         validate_reports(src, 1);
     }
 
+    #[test]
+    fn test_unnecessary_signal_assignment_fix() {
+        let src = r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                out <-- in * in;
+                in * in --> out;
+            }
+        "#;
+        let runner = AnalysisRunner::new(Curve::default()).with_src(&[src]);
+        let cfg = runner.template("T").unwrap();
+        let reports = find_signal_assignments(&runner, &cfg);
+        assert_eq!(reports.len(), 2);
+        for report in &reports {
+            let edit = &report.fix().unwrap().edits()[0];
+            let op = runner.underlying_str(&edit.file_id, &edit.location).unwrap();
+            assert!(matches!(
+                (op.as_str(), edit.replacement.as_str()),
+                ("<--", "<==") | ("-->", "==>")
+            ));
+        }
+    }
+
     fn validate_reports(src: &str, expected_len: usize) {
         // Build CFG.
         println!("{}", src);
@@ -368,7 +431,7 @@ mod tests {
         assert!(reports.is_empty());
 
         // Generate report collection.
-        let reports = find_signal_assignments(&cfg);
+        let reports = find_signal_assignments(&AnalysisRunner::new(Curve::default()), &cfg);
         for report in &reports {
             println!("{}", report.message())
         }
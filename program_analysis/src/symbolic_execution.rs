@@ -0,0 +1,1389 @@
+use log::{debug, trace};
+use num_bigint::{BigInt, ModInverse};
+use num_traits::{One, ToPrimitive, Zero};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use thiserror::Error;
+
+use circom_algebra::modular_arithmetic;
+use program_structure::cfg::{Cfg, Index};
+use program_structure::file_definition::{FileID, FileLocation};
+use program_structure::ir::*;
+
+use crate::analysis_context::AnalysisContext;
+use crate::emulation::{self, Emulator, Value};
+use crate::polynomial::{Polynomial, Variable};
+
+/// The maximum number of statements executed when building the constraint
+/// system for a template.
+const MAX_EXECUTION_STEPS: usize = 100_000;
+
+/// Errors returned by the symbolic executor. These indicate that the
+/// constraint system of the template could not be fully determined.
+#[derive(Debug, Error)]
+pub enum SymbolicError {
+    #[error("Unknown variable `{name}`.")]
+    UnknownVariable { name: String },
+    #[error("Invalid access to the variable `{name}`.")]
+    InvalidAccess { name: String },
+    #[error("The branch condition does not evaluate to a constant.")]
+    NonConstantCondition,
+    #[error("The array index or array dimension does not evaluate to a constant.")]
+    NonConstantIndex,
+    #[error("The constraint cannot be represented as a polynomial.")]
+    NonPolynomialConstraint { file_id: Option<FileID>, file_location: FileLocation },
+    #[error("Symbolic execution exceeded the maximum number of steps ({max_steps}).")]
+    StepLimitExceeded { max_steps: usize },
+    #[error("The signal `{name}` is a bus, which is not supported.")]
+    UnsupportedBus { name: String },
+}
+
+pub type SymbolicResult<T> = Result<T, SymbolicError>;
+
+/// A symbolic value computed by the symbolic executor. Values which cannot be
+/// represented as polynomials in the template signals are unknown.
+#[derive(Clone)]
+pub enum SymbolicValue {
+    Unknown,
+    Polynomial(Polynomial),
+    Array(Vec<SymbolicValue>),
+    /// An instantiated subcomponent (given by its index).
+    Component(usize),
+}
+
+impl SymbolicValue {
+    fn constant(value: &BigInt, prime: &BigInt) -> SymbolicValue {
+        SymbolicValue::Polynomial(Polynomial::constant(value, prime))
+    }
+
+    fn as_constant(&self) -> Option<BigInt> {
+        match self {
+            SymbolicValue::Polynomial(polynomial) => polynomial.as_constant(),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of scalar elements of the value.
+    fn element_count(&self) -> usize {
+        match self {
+            SymbolicValue::Array(values) => values.iter().map(SymbolicValue::element_count).sum(),
+            _ => 1,
+        }
+    }
+
+    fn with_dimensions(dimensions: &[usize], value: SymbolicValue) -> SymbolicValue {
+        match dimensions.split_first() {
+            Some((size, dimensions)) => {
+                SymbolicValue::Array(vec![SymbolicValue::with_dimensions(dimensions, value); *size])
+            }
+            None => value,
+        }
+    }
+
+    fn get(&self, indices: &[usize]) -> Option<&SymbolicValue> {
+        match (indices.split_first(), self) {
+            (Some((index, indices)), SymbolicValue::Array(values)) => {
+                values.get(*index)?.get(indices)
+            }
+            (Some(_), _) => None,
+            (None, value) => Some(value),
+        }
+    }
+
+    fn get_mut(&mut self, indices: &[usize]) -> Option<&mut SymbolicValue> {
+        match (indices.split_first(), self) {
+            (Some((index, indices)), SymbolicValue::Array(values)) => {
+                values.get_mut(*index)?.get_mut(indices)
+            }
+            (Some(_), _) => None,
+            (None, value) => Some(value),
+        }
+    }
+
+    /// Converts the value to a concrete value (used to evaluate function calls
+    /// with constant arguments).
+    fn to_concrete(&self) -> Option<Value> {
+        match self {
+            SymbolicValue::Polynomial(polynomial) => polynomial.as_constant().map(Value::from),
+            SymbolicValue::Array(values) => values
+                .iter()
+                .map(SymbolicValue::to_concrete)
+                .collect::<Option<Vec<_>>>()
+                .map(Value::from),
+            _ => None,
+        }
+    }
+
+    fn from_concrete(value: &Value, prime: &BigInt) -> SymbolicValue {
+        match value {
+            Value::FieldElement(value) => SymbolicValue::constant(value, prime),
+            Value::Array(values) => SymbolicValue::Array(
+                values.iter().map(|value| SymbolicValue::from_concrete(value, prime)).collect(),
+            ),
+            Value::Undefined | Value::Component(_) => SymbolicValue::Unknown,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SignalKind {
+    Input,
+    Output,
+    Intermediate,
+    /// A signal belonging to an instantiated subcomponent.
+    Subcomponent,
+}
+
+/// A signal (or signal array element) of the template, or of one of the
+/// instantiated subcomponents.
+#[derive(Clone)]
+pub struct SignalVariable {
+    name: String,
+    signal_name: String,
+    kind: SignalKind,
+    file_id: Option<FileID>,
+    file_location: FileLocation,
+}
+
+impl SignalVariable {
+    /// Returns the full name of the signal (e.g. `out[1]` or `c.in`).
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the name of the declared signal (e.g. `out` or `c`).
+    #[must_use]
+    pub fn signal_name(&self) -> &str {
+        &self.signal_name
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> SignalKind {
+        self.kind
+    }
+
+    #[must_use]
+    pub fn file_id(&self) -> Option<FileID> {
+        self.file_id
+    }
+
+    /// Returns the location of the signal declaration (or the first access
+    /// for subcomponent signals).
+    #[must_use]
+    pub fn file_location(&self) -> FileLocation {
+        self.file_location.clone()
+    }
+}
+
+/// A constraint of the form `p = 0`, where `p` is a polynomial in the signals
+/// of the template.
+#[derive(Clone)]
+pub struct Constraint {
+    polynomial: Polynomial,
+    file_id: Option<FileID>,
+    file_location: FileLocation,
+}
+
+impl Constraint {
+    #[must_use]
+    pub fn polynomial(&self) -> &Polynomial {
+        &self.polynomial
+    }
+
+    #[must_use]
+    pub fn file_id(&self) -> Option<FileID> {
+        self.file_id
+    }
+
+    #[must_use]
+    pub fn file_location(&self) -> FileLocation {
+        self.file_location.clone()
+    }
+}
+
+/// An instantiated subcomponent. Subcomponent signals are classified as inputs
+/// and outputs using the signals declared by the instantiated template.
+#[derive(Clone, Default)]
+pub struct Subcomponent {
+    name: String,
+    template: String,
+    inputs: BTreeSet<Variable>,
+    outputs: BTreeSet<Variable>,
+    // True if every input signal declared by the template is assigned.
+    is_complete: bool,
+}
+
+impl Subcomponent {
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    #[must_use]
+    pub fn inputs(&self) -> &BTreeSet<Variable> {
+        &self.inputs
+    }
+
+    #[must_use]
+    pub fn outputs(&self) -> &BTreeSet<Variable> {
+        &self.outputs
+    }
+}
+
+/// The constraint system of a template, given as a set of polynomials over
+/// the prime field.
+pub struct ConstraintSystem {
+    name: String,
+    prime: BigInt,
+    variables: Vec<SignalVariable>,
+    constraints: Vec<Constraint>,
+    subcomponents: Vec<Subcomponent>,
+}
+
+impl ConstraintSystem {
+    /// Returns the name of the corresponding template.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn prime(&self) -> &BigInt {
+        &self.prime
+    }
+
+    #[must_use]
+    pub fn get_variable(&self, var: Variable) -> &SignalVariable {
+        &self.variables[var]
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = (Variable, &SignalVariable)> {
+        self.variables.iter().enumerate()
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = Variable> + '_ {
+        self.variables_of_kind(SignalKind::Input)
+    }
+
+    pub fn outputs(&self) -> impl Iterator<Item = Variable> + '_ {
+        self.variables_of_kind(SignalKind::Output)
+    }
+
+    fn variables_of_kind(&self, kind: SignalKind) -> impl Iterator<Item = Variable> + '_ {
+        self.variables().filter(move |(_, signal)| signal.kind == kind).map(|(var, _)| var)
+    }
+
+    #[must_use]
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    #[must_use]
+    pub fn subcomponents(&self) -> &[Subcomponent] {
+        &self.subcomponents
+    }
+
+    /// Formats the polynomial using the names of the corresponding signals.
+    #[must_use]
+    pub fn format(&self, polynomial: &Polynomial) -> String {
+        polynomial.format(&|var| self.variables[var].name.clone(), &self.prime)
+    }
+
+    /// Returns the set of signals that are uniquely determined by the input
+    /// signals of the template. That is, the set of signals `x` such that any
+    /// two witnesses `w` and `w'` that agree on the inputs and satisfy the
+    /// constraint system also satisfy `w.x = w'.x`.
+    ///
+    /// The result is an under-approximation. Signals are determined by
+    /// repeatedly applying the following rules.
+    ///
+    ///   1. If a constraint is on the form `a * x + b = 0`, where `x` is the
+    ///      only undetermined signal and `a` is a non-zero constant, then `x`
+    ///      is determined.
+    ///   2. If `a` in (1) is a single monomial (like `c * y * z`) then `x` is
+    ///      determined if it is determined (using the remaining rules) when
+    ///      each of the signals in `a` is zero.
+    ///   3. If a constraint is on the form `k * (x_1 + 2 * x_2 + ... + 2^n *
+    ///      x_n) + b = 0`, where each `x_i` is undetermined and constrained to
+    ///      be boolean, and `2^n` is smaller than the prime, then each `x_i` is
+    ///      determined.
+    ///   4. If all inputs declared by a subcomponent are assigned and
+    ///      determined, then all outputs of the subcomponent are determined.
+    ///      (This assumes that each subcomponent is properly constrained.)
+    #[must_use]
+    pub fn determined_variables(&self) -> BTreeSet<Variable> {
+        let mut determined = self.inputs().collect();
+        let constraints = self
+            .constraints
+            .iter()
+            .map(|constraint| constraint.polynomial.clone())
+            .collect::<Vec<_>>();
+        propagate(&constraints, &self.subcomponents, &self.prime, &mut determined, true);
+        determined
+    }
+}
+
+/// Propagates the set of determined signals until a fixpoint is reached.
+fn propagate(
+    constraints: &[Polynomial],
+    subcomponents: &[Subcomponent],
+    prime: &BigInt,
+    determined: &mut BTreeSet<Variable>,
+    allow_case_split: bool,
+) {
+    let booleans = constraints
+        .iter()
+        .filter_map(|constraint| boolean_variable(constraint, prime))
+        .collect::<BTreeSet<_>>();
+    loop {
+        let mut updated = false;
+        for subcomponent in subcomponents {
+            if subcomponent.is_complete
+                && subcomponent.inputs.is_subset(determined)
+                && !subcomponent.outputs.is_subset(determined)
+            {
+                determined.extend(subcomponent.outputs.iter().copied());
+                updated = true;
+            }
+        }
+        for constraint in constraints {
+            let undetermined =
+                constraint.variables().difference(determined).copied().collect::<Vec<_>>();
+            let new_vars = match undetermined.as_slice() {
+                [] => Vec::new(),
+                [var] => {
+                    if is_determined_by(
+                        constraint,
+                        *var,
+                        constraints,
+                        subcomponents,
+                        prime,
+                        determined,
+                        allow_case_split,
+                    ) {
+                        vec![*var]
+                    } else {
+                        Vec::new()
+                    }
+                }
+                vars => {
+                    binary_decomposition(constraint, vars, &booleans, prime).unwrap_or_default()
+                }
+            };
+            if !new_vars.is_empty() {
+                trace!("constraint determines signals {new_vars:?}");
+                determined.extend(new_vars);
+                updated = true;
+            }
+        }
+        if !updated {
+            break;
+        }
+    }
+}
+
+/// Returns true if `var` (which is assumed to be the only undetermined signal
+/// in the constraint) is determined by the constraint.
+fn is_determined_by(
+    constraint: &Polynomial,
+    var: Variable,
+    constraints: &[Polynomial],
+    subcomponents: &[Subcomponent],
+    prime: &BigInt,
+    determined: &BTreeSet<Variable>,
+    allow_case_split: bool,
+) -> bool {
+    let Some((coefficient, _)) = constraint.split(var) else {
+        return false;
+    };
+    if coefficient.is_zero() {
+        return false;
+    }
+    if coefficient.as_constant().is_some() {
+        return true;
+    }
+    if !allow_case_split {
+        return false;
+    }
+    // If the coefficient is a single monomial it is zero if and only if one of
+    // the signals in the monomial is zero.
+    if coefficient.len() != 1 {
+        return false;
+    }
+    let vars = coefficient.variables();
+    vars.into_iter().all(|zero| {
+        let constraints =
+            constraints.iter().map(|constraint| constraint.set_to_zero(zero)).collect::<Vec<_>>();
+        let mut determined = determined.clone();
+        propagate(&constraints, subcomponents, prime, &mut determined, false);
+        determined.contains(&var)
+    })
+}
+
+/// Returns the signal `x` if the constraint is on the form `k * (x^2 - x) = 0`.
+fn boolean_variable(constraint: &Polynomial, prime: &BigInt) -> Option<Variable> {
+    let var = match constraint.variables().into_iter().collect::<Vec<_>>().as_slice() {
+        [var] => *var,
+        _ => return None,
+    };
+    let mut terms = constraint.terms();
+    let (Some((lhm, lhc)), Some((rhm, rhc)), None) = (terms.next(), terms.next(), terms.next())
+    else {
+        return None;
+    };
+    let degrees = (lhm.degree_in(var), rhm.degree_in(var));
+    if lhm.degree() + rhm.degree() == 3
+        && (degrees == (1, 2) || degrees == (2, 1))
+        && (lhc + rhc) % prime == BigInt::zero()
+    {
+        Some(var)
+    } else {
+        None
+    }
+}
+
+/// Returns the undetermined signals if the constraint is a binary
+/// decomposition of the remaining (determined) terms.
+fn binary_decomposition(
+    constraint: &Polynomial,
+    undetermined: &[Variable],
+    booleans: &BTreeSet<Variable>,
+    prime: &BigInt,
+) -> Option<Vec<Variable>> {
+    let mut coefficients = Vec::new();
+    for var in undetermined {
+        if !booleans.contains(var) {
+            return None;
+        }
+        let (coefficient, _) = constraint.split(*var)?;
+        coefficients.push(coefficient.as_constant()?);
+    }
+    // Look for a common factor `k` such that each coefficient is given by `k *
+    // 2^e` for distinct exponents `e`.
+    for factor in &coefficients {
+        let inverse = factor.mod_inverse(prime)?;
+        let exponents = coefficients
+            .iter()
+            .map(|coefficient| power_of_two(&(coefficient * &inverse % prime)))
+            .collect::<Option<BTreeSet<_>>>();
+        if let Some(exponents) = exponents {
+            let sum = exponents.iter().map(|exp| BigInt::one() << *exp).sum::<BigInt>();
+            if exponents.len() == coefficients.len() && sum < *prime {
+                return Some(undetermined.to_vec());
+            }
+        }
+    }
+    None
+}
+
+/// Returns `e` if the value is equal to `2^e`.
+fn power_of_two(value: &BigInt) -> Option<usize> {
+    if value > &BigInt::zero() && (value & (value - BigInt::one())).is_zero() {
+        Some(value.bits() - 1)
+    } else {
+        None
+    }
+}
+
+/// An element of an array or component access.
+enum PathElement {
+    Index(usize),
+    Signal(String),
+}
+
+/// Control flow resulting from executing a single statement.
+enum Control {
+    Next,
+    Jump(Index),
+    Exit,
+}
+
+struct ComponentInstance {
+    name: String,
+    template: String,
+    // The template arguments, if they are constant.
+    arguments: Option<Vec<BigInt>>,
+    signals: HashMap<(String, Vec<usize>), Variable>,
+    // Subcomponent signals assigned by the template.
+    assigned: BTreeSet<Variable>,
+}
+
+/// The input and output signals declared by an instantiated template, together
+/// with the number of elements of each input signal.
+struct SignalInterface {
+    inputs: HashMap<String, usize>,
+    outputs: HashSet<String>,
+}
+
+impl SignalInterface {
+    /// Returns the signal interface of the given template instance. This
+    /// requires the template to be executed symbolically, to evaluate the
+    /// dimensions of the declared signals.
+    fn new(
+        context: &dyn AnalysisContext,
+        template: &str,
+        arguments: &[BigInt],
+    ) -> Option<SignalInterface> {
+        let cfg = context.instance(template, arguments).ok()?;
+        let mut executor = SymbolicExecutor::new(context, &cfg);
+        executor.execute(&cfg).ok()?;
+        let inputs = cfg
+            .input_signals()
+            .map(|name| Some((name.to_string(), executor.get(name).ok()?.element_count())))
+            .collect::<Option<_>>()?;
+        let outputs = cfg.output_signals().map(ToString::to_string).collect();
+        Some(SignalInterface { inputs, outputs })
+    }
+}
+
+/// Builds the constraint system for the given template by executing the
+/// template symbolically. Since the executor requires all branch conditions,
+/// array indices, and array dimensions to evaluate to constants, this will
+/// typically fail for templates that depend on template parameters.
+pub fn build_constraint_system(
+    context: &dyn AnalysisContext,
+    cfg: &Cfg,
+) -> SymbolicResult<ConstraintSystem> {
+    debug!("building constraint system for `{}`", cfg.name());
+    let mut executor = SymbolicExecutor::new(context, cfg);
+    executor.execute(cfg)?;
+
+    let mut interfaces = HashMap::new();
+    let subcomponents = executor
+        .components
+        .into_iter()
+        .map(|component| {
+            // If the interface of the subcomponent is unknown, no signals are
+            // classified as outputs, so no outputs are considered determined.
+            let interface = component.arguments.as_ref().and_then(|arguments| {
+                interfaces
+                    .entry((component.template.clone(), arguments.clone()))
+                    .or_insert_with(|| {
+                        SignalInterface::new(context, &component.template, arguments)
+                    })
+                    .as_ref()
+            });
+            let mut subcomponent = Subcomponent {
+                name: component.name,
+                template: component.template,
+                ..Default::default()
+            };
+            let Some(interface) = interface else {
+                return subcomponent;
+            };
+            let mut assigned = HashMap::<&str, usize>::new();
+            for ((signal, _), var) in &component.signals {
+                if interface.inputs.contains_key(signal) {
+                    subcomponent.inputs.insert(*var);
+                    if component.assigned.contains(var) {
+                        *assigned.entry(signal.as_str()).or_default() += 1;
+                    }
+                } else if interface.outputs.contains(signal) {
+                    subcomponent.outputs.insert(*var);
+                }
+            }
+            subcomponent.is_complete = interface
+                .inputs
+                .iter()
+                .all(|(signal, len)| assigned.get(signal.as_str()) == Some(len));
+            subcomponent
+        })
+        .collect();
+    Ok(ConstraintSystem {
+        name: cfg.name().to_string(),
+        prime: executor.prime,
+        variables: executor.variables,
+        constraints: executor.constraints,
+        subcomponents,
+    })
+}
+
+struct SymbolicExecutor<'a> {
+    context: &'a dyn AnalysisContext,
+    prime: BigInt,
+    values: HashMap<VariableName, SymbolicValue>,
+    // Phi expressions are resolved by tracking when each version of a
+    // variable was last assigned.
+    timestamps: HashMap<VariableName, usize>,
+    steps: usize,
+    functions: HashMap<String, Arc<Cfg>>,
+    variables: Vec<SignalVariable>,
+    constraints: Vec<Constraint>,
+    components: Vec<ComponentInstance>,
+}
+
+impl<'a> SymbolicExecutor<'a> {
+    fn new(context: &'a dyn AnalysisContext, cfg: &Cfg) -> SymbolicExecutor<'a> {
+        let mut executor = SymbolicExecutor {
+            context,
+            prime: cfg.constants().prime().clone(),
+            values: HashMap::new(),
+            timestamps: HashMap::new(),
+            steps: 0,
+            functions: HashMap::new(),
+            variables: Vec::new(),
+            constraints: Vec::new(),
+            components: Vec::new(),
+        };
+        // Template parameters are unknown unless the template is specialized.
+        let arguments = cfg.arguments().unwrap_or_default();
+        for (index, name) in cfg.parameters().iter().enumerate() {
+            let value = match arguments.get(index) {
+                Some(value) => SymbolicValue::constant(value, &executor.prime),
+                None => SymbolicValue::Unknown,
+            };
+            executor.assign(name, value);
+        }
+        executor
+    }
+
+    fn assign(&mut self, name: &VariableName, value: SymbolicValue) {
+        self.values.insert(name.clone(), value);
+        self.timestamps.insert(name.clone(), self.steps);
+    }
+
+    fn get(&self, name: &VariableName) -> SymbolicResult<&SymbolicValue> {
+        self.values
+            .get(name)
+            .ok_or_else(|| SymbolicError::UnknownVariable { name: name.to_string() })
+    }
+
+    fn execute(&mut self, cfg: &Cfg) -> SymbolicResult<()> {
+        let mut basic_block = cfg.entry_block();
+        loop {
+            let mut next = basic_block.successors().iter().next().copied();
+            for stmt in basic_block.iter() {
+                self.steps += 1;
+                if self.steps > MAX_EXECUTION_STEPS {
+                    return Err(SymbolicError::StepLimitExceeded {
+                        max_steps: MAX_EXECUTION_STEPS,
+                    });
+                }
+                match self.execute_statement(stmt)? {
+                    Control::Next => {}
+                    Control::Jump(index) => next = Some(index),
+                    Control::Exit => next = None,
+                }
+            }
+            match next.and_then(|index| cfg.get_basic_block(index)) {
+                Some(next) => basic_block = next,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    fn execute_statement(&mut self, stmt: &Statement) -> SymbolicResult<Control> {
+        use Statement::*;
+        match stmt {
+            Declaration { meta, names, var_type, dimensions } => {
+                let dimensions = dimensions
+                    .iter()
+                    .map(|size| self.evaluate_index(size))
+                    .collect::<SymbolicResult<Vec<_>>>()?;
+                for name in names.iter() {
+                    let value = match var_type {
+                        VariableType::Local => {
+                            let zero = SymbolicValue::constant(&BigInt::zero(), &self.prime);
+                            SymbolicValue::with_dimensions(&dimensions, zero)
+                        }
+                        VariableType::Signal(signal_type, _) => {
+                            let kind = match signal_type {
+                                SignalType::Input => SignalKind::Input,
+                                SignalType::Output => SignalKind::Output,
+                                SignalType::Intermediate => SignalKind::Intermediate,
+                            };
+                            self.declare_signal(&name.to_string(), &dimensions, kind, meta)
+                        }
+                        VariableType::Component | VariableType::AnonymousComponent => {
+                            SymbolicValue::with_dimensions(&dimensions, SymbolicValue::Unknown)
+                        }
+                        VariableType::Bus(_, _, _) => {
+                            return Err(SymbolicError::UnsupportedBus { name: name.to_string() });
+                        }
+                    };
+                    self.values.insert(name.clone(), value);
+                    self.timestamps.insert(name.clone(), 0);
+                }
+                Ok(Control::Next)
+            }
+            IfThenElse { cond, true_index, false_index, .. } => {
+                let cond = self
+                    .evaluate(cond)?
+                    .as_constant()
+                    .ok_or(SymbolicError::NonConstantCondition)?;
+                if modular_arithmetic::as_bool(&cond, &self.prime) {
+                    Ok(Control::Jump(*true_index))
+                } else {
+                    Ok(false_index.map(Control::Jump).unwrap_or(Control::Exit))
+                }
+            }
+            Substitution { meta, var, op, rhe } => {
+                match op {
+                    AssignOp::AssignLocalOrComponent => {
+                        let value = self.evaluate(rhe)?;
+                        self.assign(var, value);
+                    }
+                    AssignOp::AssignSignal | AssignOp::AssignConstraintSignal => {
+                        // Signal assignments are lifted to updates if the
+                        // signal is an array or component signal.
+                        let (access, rhe) = match rhe {
+                            Expression::Update { access, rhe, .. } => (access.as_slice(), &**rhe),
+                            rhe => (&[][..], rhe),
+                        };
+                        // Signal assignments using `<--` add no constraints,
+                        // but they may still assign subcomponent inputs.
+                        let value = match op {
+                            AssignOp::AssignConstraintSignal => self.evaluate(rhe)?,
+                            _ => SymbolicValue::Unknown,
+                        };
+                        let signal = self.assigned_signal(var, access, &value, meta)?;
+                        if matches!(op, AssignOp::AssignConstraintSignal) {
+                            self.add_constraint(&signal, &value, meta)?;
+                        }
+                    }
+                }
+                Ok(Control::Next)
+            }
+            ConstraintEquality { meta, lhe, rhe } => {
+                let lhv = self.evaluate(lhe)?;
+                let rhv = self.evaluate(rhe)?;
+                self.add_constraint(&lhv, &rhv, meta)?;
+                Ok(Control::Next)
+            }
+            // Return statements only occur in functions.
+            Return { .. } => Ok(Control::Exit),
+            LogCall { .. } | Assert { .. } => Ok(Control::Next),
+        }
+    }
+
+    fn declare_signal(
+        &mut self,
+        name: &str,
+        dimensions: &[usize],
+        kind: SignalKind,
+        meta: &Meta,
+    ) -> SymbolicValue {
+        match dimensions.split_first() {
+            Some((size, dimensions)) => SymbolicValue::Array(
+                (0..*size)
+                    .map(|index| {
+                        self.declare_signal(&format!("{name}[{index}]"), dimensions, kind, meta)
+                    })
+                    .collect(),
+            ),
+            None => {
+                let signal_name = name.split('[').next().unwrap_or_default().to_string();
+                let var = self.add_variable(name.to_string(), signal_name, kind, meta);
+                SymbolicValue::Polynomial(Polynomial::variable(var))
+            }
+        }
+    }
+
+    fn add_variable(
+        &mut self,
+        name: String,
+        signal_name: String,
+        kind: SignalKind,
+        meta: &Meta,
+    ) -> Variable {
+        self.variables.push(SignalVariable {
+            name,
+            signal_name,
+            kind,
+            file_id: meta.file_id(),
+            file_location: meta.file_location(),
+        });
+        self.variables.len() - 1
+    }
+
+    /// Adds the constraint `lhv = rhv` to the constraint system.
+    fn add_constraint(
+        &mut self,
+        lhv: &SymbolicValue,
+        rhv: &SymbolicValue,
+        meta: &Meta,
+    ) -> SymbolicResult<()> {
+        match (lhv, rhv) {
+            (SymbolicValue::Polynomial(lhp), SymbolicValue::Polynomial(rhp)) => {
+                let polynomial = lhp.sub(rhp, &self.prime);
+                if !polynomial.is_zero() {
+                    self.constraints.push(Constraint {
+                        polynomial,
+                        file_id: meta.file_id(),
+                        file_location: meta.file_location(),
+                    });
+                }
+                Ok(())
+            }
+            (SymbolicValue::Array(lhvs), SymbolicValue::Array(rhvs))
+                if lhvs.len() == rhvs.len() =>
+            {
+                for (lhv, rhv) in lhvs.iter().zip(rhvs) {
+                    self.add_constraint(lhv, rhv, meta)?;
+                }
+                Ok(())
+            }
+            _ => Err(SymbolicError::NonPolynomialConstraint {
+                file_id: meta.file_id(),
+                file_location: meta.file_location(),
+            }),
+        }
+    }
+
+    /// Returns the signal (or array of signals) assigned by a signal
+    /// assignment. If the signal belongs to a subcomponent it is recorded as
+    /// an input to the subcomponent.
+    fn assigned_signal(
+        &mut self,
+        var: &VariableName,
+        access: &[AccessType],
+        value: &SymbolicValue,
+        meta: &Meta,
+    ) -> SymbolicResult<SymbolicValue> {
+        let path = self.evaluate_path(access)?;
+        let (indices, signal) = self.split_path(var, &path)?;
+        match signal {
+            None => self
+                .get(var)?
+                .get(&indices)
+                .cloned()
+                .ok_or_else(|| SymbolicError::InvalidAccess { name: var.to_string() }),
+            Some((signal, signal_indices)) => {
+                let component = self.get_component(var, &indices)?;
+                let result = self.component_signal(
+                    component,
+                    var,
+                    &indices,
+                    signal,
+                    signal_indices,
+                    value,
+                    meta,
+                );
+                let mut inputs = BTreeSet::new();
+                collect_variables(&result, &mut inputs);
+                self.components[component].assigned.extend(inputs);
+                Ok(result)
+            }
+        }
+    }
+
+    fn get_component(&self, var: &VariableName, indices: &[usize]) -> SymbolicResult<usize> {
+        match self.get(var)?.get(indices) {
+            Some(SymbolicValue::Component(component)) => Ok(*component),
+            _ => Err(SymbolicError::InvalidAccess { name: var.to_string() }),
+        }
+    }
+
+    /// Returns the variables corresponding to the given subcomponent signal.
+    /// Since the dimensions of subcomponent signals are not known, the shape
+    /// of the returned value is given by `shape`.
+    #[allow(clippy::too_many_arguments)]
+    fn component_signal(
+        &mut self,
+        component: usize,
+        var: &VariableName,
+        indices: &[usize],
+        signal: String,
+        signal_indices: Vec<usize>,
+        shape: &SymbolicValue,
+        meta: &Meta,
+    ) -> SymbolicValue {
+        if let SymbolicValue::Array(values) = shape {
+            return SymbolicValue::Array(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(index, shape)| {
+                        let mut signal_indices = signal_indices.clone();
+                        signal_indices.push(index);
+                        self.component_signal(
+                            component,
+                            var,
+                            indices,
+                            signal.clone(),
+                            signal_indices,
+                            shape,
+                            meta,
+                        )
+                    })
+                    .collect(),
+            );
+        }
+        let key = (signal, signal_indices);
+        if let Some(var) = self.components[component].signals.get(&key) {
+            return SymbolicValue::Polynomial(Polynomial::variable(*var));
+        }
+        let component_name = format!("{var}{}", format_indices(indices));
+        let name = format!("{component_name}.{}{}", key.0, format_indices(&key.1));
+        let signal_var = self.add_variable(name, var.to_string(), SignalKind::Subcomponent, meta);
+        let instance = &mut self.components[component];
+        if instance.name.is_empty() {
+            instance.name = component_name;
+        }
+        instance.signals.insert(key, signal_var);
+        SymbolicValue::Polynomial(Polynomial::variable(signal_var))
+    }
+
+    /// Returns the number of dimensions of the given signal declared by the
+    /// template, if known.
+    fn signal_dimensions(&mut self, template: &str, signal: &str) -> Option<usize> {
+        let cfg = self.context.template(template).ok()?;
+        let declaration = cfg.get_declaration(&VariableName::from_string(signal))?;
+        Some(declaration.dimensions().len())
+    }
+
+    fn evaluate_index(&mut self, expr: &Expression) -> SymbolicResult<usize> {
+        self.evaluate(expr)?
+            .as_constant()
+            .and_then(|value| value.to_usize())
+            .ok_or(SymbolicError::NonConstantIndex)
+    }
+
+    fn evaluate_path(&mut self, access: &[AccessType]) -> SymbolicResult<Vec<PathElement>> {
+        access
+            .iter()
+            .map(|access| match access {
+                AccessType::ArrayAccess(index) => {
+                    self.evaluate_index(index).map(PathElement::Index)
+                }
+                AccessType::ComponentAccess(signal) => Ok(PathElement::Signal(signal.clone())),
+            })
+            .collect()
+    }
+
+    /// Splits the access path into the array indices preceding the component
+    /// access, and the accessed signal together with the trailing indices.
+    #[allow(clippy::type_complexity)]
+    fn split_path(
+        &self,
+        var: &VariableName,
+        path: &[PathElement],
+    ) -> SymbolicResult<(Vec<usize>, Option<(String, Vec<usize>)>)> {
+        let mut indices = Vec::new();
+        let mut signal: Option<(String, Vec<usize>)> = None;
+        for element in path {
+            match (element, &mut signal) {
+                (PathElement::Index(index), None) => indices.push(*index),
+                (PathElement::Index(index), Some((_, signal_indices))) => {
+                    signal_indices.push(*index)
+                }
+                (PathElement::Signal(name), None) => signal = Some((name.clone(), Vec::new())),
+                (PathElement::Signal(_), Some(_)) => {
+                    return Err(SymbolicError::InvalidAccess { name: var.to_string() })
+                }
+            }
+        }
+        Ok((indices, signal))
+    }
+
+    fn evaluate(&mut self, expr: &Expression) -> SymbolicResult<SymbolicValue> {
+        use Expression::*;
+        use SymbolicValue::*;
+        let prime = self.prime.clone();
+        let result = match expr {
+            Number(_, value) => SymbolicValue::constant(value, &prime),
+            Variable { name, .. } => self.get(name)?.clone(),
+            InfixOp { lhe, infix_op, rhe, .. } => {
+                let lhv = self.evaluate(lhe)?;
+                let rhv = self.evaluate(rhe)?;
+                match (lhv, rhv) {
+                    (Polynomial(lhp), Polynomial(rhp)) => {
+                        evaluate_infix_op(infix_op, &lhp, &rhp, &prime)
+                    }
+                    _ => Unknown,
+                }
+            }
+            PrefixOp { prefix_op, rhe, .. } => match self.evaluate(rhe)? {
+                Polynomial(rhp) => match (prefix_op, rhp.as_constant()) {
+                    (ExpressionPrefixOpcode::Sub, _) => Polynomial(rhp.neg(&prime)),
+                    (_, Some(value)) => SymbolicValue::constant(
+                        &emulation::evaluate_prefix_op(prefix_op, &value, &prime),
+                        &prime,
+                    ),
+                    _ => Unknown,
+                },
+                _ => Unknown,
+            },
+            SwitchOp { cond, if_true, if_false, .. } => match self.evaluate(cond)?.as_constant() {
+                Some(cond) if modular_arithmetic::as_bool(&cond, &prime) => {
+                    self.evaluate(if_true)?
+                }
+                Some(_) => self.evaluate(if_false)?,
+                None => Unknown,
+            },
+            Call { name, args, .. } => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.evaluate(arg))
+                    .collect::<SymbolicResult<Vec<_>>>()?;
+                if self.context.is_template(name) {
+                    let arguments =
+                        args.iter().map(SymbolicValue::as_constant).collect::<Option<Vec<_>>>();
+                    self.components.push(ComponentInstance {
+                        name: String::new(),
+                        template: name.clone(),
+                        arguments,
+                        signals: HashMap::new(),
+                        assigned: BTreeSet::new(),
+                    });
+                    Component(self.components.len() - 1)
+                } else {
+                    self.call_function(name, &args).unwrap_or(Unknown)
+                }
+            }
+            InlineArray { values, .. } => Array(
+                values.iter().map(|value| self.evaluate(value)).collect::<SymbolicResult<_>>()?,
+            ),
+            Access { meta, var, access } => {
+                let path = self.evaluate_path(access)?;
+                let (indices, signal) = self.split_path(var, &path)?;
+                let value = self.get(var)?.get(&indices).cloned();
+                match (signal, value) {
+                    (None, Some(value)) => value,
+                    (Some((signal, signal_indices)), Some(Component(component))) => {
+                        // Only scalar subcomponent signal accesses are supported.
+                        let template = self.components[component].template.clone();
+                        match self.signal_dimensions(&template, &signal) {
+                            Some(dimensions) if dimensions == signal_indices.len() => self
+                                .component_signal(
+                                    component,
+                                    var,
+                                    &indices,
+                                    signal,
+                                    signal_indices,
+                                    &Unknown,
+                                    meta,
+                                ),
+                            _ => Unknown,
+                        }
+                    }
+                    _ => return Err(SymbolicError::InvalidAccess { name: var.to_string() }),
+                }
+            }
+            Update { var, access, rhe, .. } => {
+                let rhv = self.evaluate(rhe)?;
+                let path = self.evaluate_path(access)?;
+                let (indices, signal) = self.split_path(var, &path)?;
+                if signal.is_some() {
+                    return Err(SymbolicError::InvalidAccess { name: var.to_string() });
+                }
+                let mut result = self.get(var)?.clone();
+                *result
+                    .get_mut(&indices)
+                    .ok_or_else(|| SymbolicError::InvalidAccess { name: var.to_string() })? = rhv;
+                result
+            }
+            Phi { args, .. } => args
+                .iter()
+                .filter_map(|name| Some((name, self.timestamps.get(name)?)))
+                .max_by_key(|(_, timestamp)| **timestamp)
+                .map(|(name, _)| self.values[name].clone())
+                .unwrap_or(Unknown),
+        };
+        Ok(result)
+    }
+
+    /// Evaluates function calls with constant arguments using the emulator.
+    fn call_function(&mut self, name: &str, args: &[SymbolicValue]) -> Option<SymbolicValue> {
+        let args = args.iter().map(SymbolicValue::to_concrete).collect::<Option<Vec<_>>>()?;
+        let cfg = match self.functions.get(name) {
+            Some(cfg) => cfg.clone(),
+            None => {
+                let cfg = self.context.function(name).ok()?;
+                self.functions.insert(name.to_string(), cfg.clone());
+                cfg
+            }
+        };
+        let value = Emulator::new(self.context).emulate_function(&cfg, &args).ok()?;
+        Some(SymbolicValue::from_concrete(&value, &self.prime))
+    }
+}
+
+fn evaluate_infix_op(
+    op: &ExpressionInfixOpcode,
+    lhp: &Polynomial,
+    rhp: &Polynomial,
+    prime: &BigInt,
+) -> SymbolicValue {
+    use ExpressionInfixOpcode::*;
+    if let (Some(lhv), Some(rhv)) = (lhp.as_constant(), rhp.as_constant()) {
+        return match emulation::evaluate_infix_op(op, &lhv, &rhv, prime) {
+            Ok(value) => SymbolicValue::constant(&value, prime),
+            Err(_) => SymbolicValue::Unknown,
+        };
+    }
+    let result = match op {
+        Add => Some(lhp.add(rhp, prime)),
+        Sub => Some(lhp.sub(rhp, prime)),
+        Mul => lhp.mul(rhp, prime),
+        Div => rhp
+            .as_constant()
+            .and_then(|value| value.mod_inverse(prime))
+            .map(|inverse| lhp.scale(&inverse, prime)),
+        Pow => {
+            rhp.as_constant().and_then(|value| value.to_usize()).and_then(|exp| lhp.pow(exp, prime))
+        }
+        _ => None,
+    };
+    result.map(SymbolicValue::Polynomial).unwrap_or(SymbolicValue::Unknown)
+}
+
+fn collect_variables(value: &SymbolicValue, vars: &mut BTreeSet<Variable>) {
+    match value {
+        SymbolicValue::Polynomial(polynomial) => vars.extend(polynomial.variables()),
+        SymbolicValue::Array(values) => {
+            for value in values {
+                collect_variables(value, vars);
+            }
+        }
+        SymbolicValue::Unknown | SymbolicValue::Component(_) => {}
+    }
+}
+
+fn format_indices(indices: &[usize]) -> String {
+    indices.iter().map(|index| format!("[{index}]")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use program_structure::constants::Curve;
+
+    use crate::analysis_runner::AnalysisRunner;
+
+    use super::*;
+
+    #[test]
+    fn test_constraint_system() {
+        let src = [r#"
+            template T() {
+                signal input in[2];
+                signal output out;
+                signal tmp;
+
+                tmp <== in[0] * in[1];
+                out <-- tmp + 1;
+                out === tmp + 1;
+            }
+        "#];
+        let system = build("T", &src).unwrap();
+        assert_eq!(system.variables().count(), 4);
+        assert_eq!(system.constraints().len(), 2);
+        assert_eq!(system.format(system.constraints()[0].polynomial()), "-in[0]*in[1] + tmp");
+        assert_eq!(undetermined_outputs(&system), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_determined_signals() {
+        // `out` is not constrained.
+        let src = [r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                out <-- in + 1;
+            }
+        "#];
+        let system = build("T", &src).unwrap();
+        assert_eq!(undetermined_outputs(&system), vec!["out"]);
+
+        // `out` is only determined up to sign.
+        let src = [r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                out <-- in;
+                out * out === in * in;
+            }
+        "#];
+        let system = build("T", &src).unwrap();
+        assert_eq!(undetermined_outputs(&system), vec!["out"]);
+
+        // `out` is determined by case splitting on `in`.
+        let src = [r#"
+            template IsZero() {
+                signal input in;
+                signal output out;
+                signal inv;
+
+                inv <-- in != 0 ? 1 / in : 0;
+                out <== -in * inv + 1;
+                in * out === 0;
+            }
+        "#];
+        let system = build("IsZero", &src).unwrap();
+        assert_eq!(undetermined_outputs(&system), Vec::<String>::new());
+
+        // `out` is determined by the binary decomposition of `in`.
+        let src = [r#"
+            template Num2Bits() {
+                signal input in;
+                signal output out[8];
+
+                var lc = 0;
+                var e = 1;
+                for (var i = 0; i < 8; i++) {
+                    out[i] <-- (in >> i) & 1;
+                    out[i] * (out[i] - 1) === 0;
+                    lc += out[i] * e;
+                    e = e + e;
+                }
+                lc === in;
+            }
+        "#];
+        let system = build("Num2Bits", &src).unwrap();
+        assert_eq!(undetermined_outputs(&system), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_subcomponents() {
+        let src = [
+            r#"
+            template Square() {
+                signal input in;
+                signal output out;
+
+                out <== in * in;
+            }
+        "#,
+            r#"
+            template T() {
+                signal input in;
+                signal output out[2];
+
+                component square[2];
+                square[0] = Square();
+                square[0].in <== in;
+                square[1] = Square();
+                square[1].in <-- in;
+                out[0] <== square[0].out;
+                out[1] <== square[1].out;
+            }
+        "#,
+        ];
+        let system = build("T", &src).unwrap();
+        assert_eq!(system.subcomponents().len(), 2);
+        assert_eq!(system.subcomponents()[0].name(), "square[0]");
+        assert_eq!(undetermined_outputs(&system), vec!["out[1]"]);
+    }
+
+    #[test]
+    fn test_unassigned_subcomponent_inputs() {
+        let sq = r#"
+            template Sq() {
+                signal input i;
+                signal output o;
+
+                o <== i * i;
+            }
+        "#;
+        let sum = r#"
+            template Sum(n) {
+                signal input in[n];
+                signal output out;
+
+                var sum = 0;
+                for (var i = 0; i < n; i++) {
+                    sum += in[i];
+                }
+                out <== sum;
+            }
+        "#;
+
+        // The outputs of a subcomponent without assigned inputs are not
+        // determined, and inputs read by the template are not outputs.
+        let src = [
+            sq,
+            r#"
+            template T() {
+                signal input x;
+                signal output y;
+                signal output z;
+
+                component c = Sq();
+                y <== c.o;
+                z <== c.i;
+            }
+        "#,
+        ];
+        let system = build("T", &src).unwrap();
+        assert!(system.subcomponents()[0]
+            .outputs()
+            .iter()
+            .all(|var| { system.get_variable(*var).name() == "c.o" }));
+        assert_eq!(undetermined_outputs(&system), vec!["y", "z"]);
+
+        // All elements of array inputs must be assigned.
+        let src = [
+            sum,
+            r#"
+            template T() {
+                signal input x;
+                signal output y;
+
+                component c = Sum(2);
+                c.in[0] <== x;
+                y <== c.out;
+            }
+        "#,
+        ];
+        let system = build("T", &src).unwrap();
+        assert_eq!(undetermined_outputs(&system), vec!["y"]);
+
+        let src = [
+            sum,
+            r#"
+            template T() {
+                signal input x;
+                signal output y;
+
+                component c = Sum(2);
+                c.in[0] <== x;
+                c.in[1] <== x;
+                y <== c.out;
+            }
+        "#,
+        ];
+        let system = build("T", &src).unwrap();
+        assert_eq!(undetermined_outputs(&system), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_unsupported_templates() {
+        // Loop bounds depending on template parameters are not supported.
+        let src = [r#"
+            template T(n) {
+                signal input in[n];
+                signal output out;
+
+                var sum = 0;
+                for (var i = 0; i < n; i++) {
+                    sum += in[i];
+                }
+                out <== sum;
+            }
+        "#];
+        assert!(matches!(build("T", &src), Err(SymbolicError::NonConstantIndex)));
+    }
+
+    fn build(name: &str, src: &[&str]) -> SymbolicResult<ConstraintSystem> {
+        let context = AnalysisRunner::new(Curve::Goldilocks).with_src(src);
+        let cfg = context.template(name).unwrap();
+        build_constraint_system(&context, &cfg)
+    }
+
+    fn undetermined_outputs(system: &ConstraintSystem) -> Vec<String> {
+        let determined = system.determined_variables();
+        system
+            .outputs()
+            .filter(|var| !determined.contains(var))
+            .map(|var| system.get_variable(var).name().to_string())
+            .collect()
+    }
+}
@@ -8,11 +8,15 @@ use program_structure::cfg::Cfg;
 use program_structure::ir::variable_meta::{VariableMeta, VariableUse};
 use program_structure::ir::{Expression, Statement, VariableName};
 
+use crate::analysis_context::CfgAnalysis;
+use crate::witness_path::{shortest_path, to_variable_uses};
+
 #[derive(Clone, Default)]
 pub struct TaintAnalysis {
     taint_map: HashMap<VariableName, HashSet<VariableName>>,
     declarations: HashMap<VariableName, VariableUse>,
     definitions: HashMap<VariableName, VariableUse>,
+    step_metas: HashMap<(VariableName, VariableName), Meta>,
 }
 
 impl TaintAnalysis {
@@ -67,10 +71,12 @@ impl TaintAnalysis {
         self.declarations.values()
     }
 
-    /// Add a single step taint from source to sink.
-    fn add_taint_step(&mut self, source: &VariableName, sink: &VariableName) {
+    /// Add a single step taint from source to sink. The meta is the location
+    /// of the statement responsible for the step.
+    fn add_taint_step(&mut self, source: &VariableName, sink: &VariableName, meta: &Meta) {
         let sinks = self.taint_map.entry(source.clone()).or_default();
         sinks.insert(sink.clone());
+        self.step_metas.entry((source.clone(), sink.clone())).or_insert_with(|| meta.clone());
     }
 
     /// Returns variables tainted in a single step by `source`.
@@ -89,12 +95,33 @@ impl TaintAnalysis {
         result
     }
 
+    /// Returns a shortest path explaining why `source` taints `sink`, or `None`
+    /// if `sink` is not tainted by `source`. The first element of the path is
+    /// the definition (or declaration) of `source`, and each subsequent element
+    /// is a use of the next variable on the path, located at the statement
+    /// propagating the taint.
+    pub fn taint_path(
+        &self,
+        source: &VariableName,
+        sink: &VariableName,
+    ) -> Option<Vec<VariableUse>> {
+        let path = shortest_path(&self.taint_map, source, sink)?;
+        let start = self.get_definition(source).or_else(|| self.get_declaration(source))?;
+        to_variable_uses(&path, start, &self.step_metas)
+    }
+
     /// Returns true if the source taints any of the sinks.
     pub fn taints_any(&self, source: &VariableName, sinks: &HashSet<VariableName>) -> bool {
         self.multi_step_taint(source).iter().any(|sink| sinks.contains(sink))
     }
 }
 
+impl CfgAnalysis for TaintAnalysis {
+    fn compute(cfg: &Cfg) -> TaintAnalysis {
+        run_taint_analysis(cfg)
+    }
+}
+
 pub fn run_taint_analysis(cfg: &Cfg) -> TaintAnalysis {
     debug!("running taint analysis pass");
     let mut result = TaintAnalysis::new(cfg.parameters());
@@ -105,7 +132,7 @@ pub fn run_taint_analysis(cfg: &Cfg) -> TaintAnalysis {
         for stmt in basic_block.iter() {
             trace!("visiting statement `{stmt:?}`");
             match stmt {
-                Substitution { .. } => {
+                Substitution { meta, .. } => {
                     // Variables read taint variables written by the statement.
                     for sink in stmt.variables_written() {
                         if !matches!(stmt, Substitution { rhe: Phi { .. }, .. }) {
@@ -120,7 +147,7 @@ pub fn run_taint_analysis(cfg: &Cfg) -> TaintAnalysis {
                                 source.name(),
                                 sink.name()
                             );
-                            result.add_taint_step(source.name(), sink.name());
+                            result.add_taint_step(source.name(), sink.name(), meta);
                         }
                     }
                 }
@@ -130,7 +157,7 @@ pub fn run_taint_analysis(cfg: &Cfg) -> TaintAnalysis {
                         result.add_declaration(&VariableUse::new(meta, sink, &Vec::new()));
                         for size in dimensions {
                             for source in size.variables_read() {
-                                result.add_taint_step(source.name(), sink)
+                                result.add_taint_step(source.name(), sink, meta)
                             }
                         }
                     }
@@ -153,7 +180,7 @@ pub fn run_taint_analysis(cfg: &Cfg) -> TaintAnalysis {
                                     source.name(),
                                     sink.name()
                                 );
-                                result.add_taint_step(source.name(), sink.name());
+                                result.add_taint_step(source.name(), sink.name(), cond.meta());
                             }
                         }
                     }
@@ -230,6 +257,45 @@ mod tests {
         validate_taint(src, &taint_map);
     }
 
+    #[test]
+    fn test_taint_path() {
+        let src = r#"
+            template T() {
+                signal input in;
+                signal tmp;
+                signal output out;
+
+                var x = 2 * in;
+                tmp <== x + 1;
+                out <== tmp * tmp;
+            }
+        "#;
+        let mut reports = ReportCollection::new();
+        let cfg = parse_definition(src)
+            .unwrap()
+            .into_cfg(&Curve::default(), &mut reports)
+            .unwrap()
+            .into_ssa()
+            .unwrap();
+        assert!(reports.is_empty());
+
+        let taint_analysis = run_taint_analysis(&cfg);
+        let source = VariableName::from_string("in");
+        let sink = VariableName::from_string("out");
+        let path = taint_analysis.taint_path(&source, &sink).unwrap();
+        let names = path.iter().map(|var| var.name().to_string()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["in", "x", "tmp", "out"]);
+        // The first element is the declaration of `in`, and the remaining
+        // elements are located at the statements propagating the taint.
+        let lines = path
+            .iter()
+            .map(|var| src[..var.meta().file_location().start].lines().count())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![3, 7, 8, 9]);
+
+        assert!(taint_analysis.taint_path(&sink, &source).is_none());
+    }
+
     fn validate_taint(src: &str, taint_map: &HashMap<&str, HashSet<String>>) {
         // Build CFG.
         let mut reports = ReportCollection::new();
@@ -0,0 +1,465 @@
+use log::debug;
+
+use program_structure::{
+    ir::*,
+    report_code::ReportCode,
+    cfg::{Cfg, DefinitionType},
+    report::{Report, ReportCollection},
+    file_definition::{FileID, FileLocation},
+};
+
+use crate::analysis_context::AnalysisContext;
+use crate::variable_access::{maybe_accesses, VariableAccess};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SignalIssue {
+    // The input signal is never assigned.
+    UnassignedInput,
+    // The input signal is only assigned using `<--`.
+    UnconstrainedInput,
+    // The output signal is only read in assignments using `<--`.
+    UnconstrainedOutput,
+}
+
+struct UnconstrainedComponentSignalWarning {
+    // Location of template instantiation.
+    file_id: Option<FileID>,
+    file_location: FileLocation,
+    // The currently analyzed template.
+    current_template: String,
+    // The instantiated template.
+    component_template: String,
+    // The name of the signal.
+    signal_name: String,
+    issue: SignalIssue,
+}
+
+impl UnconstrainedComponentSignalWarning {
+    pub fn into_report(self) -> Report {
+        use SignalIssue::*;
+        let message = match self.issue {
+            UnassignedInput => format!(
+                "The input signal `{}` defined by the template `{}` is not assigned in `{}`.",
+                self.signal_name, self.component_template, self.current_template
+            ),
+            UnconstrainedInput | UnconstrainedOutput => format!(
+                "The {} signal `{}` defined by the template `{}` is not constrained in `{}`.",
+                if self.issue == UnconstrainedInput { "input" } else { "output" },
+                self.signal_name,
+                self.component_template,
+                self.current_template
+            ),
+        };
+        let mut report = Report::warning(message, ReportCode::UnconstrainedComponentSignal);
+        if let Some(file_id) = self.file_id {
+            report.add_primary(
+                self.file_location,
+                file_id,
+                format!("The template `{}` is instantiated here.", self.component_template),
+            );
+        }
+        match self.issue {
+            UnassignedInput => {}
+            UnconstrainedInput => {
+                report.add_note(format!(
+                    "The signal `{}` is only assigned using `<--`. Consider if it is possible to assign it using `<==` instead.",
+                    self.signal_name
+                ));
+            }
+            UnconstrainedOutput => {
+                report.add_note(format!(
+                    "The signal `{}` is only used in signal assignments using `<--`.",
+                    self.signal_name
+                ));
+            }
+        }
+        report
+    }
+}
+
+struct ComponentData {
+    pub meta: Meta,
+    pub var_name: VariableName,
+    pub var_access: Vec<AccessType>,
+    pub template_name: String,
+}
+
+/// Accesses to component signals, partitioned by how they are used.
+#[derive(Default)]
+struct SignalAccesses {
+    // Signals occurring in constraints (including `<==` assignments).
+    constrained: Vec<VariableAccess>,
+    // Signals occurring in signal assignments using `<--`.
+    unconstrained: Vec<VariableAccess>,
+    // Signals occurring elsewhere (e.g. in variable assignments).
+    other: Vec<VariableAccess>,
+}
+
+/// Inputs to instantiated components must be assigned and constrained by the
+/// current template. Outputs from instantiated components are typically also
+/// constrained, so outputs that are only used to assign other signals using
+/// `<--` are flagged as well. (Outputs that are not accessed at all are flagged
+/// by the unused output signal analysis pass.)
+pub fn find_unconstrained_component_signals(
+    context: &dyn AnalysisContext,
+    current_cfg: &Cfg,
+) -> ReportCollection {
+    // Exit early if the given CFG represents a function.
+    if matches!(current_cfg.definition_type(), DefinitionType::Function) {
+        return ReportCollection::new();
+    }
+    debug!("running unconstrained component signal analysis pass");
+
+    // Collect all instantiated components and component signal accesses.
+    let mut components = Vec::new();
+    let mut accesses = SignalAccesses::default();
+    for basic_block in current_cfg.iter() {
+        for stmt in basic_block.iter() {
+            visit_statement(stmt, current_cfg, &mut components, &mut accesses);
+        }
+    }
+    let mut reports = ReportCollection::new();
+    for component in components {
+        let Ok(component_cfg) = context.template(&component.template_name) else {
+            continue;
+        };
+        let signals = component_cfg
+            .input_signals()
+            .map(|name| (name, true))
+            .chain(component_cfg.output_signals().map(|name| (name, false)));
+        for (signal_name, is_input) in signals {
+            let Some(declaration) = component_cfg.get_declaration(signal_name) else {
+                continue;
+            };
+            let signal_access = VariableAccess::component_signal(
+                &component.var_name,
+                &component.var_access,
+                signal_name.name(),
+                declaration.dimensions().len(),
+            );
+            if maybe_accesses(&accesses.constrained, &signal_access) {
+                continue;
+            }
+            let issue = match (is_input, maybe_accesses(&accesses.unconstrained, &signal_access)) {
+                (true, false) if !maybe_accesses(&accesses.other, &signal_access) => {
+                    SignalIssue::UnassignedInput
+                }
+                (true, true) => SignalIssue::UnconstrainedInput,
+                (false, true) if !maybe_accesses(&accesses.other, &signal_access) => {
+                    SignalIssue::UnconstrainedOutput
+                }
+                _ => continue,
+            };
+            reports.push(build_report(
+                &component.meta,
+                current_cfg.name(),
+                &component.template_name,
+                signal_name.name(),
+                issue,
+            ));
+        }
+    }
+    debug!("{} new reports generated", reports.len());
+    reports
+}
+
+fn visit_statement(
+    stmt: &Statement,
+    cfg: &Cfg,
+    components: &mut Vec<ComponentData>,
+    accesses: &mut SignalAccesses,
+) {
+    use Statement::*;
+    use Expression::*;
+    match stmt {
+        Substitution { var: var_name, op, rhe, .. } => {
+            let (var_access, rhe) = match rhe {
+                Update { access, rhe, .. } => (access.clone(), rhe.as_ref()),
+                _ => (Vec::new(), rhe),
+            };
+            // Collect all instantiated components.
+            if let (Some(VariableType::Component), Call { meta, name: template_name, .. }) =
+                (cfg.get_type(var_name), rhe)
+            {
+                components.push(ComponentData {
+                    meta: meta.clone(),
+                    var_name: var_name.clone(),
+                    var_access: var_access.clone(),
+                    template_name: template_name.clone(),
+                });
+            }
+            let accesses = match op {
+                AssignOp::AssignConstraintSignal => &mut accesses.constrained,
+                AssignOp::AssignSignal => &mut accesses.unconstrained,
+                AssignOp::AssignLocalOrComponent => &mut accesses.other,
+            };
+            // Collect assigned component signals.
+            if var_access.iter().any(|access| matches!(access, AccessType::ComponentAccess(_))) {
+                accesses.push(VariableAccess::new(var_name, &var_access));
+            }
+            visit_expression(rhe, accesses);
+        }
+        ConstraintEquality { lhe, rhe, .. } => {
+            visit_expression(lhe, &mut accesses.constrained);
+            visit_expression(rhe, &mut accesses.constrained);
+        }
+        Return { value, .. } => visit_expression(value, &mut accesses.other),
+        Declaration { .. } => { /* We ignore dimensions in declarations. */ }
+        IfThenElse { .. } => { /* We ignore if-statement conditions. */ }
+        LogCall { .. } => { /* We ignore log statements. */ }
+        Assert { .. } => { /* We ignore asserts. */ }
+    }
+}
+
+fn visit_expression(expr: &Expression, accesses: &mut Vec<VariableAccess>) {
+    use Expression::*;
+    match expr {
+        PrefixOp { rhe, .. } => {
+            visit_expression(rhe, accesses);
+        }
+        InfixOp { lhe, rhe, .. } => {
+            visit_expression(lhe, accesses);
+            visit_expression(rhe, accesses);
+        }
+        SwitchOp { cond, if_true, if_false, .. } => {
+            visit_expression(cond, accesses);
+            visit_expression(if_true, accesses);
+            visit_expression(if_false, accesses);
+        }
+        Call { args, .. } => {
+            for arg in args {
+                visit_expression(arg, accesses);
+            }
+        }
+        InlineArray { values, .. } => {
+            for value in values {
+                visit_expression(value, accesses);
+            }
+        }
+        Access { var, access, .. } => {
+            accesses.push(VariableAccess::new(var, access));
+        }
+        Update { rhe, .. } => {
+            visit_expression(rhe, accesses);
+        }
+        Variable { .. } | Number(_, _) | Phi { .. } => (),
+    }
+}
+
+fn build_report(
+    meta: &Meta,
+    current_template: &str,
+    component_template: &str,
+    signal_name: &str,
+    issue: SignalIssue,
+) -> Report {
+    UnconstrainedComponentSignalWarning {
+        file_id: meta.file_id(),
+        file_location: meta.file_location(),
+        current_template: current_template.to_string(),
+        component_template: component_template.to_string(),
+        signal_name: signal_name.to_string(),
+        issue,
+    }
+    .into_report()
+}
+
+#[cfg(test)]
+mod tests {
+    use program_structure::constants::Curve;
+
+    use crate::analysis_runner::AnalysisRunner;
+
+    use super::*;
+
+    const TEST: &str = r#"
+        template Test() {
+            signal input in[2];
+            signal output out;
+
+            out <== in[0] * in[1];
+        }
+    "#;
+
+    #[test]
+    fn test_unconstrained_component_inputs() {
+        // All signals are constrained.
+        let src = [
+            TEST,
+            r#"
+            template Main() {
+                signal input in[2];
+                signal output out;
+
+                component test = Test();
+                test.in <== in;
+                out <== test.out;
+            }
+        "#,
+        ];
+        validate_reports("Main", &src, 0);
+
+        // The input signal `test.in` is never assigned.
+        let src = [
+            TEST,
+            r#"
+            template Main() {
+                signal output out;
+
+                component test = Test();
+                out <== test.out;
+            }
+        "#,
+        ];
+        validate_reports("Main", &src, 1);
+
+        // The input signals of `test[1]` are only assigned using `<--`.
+        let src = [
+            TEST,
+            r#"
+            template Main() {
+                signal input in[2];
+                signal output out[2];
+
+                component test[2];
+                test[0] = Test();
+                test[1] = Test();
+                out[0] <== test[0].out;
+                out[1] <== test[1].out;
+                test[0].in[0] <== in[0];
+                test[0].in[1] <== in[1];
+                test[1].in[0] <-- in[0];
+                test[1].in[1] <-- in[1];
+            }
+        "#,
+        ];
+        validate_reports("Main", &src, 1);
+
+        // Input signals assigned using `<--` may be constrained separately.
+        let src = [
+            TEST,
+            r#"
+            template Main() {
+                signal input in[2];
+                signal output out;
+
+                component test = Test();
+                test.in <-- in;
+                test.in === in;
+                out <== test.out;
+            }
+        "#,
+        ];
+        validate_reports("Main", &src, 0);
+
+        // If the template is not known we should not produce a report.
+        let src = [r#"
+            template Main() {
+                signal output out;
+
+                component test = Test();
+                out <== test.out;
+            }
+        "#];
+        validate_reports("Main", &src, 0);
+    }
+
+    #[test]
+    fn test_unconstrained_component_outputs() {
+        // The output signal `test.out` is only used to assign `out` using `<--`.
+        let src = [
+            TEST,
+            r#"
+            template Main() {
+                signal input in[2];
+                signal output out;
+
+                component test = Test();
+                test.in <== in;
+                out <-- test.out;
+            }
+        "#,
+        ];
+        validate_reports("Main", &src, 1);
+
+        // The output signal `test.out` is used in a constraint.
+        let src = [
+            TEST,
+            r#"
+            template Main() {
+                signal input in[2];
+                signal output out;
+
+                component test = Test();
+                test.in <== in;
+                out <-- test.out;
+                out === test.out;
+            }
+        "#,
+        ];
+        validate_reports("Main", &src, 0);
+    }
+
+    #[test]
+    fn test_bus_component_signals() {
+        const POINT: &str = r#"
+            bus Point() {
+                signal x;
+                signal y;
+            }
+        "#;
+        const DOUBLE: &str = r#"
+            template Double() {
+                input Point() in;
+                output Point() out;
+
+                out.x <== 2 * in.x;
+                out.y <== 2 * in.y;
+            }
+        "#;
+        // The bus input `double.in` is constrained field by field, and the bus
+        // output `double.out` is constrained as a whole.
+        let src = [
+            POINT,
+            DOUBLE,
+            r#"
+            template Main() {
+                input Point() in;
+                output Point() out;
+
+                component double = Double();
+                double.in.x <== in.x;
+                double.in.y <== in.y;
+                out <== double.out;
+            }
+        "#,
+        ];
+        validate_reports("Main", &src, 0);
+
+        // The bus input `double.in` is only assigned using `<--`, and the bus
+        // output `double.out` is only used to assign `out` using `<--`.
+        let src = [
+            POINT,
+            DOUBLE,
+            r#"
+            template Main() {
+                input Point() in;
+                output Point() out;
+
+                component double = Double();
+                double.in <-- in;
+                out.x <-- double.out.x;
+                out.y <== in.y;
+            }
+        "#,
+        ];
+        validate_reports("Main", &src, 2);
+    }
+
+    fn validate_reports(name: &str, src: &[&str], expected_len: usize) {
+        let context = AnalysisRunner::new(Curve::Goldilocks).with_src(src);
+        let cfg = context.template(name).unwrap();
+        let reports = find_unconstrained_component_signals(&context, &cfg);
+        assert_eq!(reports.len(), expected_len);
+    }
+}
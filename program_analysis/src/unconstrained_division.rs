@@ -11,11 +11,17 @@ use program_structure::report_code::ReportCode;
 use program_structure::report::{Report, ReportCollection};
 use program_structure::file_definition::{FileID, FileLocation};
 use program_structure::ir::*;
+use program_structure::ir::variable_meta::{VariableMeta, VariableUse};
+
+use crate::analysis_context::AnalysisContext;
+use crate::taint_analysis::TaintAnalysis;
+use crate::witness_path::add_code_flow;
 
 pub struct UnconstrainedDivisionWarning {
     divisor: Expression,
     file_id: Option<FileID>,
     file_location: FileLocation,
+    code_flow: Vec<VariableUse>,
 }
 
 impl UnconstrainedDivisionWarning {
@@ -30,6 +36,10 @@ impl UnconstrainedDivisionWarning {
                 file_id,
                 format!("The divisor `{}` must be constrained to be non-zero.", self.divisor),
             );
+            if let Some(input) = self.code_flow.first() {
+                let description = format!("The input signal `{}` is declared here.", input.name());
+                add_code_flow(&mut report, &self.code_flow, description);
+            }
         }
         report
     }
@@ -96,8 +106,9 @@ impl Component {
 ///
 /// This analysis pass looks for signal assignments on the form `c <-- a / b`
 /// where the signal `b` is not constrained to be non-zero using the `IsZero`
-/// circuit from Circomlib.
-pub fn find_unconstrained_division(cfg: &Cfg) -> ReportCollection {
+/// circuit from Circomlib. If the divisor depends on an input signal, the
+/// report explains how the input flows into the divisor.
+pub fn find_unconstrained_division(context: &dyn AnalysisContext, cfg: &Cfg) -> ReportCollection {
     debug!("running unconstrained divisor analysis pass");
     let mut reports = ReportCollection::new();
     let mut divisors = Vec::new();
@@ -116,6 +127,22 @@ pub fn find_unconstrained_division(cfg: &Cfg) -> ReportCollection {
             update_constraints(stmt, &mut constraints);
         }
     }
+    // Use taint analysis to explain how input signals flow into divisors.
+    let taint_analysis = context.analysis::<TaintAnalysis>(cfg);
+    let mut inputs = cfg
+        .variables()
+        .filter(|name| {
+            matches!(
+                cfg.get_type(name),
+                Some(
+                    VariableType::Signal(SignalType::Input, _)
+                        | VariableType::Bus(_, SignalType::Input, _)
+                )
+            )
+        })
+        .collect::<Vec<_>>();
+    inputs.sort();
+
     for divisor in divisors {
         let mut non_zero = false;
         for constraint in constraints.values() {
@@ -125,7 +152,8 @@ pub fn find_unconstrained_division(cfg: &Cfg) -> ReportCollection {
             }
         }
         if !non_zero {
-            reports.push(build_report(&divisor));
+            let code_flow = find_input_path(&taint_analysis, &inputs, &divisor);
+            reports.push(build_report(&divisor, code_flow));
         }
     }
     debug!("{} new reports generated", reports.len());
@@ -238,12 +266,35 @@ fn update_constraints(stmt: &Statement, constraints: &mut HashMap<VariableAccess
     }
 }
 
+/// Returns the first path (in sorted order) from an input signal to a variable
+/// read by the divisor. Paths consisting of a single step are ignored since
+/// these are already explained by the report.
+fn find_input_path(
+    taint_analysis: &TaintAnalysis,
+    inputs: &[&VariableName],
+    divisor: &Expression,
+) -> Vec<VariableUse> {
+    let mut sinks = divisor.variables_read().map(|var| var.name().clone()).collect::<Vec<_>>();
+    sinks.sort();
+    for sink in &sinks {
+        for input in inputs {
+            if let Some(path) = taint_analysis.taint_path(input, sink) {
+                if path.len() > 1 {
+                    return path;
+                }
+            }
+        }
+    }
+    Vec::new()
+}
+
 #[must_use]
-fn build_report(divisor: &Expression) -> Report {
+fn build_report(divisor: &Expression, code_flow: Vec<VariableUse>) -> Report {
     UnconstrainedDivisionWarning {
         divisor: divisor.clone(),
         file_id: divisor.meta().file_id,
         file_location: divisor.meta().file_location(),
+        code_flow,
     }
     .into_report()
 }
@@ -258,6 +309,8 @@ mod tests {
     use parser::parse_definition;
     use program_structure::{cfg::IntoCfg, constants::Curve};
 
+    use crate::analysis_runner::AnalysisRunner;
+
     use super::*;
 
     #[test]
@@ -365,6 +418,49 @@ mod tests {
         validate_reports(src, 1);
     }
 
+    #[test]
+    fn test_code_flow() {
+        let src = r#"
+            template T() {
+              signal input a;
+              signal input b;
+              signal tmp;
+              signal output c;
+
+              tmp <== b * b;
+              c <-- a / tmp;
+              c * tmp === a;
+            }
+        "#;
+        let runner = AnalysisRunner::new(Curve::default()).with_src(&[src]);
+        let cfg = runner.template("T").unwrap();
+        let reports = find_unconstrained_division(&runner, &cfg);
+        assert_eq!(reports.len(), 1);
+        let messages =
+            reports[0].code_flow().iter().map(|step| step.message.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec!["The input signal `b` is declared here.", "`tmp` depends on `b` here."]
+        );
+
+        // Divisors which are input signals do not require an explanation.
+        let src = r#"
+            template T() {
+              signal input a;
+              signal input b;
+              signal output c;
+
+              c <-- a / b;
+              c * b === a;
+            }
+        "#;
+        let runner = AnalysisRunner::new(Curve::default()).with_src(&[src]);
+        let cfg = runner.template("T").unwrap();
+        let reports = find_unconstrained_division(&runner, &cfg);
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].code_flow().is_empty());
+    }
+
     fn validate_reports(src: &str, expected_len: usize) {
         // Build CFG.
         let mut reports = ReportCollection::new();
@@ -377,7 +473,7 @@ mod tests {
         assert!(reports.is_empty());
 
         // Generate report collection.
-        let reports = find_unconstrained_division(&cfg);
+        let reports = find_unconstrained_division(&AnalysisRunner::new(Curve::default()), &cfg);
         assert_eq!(reports.len(), expected_len);
     }
 }
@@ -0,0 +1,427 @@
+use log::debug;
+use std::collections::{BTreeMap, BTreeSet};
+
+use program_structure::cfg::{Cfg, DefinitionType};
+use program_structure::report_code::ReportCode;
+use program_structure::report::{Report, ReportCollection};
+use program_structure::file_definition::{FileID, FileLocation};
+
+use crate::analysis_context::AnalysisContext;
+use crate::polynomial::Variable;
+use crate::symbolic_execution::{build_constraint_system, ConstraintSystem, SymbolicError};
+
+/// The maximum number of signals listed in each witness.
+const MAX_WITNESS_ENTRIES: usize = 8;
+
+pub struct UnderConstrainedOutputSignalWarning {
+    template_name: String,
+    signal_name: String,
+    file_id: Option<FileID>,
+    file_location: FileLocation,
+    // The undetermined signal array elements.
+    signal_elements: Vec<String>,
+    // Constraints containing the signal.
+    constraints: Vec<(Option<FileID>, FileLocation, String)>,
+    // Input signals of the template.
+    input_signals: Vec<String>,
+    // Undetermined signals occurring in the same constraints as the signal.
+    related_signals: Vec<String>,
+}
+
+impl UnderConstrainedOutputSignalWarning {
+    pub fn into_report(self) -> Report {
+        let mut report = Report::warning(
+            format!(
+                "The output signal `{}` is not uniquely determined by the inputs of `{}`.",
+                self.signal_name, self.template_name
+            ),
+            ReportCode::UnderConstrainedOutputSignal,
+        );
+        if let Some(file_id) = self.file_id {
+            report.add_primary(
+                self.file_location,
+                file_id,
+                format!("The signal `{}` is declared here.", self.signal_name),
+            );
+        }
+        for (file_id, file_location, constraint) in self.constraints {
+            if let Some(file_id) = file_id {
+                report.add_secondary(
+                    file_location,
+                    file_id,
+                    Some(format!("The signal occurs in the constraint `{constraint} = 0` here.")),
+                );
+            }
+        }
+        // Both witnesses assign the same values to the inputs, and (possibly)
+        // different values to the undetermined signals.
+        let inputs = summarize(&self.input_signals);
+        let signals = summarize(&self.signal_elements);
+        let mut undetermined = signals.clone();
+        undetermined.extend(summarize(&self.related_signals));
+        let first = format_witness(&inputs, &undetermined, |name| name.to_string());
+        let second = format_witness(&inputs, &undetermined, prime);
+        let differences = signals
+            .iter()
+            .map(|name| format!("`{}` may differ from `{name}`", prime(name)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        report.add_note(format!(
+            "Circomspect could not rule out the two witnesses `w = {first}` and `w' = {second}` satisfying all constraints, where {differences}."
+        ));
+        report
+    }
+}
+
+/// Summarizes a list of signal names by replacing consecutive array elements
+/// like `out[0]`, `out[1]`, ..., `out[7]` with `out[0..7]`.
+fn summarize(names: &[String]) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current: Option<(&str, usize, usize)> = None;
+    for name in names {
+        let element = name
+            .strip_suffix(']')
+            .and_then(|name| name.rsplit_once('['))
+            .and_then(|(prefix, index)| Some((prefix, index.parse::<usize>().ok()?)));
+        match (current, element) {
+            (Some((prefix, first, last)), Some((next_prefix, index)))
+                if prefix == next_prefix && index == last + 1 =>
+            {
+                current = Some((prefix, first, index));
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(range) = current.take() {
+            result.push(format_range(range));
+        }
+        match element {
+            Some((prefix, index)) => current = Some((prefix, index, index)),
+            None => result.push(name.clone()),
+        }
+    }
+    if let Some(range) = current {
+        result.push(format_range(range));
+    }
+    result
+}
+
+fn format_range((prefix, first, last): (&str, usize, usize)) -> String {
+    if first == last {
+        format!("{prefix}[{first}]")
+    } else {
+        format!("{prefix}[{first}..{last}]")
+    }
+}
+
+/// Returns the name of the symbolic value of the signal in the second witness.
+/// E.g. `out'[0..7]` for `out[0..7]`.
+fn prime(name: &str) -> String {
+    match name.find('[') {
+        Some(index) => format!("{}'{}", &name[..index], &name[index..]),
+        None => format!("{name}'"),
+    }
+}
+
+fn format_witness(
+    inputs: &[String],
+    undetermined: &[String],
+    value: impl Fn(&str) -> String,
+) -> String {
+    let entries = inputs
+        .iter()
+        .map(|name| format!("{name}: {name}"))
+        .chain(undetermined.iter().map(|name| format!("{name}: {}", value(name))))
+        .collect::<Vec<_>>();
+    if entries.len() > MAX_WITNESS_ENTRIES {
+        let rest = entries.len() - MAX_WITNESS_ENTRIES;
+        format!("{{{}, ... ({rest} more)}}", entries[..MAX_WITNESS_ENTRIES].join(", "))
+    } else {
+        format!("{{{}}}", entries.join(", "))
+    }
+}
+
+pub struct UnanalyzedTemplateInfo {
+    template_name: String,
+    error: SymbolicError,
+}
+
+impl UnanalyzedTemplateInfo {
+    pub fn into_report(self) -> Report {
+        if let SymbolicError::UnsupportedBus { name } = &self.error {
+            let mut report = Report::info(
+                format!(
+                    "The template `{}` was not analyzed since buses are not supported.",
+                    self.template_name
+                ),
+                ReportCode::UnderConstrainedOutputSignal,
+            );
+            report.add_note(format!(
+                "The signal `{name}` is a bus. The output signals of `{}` were not checked for uniqueness.",
+                self.template_name
+            ));
+            return report;
+        }
+        let mut report = Report::info(
+            format!(
+                "The output signals of `{}` could not be checked for uniqueness.",
+                self.template_name
+            ),
+            ReportCode::UnderConstrainedOutputSignal,
+        );
+        report.add_note(format!("Failed to build the constraint system: {}", self.error));
+        report
+    }
+}
+
+/// Output signals should be uniquely determined by the input signals of the
+/// template. This analysis pass builds the constraint system of each template
+/// using symbolic execution and generates a warning for each output signal that
+/// cannot be proven to be uniquely determined by the inputs.
+pub fn find_under_constrained_outputs(
+    context: &dyn AnalysisContext,
+    cfg: &Cfg,
+) -> ReportCollection {
+    // Exit early if the given CFG represents a function.
+    if matches!(cfg.definition_type(), DefinitionType::Function) {
+        return ReportCollection::new();
+    }
+    debug!("running under-constrained output signal analysis pass");
+    let system = match build_constraint_system(context, cfg) {
+        Ok(system) => system,
+        Err(error) => {
+            debug!("failed to build constraint system for `{}`: {error}", cfg.name());
+            let report = UnanalyzedTemplateInfo { template_name: cfg.name().to_string(), error }
+                .into_report();
+            return vec![report];
+        }
+    };
+    let determined = system.determined_variables();
+
+    // Group undetermined output signal array elements by signal name.
+    let mut undetermined: BTreeMap<&str, Vec<Variable>> = BTreeMap::new();
+    for var in system.outputs().filter(|var| !determined.contains(var)) {
+        undetermined.entry(system.get_variable(var).signal_name()).or_default().push(var);
+    }
+    let mut reports = ReportCollection::new();
+    for vars in undetermined.values() {
+        reports.push(build_report(&system, &determined, vars));
+    }
+    debug!("{} new reports generated", reports.len());
+    reports
+}
+
+fn build_report(
+    system: &ConstraintSystem,
+    determined: &BTreeSet<Variable>,
+    vars: &[Variable],
+) -> Report {
+    let signal = system.get_variable(vars[0]);
+    let vars = vars.iter().copied().collect::<BTreeSet<_>>();
+    let constraints = system
+        .constraints()
+        .iter()
+        .filter(|constraint| !constraint.polynomial().variables().is_disjoint(&vars))
+        .collect::<Vec<_>>();
+    let related_signals = constraints
+        .iter()
+        .flat_map(|constraint| constraint.polynomial().variables())
+        .filter(|var| !vars.contains(var) && !determined.contains(var))
+        .collect::<BTreeSet<_>>();
+    let constraints = constraints
+        .iter()
+        .map(|constraint| {
+            (
+                constraint.file_id(),
+                constraint.file_location(),
+                system.format(constraint.polynomial()),
+            )
+        })
+        .collect();
+    UnderConstrainedOutputSignalWarning {
+        template_name: system.name().to_string(),
+        signal_name: signal.signal_name().to_string(),
+        file_id: signal.file_id(),
+        file_location: signal.file_location(),
+        signal_elements: vars
+            .iter()
+            .map(|var| system.get_variable(*var).name().to_string())
+            .collect(),
+        constraints,
+        input_signals: system
+            .inputs()
+            .map(|var| system.get_variable(var).name().to_string())
+            .collect(),
+        related_signals: related_signals
+            .iter()
+            .map(|var| system.get_variable(*var).name().to_string())
+            .collect(),
+    }
+    .into_report()
+}
+
+#[cfg(test)]
+mod tests {
+    use program_structure::constants::Curve;
+    use program_structure::report::MessageCategory;
+
+    use crate::analysis_runner::AnalysisRunner;
+
+    use super::*;
+
+    #[test]
+    fn test_under_constrained_outputs() {
+        let src = [r#"
+            template Num2Bits() {
+                signal input in;
+                signal output out[4];
+
+                var lc = 0;
+                var e = 1;
+                for (var i = 0; i < 4; i++) {
+                    out[i] <-- (in >> i) & 1;
+                    out[i] * (out[i] - 1) === 0;
+                    lc += out[i] * e;
+                    e = e + e;
+                }
+                lc === in;
+            }
+        "#];
+        validate_reports("Num2Bits", &src, 0);
+
+        let src = [r#"
+            template IsZero() {
+                signal input in;
+                signal output out;
+                signal inv;
+
+                inv <-- in != 0 ? 1 / in : 0;
+                out <== -in * inv + 1;
+                in * out === 0;
+            }
+        "#];
+        validate_reports("IsZero", &src, 0);
+
+        let src = [r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                out <-- in + 1;
+            }
+        "#];
+        validate_reports("T", &src, 1);
+
+        // The output of `c` is not determined since the input is unconstrained.
+        let src = [
+            r#"
+            template Square() {
+                signal input in;
+                signal output out;
+
+                out <== in * in;
+            }
+        "#,
+            r#"
+            template T() {
+                signal input in;
+                signal output out;
+
+                component c = Square();
+                c.in <-- in;
+                out <== c.out;
+            }
+        "#,
+        ];
+        validate_reports("T", &src, 1);
+    }
+
+    #[test]
+    fn test_witness_note() {
+        let src = [r#"
+            template T() {
+                signal input in;
+                signal output out[254];
+
+                for (var i = 0; i < 254; i++) {
+                    out[i] <-- in;
+                }
+            }
+        "#];
+        let context = AnalysisRunner::new(Curve::Goldilocks).with_src(&src);
+        let cfg = context.template("T").unwrap();
+        let reports = find_under_constrained_outputs(&context, &cfg);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(
+            reports[0].notes(),
+            &["Circomspect could not rule out the two witnesses `w = {in: in, out[0..253]: out[0..253]}` and `w' = {in: in, out[0..253]: out'[0..253]}` satisfying all constraints, where `out'[0..253]` may differ from `out[0..253]`.".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_unanalyzed_template() {
+        // The constraint system cannot be built for the generic template.
+        let src = [r#"
+            template T(n) {
+                signal input in;
+                signal output out[n];
+
+                for (var i = 0; i < n; i++) {
+                    out[i] <== in;
+                }
+            }
+        "#];
+        let context = AnalysisRunner::new(Curve::Goldilocks).with_src(&src);
+        let cfg = context.template("T").unwrap();
+        let reports = find_under_constrained_outputs(&context, &cfg);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].category(), &MessageCategory::Info);
+    }
+
+    #[test]
+    fn test_unsupported_bus() {
+        let src = [
+            r#"
+            bus Point() {
+                signal x;
+                signal y;
+            }
+        "#,
+            r#"
+            template T() {
+                signal input in;
+                output Point() p;
+
+                p.x <== in;
+                p.y <== in;
+            }
+        "#,
+        ];
+        let context = AnalysisRunner::new(Curve::Goldilocks).with_src(&src);
+        let cfg = context.template("T").unwrap();
+        let reports = find_under_constrained_outputs(&context, &cfg);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].category(), &MessageCategory::Info);
+        assert_eq!(
+            reports[0].message(),
+            "The template `T` was not analyzed since buses are not supported."
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        let names =
+            ["in", "out[0]", "out[1]", "out[2]", "out[4]", "c.out[5]", "c.out[6]", "x[0][1]"]
+                .map(ToString::to_string);
+        assert_eq!(summarize(&names), ["in", "out[0..2]", "out[4]", "c.out[5..6]", "x[0][1]"]);
+        assert_eq!(prime("c.out[5..6]"), "c.out'[5..6]");
+        assert_eq!(prime("in"), "in'");
+    }
+
+    fn validate_reports(name: &str, src: &[&str], expected_len: usize) {
+        let context = AnalysisRunner::new(Curve::Goldilocks).with_src(src);
+        let cfg = context.template(name).unwrap();
+        let reports = find_under_constrained_outputs(&context, &cfg);
+        assert_eq!(reports.len(), expected_len);
+    }
+}
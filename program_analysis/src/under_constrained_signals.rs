@@ -1,20 +1,22 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use log::debug;
 use program_structure::cfg::Cfg;
 use program_structure::file_definition::{FileID, FileLocation};
-use program_structure::intermediate_representation::variable_meta::VariableMeta;
+use program_structure::intermediate_representation::variable_meta::{VariableMeta, VariableUse};
 use program_structure::report::{ReportCollection, Report};
 use program_structure::ir::*;
 use program_structure::report_code::ReportCode;
 
-use crate::taint_analysis::{run_taint_analysis, TaintAnalysis};
-
-const MIN_CONSTRAINT_COUNT: usize = 2;
+use crate::analysis_context::AnalysisContext;
+use crate::taint_analysis::TaintAnalysis;
+use crate::witness_path::add_code_flow;
 
 #[derive(PartialEq, Eq, Hash)]
 enum ConstraintLocation {
-    Ordinary(FileLocation),
+    /// The location of the constraint, together with a variable in the
+    /// constraint tainted by the signal.
+    Ordinary(FileLocation, VariableName),
     Loop,
 }
 
@@ -22,7 +24,7 @@ impl ConstraintLocation {
     fn file_location(&self) -> Option<FileLocation> {
         use ConstraintLocation::*;
         match self {
-            Ordinary(file_location) => Some(file_location.clone()),
+            Ordinary(file_location, _) => Some(file_location.clone()),
             Loop => None,
         }
     }
@@ -36,6 +38,7 @@ pub struct UnderConstrainedSignalWarning {
     file_id: Option<FileID>,
     primary_location: FileLocation,
     secondary_location: Option<FileLocation>,
+    code_flow: Vec<VariableUse>,
 }
 
 impl UnderConstrainedSignalWarning {
@@ -46,6 +49,11 @@ impl UnderConstrainedSignalWarning {
             ReportCode::UnderConstrainedSignal,
         );
         if let Some(file_id) = self.file_id {
+            // The path starts at either the declaration or the definition of the signal.
+            let is_declaration = self
+                .code_flow
+                .first()
+                .map_or(false, |start| start.meta().file_location() == self.primary_location);
             if self.dimensions.is_empty() {
                 report.add_primary(
                     self.primary_location,
@@ -79,6 +87,15 @@ impl UnderConstrainedSignalWarning {
                     );
                 }
             }
+            // Explain how the signal flows into the constraint.
+            if !self.code_flow.is_empty() {
+                let description = if is_declaration {
+                    format!("The intermediate signal `{}` is declared here.", self.name)
+                } else {
+                    format!("The intermediate signal `{}` is assigned here.", self.name)
+                };
+                add_code_flow(&mut report, &self.code_flow, description);
+            }
         }
         report
     }
@@ -87,18 +104,26 @@ impl UnderConstrainedSignalWarning {
 // Intermediate signals should occur in at least two separate constraints. One
 // to define the value of the signal and one to constrain an input or output
 // signal.
-pub fn find_under_constrained_signals(cfg: &Cfg) -> ReportCollection {
+pub fn find_under_constrained_signals(
+    context: &dyn AnalysisContext,
+    cfg: &Cfg,
+) -> ReportCollection {
     debug!("running under-constrained signals analysis pass");
 
-    // Run taint analysis to be able to track data flow.
-    let taint_analysis = run_taint_analysis(cfg);
+    // Use taint analysis to be able to track data flow.
+    let taint_analysis = context.analysis::<TaintAnalysis>(cfg);
 
     // Compute the set of intermediate signals.
     let mut constraint_locations = cfg
         .variables()
         .filter_map(|name| {
-            if matches!(cfg.get_type(name), Some(VariableType::Signal(SignalType::Intermediate, _)))
-            {
+            if matches!(
+                cfg.get_type(name),
+                Some(
+                    VariableType::Signal(SignalType::Intermediate, _)
+                        | VariableType::Bus(_, SignalType::Intermediate, _)
+                )
+            ) {
                 Some((name.clone(), Vec::new()))
             } else {
                 None
@@ -121,10 +146,19 @@ pub fn find_under_constrained_signals(cfg: &Cfg) -> ReportCollection {
     // Generate reports.
     let mut reports = ReportCollection::new();
     for (signal, locations) in constraint_locations {
-        if locations.len() < MIN_CONSTRAINT_COUNT && !locations.contains(&ConstraintLocation::Loop)
+        if locations.len() < context.settings().min_constraint_count
+            && !locations.contains(&ConstraintLocation::Loop)
         {
             let secondary_location =
                 locations.first().and_then(|location| location.file_location());
+            // Compute the path from the signal to the tainted variable in the constraint.
+            let code_flow = match locations.first() {
+                Some(ConstraintLocation::Ordinary(_, sink)) => taint_analysis
+                    .taint_path(&signal, sink)
+                    .filter(|path| path.len() > 1)
+                    .unwrap_or_default(),
+                _ => Vec::new(),
+            };
             if let Some(declaration) = cfg.get_declaration(&signal) {
                 reports.push(build_report(
                     &signal,
@@ -132,6 +166,7 @@ pub fn find_under_constrained_signals(cfg: &Cfg) -> ReportCollection {
                     declaration.file_id(),
                     declaration.file_location(),
                     secondary_location,
+                    code_flow,
                 ))
             }
         }
@@ -153,13 +188,29 @@ fn visit_statement(
         // statement occurs in a loop, we consider the minimum count to be
         // reached immediately.
         Substitution { meta, op: AssignConstraintSignal, .. } | ConstraintEquality { meta, .. } => {
-            let sinks = stmt.variables_used().map(|var| var.name().clone()).collect::<HashSet<_>>();
+            // Variables read by the statement are preferred over variables written
+            // when recording the sink. Each set is sorted to ensure that the
+            // recorded sink is deterministic.
+            // (Note that the signal assigned using `<==` is also considered read.)
+            let mut writes =
+                stmt.variables_written().map(|var| var.name().clone()).collect::<Vec<_>>();
+            let mut reads = stmt
+                .variables_read()
+                .map(|var| var.name().clone())
+                .filter(|name| !writes.contains(name))
+                .collect::<Vec<_>>();
+            reads.sort();
+            reads.dedup();
+            writes.sort();
+            let sinks = reads.into_iter().chain(writes).collect::<Vec<_>>();
             for (source, locations) in constraint_counts.iter_mut() {
-                if taint_analysis.taints_any(source, &sinks) {
+                let taint = taint_analysis.multi_step_taint(source);
+                if let Some(sink) = sinks.iter().find(|sink| taint.contains(sink)) {
                     if in_loop {
                         locations.push(ConstraintLocation::Loop);
                     } else {
-                        locations.push(ConstraintLocation::Ordinary(meta.file_location()))
+                        locations
+                            .push(ConstraintLocation::Ordinary(meta.file_location(), sink.clone()))
                     }
                 }
             }
@@ -174,6 +225,7 @@ fn build_report(
     file_id: Option<FileID>,
     primary_location: FileLocation,
     secondary_location: Option<FileLocation>,
+    code_flow: Vec<VariableUse>,
 ) -> Report {
     UnderConstrainedSignalWarning {
         name: signal.clone(),
@@ -181,6 +233,7 @@ fn build_report(
         file_id,
         primary_location,
         secondary_location,
+        code_flow,
     }
     .into_report()
 }
@@ -190,6 +243,8 @@ mod tests {
     use parser::parse_definition;
     use program_structure::{cfg::IntoCfg, constants::Curve};
 
+    use crate::analysis_runner::AnalysisRunner;
+
     use super::*;
 
     #[test]
@@ -257,6 +312,30 @@ mod tests {
         validate_reports(src, 0);
     }
 
+    #[test]
+    fn test_code_flow() {
+        let src = r#"
+            template T() {
+              signal input a;
+              signal b;
+              signal output c;
+
+              var d = 2 * b;
+              c <== a * d;
+            }
+        "#;
+        let runner = AnalysisRunner::new(Curve::default()).with_src(&[src]);
+        let cfg = runner.template("T").unwrap();
+        let reports = find_under_constrained_signals(&runner, &cfg);
+        assert_eq!(reports.len(), 1);
+        let messages =
+            reports[0].code_flow().iter().map(|step| step.message.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec!["The intermediate signal `b` is declared here.", "`d` depends on `b` here."]
+        );
+    }
+
     fn validate_reports(src: &str, expected_len: usize) {
         // Build CFG.
         let mut reports = ReportCollection::new();
@@ -269,7 +348,7 @@ mod tests {
         assert!(reports.is_empty());
 
         // Generate report collection.
-        let reports = find_under_constrained_signals(&cfg);
+        let reports = find_under_constrained_signals(&AnalysisRunner::new(Curve::default()), &cfg);
         assert_eq!(reports.len(), expected_len);
     }
 }
@@ -3,7 +3,6 @@ use std::collections::HashSet;
 
 use program_structure::{
     ir::*,
-    ir::value_meta::ValueMeta,
     report_code::ReportCode,
     cfg::{Cfg, DefinitionType},
     report::{Report, ReportCollection},
@@ -11,10 +10,7 @@ use program_structure::{
 };
 
 use crate::analysis_context::AnalysisContext;
-
-// Known templates that are commonly instantiated without accessing the
-// corresponding output signals.
-const ALLOW_LIST: [&str; 1] = ["Num2Bits"];
+use crate::variable_access::{maybe_accesses, VariableAccess};
 
 struct UnusedOutputSignalWarning {
     // Location of template instantiation.
@@ -48,87 +44,6 @@ impl UnusedOutputSignalWarning {
     }
 }
 
-#[derive(Clone, Debug)]
-struct VariableAccess {
-    pub var: VariableName,
-    pub access: Vec<AccessType>,
-}
-
-impl VariableAccess {
-    fn new(var: &VariableName, access: &[AccessType]) -> Self {
-        // We disregard the version to make sure accesses are not order dependent.
-        VariableAccess { var: var.without_version(), access: access.to_vec() }
-    }
-}
-
-/// A reflexive and symmetric relation capturing partial information about
-/// equality.
-trait MaybeEqual {
-    fn maybe_equal(&self, other: &Self) -> bool;
-}
-
-/// This is a reflexive and symmetric (but not transitive!) relation
-/// identifying all array accesses where the indices are not explicitly known
-/// to be different (e.g. from constant propagation). The relation is not
-/// transitive since `v[0] == v[i]` and `v[i] == v[1]`, but `v[0] != v[1]`.
-///
-/// Since `maybe_equal` is not transitive we cannot use it to define
-/// `PartialEq` for `VariableAccess`. This also means that we cannot use hash
-/// sets or hash maps to track variable accesses using this as our equality
-/// relation.
-impl MaybeEqual for VariableAccess {
-    fn maybe_equal(&self, other: &VariableAccess) -> bool {
-        use AccessType::*;
-        if self.var.name() != other.var.name() {
-            return false;
-        }
-        if self.access.len() != other.access.len() {
-            return false;
-        }
-        for (self_access, other_access) in self.access.iter().zip(other.access.iter()) {
-            match (self_access, other_access) {
-                (ArrayAccess(_), ComponentAccess(_)) => {
-                    return false;
-                }
-                (ComponentAccess(_), ArrayAccess(_)) => {
-                    return false;
-                }
-                (ComponentAccess(self_name), ComponentAccess(other_name))
-                    if self_name != other_name =>
-                {
-                    return false;
-                }
-                (ArrayAccess(self_index), ArrayAccess(other_index)) => {
-                    match (self_index.value(), other_index.value()) {
-                        (Some(self_value), Some(other_value)) if self_value != other_value => {
-                            return false;
-                        }
-                        // Identify all other array accesses.
-                        _ => {}
-                    }
-                }
-                // Identify all array accesses.
-                _ => {}
-            }
-        }
-        true
-    }
-}
-
-/// A relation capturing partial information about containment.
-trait MaybeContains<T> {
-    fn maybe_contains(&self, element: &T) -> bool;
-}
-
-impl<T> MaybeContains<T> for Vec<T>
-where
-    T: MaybeEqual,
-{
-    fn maybe_contains(&self, element: &T) -> bool {
-        self.iter().any(|item| item.maybe_equal(element))
-    }
-}
-
 struct ComponentData {
     pub meta: Meta,
     pub var_name: VariableName,
@@ -176,7 +91,7 @@ impl SignalData {
 }
 
 pub fn find_unused_output_signals(
-    context: &mut dyn AnalysisContext,
+    context: &dyn AnalysisContext,
     current_cfg: &Cfg,
 ) -> ReportCollection {
     // Exit early if the given CFG represents a function.
@@ -184,7 +99,8 @@ pub fn find_unused_output_signals(
         return ReportCollection::new();
     }
     debug!("running unused output signal analysis pass");
-    let allow_list = HashSet::from(ALLOW_LIST);
+    let allow_list =
+        context.settings().unused_output_signal_allow_list.iter().cloned().collect::<HashSet<_>>();
 
     // Collect all instantiated components.
     let mut components = Vec::new();
@@ -197,31 +113,18 @@ pub fn find_unused_output_signals(
     let mut output_signals = Vec::new();
     for component in components {
         // Ignore templates on the allow list.
-        if allow_list.contains(&component.template_name[..]) {
+        if allow_list.contains(&component.template_name) {
             continue;
         }
         if let Ok(component_cfg) = context.template(&component.template_name) {
             for output_signal in component_cfg.output_signals() {
                 if let Some(declaration) = component_cfg.get_declaration(output_signal) {
-                    // The signal access pattern is given by the component
-                    // access pattern, followed by the output signal name,
-                    // followed by an array access corresponding to each
-                    // dimension entry for the signal.
-                    //
-                    // E.g., for the component `c[i]` with an output signal
-                    // `out` which is a double array, we get `c[i].out[j][k]`.
-                    // Since we identify array accesses we simply use `i` for
-                    // each array access corresponding to the dimensions of the
-                    // signal.
-                    let mut var_access = component.var_access.clone();
-                    var_access.push(AccessType::ComponentAccess(output_signal.name().to_string()));
-                    for _ in declaration.dimensions() {
-                        let meta = Meta::new(&(0..0), &None);
-                        let index =
-                            Expression::Variable { meta, name: VariableName::from_string("i") };
-                        var_access.push(AccessType::ArrayAccess(Box::new(index)));
-                    }
-                    let signal_access = VariableAccess::new(&component.var_name, &var_access);
+                    let signal_access = VariableAccess::component_signal(
+                        &component.var_name,
+                        &component.var_access,
+                        output_signal.name(),
+                        declaration.dimensions().len(),
+                    );
                     output_signals.push(SignalData::new(
                         &component.meta,
                         &component.template_name,
@@ -248,27 +151,6 @@ pub fn find_unused_output_signals(
     reports
 }
 
-// Check if there is an access to a prefix of the output signal access which
-// contains the output signal name. E.g. for the output signal `n2b[1].out[0]`
-// it is enough that the list of all variable accesses `maybe_contains` the
-// prefix `n2b[1].out`. This is to catch instances where the template passes the
-// output signal as input to a function.
-fn maybe_accesses(accesses: &Vec<VariableAccess>, signal_access: &VariableAccess) -> bool {
-    use AccessType::*;
-    let mut signal_access = signal_access.clone();
-    while !accesses.maybe_contains(&signal_access) {
-        if let Some(ComponentAccess(_)) = signal_access.access.last() {
-            // The output signal name is the last component access in the access
-            // array. If it is not included in the access, the output signal is
-            // not accessed by the template.
-            return false;
-        } else {
-            signal_access.access.pop();
-        }
-    }
-    true
-}
-
 fn visit_statement(
     stmt: &Statement,
     cfg: &Cfg,
@@ -360,83 +242,11 @@ fn build_report(
 
 #[cfg(test)]
 mod tests {
-    use num_bigint_dig::BigInt;
-    use program_structure::{
-        constants::Curve,
-        intermediate_representation::{
-            VariableName, AccessType, Expression, Meta, value_meta::ValueReduction,
-        },
-    };
-
-    use crate::{
-        analysis_runner::AnalysisRunner,
-        unused_output_signal::{MaybeEqual, MaybeContains, maybe_accesses},
-    };
+    use program_structure::constants::Curve;
 
-    use super::{find_unused_output_signals, VariableAccess};
+    use crate::analysis_runner::AnalysisRunner;
 
-    #[test]
-    fn test_maybe_equal() {
-        use AccessType::*;
-        use Expression::*;
-        use ValueReduction::*;
-
-        let var = VariableName::from_string("var");
-        let meta = Meta::new(&(0..0), &None);
-        let mut zero = Box::new(Number(meta.clone(), BigInt::from(0)));
-        let mut one = Box::new(Number(meta.clone(), BigInt::from(1)));
-        let i = Box::new(Variable { meta, name: VariableName::from_string("i") });
-
-        // Set the value of `zero` and `one` explicitly.
-        let _ = zero
-            .meta_mut()
-            .value_knowledge_mut()
-            .set_reduces_to(FieldElement { value: BigInt::from(0) });
-        let _ = one
-            .meta_mut()
-            .value_knowledge_mut()
-            .set_reduces_to(FieldElement { value: BigInt::from(1) });
-
-        // `var[0].out`
-        let first_access = VariableAccess::new(
-            &var.with_version(1),
-            &[ArrayAccess(zero.clone()), ComponentAccess("out".to_string())],
-        );
-        // `var[i].out`
-        let second_access = VariableAccess::new(
-            &var.with_version(2),
-            &[ArrayAccess(i.clone()), ComponentAccess("out".to_string())],
-        );
-        // `var[1].out`
-        let third_access = VariableAccess::new(
-            &var.with_version(3),
-            &[ArrayAccess(one), ComponentAccess("out".to_string())],
-        );
-        // `var[i].out[0]`
-        let fourth_access = VariableAccess::new(
-            &var.with_version(4),
-            &[ArrayAccess(i), ComponentAccess("out".to_string()), ArrayAccess(zero)],
-        );
-
-        // The first and second accesses should be identified.
-        assert!(first_access.maybe_equal(&second_access));
-        // The first and third accesses should not be identified.
-        assert!(!first_access.maybe_equal(&third_access));
-
-        let accesses = vec![first_access];
-
-        // The first and second accesses should be identified.
-        assert!(accesses.maybe_contains(&second_access));
-        // The first and third accesses should not be identified.
-        assert!(!accesses.maybe_contains(&third_access));
-
-        // The fourth access is not equal to the first, but a prefix is.
-        assert!(!accesses.maybe_contains(&fourth_access));
-        assert!(maybe_accesses(&accesses, &fourth_access));
-    }
-
-    #[test]
-    fn test_maybe_accesses() {}
+    use super::*;
 
     #[test]
     fn test_unused_output_signal() {
@@ -611,9 +421,9 @@ mod tests {
     }
 
     fn validate_reports(name: &str, src: &[&str], expected_len: usize) {
-        let mut context = AnalysisRunner::new(Curve::Goldilocks).with_src(src);
-        let cfg = context.take_template(name).unwrap();
-        let reports = find_unused_output_signals(&mut context, &cfg);
+        let context = AnalysisRunner::new(Curve::Goldilocks).with_src(src);
+        let cfg = context.template(name).unwrap();
+        let reports = find_unused_output_signals(&context, &cfg);
         assert_eq!(reports.len(), expected_len);
     }
 }
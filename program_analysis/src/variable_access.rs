@@ -0,0 +1,231 @@
+use program_structure::ir::value_meta::ValueMeta;
+use program_structure::ir::{AccessType, Expression, Meta, VariableName};
+
+#[derive(Clone, Debug)]
+pub(crate) struct VariableAccess {
+    pub var: VariableName,
+    pub access: Vec<AccessType>,
+}
+
+impl VariableAccess {
+    pub fn new(var: &VariableName, access: &[AccessType]) -> Self {
+        // We disregard the version to make sure accesses are not order dependent.
+        VariableAccess { var: var.without_version(), access: access.to_vec() }
+    }
+
+    /// Returns the access corresponding to all elements of the given signal
+    /// belonging to the component `var` accessed using `access`.
+    ///
+    /// The signal access pattern is given by the component access pattern,
+    /// followed by the signal name, followed by an array access corresponding
+    /// to each dimension of the signal.
+    ///
+    /// E.g., for the component `c[i]` with an output signal `out` which is a
+    /// double array, we get `c[i].out[j][k]`. Since we identify array accesses
+    /// we simply use `i` for each array access corresponding to the dimensions
+    /// of the signal.
+    pub fn component_signal(
+        var: &VariableName,
+        access: &[AccessType],
+        signal_name: &str,
+        dimensions: usize,
+    ) -> Self {
+        let mut access = access.to_vec();
+        access.push(AccessType::ComponentAccess(signal_name.to_string()));
+        for _ in 0..dimensions {
+            let meta = Meta::new(&(0..0), &None);
+            let index = Expression::Variable { meta, name: VariableName::from_string("i") };
+            access.push(AccessType::ArrayAccess(Box::new(index)));
+        }
+        VariableAccess::new(var, &access)
+    }
+}
+
+/// A reflexive and symmetric relation capturing partial information about
+/// equality.
+pub(crate) trait MaybeEqual {
+    fn maybe_equal(&self, other: &Self) -> bool;
+}
+
+/// This is a reflexive and symmetric (but not transitive!) relation
+/// identifying all array accesses where the indices are not explicitly known
+/// to be different (e.g. from constant propagation). The relation is not
+/// transitive since `v[0] == v[i]` and `v[i] == v[1]`, but `v[0] != v[1]`.
+///
+/// Since `maybe_equal` is not transitive we cannot use it to define
+/// `PartialEq` for `VariableAccess`. This also means that we cannot use hash
+/// sets or hash maps to track variable accesses using this as our equality
+/// relation.
+impl MaybeEqual for VariableAccess {
+    fn maybe_equal(&self, other: &VariableAccess) -> bool {
+        use AccessType::*;
+        if self.var.name() != other.var.name() {
+            return false;
+        }
+        if self.access.len() != other.access.len() {
+            return false;
+        }
+        for (self_access, other_access) in self.access.iter().zip(other.access.iter()) {
+            match (self_access, other_access) {
+                (ArrayAccess(_), ComponentAccess(_)) => {
+                    return false;
+                }
+                (ComponentAccess(_), ArrayAccess(_)) => {
+                    return false;
+                }
+                (ComponentAccess(self_name), ComponentAccess(other_name))
+                    if self_name != other_name =>
+                {
+                    return false;
+                }
+                (ArrayAccess(self_index), ArrayAccess(other_index)) => {
+                    match (self_index.value(), other_index.value()) {
+                        (Some(self_value), Some(other_value)) if self_value != other_value => {
+                            return false;
+                        }
+                        // Identify all other array accesses.
+                        _ => {}
+                    }
+                }
+                // Identify all array accesses.
+                _ => {}
+            }
+        }
+        true
+    }
+}
+
+/// A relation capturing partial information about containment.
+pub(crate) trait MaybeContains<T> {
+    fn maybe_contains(&self, element: &T) -> bool;
+}
+
+impl<T> MaybeContains<T> for Vec<T>
+where
+    T: MaybeEqual,
+{
+    fn maybe_contains(&self, element: &T) -> bool {
+        self.iter().any(|item| item.maybe_equal(element))
+    }
+}
+
+/// Check if there is an access to a prefix of the signal access which contains
+/// the signal name. E.g. for the output signal `n2b[1].out[0]` it is enough that
+/// the list of all variable accesses `maybe_contains` the prefix `n2b[1].out`.
+/// This is to catch instances where the template passes the output signal as
+/// input to a function.
+///
+/// Accesses to fields of a bus are also considered accesses to the bus. E.g.
+/// for the bus output `c.p`, it is enough that `c.p.x` is accessed.
+pub(crate) fn maybe_accesses(
+    accesses: &Vec<VariableAccess>,
+    signal_access: &VariableAccess,
+) -> bool {
+    use AccessType::*;
+    let is_field_access = |access: &VariableAccess| {
+        access.access.len() > signal_access.access.len()
+            && VariableAccess::new(&access.var, &access.access[..signal_access.access.len()])
+                .maybe_equal(signal_access)
+    };
+    if accesses.iter().any(is_field_access) {
+        return true;
+    }
+    let mut signal_access = signal_access.clone();
+    while !accesses.maybe_contains(&signal_access) {
+        if let Some(ComponentAccess(_)) = signal_access.access.last() {
+            // The output signal name is the last component access in the access
+            // array. If it is not included in the access, the output signal is
+            // not accessed by the template.
+            return false;
+        } else {
+            signal_access.access.pop();
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint_dig::BigInt;
+    use program_structure::ir::value_meta::ValueReduction;
+
+    use super::*;
+
+    #[test]
+    fn test_maybe_equal() {
+        use AccessType::*;
+        use Expression::*;
+        use ValueReduction::*;
+
+        let var = VariableName::from_string("var");
+        let meta = Meta::new(&(0..0), &None);
+        let mut zero = Box::new(Number(meta.clone(), BigInt::from(0)));
+        let mut one = Box::new(Number(meta.clone(), BigInt::from(1)));
+        let i = Box::new(Variable { meta, name: VariableName::from_string("i") });
+
+        // Set the value of `zero` and `one` explicitly.
+        let _ = zero
+            .meta_mut()
+            .value_knowledge_mut()
+            .set_reduces_to(FieldElement { value: BigInt::from(0) });
+        let _ = one
+            .meta_mut()
+            .value_knowledge_mut()
+            .set_reduces_to(FieldElement { value: BigInt::from(1) });
+
+        // `var[0].out`
+        let first_access = VariableAccess::new(
+            &var.with_version(1),
+            &[ArrayAccess(zero.clone()), ComponentAccess("out".to_string())],
+        );
+        // `var[i].out`
+        let second_access = VariableAccess::new(
+            &var.with_version(2),
+            &[ArrayAccess(i.clone()), ComponentAccess("out".to_string())],
+        );
+        // `var[1].out`
+        let third_access = VariableAccess::new(
+            &var.with_version(3),
+            &[ArrayAccess(one), ComponentAccess("out".to_string())],
+        );
+        // `var[i].out[0]`
+        let fourth_access = VariableAccess::new(
+            &var.with_version(4),
+            &[ArrayAccess(i), ComponentAccess("out".to_string()), ArrayAccess(zero)],
+        );
+
+        // The first and second accesses should be identified.
+        assert!(first_access.maybe_equal(&second_access));
+        // The first and third accesses should not be identified.
+        assert!(!first_access.maybe_equal(&third_access));
+
+        let accesses = vec![first_access];
+
+        // The first and second accesses should be identified.
+        assert!(accesses.maybe_contains(&second_access));
+        // The first and third accesses should not be identified.
+        assert!(!accesses.maybe_contains(&third_access));
+
+        // The fourth access is not equal to the first, but a prefix is.
+        assert!(!accesses.maybe_contains(&fourth_access));
+        assert!(maybe_accesses(&accesses, &fourth_access));
+    }
+
+    #[test]
+    fn test_maybe_accesses() {
+        use AccessType::*;
+
+        let var = VariableName::from_string("c");
+        // `c.p.x`
+        let field_access = VariableAccess::new(
+            &var,
+            &[ComponentAccess("p".to_string()), ComponentAccess("x".to_string())],
+        );
+        let accesses = vec![field_access];
+
+        // Accessing a field of the bus `c.p` is an access to `c.p`.
+        assert!(maybe_accesses(&accesses, &VariableAccess::component_signal(&var, &[], "p", 0)));
+        // The bus `c.q` is not accessed.
+        assert!(!maybe_accesses(&accesses, &VariableAccess::component_signal(&var, &[], "q", 0)));
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use program_structure::ir::variable_meta::VariableUse;
+use program_structure::report::Report;
+use program_structure::ir::{Meta, VariableName};
+
+/// Returns a shortest path from `source` to `sink` in the graph given by the
+/// step relation `steps`. Paths are computed using a breadth-first search
+/// visiting successors in order, which ensures that the returned path is
+/// deterministic. If `source` and `sink` are equal, the path consists of the
+/// single variable `source`.
+pub(crate) fn shortest_path(
+    steps: &HashMap<VariableName, HashSet<VariableName>>,
+    source: &VariableName,
+    sink: &VariableName,
+) -> Option<Vec<VariableName>> {
+    let mut predecessors = HashMap::<&VariableName, &VariableName>::new();
+    let mut queue = VecDeque::from([source]);
+    while let Some(current) = queue.pop_front() {
+        if current == sink {
+            // Reconstruct the path by following predecessors back to the source.
+            let mut path = vec![current.clone()];
+            let mut current = current;
+            while let Some(previous) = predecessors.get(current) {
+                path.push((*previous).clone());
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let mut successors = steps.get(current).into_iter().flatten().collect::<Vec<_>>();
+        successors.sort();
+        for next in successors {
+            if next != source && !predecessors.contains_key(next) {
+                predecessors.insert(next, current);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Converts a path of variables to the corresponding variable uses. The first
+/// use is given by `start` (typically the definition or declaration of the
+/// first variable). Each subsequent use is located at the statement
+/// responsible for the corresponding step.
+pub(crate) fn to_variable_uses(
+    path: &[VariableName],
+    start: VariableUse,
+    step_metas: &HashMap<(VariableName, VariableName), Meta>,
+) -> Option<Vec<VariableUse>> {
+    let mut result = vec![start];
+    for step in path.windows(2) {
+        let meta = step_metas.get(&(step[0].clone(), step[1].clone()))?;
+        result.push(VariableUse::new(meta, &step[1], &Vec::new()));
+    }
+    Some(result)
+}
+
+/// Adds the given path as a code flow to the report. The first step is
+/// described by `description`, and each subsequent step is described as a
+/// dependency on the previous variable. Steps without a file ID are skipped.
+pub(crate) fn add_code_flow(report: &mut Report, path: &[VariableUse], description: String) {
+    let mut previous: Option<&VariableUse> = None;
+    for var_use in path {
+        let message = match previous {
+            Some(previous) => {
+                format!("`{}` depends on `{}` here.", var_use.name(), previous.name())
+            }
+            None => description.clone(),
+        };
+        if let Some(file_id) = var_use.meta().file_id() {
+            report.add_flow_step(var_use.meta().file_location(), file_id, message);
+        }
+        previous = Some(var_use);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortest_path() {
+        let var = |name: &str| VariableName::from_string(name);
+        let steps = HashMap::from([
+            (var("a"), HashSet::from([var("b"), var("c")])),
+            (var("b"), HashSet::from([var("d")])),
+            (var("c"), HashSet::from([var("d"), var("a")])),
+            (var("d"), HashSet::from([var("e")])),
+        ]);
+        assert_eq!(shortest_path(&steps, &var("a"), &var("a")), Some(vec![var("a")]));
+        assert_eq!(
+            shortest_path(&steps, &var("a"), &var("e")),
+            Some(vec![var("a"), var("b"), var("d"), var("e")])
+        );
+        assert_eq!(
+            shortest_path(&steps, &var("c"), &var("b")),
+            Some(vec![var("c"), var("a"), var("b")])
+        );
+        assert_eq!(shortest_path(&steps, &var("e"), &var("a")), None);
+    }
+}
@@ -138,6 +138,9 @@ pub enum Definition {
         arg_location: FileLocation,
         body: Statement,
     },
+    /// A Circom 2.2 bus definition. The body consists of the declarations of
+    /// the bus fields.
+    Bus { meta: Meta, name: String, args: Vec<String>, arg_location: FileLocation, body: Statement },
 }
 pub fn build_template(
     meta: Meta,
@@ -161,11 +164,22 @@ pub fn build_function(
     Definition::Function { meta, name, args, arg_location, body }
 }
 
+pub fn build_bus(
+    meta: Meta,
+    name: String,
+    args: Vec<String>,
+    arg_location: FileLocation,
+    body: Statement,
+) -> Definition {
+    Definition::Bus { meta, name, args, arg_location, body }
+}
+
 impl Definition {
     pub fn name(&self) -> String {
         match self {
             Self::Template { name, .. } => name.clone(),
             Self::Function { name, .. } => name.clone(),
+            Self::Bus { name, .. } => name.clone(),
         }
     }
 }
@@ -251,6 +265,8 @@ pub enum VariableType {
     Signal(SignalType, TagList),
     Component,
     AnonymousComponent,
+    /// A signal with the type of the given bus.
+    Bus(String, SignalType, TagList),
 }
 
 #[derive(Clone)]
@@ -313,6 +329,7 @@ pub enum Expression {
 }
 
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Access {
     ComponentAccess(String),
     ArrayAccess(Expression),
@@ -363,6 +380,7 @@ pub enum ExpressionPrefixOpcode {
 }
 
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum LogArgument {
     LogStr(String),
     LogExp(Expression),
@@ -87,6 +87,49 @@ pub fn split_declaration_into_single_nodes(
     build_initialization_block(meta, xtype, initializations)
 }
 
+/// Returns the type of a bus-typed signal declaration, together with the bus
+/// call `Bus(args)` used to initialize the declared signals.
+pub fn build_bus_header(
+    meta: Meta,
+    signal_type: SignalType,
+    bus_name: String,
+    args: Option<Vec<Expression>>,
+    tag_list: Option<TagList>,
+) -> (VariableType, Expression) {
+    let bus_call = build_call(meta, bus_name.clone(), args.unwrap_or_default());
+    (VariableType::Bus(bus_name, signal_type, tag_list.unwrap_or_default()), bus_call)
+}
+
+/// Bus declarations on the form `Bus(args) name[dims] <== init` are split into
+/// a declaration, an initialization of the form `name = Bus(args)` (similar to
+/// a component instantiation), and an optional signal assignment.
+pub fn split_bus_declaration_into_single_nodes(
+    meta: Meta,
+    xtype: VariableType,
+    bus_call: Expression,
+    symbols: Vec<Symbol>,
+    op: AssignOp,
+) -> Statement {
+    let mut initializations = Vec::new();
+    for symbol in symbols {
+        let declaration =
+            build_declaration(meta.clone(), xtype.clone(), symbol.name.clone(), symbol.is_array);
+        initializations.push(declaration);
+        let bus_init = build_substitution(
+            meta.clone(),
+            symbol.name.clone(),
+            vec![],
+            AssignOp::AssignVar,
+            bus_call.clone(),
+        );
+        initializations.push(bus_init);
+        if let Some(init) = symbol.init {
+            initializations.push(build_substitution(meta.clone(), symbol.name, vec![], op, init));
+        }
+    }
+    build_initialization_block(meta, xtype, initializations)
+}
+
 pub fn split_declaration_into_single_nodes_and_multi_substitution(
     meta: Meta,
     xtype: VariableType,
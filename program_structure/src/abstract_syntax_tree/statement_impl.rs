@@ -319,13 +319,25 @@ impl Display for VariableType {
                     write!(f, "signal {signal_type}")?;
                 }
                 if !tag_list.is_empty() {
-                    write!(f, " {{{}}}", tag_list.join("}} {{"))
+                    write!(f, " {{{}}}", tag_list.join("} {"))
                 } else {
                     Ok(())
                 }
             }
             Component => write!(f, "component"),
             AnonymousComponent => write!(f, "anonymous component"),
+            Bus(bus_name, signal_type, tag_list) => {
+                if matches!(signal_type, Intermediate) {
+                    write!(f, "{bus_name}")?;
+                } else {
+                    write!(f, "{signal_type} {bus_name}")?;
+                }
+                if !tag_list.is_empty() {
+                    write!(f, " {{{}}}", tag_list.join("} {"))
+                } else {
+                    Ok(())
+                }
+            }
         }
     }
 }
@@ -1,9 +1,11 @@
 use log::trace;
 use std::collections::HashSet;
 use std::fmt;
+use serde_derive::{Deserialize, Serialize};
 
 use crate::ir::declarations::Declarations;
 use crate::ir::degree_meta::DegreeEnvironment;
+use crate::ir::range_meta::RangeEnvironment;
 use crate::ir::value_meta::ValueEnvironment;
 use crate::ssa::traits::DirectedGraphNode;
 
@@ -13,7 +15,7 @@ use crate::ir::{Meta, Statement};
 type Index = usize;
 type IndexSet = HashSet<Index>;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BasicBlock {
     index: Index,
     meta: Meta,
@@ -147,6 +149,15 @@ impl BasicBlock {
         result
     }
 
+    pub fn propagate_ranges(&mut self, env: &mut RangeEnvironment) -> bool {
+        trace!("propagating value ranges for basic block {}", self.index());
+        let mut result = false;
+        for stmt in self.iter_mut() {
+            result |= stmt.propagate_ranges(env);
+        }
+        result
+    }
+
     pub fn propagate_types(&mut self, vars: &Declarations) {
         trace!("propagating variable types for basic block {}", self.index());
         for stmt in self.iter_mut() {
@@ -1,13 +1,16 @@
 use log::debug;
-use std::collections::HashSet;
+use num_bigint::BigInt;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::time::{Instant, Duration};
+use serde_derive::{Deserialize, Serialize};
 
 use crate::constants::UsefulConstants;
 use crate::file_definition::FileID;
 use crate::ir::declarations::{Declaration, Declarations};
 use crate::ir::degree_meta::{DegreeEnvironment, Degree, DegreeRange};
-use crate::ir::value_meta::ValueEnvironment;
+use crate::ir::range_meta::{RangeEnvironment, ValueRange};
+use crate::ir::value_meta::{ValueEnvironment, ValueReduction};
 use crate::ir::variable_meta::VariableMeta;
 use crate::ir::{VariableName, VariableType, SignalType};
 use crate::ssa::dominator_tree::DominatorTree;
@@ -24,11 +27,12 @@ pub type Index = usize;
 
 const MAX_ANALYSIS_DURATION: Duration = Duration::from_secs(10);
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum DefinitionType {
     Function,
     Template,
     CustomTemplate,
+    Bus,
 }
 
 impl fmt::Display for DefinitionType {
@@ -37,10 +41,12 @@ impl fmt::Display for DefinitionType {
             DefinitionType::Function => write!(f, "function"),
             DefinitionType::Template => write!(f, "template"),
             DefinitionType::CustomTemplate => write!(f, "custom template"),
+            DefinitionType::Bus => write!(f, "bus"),
         }
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Cfg {
     name: String,
     constants: UsefulConstants,
@@ -49,6 +55,8 @@ pub struct Cfg {
     basic_blocks: Vec<BasicBlock>,
     definition_type: DefinitionType,
     dominator_tree: DominatorTree<BasicBlock>,
+    variable_ranges: HashMap<VariableName, ValueRange>,
+    arguments: Option<Vec<BigInt>>,
 }
 
 impl Cfg {
@@ -69,8 +77,20 @@ impl Cfg {
             basic_blocks,
             definition_type,
             dominator_tree,
+            variable_ranges: HashMap::new(),
+            arguments: None,
         }
     }
+
+    /// Specialize the CFG to the given constant template arguments. The
+    /// arguments are propagated as constant values when the CFG is converted
+    /// to SSA, so this must be called before `Cfg::into_ssa`.
+    #[must_use]
+    pub fn specialize(mut self, arguments: &[BigInt]) -> Cfg {
+        self.arguments = Some(arguments.to_vec());
+        self
+    }
+
     /// Returns the entry (first) block of the CFG.
     #[must_use]
     pub fn entry_block(&self) -> &BasicBlock {
@@ -117,6 +137,7 @@ impl Cfg {
         // run before caching variable use.
         self.propagate_types();
         self.propagate_values();
+        self.propagate_ranges();
         self.propagate_degrees();
         self.cache_variable_use();
 
@@ -158,6 +179,32 @@ impl Cfg {
     }
 
     /// Returns the parameter data for the corresponding function or template.
+    /// Returns the constant arguments if the CFG has been specialized.
+    #[must_use]
+    pub fn arguments(&self) -> Option<&[BigInt]> {
+        self.arguments.as_deref()
+    }
+
+    /// Returns the name of the CFG together with the constant arguments if
+    /// the CFG has been specialized (e.g. `Num2Bits(8)`).
+    #[must_use]
+    pub fn instance_name(&self) -> String {
+        match self.arguments() {
+            Some(arguments) => {
+                let arguments =
+                    arguments.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+                format!("{}({arguments})", self.name())
+            }
+            None => self.name().to_string(),
+        }
+    }
+
+    /// Returns an iterator over the parameters and corresponding arguments if
+    /// the CFG has been specialized.
+    fn specialized_parameters(&self) -> impl Iterator<Item = (&VariableName, &BigInt)> {
+        self.parameters.iter().zip(self.arguments.iter().flatten())
+    }
+
     #[must_use]
     pub fn parameters(&self) -> &Parameters {
         &self.parameters
@@ -180,7 +227,7 @@ impl Cfg {
         use VariableType::*;
         self.declarations.iter().filter_map(|(name, declaration)| {
             match declaration.variable_type() {
-                Signal(Input, _) => Some(name),
+                Signal(Input, _) | Bus(_, Input, _) => Some(name),
                 _ => None,
             }
         })
@@ -192,7 +239,7 @@ impl Cfg {
         use VariableType::*;
         self.declarations.iter().filter_map(|(name, declaration)| {
             match declaration.variable_type() {
-                Signal(Output, _) => Some(name),
+                Signal(Output, _) | Bus(_, Output, _) => Some(name),
                 _ => None,
             }
         })
@@ -210,6 +257,13 @@ impl Cfg {
         self.declarations.get_type(name)
     }
 
+    /// Returns the range of values the given variable may take, if known. The
+    /// range is only available once the CFG has been converted to SSA.
+    #[must_use]
+    pub fn get_range(&self, name: &VariableName) -> Option<&ValueRange> {
+        self.variable_ranges.get(name)
+    }
+
     /// Returns an iterator over the basic blocks in the CFG. This iterator
     /// guarantees that if `i` dominates `j`, then `i` comes before `j`.
     pub fn iter(&self) -> impl Iterator<Item = &BasicBlock> {
@@ -480,6 +534,9 @@ impl Cfg {
     pub(crate) fn propagate_values(&mut self) {
         debug!("propagating constant values for `{}`", self.name());
         let mut env = ValueEnvironment::new(&self.constants);
+        for (param, value) in self.specialized_parameters() {
+            env.add_variable(param, &ValueReduction::FieldElement { value: value.clone() });
+        }
         let mut rerun = true;
         let start = Instant::now();
         while rerun {
@@ -496,6 +553,35 @@ impl Cfg {
         }
     }
 
+    /// Propagate value ranges along the CFG. Since ranges are seeded from
+    /// constant template arguments, this must run after value propagation.
+    pub(crate) fn propagate_ranges(&mut self) {
+        debug!("propagating value ranges for `{}`", self.name());
+        let mut env = RangeEnvironment::new(&self.constants);
+        for (param, value) in self.specialized_parameters() {
+            if let Some(range) = ValueRange::from_value(value).reduce(self.constants.prime()) {
+                env.add_variable(param, &range);
+            }
+        }
+        env.add_constraints(self.basic_blocks.iter().flat_map(|basic_block| basic_block.iter()));
+        let mut rerun = true;
+        let start = Instant::now();
+        while rerun {
+            // Rerun range propagation if a single child node was updated.
+            rerun = false;
+            for basic_block in self.iter_mut() {
+                rerun |= basic_block.propagate_ranges(&mut env);
+            }
+            // Bail out if analysis takes more than 10 seconds.
+            if start.elapsed() > MAX_ANALYSIS_DURATION {
+                debug!("failed to propagate ranges within allotted time");
+                rerun = false;
+            }
+        }
+        self.variable_ranges =
+            env.iter().map(|(name, range)| (name.clone(), range.clone())).collect();
+    }
+
     /// Propagate variable types along the CFG.
     pub(crate) fn propagate_types(&mut self) {
         debug!("propagating variable types for `{}`", self.name());
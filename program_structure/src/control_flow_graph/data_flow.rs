@@ -0,0 +1,360 @@
+//! A generic worklist solver for monotone data-flow problems over a CFG,
+//! together with a few reference analyses built on top of it.
+//!
+//! An analysis is described by implementing `DataFlowAnalysis`, which defines
+//! the analysis domain, the direction of the analysis, the join operation,
+//! and the transfer function for individual statements. The solver propagates
+//! facts between basic blocks until a fixed point is reached. For the solver
+//! to terminate, the domain must have finite height and the transfer function
+//! must be monotone.
+use log::trace;
+use std::collections::{HashSet, VecDeque};
+
+use crate::ir::variable_meta::{VariableMeta, VariableUse};
+use crate::ir::{AssignOp, Meta, Statement, VariableName, VariableType};
+
+use super::basic_block::BasicBlock;
+use super::cfg::{Cfg, Index};
+
+/// The direction in which facts are propagated through the CFG.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Facts flow from the entry of the CFG along CFG edges.
+    Forward,
+    /// Facts flow from the exits of the CFG against CFG edges.
+    Backward,
+}
+
+/// A monotone data-flow problem over a CFG.
+pub trait DataFlowAnalysis {
+    /// The facts computed by the analysis.
+    type Domain: Clone + PartialEq;
+
+    /// The direction of the analysis.
+    fn direction(&self) -> Direction;
+
+    /// The least element of the domain. This is the identity of `join` and is
+    /// used as the initial fact for each basic block.
+    fn bottom(&self) -> Self::Domain;
+
+    /// The fact holding at the entry of the CFG (for forward analyses) or at
+    /// each exit of the CFG (for backward analyses). Defaults to `bottom`.
+    fn boundary(&self) -> Self::Domain {
+        self.bottom()
+    }
+
+    /// Joins `other` into `fact`. This is used to combine facts where control
+    /// flow merges.
+    fn join(&self, fact: &mut Self::Domain, other: &Self::Domain);
+
+    /// Updates `fact` to reflect the effect of executing the given statement.
+    /// (For backward analyses, `fact` is the fact holding after the statement
+    /// and should be updated to the fact holding before the statement.)
+    fn transfer(&self, stmt: &Statement, fact: &mut Self::Domain);
+
+    /// Solves the data-flow problem on the given CFG.
+    fn solve(self, cfg: &Cfg) -> DataFlowResult<Self>
+    where
+        Self: Sized,
+    {
+        solve(self, cfg)
+    }
+}
+
+/// The fixed point computed by the data-flow solver. Facts are stored for the
+/// entry and exit of each basic block (in program order, independently of the
+/// direction of the analysis).
+pub struct DataFlowResult<A: DataFlowAnalysis> {
+    analysis: A,
+    entry: Vec<A::Domain>,
+    exit: Vec<A::Domain>,
+}
+
+impl<A: DataFlowAnalysis> DataFlowResult<A> {
+    /// Returns the analysis used to compute the result.
+    pub fn analysis(&self) -> &A {
+        &self.analysis
+    }
+
+    /// Returns the fact holding at the entry of the given basic block.
+    #[must_use]
+    pub fn entry(&self, basic_block: &BasicBlock) -> &A::Domain {
+        &self.entry[basic_block.index()]
+    }
+
+    /// Returns the fact holding at the exit of the given basic block.
+    #[must_use]
+    pub fn exit(&self, basic_block: &BasicBlock) -> &A::Domain {
+        &self.exit[basic_block.index()]
+    }
+
+    /// Returns the facts holding before and after each statement in the given
+    /// basic block (in program order).
+    #[must_use]
+    pub fn statement_facts<'a>(
+        &self,
+        basic_block: &'a BasicBlock,
+    ) -> Vec<(&'a Statement, A::Domain, A::Domain)> {
+        match self.analysis.direction() {
+            Direction::Forward => {
+                let mut fact = self.entry(basic_block).clone();
+                basic_block
+                    .iter()
+                    .map(|stmt| {
+                        let before = fact.clone();
+                        self.analysis.transfer(stmt, &mut fact);
+                        (stmt, before, fact.clone())
+                    })
+                    .collect()
+            }
+            Direction::Backward => {
+                let mut fact = self.exit(basic_block).clone();
+                let mut result = basic_block
+                    .statements()
+                    .iter()
+                    .rev()
+                    .map(|stmt| {
+                        let after = fact.clone();
+                        self.analysis.transfer(stmt, &mut fact);
+                        (stmt, fact.clone(), after)
+                    })
+                    .collect::<Vec<_>>();
+                result.reverse();
+                result
+            }
+        }
+    }
+}
+
+/// Solves the given data-flow problem using a worklist algorithm.
+#[must_use]
+pub fn solve<A: DataFlowAnalysis>(analysis: A, cfg: &Cfg) -> DataFlowResult<A> {
+    let direction = analysis.direction();
+    let mut entry = vec![analysis.bottom(); cfg.len()];
+    let mut exit = vec![analysis.bottom(); cfg.len()];
+
+    // Depending on the direction of the analysis, `input` and `output` are
+    // either the entry and exit facts or the exit and entry facts.
+    let (input, output) = match direction {
+        Direction::Forward => (&mut entry, &mut exit),
+        Direction::Backward => (&mut exit, &mut entry),
+    };
+    let is_boundary = |basic_block: &BasicBlock| match direction {
+        Direction::Forward => basic_block.index() == cfg.entry_block().index(),
+        Direction::Backward => basic_block.successors().is_empty(),
+    };
+
+    // Visit blocks in program order for forward analyses, and in reverse
+    // program order for backward analyses. This typically reduces the number
+    // of iterations required to reach a fixed point.
+    let mut worklist: VecDeque<Index> = match direction {
+        Direction::Forward => (0..cfg.len()).collect(),
+        Direction::Backward => (0..cfg.len()).rev().collect(),
+    };
+    let mut queued = vec![true; cfg.len()];
+    while let Some(index) = worklist.pop_front() {
+        queued[index] = false;
+        let basic_block = cfg.get_basic_block(index).expect("in control-flow graph");
+
+        let mut fact =
+            if is_boundary(basic_block) { analysis.boundary() } else { analysis.bottom() };
+        for source in sources(basic_block, direction) {
+            analysis.join(&mut fact, &output[*source]);
+        }
+        input[index] = fact.clone();
+
+        match direction {
+            Direction::Forward => {
+                basic_block.iter().for_each(|stmt| analysis.transfer(stmt, &mut fact));
+            }
+            Direction::Backward => {
+                basic_block
+                    .statements()
+                    .iter()
+                    .rev()
+                    .for_each(|stmt| analysis.transfer(stmt, &mut fact));
+            }
+        }
+        if fact != output[index] {
+            trace!("data-flow fact updated for basic block {index}");
+            output[index] = fact;
+            for &target in targets(basic_block, direction) {
+                if !queued[target] {
+                    queued[target] = true;
+                    worklist.push_back(target);
+                }
+            }
+        }
+    }
+    DataFlowResult { analysis, entry, exit }
+}
+
+/// Returns the basic blocks that facts flow from into the given block.
+fn sources(basic_block: &BasicBlock, direction: Direction) -> &HashSet<Index> {
+    match direction {
+        Direction::Forward => basic_block.predecessors(),
+        Direction::Backward => basic_block.successors(),
+    }
+}
+
+/// Returns the basic blocks that facts flow to from the given block.
+fn targets(basic_block: &BasicBlock, direction: Direction) -> &HashSet<Index> {
+    match direction {
+        Direction::Forward => basic_block.successors(),
+        Direction::Backward => basic_block.predecessors(),
+    }
+}
+
+/// Live variable analysis. A variable is _live_ at a program point if it may
+/// be read along some path from the point before it is overwritten.
+///
+/// Note: Writes to individual array elements or component signals do not
+/// kill the variable since the remaining elements may still be read. Phi
+/// expressions are treated as reading all their arguments, so in SSA form an
+/// argument may be considered live along paths where it is never defined.
+#[derive(Clone, Default)]
+pub struct Liveness;
+
+impl DataFlowAnalysis for Liveness {
+    type Domain = HashSet<VariableName>;
+
+    fn direction(&self) -> Direction {
+        Direction::Backward
+    }
+
+    fn bottom(&self) -> Self::Domain {
+        HashSet::new()
+    }
+
+    fn join(&self, fact: &mut Self::Domain, other: &Self::Domain) {
+        fact.extend(other.iter().cloned());
+    }
+
+    fn transfer(&self, stmt: &Statement, fact: &mut Self::Domain) {
+        for var in stmt.variables_written() {
+            if var.access().is_empty() {
+                fact.remove(var.name());
+            }
+        }
+        fact.extend(stmt.variables_read().map(|var| var.name().clone()));
+    }
+}
+
+/// Reaching definitions analysis. A definition of a variable _reaches_ a
+/// program point if there is a path from the definition to the point along
+/// which the variable is not overwritten. Template and function parameters
+/// are considered to be defined at the entry of the CFG.
+///
+/// Note: Writes to individual array elements or component signals do not
+/// kill earlier definitions of the variable.
+#[derive(Clone, Default)]
+pub struct ReachingDefinitions {
+    parameters: HashSet<VariableUse>,
+}
+
+impl ReachingDefinitions {
+    #[must_use]
+    pub fn new(cfg: &Cfg) -> ReachingDefinitions {
+        let parameters = cfg.parameters();
+        let meta = Meta::new(parameters.file_location(), parameters.file_id());
+        let parameters =
+            parameters.iter().map(|name| VariableUse::new(&meta, name, &Vec::new())).collect();
+        ReachingDefinitions { parameters }
+    }
+}
+
+impl DataFlowAnalysis for ReachingDefinitions {
+    type Domain = HashSet<VariableUse>;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn bottom(&self) -> Self::Domain {
+        HashSet::new()
+    }
+
+    fn boundary(&self) -> Self::Domain {
+        self.parameters.clone()
+    }
+
+    fn join(&self, fact: &mut Self::Domain, other: &Self::Domain) {
+        fact.extend(other.iter().cloned());
+    }
+
+    fn transfer(&self, stmt: &Statement, fact: &mut Self::Domain) {
+        for var in stmt.variables_written() {
+            if var.access().is_empty() {
+                fact.retain(|definition| definition.name() != var.name());
+            }
+            fact.insert(var.clone());
+        }
+    }
+}
+
+/// Available constraints analysis. A signal is _constrained_ at a program
+/// point if it occurs in a constraint (either `===` or `<==`) along every path
+/// from the entry of the CFG to the point.
+#[derive(Clone, Default)]
+pub struct AvailableConstraints {
+    signals: HashSet<VariableName>,
+}
+
+impl AvailableConstraints {
+    #[must_use]
+    pub fn new(cfg: &Cfg) -> AvailableConstraints {
+        let signals = cfg
+            .variables()
+            .filter(|name| {
+                matches!(
+                    cfg.get_type(name),
+                    Some(VariableType::Signal(_, _) | VariableType::Bus(_, _, _))
+                )
+            })
+            .cloned()
+            .collect();
+        AvailableConstraints { signals }
+    }
+
+    fn is_signal(&self, name: &VariableName) -> bool {
+        self.signals.contains(name)
+    }
+}
+
+impl DataFlowAnalysis for AvailableConstraints {
+    type Domain = HashSet<VariableName>;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    // This is a must-analysis, so the least element is the set of all signals
+    // and facts are joined using intersection.
+    fn bottom(&self) -> Self::Domain {
+        self.signals.clone()
+    }
+
+    fn boundary(&self) -> Self::Domain {
+        HashSet::new()
+    }
+
+    fn join(&self, fact: &mut Self::Domain, other: &Self::Domain) {
+        fact.retain(|name| other.contains(name));
+    }
+
+    fn transfer(&self, stmt: &Statement, fact: &mut Self::Domain) {
+        use AssignOp::*;
+        use Statement::*;
+        if matches!(
+            stmt,
+            Substitution { op: AssignConstraintSignal, .. } | ConstraintEquality { .. }
+        ) {
+            fact.extend(
+                stmt.variables_used()
+                    .map(|var| var.name())
+                    .filter(|name| self.is_signal(name))
+                    .cloned(),
+            );
+        }
+    }
+}
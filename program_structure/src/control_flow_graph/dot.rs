@@ -0,0 +1,109 @@
+use std::fmt::Write;
+
+use crate::ir::Statement;
+
+use super::basic_block::BasicBlock;
+use super::cfg::Cfg;
+
+/// Options controlling how a CFG is rendered in Graphviz DOT format.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DotOptions {
+    /// Overlay the dominator tree (as dashed edges from the immediate dominator
+    /// of each basic block).
+    pub dominator_tree: bool,
+    /// Overlay the dominance frontier (as dotted edges from each basic block to
+    /// the blocks in its dominance frontier).
+    pub dominance_frontier: bool,
+}
+
+impl Cfg {
+    /// Renders the CFG in Graphviz DOT format. Each basic block is rendered as
+    /// a box containing its statements (using the `Debug` representation, so
+    /// SSA versions are included if the CFG has been converted to SSA). Edges
+    /// leaving an if-statement are labelled with the corresponding branch.
+    #[must_use]
+    pub fn to_dot(&self, options: &DotOptions) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph \"{}\" {{", escape_label(&self.instance_name())).unwrap();
+        writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+        for basic_block in self.iter() {
+            writeln!(dot, "    {} [label=\"{}\"];", basic_block.index(), block_label(basic_block))
+                .unwrap();
+        }
+        for basic_block in self.iter() {
+            let mut successors = basic_block.successors().iter().collect::<Vec<_>>();
+            successors.sort();
+            for successor in successors {
+                match branch_label(basic_block, *successor) {
+                    Some(label) => writeln!(
+                        dot,
+                        "    {} -> {successor} [label=\"{label}\"];",
+                        basic_block.index()
+                    ),
+                    None => writeln!(dot, "    {} -> {successor};", basic_block.index()),
+                }
+                .unwrap();
+            }
+        }
+        if options.dominator_tree {
+            for basic_block in self.iter() {
+                if let Some(dominator) = self.get_immediate_dominator(basic_block) {
+                    writeln!(
+                        dot,
+                        "    {} -> {} [style=dashed, color=blue, constraint=false];",
+                        dominator.index(),
+                        basic_block.index()
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        if options.dominance_frontier {
+            for basic_block in self.iter() {
+                let mut frontier = self.get_dominance_frontier(basic_block);
+                frontier.sort_by_key(|frontier_block| frontier_block.index());
+                for frontier_block in frontier {
+                    writeln!(
+                        dot,
+                        "    {} -> {} [style=dotted, color=red, constraint=false];",
+                        basic_block.index(),
+                        frontier_block.index()
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+/// Returns the label of the basic block. Lines are left-justified using `\l`.
+fn block_label(basic_block: &BasicBlock) -> String {
+    let mut label = format!("block {}:\\l", basic_block.index());
+    for stmt in basic_block.iter() {
+        label.push_str(&escape_label(&format!("  {stmt:?}")));
+        label.push_str("\\l");
+    }
+    label
+}
+
+/// Returns "true" or "false" if the edge from the given block to the successor
+/// corresponds to a branch of an if-statement.
+fn branch_label(basic_block: &BasicBlock, successor: usize) -> Option<&'static str> {
+    let Some(Statement::IfThenElse { true_index, .. }) = basic_block.statements().last() else {
+        return None;
+    };
+    // If the false index is `None`, the false branch is the block following
+    // the if-statement.
+    if successor == *true_index {
+        Some("true")
+    } else {
+        Some("false")
+    }
+}
+
+/// Escapes a string for use in a DOT label.
+pub fn escape_label(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
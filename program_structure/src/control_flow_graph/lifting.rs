@@ -139,6 +139,17 @@ impl TryLift<UsefulConstants> for Definition {
                     reports,
                 )
             }
+            Definition::Bus { name, body, .. } => {
+                debug!("building CFG for bus `{name}`");
+                try_lift_impl(
+                    name.clone(),
+                    DefinitionType::Bus,
+                    constants,
+                    self.into(),
+                    body.clone(),
+                    reports,
+                )
+            }
         }
     }
 }
@@ -1,12 +1,15 @@
 pub mod basic_block;
+pub mod data_flow;
 pub mod errors;
 pub mod parameters;
 
 mod cfg;
+mod dot;
 mod lifting;
 mod ssa_impl;
 mod unique_vars;
 
 pub use basic_block::BasicBlock;
 pub use cfg::{Cfg, DefinitionType, Index};
+pub use dot::{escape_label, DotOptions};
 pub use lifting::IntoCfg;
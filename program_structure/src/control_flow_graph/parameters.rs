@@ -2,9 +2,11 @@ use crate::ast::Definition;
 use crate::file_definition::{FileID, FileLocation};
 use crate::function_data::FunctionData;
 use crate::template_data::TemplateData;
+use serde_derive::{Deserialize, Serialize};
 
 use crate::ir::VariableName;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Parameters {
     param_names: Vec<VariableName>,
     file_id: Option<FileID>,
@@ -82,7 +84,8 @@ impl From<&Definition> for Parameters {
     fn from(definition: &Definition) -> Parameters {
         match definition {
             Definition::Function { meta, args, arg_location, .. }
-            | Definition::Template { meta, args, arg_location, .. } => {
+            | Definition::Template { meta, args, arg_location, .. }
+            | Definition::Bus { meta, args, arg_location, .. } => {
                 Parameters::new(args, meta.file_id, arg_location.clone())
             }
         }
@@ -1,10 +1,11 @@
 use std::collections::HashMap;
+use serde_derive::{Deserialize, Serialize};
 
 use crate::file_definition::{FileID, FileLocation};
 use crate::ir::*;
 
 /// A structure used to track declared variables.
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Declarations(HashMap<VariableName, Declaration>);
 
 impl Declarations {
@@ -48,7 +49,7 @@ impl Declarations {
 
 /// To avoid having to add a new declaration for each new version of a variable
 /// we track all declarations as part of the CFG header.
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Declaration {
     name: VariableName,
     var_type: VariableType,
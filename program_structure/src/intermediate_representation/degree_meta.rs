@@ -2,11 +2,12 @@ use log::trace;
 use std::cmp::{Ordering, min, max};
 use std::collections::HashMap;
 use std::fmt;
+use serde_derive::{Deserialize, Serialize};
 
 use super::{VariableName, VariableType};
 
 /// The degree of an expression.
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Degree {
     Constant,
     Linear,
@@ -242,7 +243,7 @@ impl fmt::Debug for Degree {
 }
 
 /// An inclusive range of degrees.
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct DegreeRange(Degree, Degree);
 
 impl DegreeRange {
@@ -495,7 +496,7 @@ pub trait DegreeMeta {
     fn degree(&self) -> Option<&DegreeRange>;
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct DegreeKnowledge {
     // The inclusive range the degree of the node may take.
     degree_range: Option<DegreeRange>,
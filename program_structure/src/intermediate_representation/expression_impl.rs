@@ -9,6 +9,7 @@ use circom_algebra::modular_arithmetic;
 use super::declarations::Declarations;
 use super::degree_meta::{Degree, DegreeEnvironment, DegreeMeta, DegreeRange};
 use super::ir::*;
+use super::range_meta::{RangeEnvironment, RangeMeta, ValueRange};
 use super::type_meta::TypeMeta;
 use super::value_meta::{ValueEnvironment, ValueMeta, ValueReduction};
 use super::variable_meta::{VariableMeta, VariableUse, VariableUses};
@@ -396,7 +397,7 @@ impl VariableMeta for Expression {
                         trace!("adding `{name:?}` to components read");
                         components_read.insert(VariableUse::new(meta, name, &Vec::new()));
                     }
-                    Some(VariableType::Signal(_, _)) => {
+                    Some(VariableType::Signal(_, _) | VariableType::Bus(_, _, _)) => {
                         trace!("adding `{name:?}` to signals read");
                         signals_read.insert(VariableUse::new(meta, name, &Vec::new()));
                     }
@@ -446,7 +447,7 @@ impl VariableMeta for Expression {
                         trace!("adding `{var:?}` to components read");
                         components_read.insert(VariableUse::new(meta, var, access));
                     }
-                    Some(VariableType::Signal(_, _)) => {
+                    Some(VariableType::Signal(_, _) | VariableType::Bus(_, _, _)) => {
                         trace!("adding `{var:?}` to signals read");
                         signals_read.insert(VariableUse::new(meta, var, access));
                     }
@@ -482,7 +483,7 @@ impl VariableMeta for Expression {
                         trace!("adding `{var:?}` to components read");
                         components_read.insert(VariableUse::new(meta, var, &Vec::new()));
                     }
-                    Some(VariableType::Signal(_, _)) => {
+                    Some(VariableType::Signal(_, _) | VariableType::Bus(_, _, _)) => {
                         trace!("adding `{var:?}` to signals read");
                         signals_read.insert(VariableUse::new(meta, var, &Vec::new()));
                     }
@@ -666,6 +667,93 @@ impl ValueMeta for Expression {
     }
 }
 
+impl RangeMeta for Expression {
+    fn propagate_ranges(&mut self, env: &mut RangeEnvironment) -> bool {
+        use Expression::*;
+        let mut result = false;
+        let range = match self {
+            InfixOp { lhe, infix_op, rhe, .. } => {
+                result = lhe.propagate_ranges(env) | rhe.propagate_ranges(env);
+                infix_op.propagate_ranges(lhe.range(), rhe.range(), env)
+            }
+            PrefixOp { prefix_op, rhe, .. } => {
+                result = rhe.propagate_ranges(env);
+                prefix_op.propagate_ranges(rhe.range(), env)
+            }
+            SwitchOp { cond, if_true, if_false, .. } => {
+                result = cond.propagate_ranges(env)
+                    | if_true.propagate_ranges(env)
+                    | if_false.propagate_ranges(env);
+                ValueRange::iter_join([if_true.range(), if_false.range()])
+            }
+            Variable { name, .. } => env.get_variable(name).cloned(),
+            Number(_, value) => ValueRange::from_value(value).reduce(env.prime()),
+            Call { args, .. } => {
+                // TODO: Handle function calls.
+                for arg in args {
+                    result |= arg.propagate_ranges(env);
+                }
+                None
+            }
+            InlineArray { values, .. } => {
+                for value in values {
+                    result |= value.propagate_ranges(env);
+                }
+                None
+            }
+            Access { var, access, .. } => {
+                for access in access.iter_mut() {
+                    if let AccessType::ArrayAccess(index) = access {
+                        result |= index.propagate_ranges(env);
+                    }
+                }
+                // We only track the ranges of component signals. The range of
+                // a component signal applies to all array elements.
+                access
+                    .iter()
+                    .find_map(|access| match access {
+                        AccessType::ComponentAccess(signal) => Some(signal),
+                        AccessType::ArrayAccess(_) => None,
+                    })
+                    .and_then(|signal| env.get_component_signal(var, signal).cloned())
+            }
+            Update { access, rhe, .. } => {
+                // TODO: Handle array values.
+                result = rhe.propagate_ranges(env);
+                for access in access.iter_mut() {
+                    if let AccessType::ArrayAccess(index) = access {
+                        result |= index.propagate_ranges(env);
+                    }
+                }
+                None
+            }
+            Phi { args, .. } => {
+                // The range of a phi expression is the join of the ranges of
+                // the arguments, if all ranges are known.
+                ValueRange::iter_join(args.iter().map(|name| env.get_variable(name)))
+            }
+        };
+        // Constant values are always more precise than the computed range.
+        let range = match self.value() {
+            Some(ValueReduction::FieldElement { value }) => {
+                ValueRange::from_value(value).reduce(env.prime())
+            }
+            Some(ValueReduction::Boolean { value }) => {
+                Some(ValueRange::from_value(&u8::from(*value).into()))
+            }
+            None => range,
+        };
+        if let Some(range) = range {
+            result |= self.meta_mut().range_knowledge_mut().set_range(&range);
+        }
+        result
+    }
+
+    fn range(&self) -> Option<&ValueRange> {
+        self.meta().range_knowledge().range()
+    }
+}
+
 impl ExpressionInfixOpcode {
     fn propagate_degrees(
         &self,
@@ -801,6 +889,42 @@ impl ExpressionInfixOpcode {
             _ => None,
         }
     }
+
+    fn propagate_ranges(
+        &self,
+        lhr: Option<&ValueRange>,
+        rhr: Option<&ValueRange>,
+        env: &RangeEnvironment,
+    ) -> Option<ValueRange> {
+        use ExpressionInfixOpcode::*;
+        match self {
+            // Comparisons and boolean operations always evaluate to 0 or 1.
+            LesserEq | GreaterEq | Lesser | Greater | Eq | NotEq | BoolOr | BoolAnd => {
+                return Some(ValueRange::boolean());
+            }
+            _ => {}
+        }
+        let (lhr, rhr) = (lhr?, rhr?);
+        let range = match self {
+            Add => Some(lhr.add(rhr)),
+            Sub => Some(lhr.sub(rhr)),
+            Mul => lhr.mul(rhr),
+            Pow => lhr.pow(rhr),
+            IntDiv => lhr.int_div(rhr),
+            Mod => lhr.modulo(rhr),
+            ShiftL => lhr.shift_left(rhr),
+            ShiftR => lhr.shift_right(rhr),
+            BitAnd => lhr.bit_and(rhr),
+            BitOr | BitXor => lhr.bit_or(rhr),
+            // Field division may produce any field element.
+            Div => None,
+            LesserEq | GreaterEq | Lesser | Greater | Eq | NotEq | BoolOr | BoolAnd => {
+                unreachable!()
+            }
+        };
+        // If the result wraps around the prime the range is unknown.
+        range.and_then(|range| range.reduce(env.prime()))
+    }
 }
 
 impl ExpressionPrefixOpcode {
@@ -856,6 +980,20 @@ impl ExpressionPrefixOpcode {
             None => None,
         }
     }
+
+    fn propagate_ranges(
+        &self,
+        range: Option<&ValueRange>,
+        env: &RangeEnvironment,
+    ) -> Option<ValueRange> {
+        use ExpressionPrefixOpcode::*;
+        match self {
+            BoolNot => Some(ValueRange::boolean()),
+            Sub => range.and_then(|range| range.neg().reduce(env.prime())),
+            // The 256-bit complement may produce any field element.
+            Complement => None,
+        }
+    }
 }
 
 impl fmt::Debug for Expression {
@@ -1,10 +1,12 @@
 use num_bigint::BigInt;
 use std::fmt;
+use serde_derive::{Deserialize, Serialize};
 
 use crate::file_definition::{FileID, FileLocation};
 use crate::nonempty_vec::NonEmptyVec;
 
 use super::degree_meta::DegreeKnowledge;
+use super::range_meta::RangeKnowledge;
 use super::type_meta::TypeKnowledge;
 use super::value_meta::ValueKnowledge;
 use super::variable_meta::VariableKnowledge;
@@ -12,13 +14,14 @@ use super::variable_meta::VariableKnowledge;
 type Index = usize;
 type Version = usize;
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Meta {
     pub location: FileLocation,
     pub file_id: Option<FileID>,
     degree_knowledge: DegreeKnowledge,
     type_knowledge: TypeKnowledge,
     value_knowledge: ValueKnowledge,
+    range_knowledge: RangeKnowledge,
     variable_knowledge: VariableKnowledge,
 }
 
@@ -31,6 +34,7 @@ impl Meta {
             degree_knowledge: DegreeKnowledge::default(),
             type_knowledge: TypeKnowledge::default(),
             value_knowledge: ValueKnowledge::default(),
+            range_knowledge: RangeKnowledge::default(),
             variable_knowledge: VariableKnowledge::default(),
         }
     }
@@ -70,6 +74,11 @@ impl Meta {
         &self.value_knowledge
     }
 
+    #[must_use]
+    pub fn range_knowledge(&self) -> &RangeKnowledge {
+        &self.range_knowledge
+    }
+
     #[must_use]
     pub fn variable_knowledge(&self) -> &VariableKnowledge {
         &self.variable_knowledge
@@ -90,6 +99,11 @@ impl Meta {
         &mut self.value_knowledge
     }
 
+    #[must_use]
+    pub fn range_knowledge_mut(&mut self) -> &mut RangeKnowledge {
+        &mut self.range_knowledge
+    }
+
     #[must_use]
     pub fn variable_knowledge_mut(&mut self) -> &mut VariableKnowledge {
         &mut self.variable_knowledge
@@ -103,7 +117,6 @@ impl std::hash::Hash for Meta {
     {
         self.location.hash(state);
         self.file_id.hash(state);
-        state.finish();
     }
 }
 
@@ -116,7 +129,7 @@ impl PartialEq for Meta {
 impl Eq for Meta {}
 
 // TODO: Implement a custom `PartialEq` for `Statement`.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 #[allow(clippy::large_enum_variant)]
 pub enum Statement {
     // We allow for declarations of multiple variables of the same type to avoid
@@ -163,7 +176,7 @@ pub enum Statement {
     },
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Expression {
     /// An infix operation of the form `lhe * rhe`.
     InfixOp {
@@ -207,12 +220,15 @@ pub enum Expression {
 
 pub type TagList = Vec<String>;
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum VariableType {
     Local,
     Component,
     AnonymousComponent,
     Signal(SignalType, TagList),
+    /// A signal with the type of the given bus. Bus fields are accessed using
+    /// component accesses.
+    Bus(String, SignalType, TagList),
 }
 
 impl fmt::Display for VariableType {
@@ -234,11 +250,23 @@ impl fmt::Display for VariableType {
                     Ok(())
                 }
             }
+            Bus(bus_name, signal_type, tag_list) => {
+                if matches!(signal_type, Intermediate) {
+                    write!(f, "{bus_name}")?;
+                } else {
+                    write!(f, "{signal_type} {bus_name}")?;
+                }
+                if !tag_list.is_empty() {
+                    write!(f, " {{{}}}", tag_list.join(", "))
+                } else {
+                    Ok(())
+                }
+            }
         }
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SignalType {
     Input,
     Output,
@@ -261,7 +289,7 @@ impl fmt::Display for SignalType {
 ///   1. The original name (obtained from the source code).
 ///   2. An optional suffix (used to ensure uniqueness when lifting to IR).
 ///   3. An optional version (applied when the CFG is converted to SSA form).
-#[derive(Clone, Hash, PartialEq, Eq)]
+#[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct VariableName {
     /// This is the original name of the variable from the function or template
     /// AST.
@@ -351,13 +379,13 @@ impl fmt::Debug for VariableName {
     }
 }
 
-#[derive(Clone, Hash, Eq, PartialEq)]
+#[derive(Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum AccessType {
     ArrayAccess(Box<Expression>),
     ComponentAccess(String),
 }
 
-#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum AssignOp {
     /// A signal assignment (using `<--`)
     AssignSignal,
@@ -367,7 +395,7 @@ pub enum AssignOp {
     AssignLocalOrComponent,
 }
 
-#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ExpressionInfixOpcode {
     Mul,
     Div,
@@ -391,14 +419,14 @@ pub enum ExpressionInfixOpcode {
     BitXor,
 }
 
-#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ExpressionPrefixOpcode {
     Sub,
     BoolNot,
     Complement,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum LogArgument {
     String(String),
     Expr(Box<Expression>),
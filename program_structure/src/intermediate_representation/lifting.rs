@@ -219,6 +219,11 @@ impl TryLift<()> for ast::VariableType {
             ast::VariableType::Signal(signal_type, tag_list) => {
                 Ok(ir::VariableType::Signal(signal_type.try_lift((), reports)?, tag_list.clone()))
             }
+            ast::VariableType::Bus(bus_name, signal_type, tag_list) => Ok(ir::VariableType::Bus(
+                bus_name.clone(),
+                signal_type.try_lift((), reports)?,
+                tag_list.clone(),
+            )),
         }
     }
 }
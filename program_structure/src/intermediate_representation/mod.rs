@@ -1,6 +1,7 @@
 pub mod declarations;
 pub mod degree_meta;
 pub mod errors;
+pub mod range_meta;
 pub mod type_meta;
 pub mod value_meta;
 pub mod variable_meta;
@@ -8,6 +9,7 @@ pub mod variable_meta;
 mod expression_impl;
 mod ir;
 pub mod lifting;
+pub mod pretty_printer;
 mod statement_impl;
 
 pub use ir::*;
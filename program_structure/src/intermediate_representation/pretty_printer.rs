@@ -0,0 +1,237 @@
+use std::fmt::Write;
+
+use crate::cfg::Cfg;
+
+use super::ir::*;
+use super::variable_meta::VariableUse;
+
+/// The facts stored in `Meta` which are included by the pretty printer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Annotations {
+    /// Annotate expressions with the inferred degree range.
+    pub degree: bool,
+    /// Annotate expressions with the inferred constant value.
+    pub value: bool,
+    /// Annotate variables with the declared variable type.
+    pub variable_type: bool,
+    /// Annotate statements with the variables read and written.
+    pub variable_use: bool,
+}
+
+impl Annotations {
+    /// Returns annotations enabling all supported facts.
+    #[must_use]
+    pub fn all() -> Annotations {
+        Annotations { degree: true, value: true, variable_type: true, variable_use: true }
+    }
+}
+
+/// A pretty printer for the IR. Statements and expressions are printed in a
+/// Circom-like syntax with SSA versions. Expression annotations are printed as
+/// inline comments `/* ... */` following the annotated node, and statement
+/// annotations as line comments `// ...` following the statement.
+///
+/// Note that variable use must be cached for each statement before it is
+/// printed with variable use annotations enabled.
+#[derive(Clone, Debug, Default)]
+pub struct PrettyPrinter {
+    annotations: Annotations,
+}
+
+impl PrettyPrinter {
+    #[must_use]
+    pub fn new(annotations: Annotations) -> PrettyPrinter {
+        PrettyPrinter { annotations }
+    }
+
+    /// Prints the parameters and basic blocks of the CFG.
+    #[must_use]
+    pub fn print_cfg(&self, cfg: &Cfg) -> String {
+        let mut result = String::new();
+        let parameters =
+            cfg.parameters().iter().map(|name| format!("{name:?}")).collect::<Vec<_>>();
+        writeln!(result, "{} {}({})", cfg.definition_type(), cfg.name(), parameters.join(", "))
+            .unwrap();
+        for basic_block in cfg.iter() {
+            let mut predecessors = basic_block.predecessors().iter().collect::<Vec<_>>();
+            let mut successors = basic_block.successors().iter().collect::<Vec<_>>();
+            predecessors.sort();
+            successors.sort();
+            writeln!(
+                result,
+                "\nblock {} (predecessors: [{}], successors: [{}]):",
+                basic_block.index(),
+                predecessors.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "),
+                successors.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+            )
+            .unwrap();
+            for stmt in basic_block.iter() {
+                writeln!(result, "    {}", self.print_statement(stmt)).unwrap();
+            }
+        }
+        result
+    }
+
+    /// Prints the statement (without a trailing newline).
+    #[must_use]
+    pub fn print_statement(&self, stmt: &Statement) -> String {
+        use Statement::*;
+        let mut result = match stmt {
+            Declaration { names, var_type, dimensions, .. } => {
+                let dimensions = dimensions
+                    .iter()
+                    .map(|size| format!("[{}]", self.print_expression(size)))
+                    .collect::<String>();
+                let names = names
+                    .iter()
+                    .map(|name| format!("{name:?}{dimensions}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{var_type} {names}")
+            }
+            Substitution { var, op, rhe, .. } => {
+                format!("{var:?} {op} {}", self.print_expression(rhe))
+            }
+            ConstraintEquality { lhe, rhe, .. } => {
+                format!("{} === {}", self.print_expression(lhe), self.print_expression(rhe))
+            }
+            IfThenElse { cond, true_index, false_index, .. } => match false_index {
+                Some(false_index) => format!(
+                    "if {} then {true_index} else {false_index}",
+                    self.print_expression(cond)
+                ),
+                None => format!("if {} then {true_index}", self.print_expression(cond)),
+            },
+            Return { value, .. } => format!("return {}", self.print_expression(value)),
+            Assert { arg, .. } => format!("assert({})", self.print_expression(arg)),
+            LogCall { args, .. } => {
+                let args = args
+                    .iter()
+                    .map(|arg| match arg {
+                        LogArgument::String(message) => format!("{message:?}"),
+                        LogArgument::Expr(value) => self.print_expression(value),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("log({args})")
+            }
+        };
+        if self.annotations.variable_use {
+            let knowledge = stmt.meta().variable_knowledge();
+            let mut annotations = Vec::new();
+            let reads = format_uses(knowledge.variables_read());
+            if !reads.is_empty() {
+                annotations.push(format!("reads: {reads}"));
+            }
+            let writes = format_uses(knowledge.variables_written());
+            if !writes.is_empty() {
+                annotations.push(format!("writes: {writes}"));
+            }
+            if !annotations.is_empty() {
+                write!(result, "  // {}", annotations.join(", ")).unwrap();
+            }
+        }
+        result
+    }
+
+    /// Prints the expression together with any enabled annotations.
+    #[must_use]
+    pub fn print_expression(&self, expr: &Expression) -> String {
+        use Expression::*;
+        let mut result = match expr {
+            Number(_, value) => format!("{value}"),
+            Variable { name, .. } => format!("{name:?}"),
+            InfixOp { lhe, infix_op, rhe, .. } => format!(
+                "({} {infix_op} {})",
+                self.print_expression(lhe),
+                self.print_expression(rhe)
+            ),
+            PrefixOp { prefix_op, rhe, .. } => {
+                format!("({prefix_op}{})", self.print_expression(rhe))
+            }
+            SwitchOp { cond, if_true, if_false, .. } => format!(
+                "({}? {} : {})",
+                self.print_expression(cond),
+                self.print_expression(if_true),
+                self.print_expression(if_false)
+            ),
+            Call { name, args, .. } => format!("{name}({})", self.print_expressions(args)),
+            InlineArray { values, .. } => format!("[{}]", self.print_expressions(values)),
+            Access { var, access, .. } => {
+                format!("access({var:?}, [{}])", self.print_access(access))
+            }
+            Update { var, access, rhe, .. } => format!(
+                "update({var:?}, [{}], {})",
+                self.print_access(access),
+                self.print_expression(rhe)
+            ),
+            Phi { args, .. } => {
+                let args = args.iter().map(|arg| format!("{arg:?}")).collect::<Vec<_>>();
+                format!("φ({})", args.join(", "))
+            }
+        };
+        let annotations = self.expression_annotations(expr.meta());
+        if !annotations.is_empty() {
+            write!(result, " /* {} */", annotations.join(", ")).unwrap();
+        }
+        result
+    }
+
+    fn print_expressions(&self, exprs: &[Expression]) -> String {
+        exprs.iter().map(|expr| self.print_expression(expr)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn print_access(&self, access: &[AccessType]) -> String {
+        access
+            .iter()
+            .map(|access| match access {
+                AccessType::ArrayAccess(index) => self.print_expression(index),
+                AccessType::ComponentAccess(name) => name.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn expression_annotations(&self, meta: &Meta) -> Vec<String> {
+        let mut annotations = Vec::new();
+        if self.annotations.variable_type {
+            if let Some(var_type) = meta.type_knowledge().variable_type() {
+                annotations.push(format!("type: {var_type}"));
+            }
+        }
+        if self.annotations.value {
+            if let Some(value) = meta.value_knowledge().get_reduces_to() {
+                annotations.push(format!("value: {value}"));
+            }
+        }
+        if self.annotations.degree {
+            if let Some(range) = meta.degree_knowledge().degree() {
+                if range.start() == range.end() {
+                    annotations.push(format!("degree: {:?}", range.start()));
+                } else {
+                    annotations.push(format!("degree: {range:?}"));
+                }
+            }
+        }
+        annotations
+    }
+}
+
+/// Formats a set of variable uses (including SSA versions) in sorted order.
+fn format_uses<'a>(uses: impl Iterator<Item = &'a VariableUse>) -> String {
+    let mut uses = uses
+        .map(|var_use| {
+            let mut result = format!("{:?}", var_use.name());
+            for access in var_use.access() {
+                match access {
+                    AccessType::ArrayAccess(index) => write!(result, "[{index:?}]").unwrap(),
+                    AccessType::ComponentAccess(name) => write!(result, ".{name}").unwrap(),
+                }
+            }
+            result
+        })
+        .collect::<Vec<_>>();
+    uses.sort();
+    uses.dedup();
+    uses.join(", ")
+}
@@ -0,0 +1,700 @@
+use log::trace;
+use num_bigint::BigInt;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::fmt;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::constants::UsefulConstants;
+
+use super::ir::*;
+use super::value_meta::{ValueMeta, ValueReduction};
+
+/// The maximum number of bits we are willing to compute for the bounds of a
+/// range. Any bound larger than this is guaranteed to exceed the prime.
+const MAX_BOUND_SIZE: usize = 1024;
+
+/// An inclusive range of integers. When a range is attached to an IR node it
+/// bounds the canonical representative (in `[0, p)`) of the field element the
+/// node evaluates to. Ranges returned by the arithmetic methods below are
+/// computed over the integers, so they may exceed the prime, or be negative.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ValueRange {
+    lower: BigInt,
+    upper: BigInt,
+}
+
+impl ValueRange {
+    #[must_use]
+    pub fn new(lower: BigInt, upper: BigInt) -> ValueRange {
+        assert!(lower <= upper);
+        ValueRange { lower, upper }
+    }
+
+    /// Returns the range containing only the given value.
+    #[must_use]
+    pub fn from_value(value: &BigInt) -> ValueRange {
+        ValueRange::new(value.clone(), value.clone())
+    }
+
+    /// Returns the range `[0, 1]`.
+    #[must_use]
+    pub fn boolean() -> ValueRange {
+        ValueRange::new(BigInt::zero(), BigInt::one())
+    }
+
+    /// Returns the range `[0, 2^n - 1]` of all `n`-bit values.
+    #[must_use]
+    pub fn bits(n: usize) -> ValueRange {
+        ValueRange::new(BigInt::zero(), (BigInt::one() << n) - 1)
+    }
+
+    #[must_use]
+    pub fn lower(&self) -> &BigInt {
+        &self.lower
+    }
+
+    #[must_use]
+    pub fn upper(&self) -> &BigInt {
+        &self.upper
+    }
+
+    #[must_use]
+    pub fn contains(&self, value: &BigInt) -> bool {
+        &self.lower <= value && value <= &self.upper
+    }
+
+    /// Returns true if the range only contains the values 0 and 1.
+    #[must_use]
+    pub fn is_boolean(&self) -> bool {
+        !self.lower.is_negative() && self.upper <= BigInt::one()
+    }
+
+    /// Returns true if every value in the range is a canonical representative
+    /// of a field element. That is, if the range is contained in `[0, p)`.
+    #[must_use]
+    pub fn is_reduced(&self, prime: &BigInt) -> bool {
+        !self.lower.is_negative() && &self.upper < prime
+    }
+
+    /// Returns true if the range is contained in `[0, p/2]`. Field elements in
+    /// this range are not affected by the normalization to `(-p/2, p/2]`
+    /// performed by Circom before elements are compared.
+    #[must_use]
+    pub fn is_below_half_prime(&self, prime: &BigInt) -> bool {
+        !self.lower.is_negative() && self.upper <= prime / 2
+    }
+
+    /// Reduces the range modulo the prime. Since the result is a single range,
+    /// this is only possible if the range does not wrap around the prime. If
+    /// the range wraps around, `None` is returned.
+    #[must_use]
+    pub fn reduce(&self, prime: &BigInt) -> Option<ValueRange> {
+        let offset = modulus(&self.lower, prime) - &self.lower;
+        let lower = &self.lower + &offset;
+        let upper = &self.upper + &offset;
+        if &upper < prime {
+            Some(ValueRange::new(lower, upper))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the smallest range containing both `self` and `other`.
+    #[must_use]
+    pub fn join(&self, other: &ValueRange) -> ValueRange {
+        ValueRange::new(
+            min(&self.lower, &other.lower).clone(),
+            max(&self.upper, &other.upper).clone(),
+        )
+    }
+
+    /// Returns the intersection of `self` and `other`, or `None` if the two
+    /// ranges are disjoint.
+    #[must_use]
+    pub fn meet(&self, other: &ValueRange) -> Option<ValueRange> {
+        let lower = max(&self.lower, &other.lower);
+        let upper = min(&self.upper, &other.upper);
+        if lower <= upper {
+            Some(ValueRange::new(lower.clone(), upper.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Constructs the smallest range containing all the given ranges. If the
+    /// iterator is empty, or any of the ranges is `None`, this method returns
+    /// `None`.
+    pub fn iter_join<'a, T: IntoIterator<Item = Option<&'a ValueRange>>>(
+        ranges: T,
+    ) -> Option<ValueRange> {
+        let ranges = ranges.into_iter().collect::<Option<Vec<_>>>()?;
+        let (first, rest) = ranges.split_first()?;
+        Some(rest.iter().fold((*first).clone(), |result, range| result.join(range)))
+    }
+
+    #[must_use]
+    pub fn add(&self, other: &ValueRange) -> ValueRange {
+        ValueRange::new(&self.lower + &other.lower, &self.upper + &other.upper)
+    }
+
+    #[must_use]
+    pub fn sub(&self, other: &ValueRange) -> ValueRange {
+        ValueRange::new(&self.lower - &other.upper, &self.upper - &other.lower)
+    }
+
+    #[must_use]
+    pub fn neg(&self) -> ValueRange {
+        ValueRange::new(-&self.upper, -&self.lower)
+    }
+
+    /// Multiplication is only supported for non-negative ranges.
+    #[must_use]
+    pub fn mul(&self, other: &ValueRange) -> Option<ValueRange> {
+        if self.lower.is_negative() || other.lower.is_negative() {
+            return None;
+        }
+        Some(ValueRange::new(&self.lower * &other.lower, &self.upper * &other.upper))
+    }
+
+    /// Exponentiation is only supported for non-negative ranges. If the upper
+    /// bound of the result is too large to compute, `None` is returned.
+    #[must_use]
+    pub fn pow(&self, other: &ValueRange) -> Option<ValueRange> {
+        if self.lower.is_negative() || other.lower.is_negative() {
+            return None;
+        }
+        let exponent = other.upper.to_usize()?;
+        if self.upper > BigInt::one()
+            && (self.upper.bits() - 1).saturating_mul(exponent) > MAX_BOUND_SIZE
+        {
+            return None;
+        }
+        let lower = if self.lower.is_zero() && other.lower.is_zero() {
+            // Either the base or the exponent may be zero.
+            BigInt::zero()
+        } else {
+            num_traits::pow(self.lower.clone(), other.lower.to_usize()?)
+        };
+        let upper = num_traits::pow(self.upper.clone(), exponent);
+        Some(ValueRange::new(min(lower, upper.clone()), upper))
+    }
+
+    /// Integer division is only supported for non-negative ranges where the
+    /// divisor is non-zero.
+    #[must_use]
+    pub fn int_div(&self, other: &ValueRange) -> Option<ValueRange> {
+        if self.lower.is_negative() || !other.lower.is_positive() {
+            return None;
+        }
+        Some(ValueRange::new(&self.lower / &other.upper, &self.upper / &other.lower))
+    }
+
+    /// Reduction modulo `other` is only supported for non-negative ranges where
+    /// the modulus is non-zero.
+    #[must_use]
+    pub fn modulo(&self, other: &ValueRange) -> Option<ValueRange> {
+        if self.lower.is_negative() || !other.lower.is_positive() {
+            return None;
+        }
+        if self.upper < other.lower {
+            // The value is always smaller than the modulus.
+            return Some(self.clone());
+        }
+        Some(ValueRange::new(BigInt::zero(), &other.upper - 1))
+    }
+
+    /// Left shifts are only supported for non-negative ranges. If the upper
+    /// bound of the result is too large to compute, `None` is returned.
+    #[must_use]
+    pub fn shift_left(&self, other: &ValueRange) -> Option<ValueRange> {
+        if self.lower.is_negative() || other.lower.is_negative() {
+            return None;
+        }
+        let shift = other.upper.to_usize()?;
+        if shift > MAX_BOUND_SIZE {
+            return None;
+        }
+        let lower = &self.lower << other.lower.to_usize()?;
+        Some(ValueRange::new(lower, &self.upper << shift))
+    }
+
+    /// Right shifts are only supported for non-negative ranges.
+    #[must_use]
+    pub fn shift_right(&self, other: &ValueRange) -> Option<ValueRange> {
+        if self.lower.is_negative() || other.lower.is_negative() {
+            return None;
+        }
+        let lower = match other.upper.to_usize() {
+            Some(shift) => &self.lower >> shift,
+            None => BigInt::zero(),
+        };
+        let upper = &self.upper >> other.lower.to_usize()?;
+        Some(ValueRange::new(lower, upper))
+    }
+
+    /// Bitwise and is only supported for non-negative ranges.
+    #[must_use]
+    pub fn bit_and(&self, other: &ValueRange) -> Option<ValueRange> {
+        if self.lower.is_negative() || other.lower.is_negative() {
+            return None;
+        }
+        Some(ValueRange::new(BigInt::zero(), min(&self.upper, &other.upper).clone()))
+    }
+
+    /// Bitwise or and xor are only supported for non-negative ranges.
+    #[must_use]
+    pub fn bit_or(&self, other: &ValueRange) -> Option<ValueRange> {
+        if self.lower.is_negative() || other.lower.is_negative() {
+            return None;
+        }
+        Some(ValueRange::bits(max(self.upper.bits(), other.upper.bits())))
+    }
+}
+
+impl fmt::Debug for ValueRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "[{}, {}]", self.lower, self.upper)
+    }
+}
+
+impl fmt::Display for ValueRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "[{}, {}]", self.lower, self.upper)
+    }
+}
+
+/// Circomlib templates where all output signals are constrained to be either 0
+/// or 1.
+const BOOLEAN_OUTPUT_TEMPLATES: [&str; 8] = [
+    "Num2Bits",
+    "Num2Bits_strict",
+    "LessThan",
+    "LessEqThan",
+    "GreaterThan",
+    "GreaterEqThan",
+    "IsZero",
+    "IsEqual",
+];
+
+/// This type is used to track value ranges of variables and component signals
+/// during range propagation.
+#[derive(Clone)]
+pub struct RangeEnvironment {
+    constants: UsefulConstants,
+    // Ranges of variables implied by assignments. Since we assume SSA, each
+    // local variable is assigned exactly once. Signals are not versioned and
+    // may be assigned on different branches, so ranges from different
+    // assignments are joined.
+    assigned: HashMap<VariableName, ValueRange>,
+    // Ranges of variables implied by constraints. Since all constraints must
+    // hold, ranges from different constraints are intersected.
+    constrained: HashMap<VariableName, ValueRange>,
+    // The intersection of the assigned and constrained range of each variable.
+    ranges: HashMap<VariableName, ValueRange>,
+    // Ranges of component signals, keyed by the (unversioned) component name
+    // and the signal name. The same range applies to all array elements.
+    component_signals: HashMap<(VariableName, String), ValueRange>,
+}
+
+impl RangeEnvironment {
+    pub fn new(constants: &UsefulConstants) -> RangeEnvironment {
+        RangeEnvironment {
+            constants: constants.clone(),
+            assigned: HashMap::new(),
+            constrained: HashMap::new(),
+            ranges: HashMap::new(),
+            component_signals: HashMap::new(),
+        }
+    }
+
+    /// Adds the range of a value assigned to the given variable. If the
+    /// variable is assigned more than once (e.g. a signal assigned on
+    /// different branches), the smallest range containing all assigned values
+    /// is used. Returns true if the range of the variable was updated.
+    pub fn add_variable(&mut self, var: &VariableName, range: &ValueRange) -> bool {
+        let range = match self.assigned.get(var) {
+            Some(previous) => previous.join(range),
+            None => range.clone(),
+        };
+        self.assigned.insert(var.clone(), range);
+        self.update_variable(var)
+    }
+
+    /// Adds a range of the given variable implied by a constraint. If the
+    /// variable is constrained more than once, the intersection of the ranges
+    /// is used. Returns true if the range of the variable was updated.
+    pub fn add_constrained_variable(&mut self, var: &VariableName, range: &ValueRange) -> bool {
+        let range = match self.constrained.get(var) {
+            Some(previous) => match previous.meet(range) {
+                Some(range) => range,
+                None => return false,
+            },
+            None => range.clone(),
+        };
+        self.constrained.insert(var.clone(), range);
+        self.update_variable(var)
+    }
+
+    /// Updates the range of the given variable from the assigned and
+    /// constrained ranges. Returns true if the range was updated.
+    fn update_variable(&mut self, var: &VariableName) -> bool {
+        let range = match (self.assigned.get(var), self.constrained.get(var)) {
+            (Some(assigned), Some(constrained)) => {
+                assigned.meet(constrained).unwrap_or_else(|| constrained.clone())
+            }
+            (Some(range), None) | (None, Some(range)) => range.clone(),
+            (None, None) => return false,
+        };
+        if self.ranges.get(var) == Some(&range) {
+            return false;
+        }
+        trace!("setting range of `{var:?}` to {range}");
+        self.ranges.insert(var.clone(), range);
+        true
+    }
+
+    /// Gets the range of the given variable.
+    #[must_use]
+    pub fn get_variable(&self, var: &VariableName) -> Option<&ValueRange> {
+        self.ranges.get(var)
+    }
+
+    /// Sets the range of the given component signal. As for variables, ranges
+    /// from different sources are intersected. Returns true if the range of
+    /// the signal was updated.
+    pub fn add_component_signal(
+        &mut self,
+        component: &VariableName,
+        signal: &str,
+        range: &ValueRange,
+    ) -> bool {
+        let key = (component.without_version(), signal.to_string());
+        let range = match self.component_signals.get(&key) {
+            Some(previous) => match previous.meet(range) {
+                Some(range) if range != *previous => range,
+                _ => return false,
+            },
+            None => range.clone(),
+        };
+        trace!("setting range of `{component}.{signal}` to {range}");
+        self.component_signals.insert(key, range);
+        true
+    }
+
+    /// Gets the range of the given component signal.
+    #[must_use]
+    pub fn get_component_signal(
+        &self,
+        component: &VariableName,
+        signal: &str,
+    ) -> Option<&ValueRange> {
+        self.component_signals.get(&(component.without_version(), signal.to_string()))
+    }
+
+    /// Returns the prime used.
+    #[must_use]
+    pub fn prime(&self) -> &BigInt {
+        self.constants.prime()
+    }
+
+    /// Returns the size in bits of the prime used.
+    #[must_use]
+    pub fn prime_size(&self) -> usize {
+        self.constants.prime_size()
+    }
+
+    /// Returns an iterator over all variables with a known range.
+    pub fn iter(&self) -> impl Iterator<Item = (&VariableName, &ValueRange)> {
+        self.ranges.iter()
+    }
+}
+
+/// Tracks the template instantiations `var = T(args, ...)` for a single
+/// (possibly array-valued) component variable.
+#[derive(Default)]
+struct ComponentInstances {
+    instances: Vec<(String, Vec<Expression>)>,
+}
+
+impl ComponentInstances {
+    /// Returns the template name if all instances are of the same template.
+    fn template_name(&self) -> Option<&str> {
+        let (first, _) = self.instances.first()?;
+        if self.instances.iter().all(|(name, _)| name == first) {
+            Some(first)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the maximum value of the first template argument, if this is
+    /// known for all instances.
+    fn max_argument(&self) -> Option<BigInt> {
+        self.instances
+            .iter()
+            .map(|(_, args)| match args.first().and_then(|arg| arg.value()) {
+                Some(ValueReduction::FieldElement { value }) => Some(value.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .max()
+    }
+}
+
+impl RangeEnvironment {
+    /// Seeds the environment with ranges implied by the given statements. This
+    /// includes
+    ///
+    ///   1. Outputs of Circomlib templates like `Num2Bits` and `LessThan`,
+    ///      which are constrained to be either 0 or 1.
+    ///   2. Inputs to `Num2Bits(n)` which are constrained to be `n`-bit values
+    ///      whenever `n` is known.
+    ///   3. Variables `x` constrained to be 0 or 1 using a bit-decomposition
+    ///      constraint of the form `x * (x - 1) === 0`.
+    ///
+    /// Constant propagation must run before this method is called.
+    pub fn add_constraints<'a, T: IntoIterator<Item = &'a Statement>>(&mut self, stmts: T) {
+        let stmts = stmts.into_iter().collect::<Vec<_>>();
+        let mut components = HashMap::<VariableName, ComponentInstances>::new();
+        for stmt in &stmts {
+            if let Some((var, name, args)) = component_instantiation(stmt) {
+                let instances = components.entry(var.without_version()).or_default();
+                instances.instances.push((name.clone(), args.clone()));
+            }
+        }
+        // 1. Outputs of templates constraining all outputs to be 0 or 1.
+        for (var, instances) in &components {
+            if let Some(name) = instances.template_name() {
+                if BOOLEAN_OUTPUT_TEMPLATES.contains(&name) {
+                    self.add_component_signal(var, "out", &ValueRange::boolean());
+                }
+            }
+        }
+        for stmt in &stmts {
+            // 2. Inputs to `Num2Bits(n)`.
+            if let Some((component, signal, value)) = component_input(stmt) {
+                let Some(instances) = components.get(&component.without_version()) else {
+                    continue;
+                };
+                if signal != "in" || instances.template_name() != Some("Num2Bits") {
+                    continue;
+                }
+                let Some(size) = instances.max_argument().and_then(|size| size.to_usize()) else {
+                    continue;
+                };
+                if size < self.prime_size() {
+                    self.add_tracked_expression(value, &ValueRange::bits(size));
+                }
+            }
+            // 3. Bit-decomposition constraints.
+            if let Statement::ConstraintEquality { lhe, rhe, .. } = stmt {
+                if let Some(value) = bit_constraint(lhe, rhe).or_else(|| bit_constraint(rhe, lhe)) {
+                    self.add_tracked_expression(value, &ValueRange::boolean());
+                }
+            }
+        }
+    }
+
+    /// Sets the range of the expression, if the expression is a local variable
+    /// or signal.
+    fn add_tracked_expression(&mut self, expr: &Expression, range: &ValueRange) {
+        if let Expression::Variable { meta, name } = expr {
+            if meta.type_knowledge().is_local() || meta.type_knowledge().is_signal() {
+                self.add_constrained_variable(name, range);
+            }
+        }
+    }
+}
+
+/// Returns the component, template name, and template arguments if the
+/// statement is a component initialization of the form `var = T(args, ...)`,
+/// or `var[i] = T(args, ...)`.
+fn component_instantiation(stmt: &Statement) -> Option<(&VariableName, &String, &Vec<Expression>)> {
+    use Expression::*;
+    let Statement::Substitution { meta, var, op: AssignOp::AssignLocalOrComponent, rhe } = stmt
+    else {
+        return None;
+    };
+    if !meta.type_knowledge().is_component() {
+        return None;
+    }
+    let rhe = if let Update { rhe, .. } = rhe { rhe } else { rhe };
+    match rhe {
+        Call { name, args, .. } => Some((var, name, args)),
+        _ => None,
+    }
+}
+
+/// Returns the component, signal name, and assigned value if the statement
+/// constrains a component signal. That is, if the statement is of the form
+/// `var.signal <== value`, or `var.signal === value`.
+fn component_input(stmt: &Statement) -> Option<(&VariableName, &String, &Expression)> {
+    use Expression::*;
+    use Statement::*;
+    fn signal_name(access: &[AccessType]) -> Option<&String> {
+        match access.last() {
+            Some(AccessType::ComponentAccess(signal)) => Some(signal),
+            _ => None,
+        }
+    }
+    match stmt {
+        Substitution {
+            var,
+            op: AssignOp::AssignConstraintSignal,
+            rhe: Update { access, rhe, .. },
+            ..
+        } => Some((var, signal_name(access)?, rhe.as_ref())),
+        ConstraintEquality { lhe: Access { var, access, .. }, rhe, .. }
+        | ConstraintEquality { lhe: rhe, rhe: Access { var, access, .. }, .. } => {
+            Some((var, signal_name(access)?, rhe))
+        }
+        _ => None,
+    }
+}
+
+/// Returns `x` if the constraint `lhe === rhe` is of the form `x * (x - 1) ===
+/// 0`, `x * (1 - x) === 0`, or `x * x === x` (up to the order of the factors).
+fn bit_constraint<'a>(lhe: &'a Expression, rhe: &'a Expression) -> Option<&'a Expression> {
+    use Expression::*;
+    use ExpressionInfixOpcode::*;
+    let InfixOp { lhe: first, infix_op: Mul, rhe: second, .. } = lhe else {
+        return None;
+    };
+    let is_one = |expr: &Expression| matches!(expr.value(), Some(ValueReduction::FieldElement { value }) if value.is_one());
+    let is_zero = |expr: &Expression| matches!(expr.value(), Some(ValueReduction::FieldElement { value }) if value.is_zero());
+    // Returns true if `factor` is either `x - 1` or `1 - x`.
+    let is_shifted = |factor: &Expression, x: &Expression| match factor {
+        InfixOp { lhe, infix_op: Sub, rhe, .. } => {
+            (lhe.as_ref() == x && is_one(rhe)) || (is_one(lhe) && rhe.as_ref() == x)
+        }
+        _ => false,
+    };
+    if is_zero(rhe) {
+        if is_shifted(second, first) {
+            return Some(first);
+        }
+        if is_shifted(first, second) {
+            return Some(second);
+        }
+    }
+    if first == second && first.as_ref() == rhe {
+        return Some(rhe);
+    }
+    None
+}
+
+pub trait RangeMeta {
+    /// Propagate value ranges defined by the environment to each sub-node.
+    /// The method returns true if the node (or a sub-node) was updated.
+    fn propagate_ranges(&mut self, env: &mut RangeEnvironment) -> bool;
+
+    /// Returns the range of values the node may take, if it is known.
+    #[must_use]
+    fn range(&self) -> Option<&ValueRange>;
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct RangeKnowledge {
+    range: Option<ValueRange>,
+}
+
+impl RangeKnowledge {
+    #[must_use]
+    pub fn new() -> RangeKnowledge {
+        RangeKnowledge::default()
+    }
+
+    /// Sets the range of the node. Returns `true` on the first update.
+    pub fn set_range(&mut self, range: &ValueRange) -> bool {
+        let result = self.range.is_none();
+        self.range = Some(range.clone());
+        result
+    }
+
+    /// Returns the range of the node. Returns `None` if the range is unknown.
+    #[must_use]
+    pub fn range(&self) -> Option<&ValueRange> {
+        self.range.as_ref()
+    }
+
+    /// Returns true if the range is known and contained in `[0, p/2]`.
+    #[must_use]
+    pub fn is_below_half_prime(&self, prime: &BigInt) -> bool {
+        matches!(&self.range, Some(range) if range.is_below_half_prime(prime))
+    }
+}
+
+/// Returns the canonical representative of `value` modulo `prime`.
+fn modulus(value: &BigInt, prime: &BigInt) -> BigInt {
+    ((value % prime) + prime) % prime
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+
+    use super::{RangeKnowledge, ValueRange};
+
+    fn range(lower: i64, upper: i64) -> ValueRange {
+        ValueRange::new(BigInt::from(lower), BigInt::from(upper))
+    }
+
+    #[test]
+    fn test_range_arithmetic() {
+        let prime = BigInt::from(101);
+
+        assert_eq!(range(0, 1).add(&range(0, 1)), range(0, 2));
+        assert_eq!(range(0, 1).sub(&range(0, 1)), range(-1, 1));
+        assert_eq!(range(2, 3).mul(&range(4, 5)), Some(range(8, 15)));
+        assert_eq!(range(0, 2).pow(&range(0, 3)), Some(range(0, 8)));
+        assert_eq!(range(1, 3).shift_left(&range(1, 2)), Some(range(2, 12)));
+        assert_eq!(range(4, 12).shift_right(&range(1, 2)), Some(range(1, 6)));
+        assert_eq!(range(7, 30).int_div(&range(2, 3)), Some(range(2, 15)));
+        assert_eq!(range(0, 30).modulo(&range(8, 8)), Some(range(0, 7)));
+        assert_eq!(range(0, 5).bit_or(&range(0, 8)), Some(range(0, 15)));
+        assert!(range(-1, 1).mul(&range(0, 1)).is_none());
+        assert!(range(0, 1).int_div(&range(0, 1)).is_none());
+
+        // Reduction modulo the prime.
+        assert_eq!(range(0, 100).reduce(&prime), Some(range(0, 100)));
+        assert_eq!(range(-3, -1).reduce(&prime), Some(range(98, 100)));
+        assert_eq!(range(101, 105).reduce(&prime), Some(range(0, 4)));
+        assert!(range(-1, 1).reduce(&prime).is_none());
+        assert!(range(0, 101).reduce(&prime).is_none());
+
+        assert!(range(0, 50).is_below_half_prime(&prime));
+        assert!(!range(0, 51).is_below_half_prime(&prime));
+    }
+
+    #[test]
+    fn test_range_lattice() {
+        assert_eq!(range(0, 1).join(&range(4, 5)), range(0, 5));
+        assert_eq!(range(0, 4).meet(&range(2, 6)), Some(range(2, 4)));
+        assert!(range(0, 1).meet(&range(2, 6)).is_none());
+        assert_eq!(
+            ValueRange::iter_join([Some(&range(0, 1)), Some(&range(3, 4))]),
+            Some(range(0, 4))
+        );
+        assert!(ValueRange::iter_join([Some(&range(0, 1)), None]).is_none());
+        assert!(ValueRange::iter_join([]).is_none());
+    }
+
+    #[test]
+    fn test_range_knowledge() {
+        let prime = BigInt::from(101);
+        let mut knowledge = RangeKnowledge::new();
+        assert!(knowledge.range().is_none());
+        assert!(!knowledge.is_below_half_prime(&prime));
+
+        assert!(knowledge.set_range(&range(0, 1)));
+        assert_eq!(knowledge.range(), Some(&range(0, 1)));
+        assert!(knowledge.is_below_half_prime(&prime));
+
+        assert!(!knowledge.set_range(&range(0, 100)));
+        assert_eq!(knowledge.range(), Some(&range(0, 100)));
+        assert!(!knowledge.is_below_half_prime(&prime));
+    }
+}
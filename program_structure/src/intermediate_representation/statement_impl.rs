@@ -4,6 +4,7 @@ use std::fmt;
 use super::declarations::Declarations;
 use super::ir::*;
 use super::degree_meta::{Degree, DegreeEnvironment, DegreeMeta};
+use super::range_meta::{RangeEnvironment, RangeMeta};
 use super::type_meta::TypeMeta;
 use super::value_meta::{ValueEnvironment, ValueMeta};
 use super::variable_meta::{VariableMeta, VariableUse, VariableUses};
@@ -47,7 +48,10 @@ impl Statement {
             Declaration { names, var_type, .. } => {
                 for name in names.iter() {
                     // Since we disregard accesses, components are treated as signals.
-                    if matches!(var_type, Signal(_, _) | Component | AnonymousComponent) {
+                    if matches!(
+                        var_type,
+                        Signal(_, _) | Bus(_, _, _) | Component | AnonymousComponent
+                    ) {
                         result = result || env.set_degree(name, &Linear.into());
                     }
                     env.set_type(name, var_type);
@@ -127,6 +131,59 @@ impl Statement {
         }
     }
 
+    pub fn propagate_ranges(&mut self, env: &mut RangeEnvironment) -> bool {
+        use AssignOp::*;
+        use Expression::*;
+        use Statement::*;
+        match self {
+            Declaration { dimensions, .. } => {
+                let mut result = false;
+                for size in dimensions {
+                    result |= size.propagate_ranges(env);
+                }
+                result
+            }
+            Substitution { meta, var, op, rhe } => {
+                let mut result = rhe.propagate_ranges(env);
+                // TODO: Handle array values.
+                if matches!(rhe, Update { .. }) {
+                    return result;
+                }
+                // Signals assigned using `<--` are not constrained by the
+                // assigned value, so we only track ranges for local variables
+                // and signals assigned using `<==`.
+                let is_tracked = match op {
+                    AssignLocalOrComponent => meta.type_knowledge().is_local(),
+                    AssignConstraintSignal => true,
+                    AssignSignal => false,
+                };
+                if let (true, Some(range)) = (is_tracked, rhe.range()) {
+                    env.add_variable(var, range);
+                }
+                if let Some(range) = env.get_variable(var) {
+                    result |= meta.range_knowledge_mut().set_range(range);
+                }
+                result
+            }
+            LogCall { args, .. } => {
+                let mut result = false;
+                use LogArgument::*;
+                for arg in args {
+                    if let Expr(value) = arg {
+                        result |= value.propagate_ranges(env);
+                    }
+                }
+                result
+            }
+            IfThenElse { cond, .. } => cond.propagate_ranges(env),
+            Return { value, .. } => value.propagate_ranges(env),
+            Assert { arg, .. } => arg.propagate_ranges(env),
+            ConstraintEquality { lhe, rhe, .. } => {
+                lhe.propagate_ranges(env) | rhe.propagate_ranges(env)
+            }
+        }
+    }
+
     pub fn propagate_types(&mut self, vars: &Declarations) {
         use Statement::*;
         match self {
@@ -304,7 +361,14 @@ impl VariableMeta for Statement {
                         trace!("adding `{var:?}` to local variables written");
                         locals_written.insert(VariableUse::new(meta, var, &access));
                     }
-                    Some(VariableType::Signal(_, _)) => {
+                    Some(VariableType::Bus(_, _, _))
+                        if matches!(op, AssignOp::AssignLocalOrComponent) =>
+                    {
+                        // Bus initializations on the form `var = Bus(args)` do not
+                        // assign a value to the signal.
+                        trace!("ignoring initialization of `{var:?}`");
+                    }
+                    Some(VariableType::Signal(_, _) | VariableType::Bus(_, _, _)) => {
                         trace!("adding `{var:?}` to signals written");
                         signals_written.insert(VariableUse::new(meta, var, &access));
                         if matches!(op, AssignOp::AssignConstraintSignal) {
@@ -1,5 +1,6 @@
 use super::declarations::Declarations;
 use super::ir::VariableType;
+use serde_derive::{Deserialize, Serialize};
 
 pub trait TypeMeta {
     /// Propagate variable types to variable child nodes.
@@ -23,7 +24,7 @@ pub trait TypeMeta {
     fn variable_type(&self) -> Option<&VariableType>;
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct TypeKnowledge {
     var_type: Option<VariableType>,
 }
@@ -55,7 +56,7 @@ impl TypeKnowledge {
     /// Returns true if the node is a signal.
     #[must_use]
     pub fn is_signal(&self) -> bool {
-        matches!(self.var_type, Some(VariableType::Signal(_, _)))
+        matches!(self.var_type, Some(VariableType::Signal(_, _) | VariableType::Bus(_, _, _)))
     }
 
     /// Returns true if the node is a (possibly anonymous) component.
@@ -1,6 +1,7 @@
 use num_bigint::BigInt;
 use std::collections::HashMap;
 use std::fmt;
+use serde_derive::{Deserialize, Serialize};
 
 use crate::constants::UsefulConstants;
 
@@ -65,7 +66,7 @@ pub trait ValueMeta {
     fn value(&self) -> Option<&ValueReduction>;
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ValueReduction {
     Boolean { value: bool },
     FieldElement { value: BigInt },
@@ -81,7 +82,7 @@ impl fmt::Display for ValueReduction {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct ValueKnowledge {
     reduces_to: Option<ValueReduction>,
 }
@@ -138,7 +139,7 @@ mod tests {
     #[test]
     fn test_value_knowledge() {
         let mut value = ValueKnowledge::new();
-        assert!(matches!(value.get_reduces_to(), None));
+        assert!(value.get_reduces_to().is_none());
 
         let number = ValueReduction::FieldElement { value: BigInt::from(1) };
         assert!(value.set_reduces_to(number));
@@ -1,10 +1,11 @@
 use std::fmt;
 use std::collections::HashSet;
+use serde_derive::{Deserialize, Serialize};
 
 use super::ir::{AccessType, Meta, VariableName};
 
 /// A variable use (a variable, component or signal read or write).
-#[derive(Clone, Hash, PartialEq, Eq)]
+#[derive(Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VariableUse {
     meta: Meta,
     name: VariableName,
@@ -104,7 +105,7 @@ pub trait VariableMeta {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct VariableKnowledge {
     locals_read: Option<VariableUses>,
     locals_written: Option<VariableUses>,
@@ -0,0 +1,246 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_derive::{Deserialize, Serialize};
+
+use super::file_definition::FileLibrary;
+use super::report::Report;
+
+const BASELINE_VERSION: usize = 1;
+
+/// A single finding recorded in a baseline file. Only the fingerprint is used
+/// to match reports against the baseline. The remaining fields are included to
+/// make the baseline file readable, and to describe fixed findings.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub fingerprint: String,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub definition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub message: String,
+}
+
+impl BaselineEntry {
+    pub fn new(report: &Report, file_library: &FileLibrary) -> BaselineEntry {
+        BaselineEntry {
+            fingerprint: fingerprint(report, file_library),
+            id: report.id(),
+            definition: report.definition().map(ToString::to_string),
+            file: primary_file(report, file_library),
+            message: report.message().clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineFile {
+    version: usize,
+    findings: Vec<BaselineEntry>,
+}
+
+/// A set of known findings. Reports matching a finding in the baseline are
+/// filtered out, so that only new findings are reported.
+///
+/// Each finding in the baseline matches at most one report. This ensures that
+/// new findings are reported even if they are identical to a known finding
+/// (e.g. if a statement is duplicated).
+pub struct Baseline {
+    entries: Vec<BaselineEntry>,
+    file_library: FileLibrary,
+    // Indices of unused entries, indexed by fingerprint.
+    unused: RefCell<HashMap<String, Vec<usize>>>,
+}
+
+impl Baseline {
+    /// Creates a baseline from the given entries. The file library is used to
+    /// compute the fingerprints of reports.
+    pub fn new(entries: Vec<BaselineEntry>, file_library: &FileLibrary) -> Baseline {
+        let mut unused = HashMap::<String, Vec<usize>>::new();
+        for (index, entry) in entries.iter().enumerate().rev() {
+            unused.entry(entry.fingerprint.clone()).or_default().push(index);
+        }
+        Baseline { entries, file_library: file_library.clone(), unused: RefCell::new(unused) }
+    }
+
+    /// Reads a baseline from the given file.
+    pub fn read(baseline_file: &Path, file_library: &FileLibrary) -> Result<Baseline> {
+        let contents = fs::read_to_string(baseline_file)
+            .with_context(|| format!("failed to read baseline `{}`", baseline_file.display()))?;
+        let baseline: BaselineFile = serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse baseline `{}`", baseline_file.display()))?;
+        Ok(Baseline::new(baseline.findings, file_library))
+    }
+
+    /// Writes a baseline containing the given reports to the given file.
+    /// Returns the number of findings written.
+    pub fn write(
+        baseline_file: &Path,
+        reports: &[Report],
+        file_library: &FileLibrary,
+    ) -> Result<usize> {
+        let mut findings = reports
+            .iter()
+            .map(|report| BaselineEntry::new(report, file_library))
+            .collect::<Vec<_>>();
+        findings.sort_by(|lhs, rhs| {
+            (&lhs.file, &lhs.id, &lhs.fingerprint).cmp(&(&rhs.file, &rhs.id, &rhs.fingerprint))
+        });
+        let baseline = BaselineFile { version: BASELINE_VERSION, findings };
+        let contents = serde_json::to_string_pretty(&baseline)?;
+        fs::write(baseline_file, contents + "\n")
+            .with_context(|| format!("failed to write baseline `{}`", baseline_file.display()))?;
+        Ok(baseline.findings.len())
+    }
+
+    /// Returns the number of findings in the baseline.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the baseline contains no findings.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns true if the report matches an unused finding in the baseline.
+    /// The matching finding is marked as used.
+    pub fn contains(&self, report: &Report) -> bool {
+        let fingerprint = fingerprint(report, &self.file_library);
+        self.unused.borrow_mut().get_mut(&fingerprint).and_then(|indices| indices.pop()).is_some()
+    }
+
+    /// Returns the findings in the baseline that did not match any report.
+    pub fn fixed(&self) -> Vec<&BaselineEntry> {
+        let mut indices = self.unused.borrow().values().flatten().copied().collect::<Vec<_>>();
+        indices.sort();
+        indices.into_iter().map(|index| &self.entries[index]).collect()
+    }
+}
+
+/// Returns a stable fingerprint for the report. The fingerprint is computed
+/// from the report ID, the template or function analyzed, the normalized
+/// message, and the file and source text of the first primary location. Line
+/// numbers and byte offsets are not included, so the fingerprint is unchanged
+/// if code is added or removed elsewhere in the file.
+pub fn fingerprint(report: &Report, file_library: &FileLibrary) -> String {
+    let mut components = vec![
+        report.id(),
+        report.definition().unwrap_or_default().to_string(),
+        normalize(report.message()),
+    ];
+    if let Some(label) = report.primary().first() {
+        let files = file_library.to_storage();
+        if let Ok(file) = files.get(label.file_id) {
+            components.push(file_library.relative_path(file.name()));
+            if let Some(source) = file.source().get(label.range.clone()) {
+                components.push(normalize(source));
+            }
+        }
+    }
+    format!("{:016x}", fnv1a(components.join("\0").as_bytes()))
+}
+
+/// Returns the path of the file containing the first primary location of the
+/// report, relative to the project root if possible.
+fn primary_file(report: &Report, file_library: &FileLibrary) -> Option<String> {
+    let label = report.primary().first()?;
+    let file = file_library.to_storage().get(label.file_id).ok()?;
+    Some(file_library.relative_path(file.name()))
+}
+
+/// Collapses whitespace and replaces numbers by `N`. (Messages may contain
+/// values that depend on the surrounding code, like constraint coefficients.)
+fn normalize(text: &str) -> String {
+    let mut result = String::new();
+    let mut words = text.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        let mut last_digit = false;
+        for c in word.chars() {
+            if c.is_ascii_digit() {
+                if !last_digit {
+                    result.push('N');
+                }
+                last_digit = true;
+            } else {
+                result.push(c);
+                last_digit = false;
+            }
+        }
+        if words.peek().is_some() {
+            result.push(' ');
+        }
+    }
+    result
+}
+
+/// The 64-bit FNV-1a hash. (The standard library hashers are not guaranteed to
+/// be stable across Rust releases.)
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report_code::ReportCode;
+
+    use super::*;
+
+    #[test]
+    fn test_baseline() {
+        let src = "var x = 1;\nvar y = 2;\n";
+        let shifted_src = "\n\nvar y  = 2;\nvar x = 1;\nvar x = 1;\n";
+        let report = |src: &str, var: &str| {
+            let start = src.find(var).unwrap();
+            let end = start + src[start..].find('\n').unwrap();
+            let mut report = Report::warning(
+                format!("The variable `{var}` is assigned 1 time."),
+                ReportCode::UnusedVariableValue,
+            );
+            report.set_definition("T".to_string());
+            report.add_primary(start..end, 0, "test".to_string());
+            report
+        };
+        let mut file_library = FileLibrary::new();
+        file_library.add_file("test.circom".to_string(), src.to_string(), true);
+        let entries = vec![
+            BaselineEntry::new(&report(src, "var x"), &file_library),
+            BaselineEntry::new(&report(src, "var y"), &file_library),
+        ];
+
+        // Fingerprints are stable when lines shift and whitespace changes.
+        let mut shifted_library = FileLibrary::new();
+        shifted_library.add_file("test.circom".to_string(), shifted_src.to_string(), true);
+        let baseline = Baseline::new(entries, &shifted_library);
+        assert_eq!(baseline.len(), 2);
+        let x = report(shifted_src, "var x");
+        let y = report(shifted_src, "var y");
+        assert!(baseline.contains(&y));
+        assert!(baseline.contains(&x));
+        // Each finding in the baseline matches at most one report.
+        assert!(!baseline.contains(&x));
+        assert!(baseline.fixed().is_empty());
+    }
+
+    #[test]
+    fn test_fixed_findings() {
+        let src = "var x = 1;\n";
+        let mut file_library = FileLibrary::new();
+        file_library.add_file("test.circom".to_string(), src.to_string(), true);
+        let mut report = Report::warning("test".to_string(), ReportCode::UnusedVariableValue);
+        report.add_primary(0..10, 0, "test".to_string());
+        let entry = BaselineEntry::new(&report, &file_library);
+
+        let mut other = Report::warning("test".to_string(), ReportCode::FieldElementArithmetic);
+        other.add_primary(0..10, 0, "test".to_string());
+        let baseline = Baseline::new(vec![entry.clone()], &file_library);
+        assert!(!baseline.contains(&other));
+        assert_eq!(baseline.fixed(), vec![&entry]);
+    }
+}
@@ -0,0 +1,46 @@
+use super::ast::{FillMeta, Statement};
+use super::file_definition::FileID;
+use crate::file_definition::FileLocation;
+use std::collections::HashMap;
+
+pub type BusInfo = HashMap<String, BusData>;
+
+/// A Circom 2.2 bus definition. The body of the bus consists of the
+/// declarations of the bus fields.
+#[derive(Clone)]
+pub struct BusData {
+    name: String,
+    file_id: FileID,
+    name_of_params: Vec<String>,
+    param_location: FileLocation,
+    body: Statement,
+}
+
+impl BusData {
+    pub fn new(
+        name: String,
+        file_id: FileID,
+        mut body: Statement,
+        name_of_params: Vec<String>,
+        param_location: FileLocation,
+        elem_id: &mut usize,
+    ) -> BusData {
+        body.fill(file_id, elem_id);
+        BusData { name, file_id, body, name_of_params, param_location }
+    }
+    pub fn get_file_id(&self) -> FileID {
+        self.file_id
+    }
+    pub fn get_body(&self) -> &Statement {
+        &self.body
+    }
+    pub fn get_param_location(&self) -> FileLocation {
+        self.param_location.clone()
+    }
+    pub fn get_name_of_params(&self) -> &Vec<String> {
+        &self.name_of_params
+    }
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+}
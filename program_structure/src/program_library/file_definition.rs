@@ -1,5 +1,8 @@
 use codespan_reporting::files::{Files, SimpleFiles};
 use std::{ops::Range, collections::HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 pub type FileSource = String;
 pub type FilePath = String;
@@ -7,15 +10,46 @@ pub type FileID = usize;
 pub type FileLocation = Range<usize>;
 type FileStorage = SimpleFiles<FilePath, FileSource>;
 
+/// A source code comment. The text of the comment excludes the comment
+/// delimiters `//`, `/*`, and `*/`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+    location: FileLocation,
+    text: String,
+}
+
+impl Comment {
+    pub fn new(location: FileLocation, text: String) -> Comment {
+        Comment { location, text }
+    }
+
+    pub fn location(&self) -> &FileLocation {
+        &self.location
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
 #[derive(Clone)]
 pub struct FileLibrary {
     files: FileStorage,
     user_inputs: HashSet<FileID>,
+    // Comments indexed by file ID.
+    comments: Vec<Vec<Comment>>,
+    // The project root. Defaults to the current directory.
+    root: Option<PathBuf>,
 }
 
 impl Default for FileLibrary {
     fn default() -> Self {
-        FileLibrary { files: FileStorage::new(), user_inputs: HashSet::new() }
+        FileLibrary {
+            files: FileStorage::new(),
+            user_inputs: HashSet::new(),
+            comments: Vec::new(),
+            root: None,
+        }
     }
 }
 
@@ -36,6 +70,23 @@ impl FileLibrary {
         file_id
     }
 
+    pub fn add_comments(&mut self, file_id: FileID, comments: Vec<Comment>) {
+        if self.comments.len() <= file_id {
+            self.comments.resize(file_id + 1, Vec::new());
+        }
+        self.comments[file_id].extend(comments);
+    }
+
+    /// Returns the comments in the given file, ordered by location.
+    pub fn get_comments(&self, file_id: FileID) -> &[Comment] {
+        self.comments.get(file_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns the IDs of all files in the library.
+    pub fn file_ids(&self) -> impl Iterator<Item = FileID> + '_ {
+        (0..).take_while(|file_id| self.files.get(*file_id).is_ok())
+    }
+
     pub fn get_line(&self, start: usize, file_id: FileID) -> Option<usize> {
         self.files.line_index(file_id, start).map(|lines| lines + 1).ok()
     }
@@ -52,6 +103,29 @@ impl FileLibrary {
         self.user_inputs.contains(&file_id)
     }
 
+    /// Sets the project root used to compute relative file paths.
+    pub fn set_root(&mut self, root: &Path) {
+        self.root = Some(absolute_path(root));
+    }
+
+    /// Returns the path relative to the project root (or the current directory
+    /// if no root is set) if the path is contained in the project root. This
+    /// ensures that fingerprints and reported paths do not depend on the
+    /// location of the project, or on the directory Circomspect is run from.
+    pub fn relative_path(&self, path: &str) -> String {
+        let root = match &self.root {
+            Some(root) => root.clone(),
+            None => match env::current_dir() {
+                Ok(current_dir) => absolute_path(&current_dir),
+                Err(_) => return path.to_string(),
+            },
+        };
+        absolute_path(Path::new(path))
+            .strip_prefix(root)
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| path.to_string())
+    }
+
     fn get_files(&self) -> &FileStorage {
         &self.files
     }
@@ -61,6 +135,15 @@ impl FileLibrary {
     }
 }
 
+/// Returns the canonical path if it exists, and otherwise the path joined with
+/// the current directory.
+fn absolute_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| match env::current_dir() {
+        Ok(current_dir) => current_dir.join(path),
+        Err(_) => path.to_path_buf(),
+    })
+}
+
 pub fn generate_file_location(start: usize, end: usize) -> FileLocation {
     start..end
 }
@@ -0,0 +1,213 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use codespan_reporting::files::Files;
+use serde_derive::{Deserialize, Serialize};
+
+use super::file_definition::{FileID, FileLibrary, FileLocation};
+
+/// A machine-applicable edit replacing the given byte range of a file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub file_id: FileID,
+    pub location: FileLocation,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn new(file_id: FileID, location: FileLocation, replacement: &str) -> TextEdit {
+        TextEdit { file_id, location, replacement: replacement.to_string() }
+    }
+
+    /// Returns an edit removing the given byte range.
+    pub fn delete(file_id: FileID, location: FileLocation) -> TextEdit {
+        TextEdit::new(file_id, location, "")
+    }
+
+    fn overlaps(&self, other: &TextEdit) -> bool {
+        self.file_id == other.file_id
+            && (self.location == other.location
+                || (self.location.start < other.location.end
+                    && other.location.start < self.location.end))
+    }
+}
+
+/// A fix for the issue described by a report. The edits of a fix are either
+/// all applied or not applied at all.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fix {
+    description: String,
+    edits: Vec<TextEdit>,
+}
+
+impl Fix {
+    pub fn new(description: &str, edits: Vec<TextEdit>) -> Fix {
+        Fix { description: description.to_string(), edits }
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn edits(&self) -> &[TextEdit] {
+        &self.edits
+    }
+}
+
+/// The updated source of a file after fixes have been applied.
+#[derive(Clone, Debug)]
+pub struct FixedFile {
+    pub file_id: FileID,
+    pub path: PathBuf,
+    /// The original source of the file.
+    pub source: String,
+    /// The source of the file with all fixes applied.
+    pub fixed_source: String,
+    /// The number of fixes applied to the file.
+    pub nof_fixes: usize,
+}
+
+/// The result of applying a set of fixes.
+#[derive(Clone, Debug, Default)]
+pub struct FixedFiles {
+    pub files: Vec<FixedFile>,
+    /// The number of fixes that could not be applied.
+    pub nof_skipped: usize,
+}
+
+/// Applies the given fixes to the sources in the file library. Fixes are
+/// applied in order, and a fix is skipped if
+///
+///   1. it edits a file which is not a user input (like a library file),
+///   2. one of its edits is out of bounds, or
+///   3. one of its edits overlaps an edit from a previously applied fix.
+///
+/// Duplicate fixes (e.g. from reports on both a template and its instances)
+/// are only applied once. The files on disk are not modified. (Use
+/// `write_fixed_file` to update the files.)
+pub fn apply_fixes<'a>(
+    fixes: impl IntoIterator<Item = &'a Fix>,
+    file_library: &FileLibrary,
+) -> FixedFiles {
+    let files = file_library.to_storage();
+    let mut seen = HashSet::new();
+    let mut accepted = Vec::<&TextEdit>::new();
+    let mut nof_fixes = BTreeMap::<FileID, usize>::new();
+    let mut nof_skipped = 0;
+    for fix in fixes {
+        if fix.edits().is_empty() || !seen.insert(fix.edits()) {
+            continue;
+        }
+        let is_valid = fix.edits().iter().enumerate().all(|(index, edit)| {
+            // This also ensures that the edit starts and ends on a character boundary.
+            let is_in_bounds = files
+                .get(edit.file_id)
+                .map_or(false, |file| file.source().get(edit.location.clone()).is_some());
+            is_in_bounds
+                && file_library.is_user_input(edit.file_id)
+                && !fix.edits()[..index].iter().any(|other| edit.overlaps(other))
+                && !accepted.iter().any(|other| edit.overlaps(other))
+        });
+        if !is_valid {
+            nof_skipped += 1;
+            continue;
+        }
+        accepted.extend(fix.edits());
+        let file_ids = fix.edits().iter().map(|edit| edit.file_id).collect::<HashSet<_>>();
+        for file_id in file_ids {
+            *nof_fixes.entry(file_id).or_default() += 1;
+        }
+    }
+
+    let mut result = FixedFiles { files: Vec::new(), nof_skipped };
+    for (file_id, nof_fixes) in nof_fixes {
+        let (Ok(path), Ok(source)) = (files.name(file_id), files.source(file_id)) else {
+            continue;
+        };
+        let mut edits = accepted.iter().filter(|edit| edit.file_id == file_id).collect::<Vec<_>>();
+        // Apply edits back to front to keep the locations of remaining edits valid.
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.location.start));
+        let mut fixed_source = source.to_string();
+        for edit in edits {
+            fixed_source.replace_range(edit.location.clone(), &edit.replacement);
+        }
+        result.files.push(FixedFile {
+            file_id,
+            path: PathBuf::from(path),
+            source: source.to_string(),
+            fixed_source,
+            nof_fixes,
+        });
+    }
+    result
+}
+
+/// Writes the fixed sources to disk. A file is only updated if its contents
+/// are unchanged since it was analyzed. The new contents are written to a
+/// temporary file which is then renamed, to ensure that the file is never
+/// left partially written.
+pub fn write_fixed_file(file: &FixedFile) -> Result<()> {
+    let contents = fs::read_to_string(&file.path)
+        .with_context(|| format!("failed to read `{}`", file.path.display()))?;
+    if contents != file.source {
+        return Err(anyhow!(
+            "`{}` was modified after it was analyzed, no fixes applied",
+            file.path.display()
+        ));
+    }
+    let temp_path = temporary_path(&file.path);
+    fs::write(&temp_path, &file.fixed_source)
+        .and_then(|_| fs::rename(&temp_path, &file.path))
+        .with_context(|| format!("failed to write `{}`", file.path.display()))
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".circomspect-fix");
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "template T() {\n    signal x;\n    x <-- 1;\n}\n";
+
+    fn file_library() -> (FileLibrary, FileID) {
+        let mut file_library = FileLibrary::new();
+        let file_id = file_library.add_file("test.circom".to_string(), SOURCE.to_string(), true);
+        (file_library, file_id)
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let (file_library, file_id) = file_library();
+        let operator = SOURCE.find("<--").unwrap();
+        let fixes = vec![
+            Fix::new("Use `<==`.", vec![TextEdit::new(file_id, operator..operator + 3, "<==")]),
+            // Duplicate fixes are ignored.
+            Fix::new("Use `<==`.", vec![TextEdit::new(file_id, operator..operator + 3, "<==")]),
+            // Overlapping fixes are skipped.
+            Fix::new("Remove.", vec![TextEdit::delete(file_id, operator - 2..operator + 5)]),
+            // Out of bounds fixes are skipped.
+            Fix::new("Append.", vec![TextEdit::new(file_id, 100..100, "\n")]),
+        ];
+        let result = apply_fixes(&fixes, &file_library);
+        assert_eq!(result.nof_skipped, 2);
+        assert_eq!(result.files.len(), 1);
+        assert_eq!(result.files[0].nof_fixes, 1);
+        assert_eq!(result.files[0].fixed_source, SOURCE.replace("<--", "<=="));
+    }
+
+    #[test]
+    fn test_library_files() {
+        let mut file_library = FileLibrary::new();
+        let file_id = file_library.add_file("lib.circom".to_string(), SOURCE.to_string(), false);
+        let fixes = vec![Fix::new("Remove.", vec![TextEdit::delete(file_id, 0..8)])];
+        let result = apply_fixes(&fixes, &file_library);
+        assert_eq!(result.nof_skipped, 1);
+        assert!(result.files.is_empty());
+    }
+}
@@ -2,8 +2,12 @@ use super::ast;
 pub mod report_code;
 pub mod report;
 pub mod file_definition;
+pub mod bus_data;
 pub mod function_data;
 pub mod program_archive;
 pub mod program_merger;
 pub mod template_data;
 pub mod template_library;
+pub mod suppression;
+pub mod baseline;
+pub mod fix;
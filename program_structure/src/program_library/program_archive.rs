@@ -1,4 +1,5 @@
 use super::ast::{Definition, Expression, MainComponent};
+use super::bus_data::{BusData, BusInfo};
 use super::file_definition::{FileID, FileLibrary};
 use super::function_data::{FunctionData, FunctionInfo};
 use super::program_merger::Merger;
@@ -16,6 +17,7 @@ pub struct ProgramArchive {
     pub file_library: FileLibrary,
     pub functions: FunctionInfo,
     pub templates: TemplateInfo,
+    pub buses: BusInfo,
     pub function_keys: HashSet<String>,
     pub template_keys: HashSet<String>,
     pub public_inputs: Vec<String>,
@@ -23,6 +25,7 @@ pub struct ProgramArchive {
     pub custom_gates: bool,
 }
 impl ProgramArchive {
+    #[allow(clippy::result_large_err)]
     pub fn new(
         file_library: FileLibrary,
         file_id_main: FileID,
@@ -37,7 +40,7 @@ impl ProgramArchive {
                 reports.append(&mut errs);
             }
         }
-        let (mut fresh_id, functions, templates) = merger.decompose();
+        let (mut fresh_id, functions, templates, buses) = merger.decompose();
         let mut function_keys = HashSet::new();
         let mut template_keys = HashSet::new();
         for key in functions.keys() {
@@ -55,6 +58,7 @@ impl ProgramArchive {
                 file_library,
                 functions,
                 templates,
+                buses,
                 initial_template_call,
                 function_keys,
                 template_keys,
@@ -122,6 +126,18 @@ impl ProgramArchive {
         self.functions.remove(id);
     }
 
+    //bus functions
+    pub fn contains_bus(&self, bus_name: &str) -> bool {
+        self.buses.contains_key(bus_name)
+    }
+    pub fn get_bus_data(&self, bus_name: &str) -> &BusData {
+        assert!(self.contains_bus(bus_name));
+        self.buses.get(bus_name).unwrap()
+    }
+    pub fn get_buses(&self) -> &BusInfo {
+        &self.buses
+    }
+
     //main_component functions
     pub fn get_public_inputs_main_component(&self) -> &Vec<String> {
         &self.public_inputs
@@ -2,6 +2,7 @@ use super::ast::Definition;
 use super::report_code::ReportCode;
 use super::report::Report;
 use super::file_definition::FileID;
+use super::bus_data::{BusData, BusInfo};
 use super::function_data::{FunctionData, FunctionInfo};
 use super::template_data::{TemplateData, TemplateInfo};
 
@@ -10,6 +11,7 @@ pub struct Merger {
     fresh_id: usize,
     function_info: FunctionInfo,
     template_info: TemplateInfo,
+    bus_info: BusInfo,
 }
 
 impl Merger {
@@ -34,7 +36,7 @@ impl Merger {
                     parallel,
                     is_custom_gate,
                 } => {
-                    if self.contains_function(name) || self.contains_template(name) {
+                    if self.contains_name(name) {
                         (Option::Some(name), meta)
                     } else {
                         let new_data = TemplateData::new(
@@ -53,7 +55,7 @@ impl Merger {
                     }
                 }
                 Definition::Function { name, body, args, arg_location, meta } => {
-                    if self.contains_function(name) || self.contains_template(name) {
+                    if self.contains_name(name) {
                         (Option::Some(name), meta)
                     } else {
                         let new_data = FunctionData::new(
@@ -69,10 +71,26 @@ impl Merger {
                         (Option::None, meta)
                     }
                 }
+                Definition::Bus { name, body, args, arg_location, meta } => {
+                    if self.contains_name(name) {
+                        (Option::Some(name), meta)
+                    } else {
+                        let new_data = BusData::new(
+                            name.clone(),
+                            file_id,
+                            body.clone(),
+                            args.clone(),
+                            arg_location.clone(),
+                            &mut self.fresh_id,
+                        );
+                        self.bus_info.insert(name.clone(), new_data);
+                        (Option::None, meta)
+                    }
+                }
             };
             if let Option::Some(definition_name) = name {
                 let mut report = Report::error(
-                    String::from("Duplicated function or template."),
+                    String::from("Duplicated function, template, or bus."),
                     ReportCode::SameSymbolDeclaredTwice,
                 );
                 report.add_primary(
@@ -89,6 +107,10 @@ impl Merger {
             Err(reports)
         }
     }
+    fn contains_name(&self, name: &str) -> bool {
+        self.contains_function(name) || self.contains_template(name) || self.contains_bus(name)
+    }
+
     pub fn contains_function(&self, function_name: &str) -> bool {
         self.get_function_info().contains_key(function_name)
     }
@@ -109,7 +131,11 @@ impl Merger {
         &mut self.template_info
     }
 
-    pub fn decompose(self) -> (usize, FunctionInfo, TemplateInfo) {
-        (self.fresh_id, self.function_info, self.template_info)
+    pub fn contains_bus(&self, bus_name: &str) -> bool {
+        self.bus_info.contains_key(bus_name)
+    }
+
+    pub fn decompose(self) -> (usize, FunctionInfo, TemplateInfo, BusInfo) {
+        (self.fresh_id, self.function_info, self.template_info, self.bus_info)
     }
 }
@@ -2,9 +2,11 @@ use anyhow::anyhow;
 use std::cmp::Ordering;
 use std::fmt::Display;
 use std::str::FromStr;
+use serde_derive::{Deserialize, Serialize};
 
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 
+use super::fix::Fix;
 use super::report_code::ReportCode;
 use super::file_definition::{FileID, FileLocation};
 
@@ -13,7 +15,7 @@ pub type DiagnosticCode = String;
 pub type ReportLabel = Label<FileID>;
 type ReportNote = String;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MessageCategory {
     Error,
     Warning,
@@ -82,7 +84,23 @@ impl MessageCategory {
     }
 }
 
-#[derive(Clone)]
+/// A location on a data-flow path explaining a report, like a statement
+/// propagating a value from a source to a sink.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowStep {
+    pub file_id: FileID,
+    pub location: FileLocation,
+    pub message: String,
+}
+
+impl FlowStep {
+    /// Returns the step as a secondary label.
+    pub fn to_label(&self) -> ReportLabel {
+        ReportLabel::secondary(self.file_id, self.location.clone()).with_message(&self.message)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Report {
     category: MessageCategory,
     message: String,
@@ -91,6 +109,10 @@ pub struct Report {
     secondary: Vec<ReportLabel>,
     notes: Vec<ReportNote>,
     code: ReportCode,
+    definition: Option<String>,
+    instance: Option<String>,
+    fix: Option<Fix>,
+    code_flow: Vec<FlowStep>,
 }
 
 impl Report {
@@ -103,6 +125,10 @@ impl Report {
             secondary: Vec::new(),
             notes: Vec::new(),
             code,
+            definition: None,
+            instance: None,
+            fix: None,
+            code_flow: Vec::new(),
         }
     }
 
@@ -149,10 +175,54 @@ impl Report {
         self
     }
 
+    /// Override the category of the report (e.g. from a user configuration).
+    pub fn set_category(&mut self, category: MessageCategory) -> &mut Self {
+        self.category = category;
+        self
+    }
+
+    /// Tag the report with the name of the template or function that was
+    /// analyzed when the report was generated.
+    pub fn set_definition(&mut self, definition: String) -> &mut Self {
+        self.definition = Some(definition);
+        self
+    }
+
+    /// Tag the report with the template instance (e.g. `Num2Bits(8)`) that
+    /// was analyzed when the report was generated.
+    pub fn set_instance(&mut self, instance: String) -> &mut Self {
+        self.instance = Some(instance);
+        self
+    }
+
+    /// Add a step to the data-flow path explaining the report. Steps are
+    /// numbered in the order they are added.
+    pub fn add_flow_step(
+        &mut self,
+        location: FileLocation,
+        file_id: FileID,
+        message: String,
+    ) -> &mut Self {
+        self.code_flow.push(FlowStep { file_id, location, message });
+        self
+    }
+
+    /// Attach a machine-applicable fix to the report.
+    pub fn set_fix(&mut self, fix: Fix) -> &mut Self {
+        self.fix = Some(fix);
+        self
+    }
+
     pub fn to_diagnostic(&self, verbose: bool) -> Diagnostic<FileID> {
         let mut labels = self.primary().clone();
         let mut secondary = self.secondary().clone();
         labels.append(&mut secondary);
+        // Data-flow steps are numbered since labels are ordered by location.
+        for (index, step) in self.code_flow().iter().enumerate() {
+            let label = step.to_label();
+            let message = format!("({}) {}", index + 1, label.message);
+            labels.push(label.with_message(message));
+        }
 
         let diagnostic = match self.category() {
             MessageCategory::Error => Diagnostic::error(),
@@ -163,6 +233,17 @@ impl Report {
         .with_labels(labels);
 
         let mut notes = self.notes().clone();
+        if let Some(instance) = self.instance() {
+            notes.push(format!(
+                "This issue was found when analyzing the template instance `{instance}`."
+            ));
+        }
+        if let Some(fix) = self.fix() {
+            notes.push(format!(
+                "{} (This can be fixed automatically using `--fix`.)",
+                fix.description()
+            ));
+        }
         if let Some(url) = self.code().url() {
             // Add URL to documentation if available.
             notes.push(format!("For more details, see {url}."));
@@ -216,6 +297,22 @@ impl Report {
         &mut self.notes
     }
 
+    pub fn definition(&self) -> Option<&str> {
+        self.definition.as_deref()
+    }
+
+    pub fn instance(&self) -> Option<&str> {
+        self.instance.as_deref()
+    }
+
+    pub fn code_flow(&self) -> &Vec<FlowStep> {
+        &self.code_flow
+    }
+
+    pub fn fix(&self) -> Option<&Fix> {
+        self.fix.as_ref()
+    }
+
     pub fn code(&self) -> &ReportCode {
         &self.code
     }
@@ -1,6 +1,10 @@
+use serde_derive::{Deserialize, Serialize};
+
+use super::report::MessageCategory;
+
 const DOC_URL: &str = "https://github.com/trailofbits/circomspect/blob/main/doc/analysis_passes.md";
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum ReportCode {
     AssertWrongType,
     ParseFail,
@@ -88,9 +92,44 @@ pub enum ReportCode {
     Bn254SpecificCircuit,
     UnderConstrainedSignal,
     UnusedOutputSignal,
+    FieldElementOverflow,
+    UnderConstrainedOutputSignal,
+    UnconstrainedComponentSignal,
+    UnusedSuppression,
+    CustomRule,
 }
 
 impl ReportCode {
+    /// Returns all Circomspect specific report codes, ordered by ID.
+    pub fn circomspect_codes() -> Vec<ReportCode> {
+        use self::ReportCode::*;
+        vec![
+            ShadowingVariable,
+            ParameterNameCollision,
+            FieldElementComparison,
+            FieldElementArithmetic,
+            SignalAssignmentStatement,
+            UnusedVariableValue,
+            UnusedParameterValue,
+            VariableWithoutSideEffect,
+            ConstantBranchCondition,
+            NonStrictBinaryConversion,
+            CyclomaticComplexity,
+            TooManyArguments,
+            UnnecessarySignalAssignment,
+            UnconstrainedLessThan,
+            UnconstrainedDivision,
+            Bn254SpecificCircuit,
+            UnderConstrainedSignal,
+            UnusedOutputSignal,
+            FieldElementOverflow,
+            UnderConstrainedOutputSignal,
+            UnconstrainedComponentSignal,
+            UnusedSuppression,
+            CustomRule,
+        ]
+    }
+
     pub fn id(&self) -> String {
         use self::ReportCode::*;
         match self {
@@ -180,6 +219,11 @@ impl ReportCode {
             Bn254SpecificCircuit => "CS0016",
             UnderConstrainedSignal => "CS0017",
             UnusedOutputSignal => "CS0018",
+            FieldElementOverflow => "CS0019",
+            UnderConstrainedOutputSignal => "CS0020",
+            UnconstrainedComponentSignal => "CS0021",
+            UnusedSuppression => "CS0022",
+            CustomRule => "CS0023",
         }
         .to_string()
     }
@@ -271,10 +315,91 @@ impl ReportCode {
             Bn254SpecificCircuit => "bn254-specific-circuit",
             UnderConstrainedSignal => "under-constrained-signal",
             UnusedOutputSignal => "unused-output-signal",
+            FieldElementOverflow => "field-element-overflow",
+            UnderConstrainedOutputSignal => "under-constrained-output-signal",
+            UnconstrainedComponentSignal => "unconstrained-component-signal",
+            UnusedSuppression => "unused-suppression",
+            CustomRule => "custom-rule",
         }
         .to_string()
     }
 
+    /// Returns the default category of reports with this code. (The category
+    /// of a report may be overridden by the user.)
+    pub fn default_category(&self) -> MessageCategory {
+        use ReportCode::*;
+        match self {
+            FieldElementComparison | FieldElementArithmetic => MessageCategory::Info,
+            ShadowingVariable
+            | ParameterNameCollision
+            | SignalAssignmentStatement
+            | UnnecessarySignalAssignment
+            | UnusedVariableValue
+            | UnusedParameterValue
+            | VariableWithoutSideEffect
+            | ConstantBranchCondition
+            | NonStrictBinaryConversion
+            | CyclomaticComplexity
+            | TooManyArguments
+            | UnconstrainedLessThan
+            | UnconstrainedDivision
+            | Bn254SpecificCircuit
+            | UnderConstrainedSignal
+            | UnusedOutputSignal
+            | FieldElementOverflow
+            | UnderConstrainedOutputSignal
+            | UnconstrainedComponentSignal
+            | UnusedSuppression
+            | CustomRule => MessageCategory::Warning,
+            NoCompilerVersionWarning
+            | UnconstrainedSignal
+            | OneConstraintIntermediate
+            | NoOutputInInstance => MessageCategory::Warning,
+            _ => MessageCategory::Error,
+        }
+    }
+
+    /// Returns a description of the issue. Circomspect specific issues have a
+    /// dedicated description. For other issues the description is derived from
+    /// the name.
+    pub fn description(&self) -> String {
+        use ReportCode::*;
+        let description = match self {
+            ShadowingVariable => "A variable declaration shadows a variable declared in an outer scope.",
+            ParameterNameCollision => "A variable declaration shadows a function or template parameter.",
+            FieldElementComparison => "Field elements are compared using a signed comparison, which may produce unexpected results for values greater than `p/2`.",
+            FieldElementArithmetic => "Field element arithmetic could overflow, which may produce unexpected results.",
+            SignalAssignmentStatement => "The signal assignment operator `<--` does not constrain the assigned signal.",
+            UnusedVariableValue => "A value assigned to a variable is never read.",
+            UnusedParameterValue => "A function or template parameter is never read.",
+            VariableWithoutSideEffect => "An assigned value does not contribute to a constraint or a function return value.",
+            ConstantBranchCondition => "A branching condition is constant and always evaluates to the same value.",
+            NonStrictBinaryConversion => "A conversion to binary using `Num2Bits` or `Bits2Num` is not strict, which allows two different binary representations of the same value.",
+            CyclomaticComplexity => "A function or template is overly complex and would benefit from being refactored.",
+            TooManyArguments => "A function or template takes too many parameters.",
+            UnnecessarySignalAssignment => "A signal is assigned using `<--` even though the assigned value is quadratic and could be constrained using `<==`.",
+            UnconstrainedLessThan => "The inputs to `LessThan` are not constrained to the number of bits used by the comparison.",
+            UnconstrainedDivision => "The divisor of a signal assignment is not constrained to be non-zero.",
+            Bn254SpecificCircuit => "A circomlib template hard-coded for the BN254 curve is used with a different curve.",
+            UnderConstrainedSignal => "An intermediate signal occurs in too few constraints to be properly constrained.",
+            UnusedOutputSignal => "An output signal of an instantiated component is never used.",
+            FieldElementOverflow => "The result of an arithmetic expression on bounded values may exceed the prime.",
+            UnderConstrainedOutputSignal => "An output signal is not uniquely determined by the input signals of the template.",
+            UnconstrainedComponentSignal => "An input signal of an instantiated component is not constrained.",
+            UnusedSuppression => "A suppression comment does not suppress any reports.",
+            CustomRule => "A custom rule matches.",
+            _ => {
+                let name = self.name().replace('-', " ");
+                let mut chars = name.chars();
+                return match chars.next() {
+                    Some(first) => format!("{}{}.", first.to_uppercase(), chars.as_str()),
+                    None => name,
+                };
+            }
+        };
+        description.to_string()
+    }
+
     pub fn url(&self) -> Option<String> {
         use ReportCode::*;
         match self {
@@ -285,7 +410,7 @@ impl ReportCode {
             UnusedVariableValue => Some("unused-variable-or-parameter"),
             UnusedParameterValue => Some("unused-variable-or-parameter"),
             VariableWithoutSideEffect => Some("side-effect-free-assignment"),
-            ConstantBranchCondition => Some("constant-branch-condition"),
+            ConstantBranchCondition => Some("constant-branching-condition"),
             NonStrictBinaryConversion => Some("non-strict-binary-conversion"),
             CyclomaticComplexity => Some("overly-complex-function-or-template"),
             TooManyArguments => Some("overly-complex-function-or-template"),
@@ -295,6 +420,9 @@ impl ReportCode {
             Bn254SpecificCircuit => Some("bn254-specific-circuit"),
             UnderConstrainedSignal => Some("under-constrained-signal"),
             UnusedOutputSignal => Some("unused-output-signal"),
+            FieldElementOverflow => Some("field-element-overflow"),
+            UnderConstrainedOutputSignal => Some("under-constrained-output-signal"),
+            UnconstrainedComponentSignal => Some("unconstrained-component-signal"),
             // We only provide a URL for Circomspect specific issues.
             _ => None,
         }
@@ -0,0 +1,228 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use codespan_reporting::files::Files;
+
+use super::file_definition::{Comment, FileID, FileLibrary, FileLocation};
+use super::report::{Report, ReportCollection};
+use super::report_code::ReportCode;
+
+const DIRECTIVE_PREFIX: &str = "circomspect:";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SuppressionScope {
+    /// The suppression applies to reports with a primary location starting in
+    /// the given range (corresponding to a single line).
+    Line(FileLocation),
+    /// The suppression applies to all reports in the file.
+    File,
+}
+
+/// A suppression declared in a source comment. The comment
+///
+///   `// circomspect: allow CS0010, CS0011`
+///
+/// suppresses the given reports on the same line (if the comment follows code
+/// on the same line) or the next line (if the comment is on a line of its
+/// own). The comment
+///
+///   `// circomspect: allow-file CS0005`
+///
+/// suppresses the given reports in the entire file. Rules may be given either
+/// by ID (e.g. `CS0005`) or by name (e.g. `shadowing-variable`).
+#[derive(Clone, Debug)]
+struct Suppression {
+    file_id: FileID,
+    location: FileLocation,
+    scope: SuppressionScope,
+    rules: Vec<String>,
+}
+
+impl Suppression {
+    /// Parses a suppression from the given comment. Returns `None` if the
+    /// comment does not contain a suppression directive.
+    fn parse(
+        comment: &Comment,
+        file_id: FileID,
+        file_library: &FileLibrary,
+    ) -> Option<Suppression> {
+        let directive = comment.text().trim().strip_prefix(DIRECTIVE_PREFIX)?.trim_start();
+        let (kind, rules) = directive.split_once(char::is_whitespace).unwrap_or((directive, ""));
+        let rules = rules
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|rule| !rule.is_empty())
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        if rules.is_empty() {
+            return None;
+        }
+        let scope = match kind {
+            "allow" => SuppressionScope::Line(covered_line(comment, file_id, file_library)?),
+            "allow-file" => SuppressionScope::File,
+            _ => return None,
+        };
+        Some(Suppression { file_id, location: comment.location().clone(), scope, rules })
+    }
+
+    /// Returns true if the given primary location is covered by the suppression.
+    fn covers(&self, file_id: FileID, location: &FileLocation) -> bool {
+        if file_id != self.file_id {
+            return false;
+        }
+        match &self.scope {
+            SuppressionScope::Line(range) => range.contains(&location.start),
+            SuppressionScope::File => true,
+        }
+    }
+
+    /// Returns the index of the first rule matching the report.
+    fn matching_rule(&self, report: &Report) -> Option<usize> {
+        let (id, name) = (report.id(), report.name());
+        self.rules.iter().position(|rule| rule.eq_ignore_ascii_case(&id) || *rule == name)
+    }
+}
+
+/// Returns the byte range of the line covered by a line suppression comment.
+/// This is the line containing the comment if the comment follows code on the
+/// same line, and the next line otherwise.
+fn covered_line(
+    comment: &Comment,
+    file_id: FileID,
+    file_library: &FileLibrary,
+) -> Option<FileLocation> {
+    let files = file_library.to_storage();
+    let source = files.source(file_id).ok()?;
+    let line_index = files.line_index(file_id, comment.location().start).ok()?;
+    let line_start = files.line_range(file_id, line_index).ok()?.start;
+    if source[line_start..comment.location().start].trim().is_empty() {
+        let line_index = files.line_index(file_id, comment.location().end).ok()?;
+        files.line_range(file_id, line_index + 1).ok()
+    } else {
+        files.line_range(file_id, line_index).ok()
+    }
+}
+
+/// The set of suppressions declared in the comments of a file library.
+/// Suppressions that are used to filter reports are tracked so that unused
+/// suppressions can be reported.
+#[derive(Default)]
+pub struct Suppressions {
+    suppressions: Vec<Suppression>,
+    // The (suppression, rule) index pairs used to suppress a report.
+    used: RefCell<HashSet<(usize, usize)>>,
+}
+
+impl Suppressions {
+    pub fn new(file_library: &FileLibrary) -> Suppressions {
+        let suppressions = file_library
+            .file_ids()
+            .flat_map(|file_id| {
+                file_library
+                    .get_comments(file_id)
+                    .iter()
+                    .filter_map(move |comment| Suppression::parse(comment, file_id, file_library))
+            })
+            .collect();
+        Suppressions { suppressions, used: RefCell::new(HashSet::new()) }
+    }
+
+    /// Returns the number of suppressions.
+    pub fn len(&self) -> usize {
+        self.suppressions.len()
+    }
+
+    /// Returns true if there are no suppressions.
+    pub fn is_empty(&self) -> bool {
+        self.suppressions.is_empty()
+    }
+
+    /// Returns true if a primary location of the report is covered by a
+    /// matching suppression. The matching suppression is marked as used.
+    pub fn is_suppressed(&self, report: &Report) -> bool {
+        for (index, suppression) in self.suppressions.iter().enumerate() {
+            let Some(rule) = suppression.matching_rule(report) else {
+                continue;
+            };
+            if report.primary().iter().any(|label| suppression.covers(label.file_id, &label.range))
+            {
+                self.used.borrow_mut().insert((index, rule));
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns a report for each suppressed rule that was never used to
+    /// suppress a report.
+    pub fn unused_suppressions(&self) -> ReportCollection {
+        let used = self.used.borrow();
+        let mut reports = ReportCollection::new();
+        for (index, suppression) in self.suppressions.iter().enumerate() {
+            for (rule_index, rule) in suppression.rules.iter().enumerate() {
+                if used.contains(&(index, rule_index)) {
+                    continue;
+                }
+                let mut report = Report::warning(
+                    format!("The suppression of `{rule}` is never used."),
+                    ReportCode::UnusedSuppression,
+                );
+                report.add_primary(
+                    suppression.location.clone(),
+                    suppression.file_id,
+                    "The suppression is declared here.".to_string(),
+                );
+                report.add_note("Consider removing the suppression.".to_string());
+                reports.push(report);
+            }
+        }
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suppressions() {
+        let src = "// circomspect: allow-file CS0005\n\
+                   var x = 1; // circomspect: allow CS0010\n\
+                   // circomspect: allow unused-variable-value, CS0001\n\
+                   var y = 2;\n\
+                   var z = 3;\n";
+        let mut file_library = FileLibrary::new();
+        let file_id = file_library.add_file("test.circom".to_string(), src.to_string(), true);
+        let comments = src
+            .match_indices("//")
+            .map(|(start, _)| {
+                let end = start + src[start..].find('\n').unwrap();
+                Comment::new(start..end, src[start + 2..end].to_string())
+            })
+            .collect();
+        file_library.add_comments(file_id, comments);
+        let suppressions = Suppressions::new(&file_library);
+        assert_eq!(suppressions.len(), 3);
+
+        let report = |code: ReportCode, var: &str| {
+            let start = src.find(var).unwrap();
+            let mut report = Report::warning("test".to_string(), code);
+            report.add_primary(start..start + 1, file_id, "test".to_string());
+            report
+        };
+        // File suppressions apply everywhere in the file.
+        assert!(suppressions.is_suppressed(&report(ReportCode::SignalAssignmentStatement, "var z")));
+        // Trailing comments apply to the same line.
+        assert!(suppressions.is_suppressed(&report(ReportCode::NonStrictBinaryConversion, "var x")));
+        assert!(
+            !suppressions.is_suppressed(&report(ReportCode::NonStrictBinaryConversion, "var y"))
+        );
+        // Comments on a line of their own apply to the next line.
+        assert!(suppressions.is_suppressed(&report(ReportCode::UnusedVariableValue, "var y")));
+        assert!(!suppressions.is_suppressed(&report(ReportCode::UnusedVariableValue, "var z")));
+
+        // `CS0001` is never used.
+        let unused = suppressions.unused_suppressions();
+        assert_eq!(unused.len(), 1);
+        assert_eq!(unused[0].message(), "The suppression of `CS0001` is never used.");
+    }
+}
@@ -193,7 +193,8 @@ fn fill_inputs_and_outputs(
             }
         }
         Statement::Declaration {
-            xtype: ast::VariableType::Signal(stype, tag_list),
+            xtype:
+                ast::VariableType::Signal(stype, tag_list) | ast::VariableType::Bus(_, stype, tag_list),
             name,
             dimensions,
             ..
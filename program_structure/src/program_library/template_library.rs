@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use crate::ast::Definition;
+use crate::bus_data::{BusData, BusInfo};
 use crate::file_definition::{FileID, FileLibrary};
 use crate::function_data::{FunctionData, FunctionInfo};
 use crate::template_data::{TemplateData, TemplateInfo};
@@ -10,6 +11,7 @@ type Contents = HashMap<FileID, Vec<Definition>>;
 pub struct TemplateLibrary {
     pub functions: FunctionInfo,
     pub templates: TemplateInfo,
+    pub buses: BusInfo,
     pub file_library: FileLibrary,
 }
 
@@ -17,6 +19,7 @@ impl TemplateLibrary {
     pub fn new(library_contents: Contents, file_library: FileLibrary) -> TemplateLibrary {
         let mut functions = HashMap::new();
         let mut templates = HashMap::new();
+        let mut buses = HashMap::new();
 
         let mut elem_id = 0;
         for (file_id, file_contents) in library_contents {
@@ -60,10 +63,16 @@ impl TemplateLibrary {
                             ),
                         );
                     }
+                    Definition::Bus { name, args, arg_location, body, .. } => {
+                        buses.insert(
+                            name.clone(),
+                            BusData::new(name, file_id, body, args, arg_location, &mut elem_id),
+                        );
+                    }
                 }
             }
         }
-        TemplateLibrary { functions, templates, file_library }
+        TemplateLibrary { functions, templates, buses, file_library }
     }
     // Template methods.
     pub fn contains_template(&self, template_name: &str) -> bool {
@@ -1,6 +1,7 @@
 use log::trace;
 use std::collections::HashSet;
 use std::marker::PhantomData;
+use serde_derive::{Deserialize, Serialize};
 
 use super::traits::DirectedGraphNode;
 
@@ -9,6 +10,8 @@ type DominatorInfo = Vec<HashSet<Index>>;
 type ImmediateDominatorInfo = Vec<Option<Index>>;
 
 // A structure which encapsulates the dominance relation on a CFG.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct DominatorTree<T: DirectedGraphNode> {
     dominators: DominatorInfo,
     immediate_dominators: ImmediateDominatorInfo,
@@ -20,9 +20,11 @@ pub fn insert_phi_statements<Cfg: SSAConfig>(
     // Insert phi statements at the dominance frontier of each block.
     let mut work_list: Vec<Index> = (0..basic_blocks.len()).collect();
     while let Some(current_index) = work_list.pop() {
+        // Variables and blocks are sorted to ensure that phi statements are
+        // inserted in a deterministic order.
         let variables_written = {
             let current_block = &basic_blocks[current_index];
-            current_block.variables_written().clone()
+            sorted(current_block.variables_written().iter().cloned())
         };
         if variables_written.is_empty() {
             trace!("basic block {current_index} does not write any variables");
@@ -32,7 +34,7 @@ pub fn insert_phi_statements<Cfg: SSAConfig>(
             "dominance frontier for block {current_index} is {:?}",
             dominator_tree.get_dominance_frontier(current_index)
         );
-        for frontier_index in dominator_tree.get_dominance_frontier(current_index) {
+        for frontier_index in sorted(dominator_tree.get_dominance_frontier(current_index)) {
             let frontier_block = &mut basic_blocks[frontier_index];
             for var in &variables_written {
                 if !frontier_block.has_phi_statement(var) {
@@ -72,7 +74,7 @@ fn insert_ssa_variables_impl<Cfg: SSAConfig>(
         let current_block =
             basic_blocks.get_mut(current_index).expect("invalid block index during SSA generation");
         current_block.insert_ssa_variables(env)?;
-        current_block.successors().clone()
+        sorted(current_block.successors().iter().copied())
     };
     // 2. Update phi statements in successor blocks.
     for successor_index in successors {
@@ -82,10 +84,17 @@ fn insert_ssa_variables_impl<Cfg: SSAConfig>(
         successor_block.update_phi_statements(env);
     }
     // 3. Update dominator tree successors recursively.
-    for successor_index in dominator_tree.get_dominator_successors(current_index) {
+    for successor_index in sorted(dominator_tree.get_dominator_successors(current_index)) {
         env.add_variable_scope();
         insert_ssa_variables_impl::<Cfg>(successor_index, basic_blocks, dominator_tree, env)?;
         env.remove_variable_scope();
     }
     Ok(())
 }
+
+/// Returns the elements of the iterator in sorted order.
+fn sorted<T: Ord, I: IntoIterator<Item = T>>(iter: I) -> Vec<T> {
+    let mut elems = iter.into_iter().collect::<Vec<_>>();
+    elems.sort();
+    elems
+}
@@ -9,7 +9,7 @@ pub trait SSAConfig: Sized {
     type Version;
 
     /// The type of a variable.
-    type Variable: PartialEq + Eq + Hash + Clone;
+    type Variable: PartialEq + Eq + Hash + Ord + Clone;
 
     /// An environment type used to track version across the CFG.
     type Environment: SSAEnvironment;
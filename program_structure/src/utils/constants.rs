@@ -1,14 +1,22 @@
-use anyhow::{anyhow, Error};
+use anyhow::{anyhow, Error, Result};
 use num_bigint::BigInt;
 use std::fmt;
 use std::str::FromStr;
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Default, Clone, PartialEq, Eq)]
+#[derive(Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Curve {
     #[default] // Used for testing.
     Bn254,
     Bls12_381,
     Goldilocks,
+    Grumpkin,
+    Pallas,
+    Vesta,
+    Secq256r1,
+    Bls12_377,
+    /// A user-supplied prime.
+    Custom(BigInt),
 }
 
 impl fmt::Display for Curve {
@@ -18,6 +26,12 @@ impl fmt::Display for Curve {
             Bn254 => write!(f, "BN254"),
             Bls12_381 => write!(f, "BLS12_381"),
             Goldilocks => write!(f, "Goldilocks"),
+            Grumpkin => write!(f, "Grumpkin"),
+            Pallas => write!(f, "Pallas"),
+            Vesta => write!(f, "Vesta"),
+            Secq256r1 => write!(f, "secq256r1"),
+            Bls12_377 => write!(f, "BLS12_377"),
+            Custom(prime) => write!(f, "{prime}"),
         }
     }
 }
@@ -29,7 +43,42 @@ impl fmt::Debug for Curve {
 }
 
 impl Curve {
-    fn prime(&self) -> BigInt {
+    /// The curves with a known name.
+    const NAMED_CURVES: [Curve; 8] = [
+        Curve::Bn254,
+        Curve::Bls12_381,
+        Curve::Goldilocks,
+        Curve::Grumpkin,
+        Curve::Pallas,
+        Curve::Vesta,
+        Curve::Secq256r1,
+        Curve::Bls12_377,
+    ];
+
+    /// Returns the curve with the given prime, given either in decimal or in
+    /// hexadecimal (with a `0x` prefix). If the prime is the prime of a named
+    /// curve, the named curve is returned.
+    pub fn from_prime(prime: &str) -> Result<Curve> {
+        let prime = prime.trim();
+        let value = match prime.strip_prefix("0x").or_else(|| prime.strip_prefix("0X")) {
+            Some(digits) => BigInt::parse_bytes(digits.as_bytes(), 16),
+            None => BigInt::parse_bytes(prime.as_bytes(), 10),
+        }
+        .ok_or_else(|| anyhow!("failed to parse prime `{prime}`"))?;
+        if value <= BigInt::from(2) {
+            return Err(anyhow!("the prime `{prime}` must be greater than 2"));
+        }
+        if !is_probable_prime(&value) {
+            return Err(anyhow!("the number `{prime}` is not a prime"));
+        }
+        Ok(Curve::NAMED_CURVES
+            .into_iter()
+            .find(|curve| curve.prime() == value)
+            .unwrap_or(Curve::Custom(value)))
+    }
+
+    /// Returns the prime of the curve.
+    pub fn prime(&self) -> BigInt {
         use Curve::*;
         let prime = match self {
             Bn254 => {
@@ -39,6 +88,22 @@ impl Curve {
                 "52435875175126190479447740508185965837690552500527637822603658699938581184513"
             }
             Goldilocks => "18446744069414584321",
+            Grumpkin => {
+                "21888242871839275222246405745257275088696311157297823662689037894645226208583"
+            }
+            Pallas => {
+                "28948022309329048855892746252171976963363056481941560715954676764349967630337"
+            }
+            Vesta => {
+                "28948022309329048855892746252171976963363056481941647379679742748393362948097"
+            }
+            Secq256r1 => {
+                "115792089210356248762697446949407573530086143415290314195533631308867097853951"
+            }
+            Bls12_377 => {
+                "8444461749428370424248824938781546531375899335154063827935233455917409239041"
+            }
+            Custom(prime) => return prime.clone(),
         };
         BigInt::parse_bytes(prime.as_bytes(), 10).expect("failed to parse prime")
     }
@@ -52,12 +117,57 @@ impl FromStr for Curve {
             "BN254" => Ok(Curve::Bn254),
             "BLS12_381" => Ok(Curve::Bls12_381),
             "GOLDILOCKS" => Ok(Curve::Goldilocks),
+            "GRUMPKIN" => Ok(Curve::Grumpkin),
+            "PALLAS" => Ok(Curve::Pallas),
+            "VESTA" => Ok(Curve::Vesta),
+            "SECQ256R1" => Ok(Curve::Secq256r1),
+            "BLS12_377" => Ok(Curve::Bls12_377),
             _ => Err(anyhow!("failed to parse curve `{curve}`")),
         }
     }
 }
 
-#[derive(Clone)]
+/// Miller-Rabin primality test using the first twelve primes as bases. This is
+/// deterministic for values smaller than 3.3 * 10^24, and probabilistic for
+/// larger values.
+fn is_probable_prime(value: &BigInt) -> bool {
+    const BASES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    let one = BigInt::from(1);
+    let two = BigInt::from(2);
+    for base in BASES {
+        if *value == BigInt::from(base) {
+            return true;
+        }
+        if (value % base) == BigInt::from(0) {
+            return false;
+        }
+    }
+    // Write value - 1 = d * 2^s with d odd.
+    let value_minus_one = value - &one;
+    let mut d = value_minus_one.clone();
+    let mut s = 0;
+    while (&d % &two) == BigInt::from(0) {
+        d /= &two;
+        s += 1;
+    }
+    'witness: for base in BASES {
+        let mut x = BigInt::from(base).modpow(&d, value);
+        if x == one || x == value_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, value);
+            if x == value_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct UsefulConstants {
     curve: Curve,
     prime: BigInt,
@@ -83,3 +193,43 @@ impl UsefulConstants {
         self.prime.bits()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_prime() {
+        let goldilocks = Curve::from_prime("18446744069414584321").unwrap();
+        assert_eq!(goldilocks, Curve::Goldilocks);
+        let goldilocks = Curve::from_prime("0xffffffff00000001").unwrap();
+        assert_eq!(goldilocks, Curve::Goldilocks);
+
+        let custom = Curve::from_prime("0x7fffffff").unwrap();
+        assert_eq!(custom, Curve::Custom(BigInt::from(2147483647)));
+        assert_eq!(UsefulConstants::new(&custom).prime_size(), 31);
+
+        assert!(Curve::from_prime("2").is_err());
+        assert!(Curve::from_prime("1024").is_err());
+        assert!(Curve::from_prime("9").is_err());
+        assert!(Curve::from_prime("3215031751").is_err());
+        assert!(Curve::from_prime("3").is_ok());
+        assert!(Curve::from_prime("BN254").is_err());
+    }
+
+    #[test]
+    fn test_is_probable_prime() {
+        for curve in Curve::NAMED_CURVES {
+            assert!(is_probable_prime(&curve.prime()));
+        }
+        let primes = [3, 5, 37, 41, 7919, 2147483647];
+        for prime in primes {
+            assert!(is_probable_prime(&BigInt::from(prime)));
+        }
+        // 3215031751 is a strong pseudoprime to the bases 2, 3, 5, and 7.
+        let composites = [9, 15, 561, 1105, 3215031751_u64];
+        for composite in composites {
+            assert!(!is_probable_prime(&BigInt::from(composite)));
+        }
+    }
+}
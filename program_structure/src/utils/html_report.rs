@@ -0,0 +1,296 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::ops::Range;
+
+use codespan_reporting::files::Files;
+
+use crate::file_definition::{FileID, FileLibrary};
+use crate::report::Report;
+use crate::report_formats::{escape_xml as escape, ReportFormat};
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1, h2 { font-weight: normal; }
+fieldset { display: inline-block; vertical-align: top; margin-right: 1em; }
+label { margin-right: 1em; white-space: nowrap; }
+.report { border-left: 4px solid #888; padding: 0.2em 0.8em; margin: 0.8em 0; }
+.report.error { border-color: #c0392b; }
+.report.warning { border-color: #e67e22; }
+.report.info { border-color: #2980b9; }
+.category { font-weight: bold; text-transform: uppercase; }
+.notes { margin: 0.3em 0; color: #555; }
+.hidden { display: none; }
+table.source { border-collapse: collapse; font-family: monospace; font-size: 0.9em; }
+table.source td { padding: 0 0.5em; white-space: pre; vertical-align: top; }
+table.source td.line-number { color: #888; text-align: right; user-select: none; }
+table.source tr:target { background: #ffffcc; }
+span.primary.active { background: #f5b7b1; border-bottom: 2px solid #c0392b; }
+span.secondary.active { background: #d6eaf8; border-bottom: 2px dotted #2980b9; }
+"#;
+
+const SCRIPT: &str = r#"
+function update() {
+  const checked = (name) => new Set(
+    Array.from(document.querySelectorAll(`input[name=${name}]:checked`)).map((input) => input.value)
+  );
+  const categories = checked("category");
+  const codes = checked("code");
+  const visible = new Set();
+  document.querySelectorAll(".report").forEach((report) => {
+    const show = categories.has(report.dataset.category) && codes.has(report.dataset.code);
+    report.classList.toggle("hidden", !show);
+    if (show) {
+      visible.add(report.dataset.index);
+    }
+  });
+  document.querySelectorAll("span[data-reports]").forEach((span) => {
+    const active = span.dataset.reports.split(" ").some((index) => visible.has(index));
+    span.classList.toggle("active", active);
+  });
+}
+document.querySelectorAll("input").forEach((input) => input.addEventListener("change", update));
+update();
+"#;
+
+/// A label to highlight in the source view.
+struct Highlight {
+    range: Range<usize>,
+    primary: bool,
+    message: String,
+    report_index: usize,
+}
+
+/// A self-contained static HTML report. The report contains the list of
+/// reports (with notes and links to the documentation of each issue), and a
+/// source view of each file with primary and secondary labels highlighted.
+/// Reports may be filtered by category and report ID. The HTML file does not
+/// depend on any network assets.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Html;
+
+impl ReportFormat for Html {
+    fn format(&self, reports: &[Report], file_library: &FileLibrary) -> anyhow::Result<String> {
+        let files = file_library.to_storage();
+        let mut highlights = BTreeMap::<FileID, Vec<Highlight>>::new();
+        for (report_index, report) in reports.iter().enumerate() {
+            let labels = report
+                .primary()
+                .iter()
+                .map(|label| (label, true))
+                .chain(report.secondary().iter().map(|label| (label, false)));
+            for (label, primary) in labels {
+                highlights.entry(label.file_id).or_default().push(Highlight {
+                    range: label.range.clone(),
+                    primary,
+                    message: label.message.clone(),
+                    report_index,
+                });
+            }
+        }
+
+        let mut html = String::new();
+        writeln!(html, "<!DOCTYPE html>")?;
+        writeln!(html, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(html, "<title>Circomspect report</title>")?;
+        writeln!(html, "<style>{STYLE}</style>\n</head>\n<body>")?;
+        writeln!(html, "<h1>Circomspect report</h1>")?;
+        writeln!(html, "<p>{} issues found in {} files.</p>", reports.len(), highlights.len())?;
+
+        // Filters.
+        let categories = reports
+            .iter()
+            .map(|report| report.category())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .rev();
+        writeln!(html, "<fieldset><legend>Level</legend>")?;
+        for category in categories {
+            writeln!(
+                html,
+                "<label><input type=\"checkbox\" name=\"category\" value=\"{category}\" checked> {category}</label>"
+            )?;
+        }
+        writeln!(html, "</fieldset>")?;
+        let codes =
+            reports.iter().map(|report| (report.id(), report.name())).collect::<BTreeSet<_>>();
+        writeln!(html, "<fieldset><legend>Issue</legend>")?;
+        for (id, name) in codes {
+            writeln!(
+                html,
+                "<label><input type=\"checkbox\" name=\"code\" value=\"{id}\" checked> {id} ({name})</label>"
+            )?;
+        }
+        writeln!(html, "</fieldset>")?;
+
+        // Reports.
+        writeln!(html, "<h2>Issues</h2>")?;
+        for (report_index, report) in reports.iter().enumerate() {
+            writeln!(
+                html,
+                "<div class=\"report {category}\" data-index=\"{report_index}\" data-category=\"{category}\" data-code=\"{id}\">",
+                category = report.category(),
+                id = report.id()
+            )?;
+            let id = match report.code().url() {
+                Some(url) => format!("<a href=\"{}\">{}</a>", escape(&url), report.id()),
+                None => report.id(),
+            };
+            writeln!(
+                html,
+                "<p><span class=\"category\">{}</span> [{id}] {}</p>",
+                report.category(),
+                escape(report.message())
+            )?;
+            for label in report.primary() {
+                let Ok(location) = files.location(label.file_id, label.range.start) else {
+                    continue;
+                };
+                let file_name =
+                    files.name(label.file_id).map(|name| file_library.relative_path(&name));
+                writeln!(
+                    html,
+                    "<p><a href=\"#file-{}-line-{}\">{}:{}:{}</a> {}</p>",
+                    label.file_id,
+                    location.line_number,
+                    escape(&file_name.unwrap_or_default()),
+                    location.line_number,
+                    location.column_number,
+                    escape(&label.message)
+                )?;
+            }
+            let mut notes = report.notes().clone();
+            if let Some(instance) = report.instance() {
+                notes.push(format!(
+                    "This issue was found when analyzing the template instance `{instance}`."
+                ));
+            }
+            if !notes.is_empty() {
+                writeln!(html, "<ul class=\"notes\">")?;
+                for note in notes {
+                    writeln!(html, "<li>{}</li>", escape(&note))?;
+                }
+                writeln!(html, "</ul>")?;
+            }
+            writeln!(html, "</div>")?;
+        }
+
+        // Source views.
+        writeln!(html, "<h2>Source</h2>")?;
+        for (file_id, highlights) in highlights {
+            let Ok(file) = files.get(file_id) else {
+                continue;
+            };
+            writeln!(html, "<h3>{}</h3>", escape(&file_library.relative_path(file.name())))?;
+            writeln!(html, "<table class=\"source\">")?;
+            let mut line_start = 0;
+            for (line_index, line) in file.source().split_inclusive('\n').enumerate() {
+                let line_end = line_start + line.trim_end_matches(['\n', '\r']).len();
+                writeln!(
+                    html,
+                    "<tr id=\"file-{file_id}-line-{line_number}\"><td class=\"line-number\">{line_number}</td><td>{}</td></tr>",
+                    render_line(file.source(), line_start..line_end, &highlights),
+                    line_number = line_index + 1
+                )?;
+                line_start += line.len();
+            }
+            writeln!(html, "</table>")?;
+        }
+
+        writeln!(html, "<script>{SCRIPT}</script>\n</body>\n</html>")?;
+        Ok(html)
+    }
+}
+
+/// Renders the given line of the source, wrapping highlighted segments in
+/// `span` elements. A segment covered by both primary and secondary labels is
+/// highlighted as primary.
+fn render_line(source: &str, line: Range<usize>, highlights: &[Highlight]) -> String {
+    let covering = |highlight: &&Highlight| {
+        highlight.range.start < line.end && line.start < highlight.range.end
+    };
+    let highlights = highlights.iter().filter(covering).collect::<Vec<_>>();
+    let mut boundaries = BTreeSet::from([line.start, line.end]);
+    for highlight in &highlights {
+        boundaries.insert(highlight.range.start.clamp(line.start, line.end));
+        boundaries.insert(highlight.range.end.clamp(line.start, line.end));
+    }
+    let boundaries = boundaries.into_iter().collect::<Vec<_>>();
+    let mut result = String::new();
+    for segment in boundaries.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let Some(text) = source.get(start..end) else {
+            continue;
+        };
+        let covering = highlights
+            .iter()
+            .filter(|highlight| highlight.range.start <= start && end <= highlight.range.end)
+            .collect::<Vec<_>>();
+        if covering.is_empty() {
+            result.push_str(&escape(text));
+            continue;
+        }
+        let class = if covering.iter().any(|highlight| highlight.primary) {
+            "primary"
+        } else {
+            "secondary"
+        };
+        let reports = covering
+            .iter()
+            .map(|highlight| highlight.report_index)
+            .collect::<BTreeSet<_>>()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let title = covering
+            .iter()
+            .map(|highlight| highlight.message.as_str())
+            .filter(|message| !message.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        result.push_str(&format!(
+            "<span class=\"{class}\" data-reports=\"{reports}\" title=\"{}\">{}</span>",
+            escape(&title),
+            escape(text)
+        ));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report_code::ReportCode;
+
+    use super::*;
+
+    #[test]
+    fn test_html_report() {
+        let src = "template T() {\n    x <-- 1 < 2;\n}\n";
+        let mut file_library = FileLibrary::new();
+        let file_id = file_library.add_file("test.circom".to_string(), src.to_string(), true);
+        let mut report = Report::warning(
+            "Signal assignment.".to_string(),
+            ReportCode::SignalAssignmentStatement,
+        );
+        report.add_primary(19..31, file_id, "here".to_string());
+        report.add_secondary(25..30, file_id, Some("comparison".to_string()));
+        report.add_note("Use `<==`.".to_string());
+
+        let html = Html.format(&[report], &file_library).unwrap();
+        // The report links to the documentation and the source location.
+        assert!(html.contains(&format!(
+            "<a href=\"{}\">CS0005</a>",
+            ReportCode::SignalAssignmentStatement.url().unwrap()
+        )));
+        assert!(html.contains("<a href=\"#file-0-line-2\">test.circom:2:5</a> here"));
+        assert!(html.contains("<li>Use `&lt;==`.</li>"));
+        // Overlapping labels are split into segments.
+        assert!(html.contains(
+            "<td>    <span class=\"primary\" data-reports=\"0\" title=\"here\">x &lt;-- </span>\
+             <span class=\"primary\" data-reports=\"0\" title=\"here\ncomparison\">1 &lt; 2</span>\
+             <span class=\"primary\" data-reports=\"0\" title=\"here\">;</span></td>"
+        ));
+        // No network assets are referenced.
+        assert!(!html.contains("http://") && !html.contains("src="));
+    }
+}
@@ -3,3 +3,5 @@ pub mod environment;
 pub mod nonempty_vec;
 pub mod writers;
 pub mod sarif_conversion;
+pub mod report_formats;
+pub mod html_report;
@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Error};
 use std::convert::TryFrom;
 use std::ops::{Index, IndexMut};
+use serde_derive::{Deserialize, Serialize};
 
 /// A vector type which is guaranteed to be non-empty.
 ///
@@ -28,7 +29,7 @@ use std::ops::{Index, IndexMut};
 /// assert_eq!(v.pop(), Some(2));
 /// assert_eq!(v.pop(), None);
 /// ```
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct NonEmptyVec<T> {
     head: T,
     tail: Vec<T>,
@@ -0,0 +1,409 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+use codespan_reporting::files::Files;
+use serde_json::{json, Value};
+
+use crate::baseline::fingerprint;
+use crate::fix::Fix;
+use crate::file_definition::{FileID, FileLibrary, FileLocation};
+use crate::report::{FlowStep, MessageCategory, Report, ReportLabel};
+
+/// A machine-readable output format for reports.
+pub trait ReportFormat {
+    /// Formats the given reports.
+    fn format(&self, reports: &[Report], file_library: &FileLibrary) -> anyhow::Result<String>;
+}
+
+/// A resolved source location. Line and column numbers are 1-based.
+struct SourceLocation {
+    file: String,
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+impl SourceLocation {
+    fn new(label: &ReportLabel, file_library: &FileLibrary) -> Option<SourceLocation> {
+        SourceLocation::from_range(label.file_id, &label.range, file_library)
+    }
+
+    fn from_range(
+        file_id: FileID,
+        range: &FileLocation,
+        file_library: &FileLibrary,
+    ) -> Option<SourceLocation> {
+        let files = file_library.to_storage();
+        let file = file_library.relative_path(files.name(file_id).ok()?.as_str());
+        let start = files.location(file_id, range.start).ok()?;
+        let end = files.location(file_id, range.end).ok()?;
+        Some(SourceLocation {
+            file,
+            start_line: start.line_number,
+            start_column: start.column_number,
+            end_line: end.line_number,
+            end_column: end.column_number,
+        })
+    }
+
+    /// Returns the location of the first primary label of the report.
+    fn primary(report: &Report, file_library: &FileLibrary) -> Option<SourceLocation> {
+        SourceLocation::new(report.primary().first()?, file_library)
+    }
+}
+
+/// Plain JSON containing all report fields.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Json;
+
+impl ReportFormat for Json {
+    fn format(&self, reports: &[Report], file_library: &FileLibrary) -> anyhow::Result<String> {
+        let labels = |labels: &[ReportLabel]| {
+            labels
+                .iter()
+                .map(|label| {
+                    let mut value = json!({ "message": label.message });
+                    if let Some(location) = SourceLocation::new(label, file_library) {
+                        value["file"] = json!(location.file);
+                        value["start"] =
+                            json!({ "line": location.start_line, "column": location.start_column });
+                        value["end"] =
+                            json!({ "line": location.end_line, "column": location.end_column });
+                    }
+                    value
+                })
+                .collect::<Vec<_>>()
+        };
+        let fix = |fix: &Fix| {
+            let edits = fix
+                .edits()
+                .iter()
+                .map(|edit| {
+                    let mut value = json!({ "replacement": edit.replacement });
+                    if let Some(location) =
+                        SourceLocation::from_range(edit.file_id, &edit.location, file_library)
+                    {
+                        value["file"] = json!(location.file);
+                        value["start"] =
+                            json!({ "line": location.start_line, "column": location.start_column });
+                        value["end"] =
+                            json!({ "line": location.end_line, "column": location.end_column });
+                    }
+                    value
+                })
+                .collect::<Vec<_>>();
+            json!({ "description": fix.description(), "edits": edits })
+        };
+        let reports = reports
+            .iter()
+            .map(|report| {
+                json!({
+                    "id": report.id(),
+                    "name": report.name(),
+                    "category": report.category().to_string(),
+                    "message": report.message(),
+                    "definition": report.definition(),
+                    "instance": report.instance(),
+                    "primary": labels(report.primary()),
+                    "secondary": labels(report.secondary()),
+                    "notes": report.notes(),
+                    "url": report.code().url(),
+                    "fix": report.fix().map(fix),
+                    "flow": labels(
+                        &report.code_flow().iter().map(FlowStep::to_label).collect::<Vec<_>>()
+                    ),
+                    "fingerprint": fingerprint(report, file_library),
+                })
+            })
+            .collect::<Vec<_>>();
+        Ok(serde_json::to_string_pretty(&Value::from(reports))? + "\n")
+    }
+}
+
+/// JUnit XML with one test case per template or function. Each report is
+/// represented as a failure of the test case corresponding to the template or
+/// function analyzed when the report was generated.
+#[derive(Clone, Debug, Default)]
+pub struct JUnit {
+    definitions: Vec<String>,
+}
+
+impl JUnit {
+    /// Test cases are generated for the given templates and functions even if
+    /// there are no corresponding reports.
+    pub fn new(definitions: Vec<String>) -> JUnit {
+        JUnit { definitions }
+    }
+}
+
+impl ReportFormat for JUnit {
+    fn format(&self, reports: &[Report], file_library: &FileLibrary) -> anyhow::Result<String> {
+        // Reports without a template or function (like parse errors) are
+        // grouped by file.
+        let mut test_cases = BTreeMap::<String, Vec<&Report>>::new();
+        for definition in &self.definitions {
+            test_cases.entry(definition.clone()).or_default();
+        }
+        for report in reports {
+            let name = match (report.definition(), SourceLocation::primary(report, file_library)) {
+                (Some(definition), _) => definition.to_string(),
+                (None, Some(location)) => location.file,
+                (None, None) => "circomspect".to_string(),
+            };
+            test_cases.entry(name).or_default().push(report);
+        }
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let tests = test_cases.len();
+        let failures = test_cases.values().filter(|reports| !reports.is_empty()).count();
+        writeln!(
+            xml,
+            "<testsuites name=\"circomspect\" tests=\"{tests}\" failures=\"{failures}\">"
+        )?;
+        writeln!(
+            xml,
+            "  <testsuite name=\"circomspect\" tests=\"{tests}\" failures=\"{failures}\">"
+        )?;
+        for (name, reports) in test_cases {
+            let name = escape_xml(&name);
+            if reports.is_empty() {
+                writeln!(xml, "    <testcase classname=\"circomspect\" name=\"{name}\"/>")?;
+                continue;
+            }
+            writeln!(xml, "    <testcase classname=\"circomspect\" name=\"{name}\">")?;
+            for report in reports {
+                let mut text = String::new();
+                if let Some(location) = SourceLocation::primary(report, file_library) {
+                    write!(
+                        text,
+                        "{}:{}:{}: ",
+                        location.file, location.start_line, location.start_column
+                    )?;
+                }
+                text.push_str(report.message());
+                writeln!(
+                    xml,
+                    "      <failure type=\"{}\" message=\"{}\">{}</failure>",
+                    report.id(),
+                    escape_xml(report.message()),
+                    escape_xml(&text)
+                )?;
+            }
+            writeln!(xml, "    </testcase>")?;
+        }
+        writeln!(xml, "  </testsuite>")?;
+        writeln!(xml, "</testsuites>")?;
+        Ok(xml)
+    }
+}
+
+/// Checkstyle XML, with reports grouped by file.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Checkstyle;
+
+impl ReportFormat for Checkstyle {
+    fn format(&self, reports: &[Report], file_library: &FileLibrary) -> anyhow::Result<String> {
+        let mut files = BTreeMap::<String, Vec<(usize, usize, &Report)>>::new();
+        for report in reports {
+            // Checkstyle requires a file for each report.
+            if let Some(location) = SourceLocation::primary(report, file_library) {
+                files.entry(location.file).or_default().push((
+                    location.start_line,
+                    location.start_column,
+                    report,
+                ));
+            }
+        }
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        writeln!(xml, "<checkstyle version=\"4.3\">")?;
+        for (file, reports) in files {
+            writeln!(xml, "  <file name=\"{}\">", escape_xml(&file))?;
+            for (line, column, report) in reports {
+                writeln!(
+                    xml,
+                    "    <error line=\"{line}\" column=\"{column}\" severity=\"{}\" message=\"{}\" source=\"circomspect.{}\"/>",
+                    report.category(),
+                    escape_xml(report.message()),
+                    report.id()
+                )?;
+            }
+            writeln!(xml, "  </file>")?;
+        }
+        writeln!(xml, "</checkstyle>")?;
+        Ok(xml)
+    }
+}
+
+/// GitLab Code Quality JSON.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GitLab;
+
+impl ReportFormat for GitLab {
+    fn format(&self, reports: &[Report], file_library: &FileLibrary) -> anyhow::Result<String> {
+        // Fingerprints must be unique, so identical reports are disambiguated
+        // using a counter.
+        let mut fingerprints = HashSet::new();
+        let issues = reports
+            .iter()
+            .filter_map(|report| {
+                // GitLab requires a file for each report.
+                let location = SourceLocation::primary(report, file_library)?;
+                let mut report_fingerprint = fingerprint(report, file_library);
+                let mut index = 1;
+                while !fingerprints.insert(report_fingerprint.clone()) {
+                    report_fingerprint = format!("{}-{index}", fingerprint(report, file_library));
+                    index += 1;
+                }
+                let severity = match report.category() {
+                    MessageCategory::Error => "critical",
+                    MessageCategory::Warning => "major",
+                    MessageCategory::Info => "info",
+                };
+                Some(json!({
+                    "description": report.message(),
+                    "check_name": report.id(),
+                    "fingerprint": report_fingerprint,
+                    "severity": severity,
+                    "location": {
+                        "path": location.file,
+                        "lines": { "begin": location.start_line, "end": location.end_line },
+                    },
+                }))
+            })
+            .collect::<Vec<_>>();
+        Ok(serde_json::to_string_pretty(&Value::from(issues))? + "\n")
+    }
+}
+
+/// GitHub Actions workflow commands (e.g. `::warning file=...::message`). These
+/// are rendered as annotations when written to `stdout` in a workflow.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GitHub;
+
+impl ReportFormat for GitHub {
+    fn format(&self, reports: &[Report], file_library: &FileLibrary) -> anyhow::Result<String> {
+        let mut output = String::new();
+        for report in reports {
+            let command = match report.category() {
+                MessageCategory::Error => "error",
+                MessageCategory::Warning => "warning",
+                MessageCategory::Info => "notice",
+            };
+            let mut properties = Vec::new();
+            if let Some(location) = SourceLocation::primary(report, file_library) {
+                properties.push(format!("file={}", escape_property(&location.file)));
+                properties.push(format!("line={}", location.start_line));
+                properties.push(format!("col={}", location.start_column));
+                properties.push(format!("endLine={}", location.end_line));
+                properties.push(format!("endColumn={}", location.end_column));
+            }
+            properties.push(format!("title={}", escape_property(&report.id())));
+            writeln!(
+                output,
+                "::{command} {}::{}",
+                properties.join(","),
+                escape_data(report.message())
+            )?;
+        }
+        Ok(output)
+    }
+}
+
+/// Escapes special characters in XML (and HTML) text and attribute values.
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Escapes the message of a GitHub workflow command.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escapes a property value of a GitHub workflow command.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report_code::ReportCode;
+
+    use super::*;
+
+    fn reports(file_library: &mut FileLibrary) -> Vec<Report> {
+        let file_id = file_library.add_file(
+            "test.circom".to_string(),
+            "template T() {\n    x <-- 1;\n}\n".to_string(),
+            true,
+        );
+        let mut report = Report::warning(
+            "Using `<--` & friends.".to_string(),
+            ReportCode::SignalAssignmentStatement,
+        );
+        report.add_primary(19..27, file_id, "here".to_string());
+        report.set_definition("T".to_string());
+        vec![report]
+    }
+
+    #[test]
+    fn test_json() {
+        let mut file_library = FileLibrary::new();
+        let reports = reports(&mut file_library);
+        let output = Json.format(&reports, &file_library).unwrap();
+        let value: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value[0]["id"], "CS0005");
+        assert_eq!(value[0]["definition"], "T");
+        assert_eq!(value[0]["primary"][0]["start"], json!({ "line": 2, "column": 5 }));
+        assert_eq!(value[0]["primary"][0]["end"], json!({ "line": 2, "column": 13 }));
+    }
+
+    #[test]
+    fn test_junit() {
+        let mut file_library = FileLibrary::new();
+        let reports = reports(&mut file_library);
+        let output = JUnit::new(vec!["S".to_string(), "T".to_string()])
+            .format(&reports, &file_library)
+            .unwrap();
+        assert!(output.contains("tests=\"2\" failures=\"1\""));
+        assert!(output.contains("<testcase classname=\"circomspect\" name=\"S\"/>"));
+        assert!(output.contains(
+            "<failure type=\"CS0005\" message=\"Using `&lt;--` &amp; friends.\">test.circom:2:5: "
+        ));
+    }
+
+    #[test]
+    fn test_checkstyle() {
+        let mut file_library = FileLibrary::new();
+        let reports = reports(&mut file_library);
+        let output = Checkstyle.format(&reports, &file_library).unwrap();
+        assert!(output.contains("<file name=\"test.circom\">"));
+        assert!(output.contains("<error line=\"2\" column=\"5\" severity=\"warning\""));
+    }
+
+    #[test]
+    fn test_gitlab() {
+        let mut file_library = FileLibrary::new();
+        let mut reports = reports(&mut file_library);
+        reports.push(reports[0].clone());
+        let output = GitLab.format(&reports, &file_library).unwrap();
+        let value: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value[0]["severity"], "major");
+        assert_eq!(value[0]["location"]["lines"]["begin"], 2);
+        assert_ne!(value[0]["fingerprint"], value[1]["fingerprint"]);
+    }
+
+    #[test]
+    fn test_github() {
+        let mut file_library = FileLibrary::new();
+        let reports = reports(&mut file_library);
+        let output = GitHub.format(&reports, &file_library).unwrap();
+        assert_eq!(
+            output,
+            "::warning file=test.circom,line=2,col=5,endLine=2,endColumn=13,title=CS0005::Using `<--` & friends.\n"
+        );
+    }
+}
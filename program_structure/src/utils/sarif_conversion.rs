@@ -1,13 +1,15 @@
 use codespan_reporting::files::Files;
 use log::{debug, trace};
 use serde_sarif::sarif;
-use std::collections::HashSet;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::ops::Range;
 use std::path::PathBuf;
 use thiserror::Error;
 
-use crate::report::{Report, ReportCollection, ReportLabel};
+use crate::baseline::fingerprint;
+use crate::fix::{Fix, TextEdit};
+use crate::report::{MessageCategory, Report, ReportCollection, ReportLabel};
 use crate::file_definition::{FileID, FileLibrary};
 
 // This is the Sarif file format version, not the tool version.
@@ -15,6 +17,8 @@ const SARIF_VERSION: &str = "2.1.0";
 const DRIVER_NAME: &str = "Circomspect";
 const ORGANIZATION: &str = "Trail of Bits";
 const DOWNLOAD_URI: &str = "https://github.com/trailofbits/circomspect";
+// The key used for report fingerprints in `partialFingerprints`.
+const FINGERPRINT_KEY: &str = "circomspect/v1";
 
 /// A trait for objects that can be converted into a Sarif artifact.
 pub trait ToSarif {
@@ -25,7 +29,18 @@ pub trait ToSarif {
     fn to_sarif(&self, files: &FileLibrary) -> Result<Self::Sarif, Self::Error>;
 }
 
-impl ToSarif for ReportCollection {
+/// A Sarif log containing the given results, together with information about
+/// the tool and the analysis run.
+pub struct SarifLog<'a> {
+    /// The reports included as Sarif results.
+    pub results: &'a [Report],
+    /// The version of the tool generating the reports.
+    pub tool_version: Option<&'a str>,
+    /// Reports (like parse errors) included as tool execution notifications.
+    pub notifications: &'a [Report],
+}
+
+impl ToSarif for SarifLog<'_> {
     type Sarif = sarif::Sarif;
     type Error = SarifError;
 
@@ -33,32 +48,48 @@ impl ToSarif for ReportCollection {
         debug!("converting report collection to Sarif-format");
         // Build reporting descriptors.
         trace!("building reporting descriptors");
-        let rules = self
-            .iter()
-            .map(|report| (report.name(), report.id()))
-            .collect::<HashSet<_>>()
-            .iter()
-            .map(|(name, id)| {
-                sarif::ReportingDescriptorBuilder::default().name(name).id(id).build()
-            })
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(SarifError::from)?;
+        let rules = reporting_descriptors(self.results)?;
+        let notifications = reporting_descriptors(self.notifications)?;
         // Build tool.
-        //
-        // TODO: Should include primary package version.
         trace!("building tool");
-        let driver = sarif::ToolComponentBuilder::default()
-            .name(DRIVER_NAME)
-            .organization(ORGANIZATION)
-            .download_uri(DOWNLOAD_URI)
-            .rules(rules)
-            .build()?;
-        let tool = sarif::ToolBuilder::default().driver(driver).build()?;
+        let mut driver = sarif::ToolComponentBuilder::default();
+        driver.name(DRIVER_NAME).organization(ORGANIZATION).download_uri(DOWNLOAD_URI).rules(rules);
+        if !notifications.is_empty() {
+            driver.notifications(notifications);
+        }
+        if let Some(version) = self.tool_version {
+            driver.version(version).semantic_version(version);
+        }
+        let tool = sarif::ToolBuilder::default().driver(driver.build()?).build()?;
+        // Build invocation.
+        trace!("building invocation");
+        let execution_successful = !self
+            .notifications
+            .iter()
+            .any(|report| matches!(report.category(), MessageCategory::Error));
+        let notifications = self
+            .notifications
+            .iter()
+            .map(|report| to_notification(report, files))
+            .collect::<SarifResult<Vec<_>>>()?;
+        let mut invocation = sarif::InvocationBuilder::default();
+        invocation.execution_successful(execution_successful);
+        if !notifications.is_empty() {
+            invocation.tool_execution_notifications(notifications);
+        }
+        let invocation = invocation.build()?;
         // Build run.
         trace!("building run");
-        let results =
-            self.iter().map(|report| report.to_sarif(files)).collect::<SarifResult<Vec<_>>>()?;
-        let run = sarif::RunBuilder::default().tool(tool).results(results).build()?;
+        let results = self
+            .results
+            .iter()
+            .map(|report| report.to_sarif(files))
+            .collect::<SarifResult<Vec<_>>>()?;
+        let run = sarif::RunBuilder::default()
+            .tool(tool)
+            .invocations(vec![invocation])
+            .results(results)
+            .build()?;
         // Build main object.
         trace!("building main Sarif object");
         let sarif = sarif::SarifBuilder::default().runs(vec![run]).version(SARIF_VERSION).build();
@@ -66,6 +97,66 @@ impl ToSarif for ReportCollection {
     }
 }
 
+impl ToSarif for ReportCollection {
+    type Sarif = sarif::Sarif;
+    type Error = SarifError;
+
+    fn to_sarif(&self, files: &FileLibrary) -> Result<Self::Sarif, Self::Error> {
+        SarifLog { results: self, tool_version: None, notifications: &[] }.to_sarif(files)
+    }
+}
+
+/// Builds a reporting descriptor for each report code occurring in the given
+/// reports, ordered by ID. The default level of each descriptor is given by
+/// the default category of the report code (and not by the category of the
+/// reports, which may be overridden by the user).
+fn reporting_descriptors(reports: &[Report]) -> SarifResult<Vec<sarif::ReportingDescriptor>> {
+    let mut codes = BTreeMap::new();
+    for report in reports {
+        codes.entry(report.id()).or_insert(report.code());
+    }
+    codes
+        .into_iter()
+        .map(|(id, code)| {
+            let description = sarif::MultiformatMessageStringBuilder::default()
+                .text(code.description())
+                .build()?;
+            let configuration = sarif::ReportingConfigurationBuilder::default()
+                .level(code.default_category().to_level())
+                .build()?;
+            let mut descriptor = sarif::ReportingDescriptorBuilder::default();
+            descriptor
+                .id(id)
+                .name(code.name())
+                .full_description(description)
+                .default_configuration(configuration);
+            if let Some(url) = code.url() {
+                descriptor.help_uri(url);
+            }
+            descriptor.build().map_err(SarifError::from)
+        })
+        .collect()
+}
+
+/// Converts the report to a Sarif tool execution notification.
+fn to_notification(report: &Report, files: &FileLibrary) -> SarifResult<sarif::Notification> {
+    let message = sarif::MessageBuilder::default().text(report.message()).build()?;
+    let locations = report
+        .primary()
+        .iter()
+        .map(|label| label.to_sarif(files))
+        .collect::<SarifResult<Vec<_>>>()?;
+    let descriptor =
+        sarif::ReportingDescriptorReferenceBuilder::default().id(report.id()).build()?;
+    sarif::NotificationBuilder::default()
+        .level(report.category().to_level())
+        .message(message)
+        .locations(locations)
+        .descriptor(descriptor)
+        .build()
+        .map_err(SarifError::from)
+}
+
 impl ToSarif for Report {
     type Sarif = sarif::Result;
     type Error = SarifError;
@@ -93,15 +184,95 @@ impl ToSarif for Report {
             .id(&rule_id)
             .build()
             .map_err(SarifError::from)?;
+        // Build fingerprints.
+        let fingerprints =
+            BTreeMap::from([(FINGERPRINT_KEY.to_string(), fingerprint(self, files))]);
         // Build result.
         trace!("building result");
-        sarif::ResultBuilder::default()
+        let mut result = sarif::ResultBuilder::default();
+        result
             .level(level)
+            .partial_fingerprints(fingerprints)
             .message(message)
             .rule_id(rule_id)
             .rule(rule)
             .locations(locations)
-            .related_locations(related_locations)
+            .related_locations(related_locations);
+        if !self.code_flow().is_empty() {
+            result.code_flows(vec![to_code_flow(self, files)?]);
+        }
+        if let Some(fix) = self.fix() {
+            result.fixes(vec![fix.to_sarif(files)?]);
+        }
+        result.build().map_err(SarifError::from)
+    }
+}
+
+/// Converts the data-flow path explaining the report to a Sarif code flow.
+fn to_code_flow(report: &Report, files: &FileLibrary) -> SarifResult<sarif::CodeFlow> {
+    trace!("building code flow");
+    let locations = report
+        .code_flow()
+        .iter()
+        .enumerate()
+        .map(|(index, step)| {
+            sarif::ThreadFlowLocationBuilder::default()
+                .location(step.to_label().to_sarif(files)?)
+                .execution_order(index as i64)
+                .build()
+                .map_err(SarifError::from)
+        })
+        .collect::<SarifResult<Vec<_>>>()?;
+    let thread_flow = sarif::ThreadFlowBuilder::default().locations(locations).build()?;
+    sarif::CodeFlowBuilder::default()
+        .thread_flows(vec![thread_flow])
+        .build()
+        .map_err(SarifError::from)
+}
+
+impl ToSarif for Fix {
+    type Sarif = sarif::Fix;
+    type Error = SarifError;
+
+    fn to_sarif(&self, files: &FileLibrary) -> SarifResult<sarif::Fix> {
+        // Group replacements by file, as required by the Sarif format.
+        trace!("building artifact changes");
+        let mut edits = BTreeMap::<FileID, Vec<&TextEdit>>::new();
+        for edit in self.edits() {
+            edits.entry(edit.file_id).or_default().push(edit);
+        }
+        let artifact_changes = edits
+            .into_iter()
+            .map(|(file_id, edits)| {
+                let artifact_location = sarif::ArtifactLocationBuilder::default()
+                    .uri(file_id.to_uri(files)?)
+                    .build()?;
+                let replacements = edits
+                    .into_iter()
+                    .map(|edit| {
+                        let inserted_content = sarif::ArtifactContentBuilder::default()
+                            .text(edit.replacement.clone())
+                            .build()?;
+                        sarif::ReplacementBuilder::default()
+                            .deleted_region(to_region(edit.file_id, &edit.location, files)?)
+                            .inserted_content(inserted_content)
+                            .build()
+                            .map_err(SarifError::from)
+                    })
+                    .collect::<SarifResult<Vec<_>>>()?;
+                sarif::ArtifactChangeBuilder::default()
+                    .artifact_location(artifact_location)
+                    .replacements(replacements)
+                    .build()
+                    .map_err(SarifError::from)
+            })
+            .collect::<SarifResult<Vec<_>>>()?;
+        // Build fix.
+        trace!("building fix");
+        let description = sarif::MessageBuilder::default().text(self.description()).build()?;
+        sarif::FixBuilder::default()
+            .description(description)
+            .artifact_changes(artifact_changes)
             .build()
             .map_err(SarifError::from)
     }
@@ -118,21 +289,7 @@ impl ToSarif for ReportLabel {
         let artifact_location = sarif::ArtifactLocationBuilder::default().uri(file_uri).build()?;
         // Build region.
         trace!("building region");
-        assert!(self.range.start <= self.range.end);
-        let start = files
-            .to_storage()
-            .location(self.file_id, self.range.start)
-            .map_err(|_| SarifError::UnknownLocation(self.file_id, self.range.clone()))?;
-        let end = files
-            .to_storage()
-            .location(self.file_id, self.range.end)
-            .map_err(|_| SarifError::UnknownLocation(self.file_id, self.range.clone()))?;
-        let region = sarif::RegionBuilder::default()
-            .start_line(start.line_number as i64)
-            .start_column(start.column_number as i64)
-            .end_line(end.line_number as i64)
-            .end_column(end.column_number as i64)
-            .build()?;
+        let region = to_region(self.file_id, &self.range, files)?;
         // Build physical location.
         trace!("building physical location");
         let physical_location = sarif::PhysicalLocationBuilder::default()
@@ -153,6 +310,30 @@ impl ToSarif for ReportLabel {
     }
 }
 
+/// Converts the given file location to a Sarif region.
+fn to_region(
+    file_id: FileID,
+    range: &Range<usize>,
+    files: &FileLibrary,
+) -> SarifResult<sarif::Region> {
+    assert!(range.start <= range.end);
+    let start = files
+        .to_storage()
+        .location(file_id, range.start)
+        .map_err(|_| SarifError::UnknownLocation(file_id, range.clone()))?;
+    let end = files
+        .to_storage()
+        .location(file_id, range.end)
+        .map_err(|_| SarifError::UnknownLocation(file_id, range.clone()))?;
+    sarif::RegionBuilder::default()
+        .start_line(start.line_number as i64)
+        .start_column(start.column_number as i64)
+        .end_line(end.line_number as i64)
+        .end_column(end.column_number as i64)
+        .build()
+        .map_err(SarifError::from)
+}
+
 trait ToUri {
     type Error;
     fn to_uri(&self, files: &FileLibrary) -> Result<String, Self::Error>;
@@ -189,6 +370,16 @@ pub enum SarifError {
     InvalidSarif(#[from] sarif::SarifBuilderError),
     InvalidTool(#[from] sarif::ToolBuilderError),
     InvalidFix(#[from] sarif::FixBuilderError),
+    InvalidCodeFlow(#[from] sarif::CodeFlowBuilderError),
+    InvalidThreadFlow(#[from] sarif::ThreadFlowBuilderError),
+    InvalidThreadFlowLocation(#[from] sarif::ThreadFlowLocationBuilderError),
+    InvalidArtifactChange(#[from] sarif::ArtifactChangeBuilderError),
+    InvalidReplacement(#[from] sarif::ReplacementBuilderError),
+    InvalidArtifactContent(#[from] sarif::ArtifactContentBuilderError),
+    InvalidInvocation(#[from] sarif::InvocationBuilderError),
+    InvalidNotification(#[from] sarif::NotificationBuilderError),
+    InvalidMessageString(#[from] sarif::MultiformatMessageStringBuilderError),
+    InvalidReportingConfiguration(#[from] sarif::ReportingConfigurationBuilderError),
     UnknownLocation(FileID, Range<usize>),
     UnknownFile(FileID),
 }
@@ -200,3 +391,65 @@ impl fmt::Display for SarifError {
         write!(f, "failed to convert analysis results to Sarif format")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::report_code::ReportCode;
+
+    use super::*;
+
+    #[test]
+    fn test_sarif_log() {
+        let mut file_library = FileLibrary::new();
+        let file_id =
+            file_library.add_file("test.circom".to_string(), "x <-- 1;\n".to_string(), true);
+        let mut result = Report::warning("test".to_string(), ReportCode::SignalAssignmentStatement);
+        result.set_category(MessageCategory::Error);
+        result.add_primary(0..8, file_id, "test".to_string());
+        result.set_fix(Fix::new("Use `<==`.", vec![TextEdit::new(file_id, 2..5, "<==")]));
+        result.add_flow_step(6..7, file_id, "The value `1` is assigned here.".to_string());
+        result.add_flow_step(0..1, file_id, "The signal `x` is assigned here.".to_string());
+        let mut notification = Report::error("test".to_string(), ReportCode::ParseFail);
+        notification.add_primary(0..1, file_id, "test".to_string());
+
+        let sarif = SarifLog {
+            results: &[result.clone()],
+            tool_version: Some("1.2.3"),
+            notifications: &[notification],
+        }
+        .to_sarif(&file_library)
+        .unwrap();
+        let sarif = serde_json::to_value(sarif).unwrap();
+        let run = &sarif["runs"][0];
+
+        let driver = &run["tool"]["driver"];
+        assert_eq!(driver["version"], "1.2.3");
+        assert_eq!(driver["semanticVersion"], "1.2.3");
+        let rule = &driver["rules"][0];
+        assert_eq!(rule["id"], "CS0005");
+        // The default level is independent of the (overridden) report level.
+        assert_eq!(rule["defaultConfiguration"]["level"], "warning");
+        assert_eq!(run["results"][0]["level"], "error");
+        assert_eq!(rule["helpUri"], ReportCode::SignalAssignmentStatement.url().unwrap());
+        assert!(rule["fullDescription"]["text"].is_string());
+
+        assert_eq!(
+            run["results"][0]["partialFingerprints"][FINGERPRINT_KEY],
+            fingerprint(&result, &file_library)
+        );
+        let flow = &run["results"][0]["codeFlows"][0]["threadFlows"][0]["locations"];
+        assert_eq!(flow[0]["executionOrder"], 0);
+        assert_eq!(flow[0]["location"]["physicalLocation"]["region"]["startColumn"], 7);
+        assert_eq!(flow[1]["location"]["message"]["text"], "The signal `x` is assigned here.");
+        let fix = &run["results"][0]["fixes"][0];
+        assert_eq!(fix["description"]["text"], "Use `<==`.");
+        let change = &fix["artifactChanges"][0];
+        assert_eq!(change["artifactLocation"]["uri"], "file://test.circom");
+        assert_eq!(change["replacements"][0]["deletedRegion"]["startColumn"], 3);
+        assert_eq!(change["replacements"][0]["deletedRegion"]["endColumn"], 6);
+        assert_eq!(change["replacements"][0]["insertedContent"]["text"], "<==");
+        let invocation = &run["invocations"][0];
+        assert_eq!(invocation["executionSuccessful"], false);
+        assert_eq!(invocation["toolExecutionNotifications"][0]["descriptor"]["id"], "P1000");
+    }
+}
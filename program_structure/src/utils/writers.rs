@@ -8,7 +8,9 @@ use std::path::{PathBuf, Path};
 use codespan_reporting::term;
 use termcolor::{StandardStream, ColorChoice, WriteColor, ColorSpec, Color};
 
-use crate::sarif_conversion::ToSarif;
+use crate::html_report::Html;
+use crate::report_formats::{Checkstyle, GitHub, GitLab, JUnit, Json, ReportFormat};
+use crate::sarif_conversion::{SarifLog, ToSarif};
 use crate::{
     program_library::report::{Report, ReportCollection},
     file_definition::FileLibrary,
@@ -99,7 +101,18 @@ impl LogWriter for StdoutWriter {
 impl ReportWriter for StdoutWriter {
     fn write_reports(&mut self, reports: &[Report], file_library: &FileLibrary) -> usize {
         let reports = self.filter(reports);
+        self.emit(&reports, file_library)
+    }
 
+    /// Returns the number of reports written.
+    fn reports_written(&self) -> usize {
+        self.written
+    }
+}
+
+impl StdoutWriter {
+    /// Write the given (already filtered) reports to `stdout`.
+    fn emit(&mut self, reports: &[Report], file_library: &FileLibrary) -> usize {
         let mut config = term::Config::default();
         let mut diagnostics = Vec::new();
         let files = file_library.to_storage();
@@ -117,14 +130,10 @@ impl ReportWriter for StdoutWriter {
         self.written += reports.len();
         reports.len()
     }
-
-    /// Returns the number of reports written.
-    fn reports_written(&self) -> usize {
-        self.written
-    }
 }
 
-/// A `StdoutWriter` that caches all reports.
+/// A `StdoutWriter` that caches all reports written (that is, all reports
+/// passing the filters of the writer).
 pub struct CachedStdoutWriter {
     writer: StdoutWriter,
     reports: ReportCollection,
@@ -153,8 +162,9 @@ impl LogWriter for CachedStdoutWriter {
 
 impl ReportWriter for CachedStdoutWriter {
     fn write_reports(&mut self, reports: &[Report], file_library: &FileLibrary) -> usize {
+        let reports = self.writer.filter(reports);
         self.reports.extend(reports.iter().cloned());
-        self.writer.write_reports(reports, file_library)
+        self.writer.emit(&reports, file_library)
     }
 
     fn reports_written(&self) -> usize {
@@ -165,6 +175,8 @@ impl ReportWriter for CachedStdoutWriter {
 #[derive(Default)]
 pub struct SarifWriter {
     sarif_file: PathBuf,
+    tool_version: Option<String>,
+    notifications: ReportCollection,
     written: usize,
     filters: Vec<Box<dyn ReportFilter>>,
 }
@@ -174,6 +186,19 @@ impl SarifWriter {
         SarifWriter { sarif_file: sarif_file.to_owned(), ..Default::default() }
     }
 
+    /// Set the tool version included in the Sarif output.
+    pub fn with_tool_version(mut self, tool_version: &str) -> SarifWriter {
+        self.tool_version = Some(tool_version.to_string());
+        self
+    }
+
+    /// Set the reports (like parse errors) included as tool execution
+    /// notifications in the Sarif output. Notifications are not filtered.
+    pub fn with_notifications(mut self, notifications: &[Report]) -> SarifWriter {
+        self.notifications = notifications.to_vec();
+        self
+    }
+
     pub fn add_filter(mut self, filter: impl ReportFilter + 'static) -> SarifWriter {
         self.filters.push(Box::new(filter));
         self
@@ -192,8 +217,13 @@ impl SarifWriter {
         reports: &ReportCollection,
         file_library: &FileLibrary,
     ) -> anyhow::Result<()> {
-        let sarif =
-            reports.to_sarif(file_library).context("failed to convert reports to Sarif format")?;
+        let sarif = SarifLog {
+            results: reports,
+            tool_version: self.tool_version.as_deref(),
+            notifications: &self.notifications,
+        }
+        .to_sarif(file_library)
+        .context("failed to convert reports to Sarif format")?;
         let json = serde_json::to_string_pretty(&sarif)?;
         let mut sarif_file = File::create(&self.sarif_file)?;
         writeln!(sarif_file, "{}", &json)
@@ -222,3 +252,83 @@ impl ReportWriter for SarifWriter {
         self.written
     }
 }
+
+/// A writer which writes reports in the given machine-readable format to a
+/// file, or to `stdout` if no file is given.
+pub struct FormatWriter<F: ReportFormat> {
+    format: F,
+    output_file: Option<PathBuf>,
+    written: usize,
+    filters: Vec<Box<dyn ReportFilter>>,
+}
+
+pub type JsonWriter = FormatWriter<Json>;
+pub type JUnitWriter = FormatWriter<JUnit>;
+pub type CheckstyleWriter = FormatWriter<Checkstyle>;
+pub type GitLabWriter = FormatWriter<GitLab>;
+pub type GitHubWriter = FormatWriter<GitHub>;
+pub type HtmlWriter = FormatWriter<Html>;
+
+impl<F: ReportFormat> FormatWriter<F> {
+    pub fn new(format: F, output_file: Option<&Path>) -> FormatWriter<F> {
+        FormatWriter {
+            format,
+            output_file: output_file.map(ToOwned::to_owned),
+            written: 0,
+            filters: Vec::new(),
+        }
+    }
+
+    pub fn add_filter(mut self, filter: impl ReportFilter + 'static) -> FormatWriter<F> {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    fn filter(&self, reports: &[Report]) -> ReportCollection {
+        reports
+            .iter()
+            .filter(|report| self.filters.iter().all(|f| f.filter(report)))
+            .cloned()
+            .collect()
+    }
+
+    fn serialize_reports(
+        &self,
+        reports: &ReportCollection,
+        file_library: &FileLibrary,
+    ) -> anyhow::Result<()> {
+        let output = self.format.format(reports, file_library)?;
+        match &self.output_file {
+            Some(output_file) => {
+                let mut file = File::create(output_file)?;
+                write!(file, "{output}")
+                    .with_context(|| format!("could not write to {}", output_file.display()))?;
+            }
+            None => print!("{output}"),
+        }
+        Ok(())
+    }
+}
+
+impl<F: ReportFormat> ReportWriter for FormatWriter<F> {
+    fn write_reports(&mut self, reports: &[Report], file_library: &FileLibrary) -> usize {
+        let reports = self.filter(reports);
+        match self.serialize_reports(&reports, file_library) {
+            Ok(()) => {
+                if let Some(output_file) = &self.output_file {
+                    info!("reports written to `{}`", output_file.display());
+                }
+                self.written += reports.len();
+                reports.len()
+            }
+            Err(_) => {
+                warn!("failed to write reports");
+                0
+            }
+        }
+    }
+
+    fn reports_written(&self) -> usize {
+        self.written
+    }
+}
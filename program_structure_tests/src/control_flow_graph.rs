@@ -402,6 +402,34 @@ fn test_branches_from_nested_if() {
     validate_branches(src, &true_branches, &false_branches);
 }
 
+#[test]
+fn test_cfg_to_dot() {
+    let src = r#"
+        function f(x) {
+            var y = 0;
+            if (x > 0) {
+                y = x;
+            }
+            return y + x;
+        }
+    "#;
+    let mut reports = ReportCollection::new();
+    let cfg = parse_definition(src).unwrap().into_cfg(&Curve::default(), &mut reports).unwrap();
+    assert!(reports.is_empty());
+
+    let dot = cfg.to_dot(&DotOptions::default());
+    assert!(dot.starts_with("digraph \"f\" {"));
+    assert!(dot.contains("0 -> 1 [label=\"true\"];"));
+    assert!(dot.contains("0 -> 2 [label=\"false\"];"));
+    assert!(dot.contains("1 -> 2;"));
+    assert!(!dot.contains("style=dashed"));
+
+    let options = DotOptions { dominator_tree: true, dominance_frontier: true };
+    let dot = cfg.to_dot(&options);
+    assert!(dot.contains("0 -> 2 [style=dashed, color=blue, constraint=false];"));
+    assert!(dot.contains("1 -> 2 [style=dotted, color=red, constraint=false];"));
+}
+
 fn validate_cfg(
     src: &str,
     variables: &[&str],
@@ -3,3 +3,9 @@ mod control_flow_graph;
 
 #[cfg(test)]
 mod static_single_assignment;
+
+#[cfg(test)]
+mod value_ranges;
+
+#[cfg(test)]
+mod pretty_printer;
@@ -0,0 +1,103 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use parser::parse_definition;
+use program_structure::cfg::IntoCfg;
+use program_structure::constants::Curve;
+use program_structure::report::ReportCollection;
+use program_structure::ir::pretty_printer::{Annotations, PrettyPrinter};
+
+/// Snapshots are stored under `snapshots`. To update the snapshots after an
+/// intentional change to lifting or SSA, run the tests with the environment
+/// variable `UPDATE_SNAPSHOTS` set.
+const SNAPSHOT_DIR: &str = "snapshots";
+
+#[test]
+fn test_snapshot_if_then_else() {
+    let src = r#"
+        function f(x) {
+            var y = 0;
+            if (x > 0) {
+                y = x;
+                y += y * x;
+            } else {
+                y = 1;
+            }
+            return y + x;
+        }
+    "#;
+    validate_snapshots("if_then_else", src);
+}
+
+#[test]
+fn test_snapshot_loop() {
+    let src = r#"
+        template Num2Bits(n) {
+            signal input in;
+            signal output out[n];
+
+            var lc = 0;
+            var e = 1;
+            for (var i = 0; i < n; i++) {
+                out[i] <-- (in >> i) & 1;
+                out[i] * (out[i] - 1) === 0;
+                lc += out[i] * e;
+                e = e + e;
+            }
+            lc === in;
+        }
+    "#;
+    validate_snapshots("loop", src);
+}
+
+#[test]
+fn test_snapshot_components() {
+    let src = r#"
+        template IsZero() {
+            signal input in;
+            signal output out;
+            signal inv;
+
+            inv <-- in != 0 ? 1 / in : 0;
+            out <== -in * inv + 1;
+            in * out === 0;
+
+            component c = Square();
+            c.in <== 2;
+            log("out", c.out);
+        }
+    "#;
+    validate_snapshots("components", src);
+}
+
+/// Validates the lifted CFG (`<name>.cfg.ir`) and the SSA form with all
+/// annotations enabled (`<name>.ssa.ir`) against the stored snapshots.
+fn validate_snapshots(name: &str, src: &str) {
+    let mut reports = ReportCollection::new();
+    let cfg = parse_definition(src).unwrap().into_cfg(&Curve::default(), &mut reports).unwrap();
+    assert!(reports.is_empty());
+    validate_snapshot(&format!("{name}.cfg.ir"), &PrettyPrinter::default().print_cfg(&cfg));
+
+    let cfg = cfg.into_ssa().unwrap();
+    let printer = PrettyPrinter::new(Annotations::all());
+    validate_snapshot(&format!("{name}.ssa.ir"), &printer.print_cfg(&cfg));
+}
+
+fn validate_snapshot(file_name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(SNAPSHOT_DIR).join(file_name);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!("missing snapshot `{}` (run with `UPDATE_SNAPSHOTS=1`)", path.display())
+    });
+    assert_eq!(
+        actual,
+        expected,
+        "snapshot `{}` does not match (run with `UPDATE_SNAPSHOTS=1` to update)",
+        path.display()
+    );
+}
@@ -0,0 +1,120 @@
+use num_bigint_dig::BigInt;
+
+use parser::parse_definition;
+use program_structure::cfg::{Cfg, IntoCfg};
+use program_structure::constants::Curve;
+use program_structure::report::ReportCollection;
+use program_structure::ir::range_meta::ValueRange;
+use program_structure::ir::{Expression, Statement, VariableName};
+
+#[test]
+fn test_ranges_from_constants() {
+    let src = r#"
+        function f(x) {
+            var y = 7;
+            var z = y * 3 + 1;
+            var w = x + 1;
+            return y + z + w;
+        }
+    "#;
+    let cfg = build_cfg(src);
+    assert_eq!(get_range(&cfg, "y", 0), Some(range(7, 7)));
+    assert_eq!(get_range(&cfg, "z", 0), Some(range(22, 22)));
+    assert_eq!(get_range(&cfg, "w", 0), None);
+}
+
+#[test]
+fn test_ranges_from_phi_expressions() {
+    let src = r#"
+        function f(x) {
+            var y = 1;
+            if (x > 0) {
+                y = 5;
+            }
+            return y;
+        }
+    "#;
+    let cfg = build_cfg(src);
+    // SSA versions are not assigned deterministically, so we need to look up
+    // the variable that the phi expression is assigned to.
+    let name = get_phi_variable(&cfg, "y").unwrap();
+    assert_eq!(cfg.get_range(&name), Some(&range(1, 5)));
+}
+
+#[test]
+fn test_ranges_from_constraints() {
+    let src = r#"
+        template T() {
+            signal input a;
+            signal input b;
+            signal c;
+            signal output out;
+
+            component n2b = Num2Bits(8);
+            n2b.in <== a;
+            b * (b - 1) === 0;
+            c <== a + b;
+
+            component lt = LessThan(8);
+            lt.in[0] <== a;
+            lt.in[1] <== c;
+            out <== lt.out * n2b.out[0];
+        }
+    "#;
+    let cfg = build_cfg(src);
+    assert_eq!(get_range(&cfg, "a", 0), Some(range(0, 255)));
+    assert_eq!(get_range(&cfg, "b", 0), Some(range(0, 1)));
+    assert_eq!(get_range(&cfg, "c", 0), Some(range(0, 256)));
+    assert_eq!(get_range(&cfg, "out", 0), Some(range(0, 1)));
+}
+
+#[test]
+fn test_ranges_from_branches() {
+    let src = r#"
+        template T(n) {
+            signal input in;
+            signal output out;
+
+            in * (in - 1) === 0;
+            if (n > 0) {
+                out <== in;
+            } else {
+                out <== in + 2;
+            }
+        }
+    "#;
+    let cfg = build_cfg(src);
+    // Ranges of signals assigned on different branches are joined.
+    assert_eq!(get_range(&cfg, "out", 0), Some(range(0, 3)));
+}
+
+fn build_cfg(src: &str) -> Cfg {
+    let mut reports = ReportCollection::new();
+    let cfg = parse_definition(src)
+        .unwrap()
+        .into_cfg(&Curve::default(), &mut reports)
+        .unwrap()
+        .into_ssa()
+        .unwrap();
+    assert!(reports.is_empty());
+    cfg
+}
+
+fn get_range(cfg: &Cfg, name: &str, version: usize) -> Option<ValueRange> {
+    // Signals are not versioned.
+    let name = VariableName::from_string(name);
+    cfg.get_range(&name.with_version(version)).or_else(|| cfg.get_range(&name)).cloned()
+}
+
+fn get_phi_variable(cfg: &Cfg, name: &str) -> Option<VariableName> {
+    cfg.iter().flat_map(|basic_block| basic_block.iter()).find_map(|stmt| match stmt {
+        Statement::Substitution { var, rhe: Expression::Phi { .. }, .. } if var.name() == name => {
+            Some(var.clone())
+        }
+        _ => None,
+    })
+}
+
+fn range(lower: i64, upper: i64) -> ValueRange {
+    ValueRange::new(BigInt::from(lower), BigInt::from(upper))
+}